@@ -0,0 +1,338 @@
+//! Runtime support for `#[derive(TypeBox)]`. Not public API.
+
+use crate::builder::SchemaBuilder;
+use crate::registry::SchemaRegistry;
+use crate::schema::{Schema, SchemaKind, StringFormat};
+use indexmap::{IndexMap, IndexSet};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+thread_local! {
+    /// Names of the types whose inline schemas are being built, with whether
+    /// each has been referenced from within itself.
+    static BUILDING: RefCell<Vec<(String, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Pops the innermost [`BUILDING`] entry, even if building panics.
+struct Frame;
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        BUILDING.with(|building| building.borrow_mut().pop());
+    }
+}
+
+/// Marks `name` as referenced from within itself, returning whether it is
+/// being built.
+fn mark(name: &str) -> bool {
+    BUILDING.with(|building| {
+        match building
+            .borrow_mut()
+            .iter_mut()
+            .rev()
+            .find(|(building, _)| building == name)
+        {
+            Some((_, recursive)) => {
+                *recursive = true;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Builds the inline schema of the type `name`.
+///
+/// A type reached again while its own schema is being built, such as
+/// through a `Box` of another type that refers back to it, becomes a `Ref`
+/// to `name`, and the outer schema is then wrapped in a `Recursive` with
+/// `name` as its `$id` so the `Ref` resolves.
+pub fn named(name: &str, build: impl FnOnce() -> Schema) -> Schema {
+    if mark(name) {
+        return SchemaBuilder::r#ref(name);
+    }
+
+    BUILDING.with(|building| building.borrow_mut().push((name.to_string(), false)));
+    let frame = Frame;
+    let schema = build();
+    let recursive = BUILDING.with(|building| building.borrow().last().is_some_and(|(_, r)| *r));
+    drop(frame);
+
+    let schema = if recursive {
+        Schema::new(SchemaKind::Recursive {
+            schema: Box::new(schema),
+        })
+        .with_id(name)
+    } else {
+        schema
+    };
+    SchemaBuilder::named(name, schema)
+}
+
+/// A reference from the type `name` to itself, within [`named`].
+pub fn self_ref(name: &str) -> Schema {
+    mark(name);
+    SchemaBuilder::r#ref(name)
+}
+
+/// Accumulates object properties, including those merged in by
+/// `#[serde(flatten)]`.
+#[derive(Default)]
+pub struct ObjectParts {
+    properties: IndexMap<String, Schema>,
+    required: Vec<String>,
+    additional_properties: Option<Box<Schema>>,
+    all_of: Vec<Schema>,
+}
+
+impl ObjectParts {
+    pub fn field(&mut self, name: &str, schema: Schema, required: bool) {
+        if required && !self.required.iter().any(|r| r == name) {
+            self.required.push(name.to_string());
+        }
+        self.properties.insert(name.to_string(), schema);
+    }
+
+    /// Merges a flattened schema. Objects contribute their properties, maps
+    /// their value schema; anything else is kept as an intersection member.
+    pub fn flatten(&mut self, schema: Schema) {
+        let schema = match schema.kind {
+            SchemaKind::Named { schema, .. } => *schema,
+            _ => schema,
+        };
+        match schema.kind {
+            SchemaKind::Object {
+                properties,
+                required,
+                additional_properties,
+            } => {
+                for (name, property) in properties {
+                    let is_required = required.contains(&name);
+                    self.field(&name, property, is_required);
+                }
+                if additional_properties.is_some() {
+                    self.additional_properties = additional_properties;
+                }
+            }
+            _ => self.all_of.push(schema),
+        }
+    }
+
+    /// Like [`flatten`](Self::flatten), resolving a `Ref` through `registry`.
+    pub fn flatten_from(&mut self, schema: Schema, registry: &SchemaRegistry) {
+        let resolved = registry.resolve(&schema).cloned().unwrap_or(schema);
+        self.flatten(resolved);
+    }
+
+    pub fn build(self) -> Schema {
+        let object = Schema::new(SchemaKind::Object {
+            properties: self.properties,
+            required: self.required,
+            additional_properties: self.additional_properties,
+        });
+        if self.all_of.is_empty() {
+            object
+        } else {
+            let mut all_of = vec![object];
+            all_of.extend(self.all_of);
+            Schema::new(SchemaKind::Intersect { all_of })
+        }
+    }
+}
+
+/// Returns the schema a constraint applies to, looking through the
+/// `Union[T, Null]` produced for `Option<T>`.
+fn target(schema: &mut Schema) -> &mut Schema {
+    let is_nullable = matches!(
+        &schema.kind,
+        SchemaKind::Union { any_of }
+            if any_of.len() == 2 && matches!(any_of[1].kind, SchemaKind::Null)
+    );
+    if !is_nullable {
+        return schema;
+    }
+    match &mut schema.kind {
+        SchemaKind::Union { any_of } => &mut any_of[0],
+        _ => unreachable!(),
+    }
+}
+
+/// Field types whose schema takes `min`/`max`. Bounds are given in the
+/// field's own type, so the compiler rejects values it cannot hold.
+pub trait Bounded {
+    type Bound;
+
+    fn bound(kind: &mut SchemaKind, value: Self::Bound, is_maximum: bool);
+}
+
+macro_rules! impl_bounded {
+    ($($ty:ty => $variant:ident($bound:ty)),* $(,)?) => {
+        $(
+            impl Bounded for $ty {
+                type Bound = $bound;
+
+                fn bound(kind: &mut SchemaKind, value: $bound, is_maximum: bool) {
+                    if let SchemaKind::$variant { minimum, maximum } = kind {
+                        *if is_maximum { maximum } else { minimum } = Some(value);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_bounded! {
+    i8 => Int8(i8),
+    i16 => Int16(i16),
+    i32 => Int32(i32),
+    i64 => Int64(i64),
+    isize => Int64(i64),
+    u8 => UInt8(u8),
+    u16 => UInt16(u16),
+    u32 => UInt32(u32),
+    u64 => UInt64(u64),
+    usize => UInt64(u64),
+    f32 => Float32(f32),
+    f64 => Float64(f64),
+}
+
+/// Field types whose schema is a `String`, taking `format` and `pattern`.
+pub trait Text {}
+
+/// Field types whose schema is a `String` or `Bytes`, taking `min_length`
+/// and `max_length`.
+pub trait Length {}
+
+/// Field types whose schema is an `Array`, or `Bytes` for sequences of
+/// `u8`, taking `min_items`, `max_items` and `unique_items`.
+pub trait Items {}
+
+impl Text for String {}
+impl Text for str {}
+impl Text for char {}
+
+impl Length for String {}
+impl Length for str {}
+impl Length for char {}
+impl Length for Vec<u8> {}
+impl Length for VecDeque<u8> {}
+impl Length for [u8] {}
+impl<const N: usize> Length for [u8; N] {}
+
+impl<T> Items for Vec<T> {}
+impl<T> Items for VecDeque<T> {}
+impl<T> Items for [T] {}
+impl<T, const N: usize> Items for [T; N] {}
+impl<T, S> Items for HashSet<T, S> {}
+impl<T> Items for BTreeSet<T> {}
+impl<T, S> Items for IndexSet<T, S> {}
+
+/// Forwards the constraint traits through `Option` and pointers, whose
+/// schemas are those of the inner type.
+macro_rules! impl_forward {
+    ($($ty:ty => [$($bound:tt)*]),* $(,)?) => {
+        $(
+            impl<T: Bounded + $($bound)*> Bounded for $ty {
+                type Bound = T::Bound;
+
+                fn bound(kind: &mut SchemaKind, value: T::Bound, is_maximum: bool) {
+                    T::bound(kind, value, is_maximum)
+                }
+            }
+            impl<T: Text + $($bound)*> Text for $ty {}
+            impl<T: Length + $($bound)*> Length for $ty {}
+            impl<T: Items + $($bound)*> Items for $ty {}
+        )*
+    };
+}
+
+impl_forward! {
+    Option<T> => [Sized],
+    Box<T> => [?Sized],
+    Rc<T> => [?Sized],
+    Arc<T> => [?Sized],
+    &T => [?Sized],
+    &mut T => [?Sized],
+    Cow<'_, T> => [ToOwned + ?Sized],
+}
+
+pub fn minimum<T: Bounded + ?Sized>(mut schema: Schema, value: T::Bound) -> Schema {
+    T::bound(&mut target(&mut schema).kind, value, false);
+    schema
+}
+
+pub fn maximum<T: Bounded + ?Sized>(mut schema: Schema, value: T::Bound) -> Schema {
+    T::bound(&mut target(&mut schema).kind, value, true);
+    schema
+}
+
+pub fn min_length<T: Length + ?Sized>(mut schema: Schema, value: usize) -> Schema {
+    if let SchemaKind::String { min_length, .. } | SchemaKind::Bytes { min_length, .. } =
+        &mut target(&mut schema).kind
+    {
+        *min_length = Some(value);
+    }
+    schema
+}
+
+pub fn max_length<T: Length + ?Sized>(mut schema: Schema, value: usize) -> Schema {
+    if let SchemaKind::String { max_length, .. } | SchemaKind::Bytes { max_length, .. } =
+        &mut target(&mut schema).kind
+    {
+        *max_length = Some(value);
+    }
+    schema
+}
+
+pub fn min_items<T: Items + ?Sized>(mut schema: Schema, value: usize) -> Schema {
+    match &mut target(&mut schema).kind {
+        SchemaKind::Array { min_items, .. } => *min_items = Some(value),
+        SchemaKind::Bytes { min_length, .. } => *min_length = Some(value),
+        _ => {}
+    }
+    schema
+}
+
+pub fn max_items<T: Items + ?Sized>(mut schema: Schema, value: usize) -> Schema {
+    match &mut target(&mut schema).kind {
+        SchemaKind::Array { max_items, .. } => *max_items = Some(value),
+        SchemaKind::Bytes { max_length, .. } => *max_length = Some(value),
+        _ => {}
+    }
+    schema
+}
+
+/// # Panics
+///
+/// If `value` is `true` for a sequence of `u8`, whose `Bytes` schema cannot
+/// hold it. The derive rejects this where it can see the field type.
+pub fn unique_items<T: Items + ?Sized>(mut schema: Schema, value: bool) -> Schema {
+    match &mut target(&mut schema).kind {
+        SchemaKind::Array { unique_items, .. } => *unique_items = Some(value),
+        SchemaKind::Bytes { .. } if value => {
+            panic!("`unique_items` does not apply to byte sequences")
+        }
+        _ => {}
+    }
+    schema
+}
+
+pub fn format<T: Text + ?Sized>(mut schema: Schema, value: &str) -> Schema {
+    if let SchemaKind::String { format, .. } = &mut target(&mut schema).kind {
+        *format = Some(
+            serde_json::from_value(serde_json::Value::String(value.to_string()))
+                .unwrap_or_else(|_| StringFormat::Custom(value.to_string())),
+        );
+    }
+    schema
+}
+
+pub fn pattern<T: Text + ?Sized>(mut schema: Schema, value: &str) -> Schema {
+    if let SchemaKind::String { pattern, .. } = &mut target(&mut schema).kind {
+        *pattern = Some(value.to_string());
+    }
+    schema
+}
@@ -0,0 +1,984 @@
+//! Avro binary encoding of [`Value`]s.
+
+use super::{AvroSchema, Decimal, Enum, Fixed, Record};
+use crate::error::AvroError;
+use crate::value::Value;
+use indexmap::IndexMap;
+use serde_json::Value as Json;
+use std::collections::HashMap;
+
+/// The two bytes starting every single-object encoded message.
+const SINGLE_OBJECT_MARKER: [u8; 2] = [0xc3, 0x01];
+
+impl AvroSchema {
+    /// Encodes `value` in Avro's binary format.
+    ///
+    /// Integers and floats take [`Value::Int64`] and [`Value::Float64`]
+    /// (or `Int64` for floats), records and maps [`Value::Object`], enums
+    /// and logical types [`Value::String`]. A union takes the first branch
+    /// `value` encodes with; record fields missing from `value` are written
+    /// from their defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AvroError::Encode`] when `value` does not match the schema,
+    /// including integers out of range and fields that are neither present
+    /// nor defaulted.
+    pub fn encode(&self, value: &Value) -> Result<Vec<u8>, AvroError> {
+        let mut out = Vec::new();
+        Encoder::new(self).write(self, value, "#", &mut out)?;
+        Ok(out)
+    }
+
+    /// Decodes a value written with this schema by [`encode`](Self::encode)
+    /// or any other Avro implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AvroError::Decode`] when `bytes` are truncated, hold
+    /// anything after the value, or do not match the schema.
+    pub fn decode(&self, bytes: &[u8]) -> Result<Value, AvroError> {
+        let mut reader = Reader {
+            names: definitions(self),
+            bytes,
+            pos: 0,
+        };
+        let value = reader.read(self)?;
+        if reader.pos != bytes.len() {
+            return Err(AvroError::Decode(format!(
+                "{} bytes left after the value",
+                bytes.len() - reader.pos
+            )));
+        }
+        Ok(value)
+    }
+
+    /// Encodes `value` in the single-object encoding: a two-byte marker,
+    /// the schema's [`fingerprint`](Self::fingerprint) in little-endian
+    /// order, then the binary encoding.
+    pub fn encode_single_object(&self, value: &Value) -> Result<Vec<u8>, AvroError> {
+        let mut out = Vec::from(SINGLE_OBJECT_MARKER);
+        out.extend_from_slice(&self.fingerprint().to_le_bytes());
+        Encoder::new(self).write(self, value, "#", &mut out)?;
+        Ok(out)
+    }
+
+    /// Decodes a single-object encoded message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AvroError::FingerprintMismatch`] when the message was
+    /// written with a schema of another fingerprint, and
+    /// [`AvroError::Decode`] as [`decode`](Self::decode) does.
+    pub fn decode_single_object(&self, bytes: &[u8]) -> Result<Value, AvroError> {
+        let found = single_object_fingerprint(bytes)?;
+        let expected = self.fingerprint();
+        if found != expected {
+            return Err(AvroError::FingerprintMismatch { expected, found });
+        }
+        self.decode(&bytes[10..])
+    }
+}
+
+/// The schema fingerprint of a single-object encoded message, to look up
+/// the schema that decodes it.
+///
+/// # Errors
+///
+/// Returns [`AvroError::Decode`] when `bytes` do not start with the
+/// single-object header.
+///
+/// # Examples
+///
+/// ```
+/// use typebox::avro::{single_object_fingerprint, AvroSchema};
+/// use typebox::Value;
+///
+/// let schema = AvroSchema::Long;
+/// let message = schema.encode_single_object(&Value::int64(7)).unwrap();
+/// assert_eq!(single_object_fingerprint(&message).unwrap(), schema.fingerprint());
+/// ```
+pub fn single_object_fingerprint(bytes: &[u8]) -> Result<u64, AvroError> {
+    match bytes.get(..10) {
+        Some(header) if header[..2] == SINGLE_OBJECT_MARKER => {
+            let mut fingerprint = [0; 8];
+            fingerprint.copy_from_slice(&header[2..]);
+            Ok(u64::from_le_bytes(fingerprint))
+        }
+        _ => Err(AvroError::Decode(
+            "missing the single-object encoding header".to_string(),
+        )),
+    }
+}
+
+/// The records, enums and fixed types defined in `schema`, by full name.
+fn definitions(schema: &AvroSchema) -> HashMap<&str, &AvroSchema> {
+    fn walk<'a>(schema: &'a AvroSchema, names: &mut HashMap<&'a str, &'a AvroSchema>) {
+        match schema {
+            AvroSchema::Array(inner) | AvroSchema::Map(inner) => walk(inner, names),
+            AvroSchema::Union(branches) => branches.iter().for_each(|b| walk(b, names)),
+            AvroSchema::Record(record) => {
+                names.insert(&record.name, schema);
+                record.fields.iter().for_each(|f| walk(&f.schema, names));
+            }
+            AvroSchema::Enum(Enum { name, .. }) | AvroSchema::Fixed(Fixed { name, .. }) => {
+                names.insert(name, schema);
+            }
+            _ => {}
+        }
+    }
+    let mut names = HashMap::new();
+    walk(schema, &mut names);
+    names
+}
+
+struct Encoder<'a> {
+    names: HashMap<&'a str, &'a AvroSchema>,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(schema: &'a AvroSchema) -> Self {
+        Self {
+            names: definitions(schema),
+        }
+    }
+
+    fn write(
+        &self,
+        schema: &AvroSchema,
+        value: &Value,
+        path: &str,
+        out: &mut Vec<u8>,
+    ) -> Result<(), AvroError> {
+        let mismatch = || {
+            encode_error(
+                path,
+                &format!(
+                    "expected {}, got {}",
+                    schema_description(schema),
+                    value.kind()
+                ),
+            )
+        };
+        match (schema, value) {
+            (AvroSchema::Named(name), _) => {
+                let definition = self
+                    .names
+                    .get(name.as_str())
+                    .ok_or_else(|| encode_error(path, &format!("unknown type `{}`", name)))?;
+                self.write(definition, value, path, out)?;
+            }
+            (AvroSchema::Null, Value::Null) => {}
+            (AvroSchema::Boolean, Value::Bool(b)) => out.push(*b as u8),
+            (AvroSchema::Int, Value::Int64(n)) => {
+                let n = i32::try_from(*n)
+                    .map_err(|_| encode_error(path, &format!("{} is out of range for int", n)))?;
+                write_long(n as i64, out);
+            }
+            (AvroSchema::Long, Value::Int64(n)) => write_long(*n, out),
+            (AvroSchema::Float, Value::Float64(f)) => out.extend((*f as f32).to_le_bytes()),
+            (AvroSchema::Float, Value::Int64(n)) => out.extend((*n as f32).to_le_bytes()),
+            (AvroSchema::Double, Value::Float64(f)) => out.extend(f.to_le_bytes()),
+            (AvroSchema::Double, Value::Int64(n)) => out.extend((*n as f64).to_le_bytes()),
+            (AvroSchema::Bytes, Value::Bytes(bytes) | Value::UInt8Array(bytes)) => {
+                write_bytes(bytes, out)
+            }
+            (AvroSchema::String | AvroSchema::Uuid, Value::String(s)) => {
+                write_bytes(s.as_bytes(), out)
+            }
+            (AvroSchema::Array(items), _) => {
+                let elements = elements(value).ok_or_else(mismatch)?;
+                if !elements.is_empty() {
+                    write_long(elements.len() as i64, out);
+                    for (i, element) in elements.iter().enumerate() {
+                        self.write(items, element, &format!("{}/{}", path, i), out)?;
+                    }
+                }
+                out.push(0);
+            }
+            (AvroSchema::Map(values), Value::Object(entries)) => {
+                if !entries.is_empty() {
+                    write_long(entries.len() as i64, out);
+                    for (key, entry) in entries {
+                        write_bytes(key.as_bytes(), out);
+                        self.write(values, entry, &format!("{}/{}", path, key), out)?;
+                    }
+                }
+                out.push(0);
+            }
+            (AvroSchema::Union(branches), _) => {
+                let mut last_error = None;
+                for (i, branch) in branches.iter().enumerate() {
+                    let mut encoded = Vec::new();
+                    write_long(i as i64, &mut encoded);
+                    match self.write(branch, value, path, &mut encoded) {
+                        Ok(()) => {
+                            out.extend(encoded);
+                            return Ok(());
+                        }
+                        Err(error) if *branch != AvroSchema::Null => last_error = Some(error),
+                        Err(_) => {}
+                    }
+                }
+                // Report why the only candidate failed, as for an optional field.
+                let candidates = branches.iter().filter(|b| **b != AvroSchema::Null).count();
+                return Err(match last_error {
+                    Some(error) if candidates == 1 && !value.is_null() => error,
+                    _ => encode_error(path, "matches no branch of the union"),
+                });
+            }
+            (AvroSchema::Record(record), Value::Object(entries)) => {
+                if let Some(unknown) = entries
+                    .keys()
+                    .find(|key| !record.fields.iter().any(|f| f.name == **key))
+                {
+                    return Err(encode_error(
+                        path,
+                        &format!("unknown field `{}` for `{}`", unknown, record.name),
+                    ));
+                }
+                for field in &record.fields {
+                    let field_path = format!("{}/{}", path, field.name);
+                    match (entries.get(&field.name), &field.default) {
+                        (Some(entry), _) => self.write(&field.schema, entry, &field_path, out)?,
+                        (None, Some(default)) => {
+                            let default = default_value(default, &field.schema);
+                            self.write(&field.schema, &default, &field_path, out)?
+                        }
+                        (None, None) => {
+                            return Err(encode_error(
+                                path,
+                                &format!("missing field `{}`", field.name),
+                            ))
+                        }
+                    }
+                }
+            }
+            (AvroSchema::Enum(declaration), Value::String(symbol)) => {
+                let index = declaration
+                    .symbols
+                    .iter()
+                    .position(|s| s == symbol)
+                    .ok_or_else(|| {
+                        encode_error(
+                            path,
+                            &format!("`{}` is not a symbol of `{}`", symbol, declaration.name),
+                        )
+                    })?;
+                write_long(index as i64, out);
+            }
+            (
+                AvroSchema::Fixed(Fixed {
+                    decimal: Some(decimal),
+                    size,
+                    ..
+                }),
+                Value::String(s),
+            ) => {
+                let unscaled = parse_decimal(s, *decimal).map_err(|e| encode_error(path, &e))?;
+                let bytes = twos_complement(unscaled);
+                if bytes.len() > *size {
+                    return Err(encode_error(
+                        path,
+                        &format!("{} does not fit in {} bytes", s, size),
+                    ));
+                }
+                let fill = if unscaled < 0 { 0xff } else { 0 };
+                out.extend(std::iter::repeat_n(fill, size - bytes.len()));
+                out.extend(bytes);
+            }
+            (
+                AvroSchema::Fixed(Fixed {
+                    size,
+                    decimal: None,
+                    ..
+                }),
+                Value::Bytes(bytes) | Value::UInt8Array(bytes),
+            ) => {
+                if bytes.len() != *size {
+                    return Err(encode_error(
+                        path,
+                        &format!("expected {} bytes, got {}", size, bytes.len()),
+                    ));
+                }
+                out.extend(bytes);
+            }
+            (AvroSchema::Decimal(decimal), Value::String(s)) => {
+                let unscaled = parse_decimal(s, *decimal).map_err(|e| encode_error(path, &e))?;
+                write_bytes(&twos_complement(unscaled), out);
+            }
+            (AvroSchema::Date, Value::String(s)) => {
+                let days = parse_date(s).ok_or_else(|| {
+                    encode_error(path, &format!("`{}` is not a date (YYYY-MM-DD)", s))
+                })?;
+                let days = i32::try_from(days)
+                    .map_err(|_| encode_error(path, &format!("{} is out of range", s)))?;
+                write_long(days as i64, out);
+            }
+            (AvroSchema::TimeMillis | AvroSchema::TimeMicros, Value::String(s)) => {
+                let micros = parse_time(s).ok_or_else(|| {
+                    encode_error(path, &format!("`{}` is not a time (HH:MM:SS)", s))
+                })?;
+                match schema {
+                    AvroSchema::TimeMillis => write_long(micros / 1000, out),
+                    _ => write_long(micros, out),
+                }
+            }
+            (AvroSchema::TimestampMillis | AvroSchema::TimestampMicros, Value::String(s)) => {
+                let micros = parse_timestamp(s).ok_or_else(|| {
+                    encode_error(path, &format!("`{}` is not an RFC 3339 timestamp", s))
+                })?;
+                match schema {
+                    AvroSchema::TimestampMillis => write_long(micros.div_euclid(1000), out),
+                    _ => write_long(micros, out),
+                }
+            }
+            _ => return Err(mismatch()),
+        }
+        Ok(())
+    }
+}
+
+/// The items of an array value, including typed arrays.
+fn elements(value: &Value) -> Option<Vec<Value>> {
+    Some(match value {
+        Value::Array(items) => items.clone(),
+        Value::Float32Array(items) => items.iter().map(|f| Value::Float64(*f as f64)).collect(),
+        Value::Float64Array(items) => items.iter().map(|f| Value::Float64(*f)).collect(),
+        Value::Int32Array(items) => items.iter().map(|n| Value::Int64(*n as i64)).collect(),
+        Value::Int64Array(items) => items.iter().map(|n| Value::Int64(*n)).collect(),
+        Value::UInt8Array(items) => items.iter().map(|n| Value::Int64(*n as i64)).collect(),
+        _ => return None,
+    })
+}
+
+/// A field default as a value. Defaults of unions take the first branch,
+/// and defaults of bytes and fixed types are strings of code points 0-255.
+fn default_value(json: &Json, schema: &AvroSchema) -> Value {
+    match (json, schema) {
+        (_, AvroSchema::Union(branches)) if !branches.is_empty() => {
+            default_value(json, &branches[0])
+        }
+        (Json::String(s), AvroSchema::Bytes | AvroSchema::Fixed(Fixed { decimal: None, .. })) => {
+            Value::Bytes(s.chars().map(|c| c as u8).collect())
+        }
+        (Json::Array(items), AvroSchema::Array(inner)) => Value::Array(
+            items
+                .iter()
+                .map(|item| default_value(item, inner))
+                .collect(),
+        ),
+        (Json::Object(entries), AvroSchema::Map(inner)) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, entry)| (key.clone(), default_value(entry, inner)))
+                .collect(),
+        ),
+        (Json::Object(entries), AvroSchema::Record(record)) => Value::Object(
+            record
+                .fields
+                .iter()
+                .filter_map(|field| {
+                    let entry = entries.get(&field.name)?;
+                    Some((field.name.clone(), default_value(entry, &field.schema)))
+                })
+                .collect(),
+        ),
+        (Json::Number(n), AvroSchema::Float | AvroSchema::Double) => {
+            Value::Float64(n.as_f64().unwrap_or_default())
+        }
+        (json, _) => plain_value(json),
+    }
+}
+
+fn plain_value(json: &Json) -> Value {
+    match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Bool(*b),
+        Json::Number(n) => n
+            .as_i64()
+            .map(Value::Int64)
+            .unwrap_or_else(|| Value::Float64(n.as_f64().unwrap_or_default())),
+        Json::String(s) => Value::String(s.clone()),
+        Json::Array(items) => Value::Array(items.iter().map(plain_value).collect()),
+        Json::Object(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, entry)| (key.clone(), plain_value(entry)))
+                .collect(),
+        ),
+    }
+}
+
+struct Reader<'a> {
+    names: HashMap<&'a str, &'a AvroSchema>,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, schema: &'a AvroSchema) -> Result<Value, AvroError> {
+        let value = match schema {
+            AvroSchema::Named(name) => {
+                let definition = *self
+                    .names
+                    .get(name.as_str())
+                    .ok_or_else(|| AvroError::Decode(format!("unknown type `{}`", name)))?;
+                return self.read(definition);
+            }
+            AvroSchema::Null => Value::Null,
+            AvroSchema::Boolean => match self.take(1)?[0] {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                b => return Err(AvroError::Decode(format!("invalid boolean byte {}", b))),
+            },
+            AvroSchema::Int => Value::Int64(self.read_int()? as i64),
+            AvroSchema::Long => Value::Int64(self.read_long()?),
+            AvroSchema::Float => {
+                let bytes = self.take(4)?;
+                Value::Float64(f32::from_le_bytes(bytes.try_into().unwrap()) as f64)
+            }
+            AvroSchema::Double => {
+                let bytes = self.take(8)?;
+                Value::Float64(f64::from_le_bytes(bytes.try_into().unwrap()))
+            }
+            AvroSchema::Bytes => Value::Bytes(self.read_bytes()?.to_vec()),
+            AvroSchema::String | AvroSchema::Uuid => Value::String(self.read_string()?),
+            AvroSchema::Array(items) => {
+                let mut elements = Vec::new();
+                while let Some(count) = self.read_block()? {
+                    for _ in 0..count {
+                        elements.push(self.read(items)?);
+                    }
+                }
+                Value::Array(elements)
+            }
+            AvroSchema::Map(values) => {
+                let mut entries = IndexMap::new();
+                while let Some(count) = self.read_block()? {
+                    for _ in 0..count {
+                        let key = self.read_string()?;
+                        entries.insert(key, self.read(values)?);
+                    }
+                }
+                Value::Object(entries)
+            }
+            AvroSchema::Union(branches) => {
+                let index = self.read_long()?;
+                let branch = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| branches.get(i))
+                    .ok_or_else(|| {
+                        AvroError::Decode(format!("union branch {} does not exist", index))
+                    })?;
+                self.read(branch)?
+            }
+            AvroSchema::Record(Record { fields, .. }) => {
+                let mut entries = IndexMap::new();
+                for field in fields {
+                    let value = self.read(&field.schema)?;
+                    // Absent and null are the same for fields defaulting to null.
+                    if !(value.is_null() && field.default == Some(Json::Null)) {
+                        entries.insert(field.name.clone(), value);
+                    }
+                }
+                Value::Object(entries)
+            }
+            AvroSchema::Enum(Enum { name, symbols, .. }) => {
+                let index = self.read_int()?;
+                let symbol = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| symbols.get(i))
+                    .ok_or_else(|| {
+                        AvroError::Decode(format!("`{}` has no symbol {}", name, index))
+                    })?;
+                Value::String(symbol.clone())
+            }
+            AvroSchema::Fixed(Fixed { size, decimal, .. }) => {
+                let bytes = self.take(*size)?;
+                match decimal {
+                    Some(decimal) => {
+                        Value::String(format_decimal(from_twos_complement(bytes)?, *decimal))
+                    }
+                    None => Value::Bytes(bytes.to_vec()),
+                }
+            }
+            AvroSchema::Decimal(decimal) => {
+                let bytes = self.read_bytes()?;
+                Value::String(format_decimal(from_twos_complement(bytes)?, *decimal))
+            }
+            AvroSchema::Date => {
+                let (year, month, day) = civil_from_days(self.read_int()? as i64);
+                Value::String(format!("{:04}-{:02}-{:02}", year, month, day))
+            }
+            AvroSchema::TimeMillis => Value::String(format_time(self.read_int()? as i64 * 1000, 3)),
+            AvroSchema::TimeMicros => Value::String(format_time(self.read_long()?, 6)),
+            AvroSchema::TimestampMillis => {
+                let millis = self.read_long()?;
+                Value::String(format_timestamp(millis.saturating_mul(1000), 3))
+            }
+            AvroSchema::TimestampMicros => Value::String(format_timestamp(self.read_long()?, 6)),
+        };
+        Ok(value)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], AvroError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| AvroError::Decode("unexpected end of data".to_string()))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_long(&mut self) -> Result<i64, AvroError> {
+        let mut encoded: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            encoded |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((encoded >> 1) as i64 ^ -((encoded & 1) as i64));
+            }
+        }
+        Err(AvroError::Decode(
+            "variable-length integer is too long".to_string(),
+        ))
+    }
+
+    fn read_int(&mut self) -> Result<i32, AvroError> {
+        let n = self.read_long()?;
+        i32::try_from(n).map_err(|_| AvroError::Decode(format!("{} is out of range for int", n)))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], AvroError> {
+        let len = self.read_long()?;
+        let len = usize::try_from(len)
+            .map_err(|_| AvroError::Decode(format!("negative length {}", len)))?;
+        self.take(len)
+    }
+
+    fn read_string(&mut self) -> Result<String, AvroError> {
+        let bytes = self.read_bytes()?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| AvroError::Decode("string is not valid UTF-8".to_string()))
+    }
+
+    /// The item count of the next array or map block, or `None` at the end.
+    fn read_block(&mut self) -> Result<Option<u64>, AvroError> {
+        let count = self.read_long()?;
+        if count < 0 {
+            // A negative count is followed by the block's size in bytes.
+            self.read_long()?;
+        }
+        Ok((count != 0).then_some(count.unsigned_abs()))
+    }
+}
+
+fn write_long(n: i64, out: &mut Vec<u8>) {
+    let mut encoded = ((n << 1) ^ (n >> 63)) as u64;
+    while encoded >= 0x80 {
+        out.push((encoded as u8 & 0x7f) | 0x80);
+        encoded >>= 7;
+    }
+    out.push(encoded as u8);
+}
+
+fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    write_long(bytes.len() as i64, out);
+    out.extend_from_slice(bytes);
+}
+
+fn schema_description(schema: &AvroSchema) -> String {
+    match (schema.logical_type(), schema) {
+        (_, AvroSchema::Record(Record { name, .. })) => format!("record `{}`", name),
+        (_, AvroSchema::Enum(Enum { name, .. })) => format!("enum `{}`", name),
+        (_, AvroSchema::Fixed(Fixed { name, .. })) => format!("fixed `{}`", name),
+        (Some(logical), _) => format!("{} string", logical),
+        (None, schema) => schema.primitive_name().to_string(),
+    }
+}
+
+fn encode_error(path: &str, message: &str) -> AvroError {
+    AvroError::Encode {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// The unscaled value of a decimal string such as `-12.5`.
+fn parse_decimal(s: &str, decimal: Decimal) -> Result<i128, String> {
+    let invalid = || format!("`{}` is not a decimal number", s);
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty()
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+        || (digits.contains('.') && fraction.is_empty())
+    {
+        return Err(invalid());
+    }
+    let scale = decimal.scale as usize;
+    if fraction.len() > scale {
+        return Err(format!("`{}` has more than {} decimal places", s, scale));
+    }
+    let whole = whole.trim_start_matches('0');
+    if whole.len() > (decimal.precision - decimal.scale) as usize {
+        return Err(format!(
+            "`{}` has more than {} digits",
+            s, decimal.precision
+        ));
+    }
+    let mut unscaled: i128 = 0;
+    for b in whole
+        .bytes()
+        .chain(fraction.bytes())
+        .chain(std::iter::repeat_n(b'0', scale - fraction.len()))
+    {
+        unscaled = unscaled
+            .checked_mul(10)
+            .and_then(|n| n.checked_add((b - b'0') as i128))
+            .ok_or_else(invalid)?;
+    }
+    Ok(if negative { -unscaled } else { unscaled })
+}
+
+fn format_decimal(unscaled: i128, decimal: Decimal) -> String {
+    let digits = unscaled.unsigned_abs().to_string();
+    let scale = decimal.scale as usize;
+    let digits = format!("{:0>width$}", digits, width = scale + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - scale);
+    let sign = if unscaled < 0 { "-" } else { "" };
+    if scale == 0 {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+}
+
+/// The shortest big-endian two's-complement encoding of `n`.
+fn twos_complement(n: i128) -> Vec<u8> {
+    let bytes = n.to_be_bytes();
+    let mut start = 0;
+    while start < 15 {
+        let redundant = match bytes[start] {
+            0x00 => bytes[start + 1] & 0x80 == 0,
+            0xff => bytes[start + 1] & 0x80 != 0,
+            _ => false,
+        };
+        if !redundant {
+            break;
+        }
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+fn from_twos_complement(bytes: &[u8]) -> Result<i128, AvroError> {
+    let negative = bytes.first().is_some_and(|b| b & 0x80 != 0);
+    let fill = if negative { 0xff } else { 0 };
+    let excess = bytes.len().saturating_sub(16);
+    if bytes[..excess].iter().any(|b| *b != fill) {
+        return Err(AvroError::Decode("decimal is too large".to_string()));
+    }
+    let mut buffer = [fill; 16];
+    let significant = &bytes[excess..];
+    buffer[16 - significant.len()..].copy_from_slice(significant);
+    Ok(i128::from_be_bytes(buffer))
+}
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date.
+fn parse_date(s: &str) -> Option<i64> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let year = number(&s[..4])?;
+    let month = number(&s[5..7])?;
+    let day = number(&s[8..])?;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Microseconds since midnight of an `HH:MM:SS[.ffffff]` time. Digits
+/// beyond microseconds are dropped.
+fn parse_time(s: &str) -> Option<i64> {
+    let (clock, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let bytes = clock.as_bytes();
+    if bytes.len() != 8 || bytes[2] != b':' || bytes[5] != b':' {
+        return None;
+    }
+    let hour = number(&clock[..2])?;
+    let minute = number(&clock[3..5])?;
+    let second = number(&clock[6..])?;
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    if s.contains('.') && (fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit())) {
+        return None;
+    }
+    let micros: i64 = format!("{:0<6}", &fraction[..fraction.len().min(6)])
+        .parse()
+        .ok()?;
+    Some(((hour * 60 + minute) * 60 + second) * 1_000_000 + micros)
+}
+
+/// Microseconds since the Unix epoch of an RFC 3339 timestamp.
+fn parse_timestamp(s: &str) -> Option<i64> {
+    let date = parse_date(s.get(..10)?)?;
+    if !matches!(s.as_bytes().get(10), Some(b'T' | b't' | b' ')) {
+        return None;
+    }
+    let rest = &s[11..];
+    let (time, offset) = if let Some(time) = rest.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else {
+        let split = rest.len().checked_sub(6)?;
+        let (time, offset) = rest.split_at(split);
+        let sign = match offset.as_bytes()[0] {
+            b'+' => 1,
+            b'-' => -1,
+            _ => return None,
+        };
+        if offset.as_bytes()[3] != b':' {
+            return None;
+        }
+        let minutes = number(&offset[1..3])? * 60 + number(&offset[4..])?;
+        (time, sign * minutes * 60_000_000)
+    };
+    Some(date * MICROS_PER_DAY + parse_time(time)? - offset)
+}
+
+fn format_time(micros: i64, digits: usize) -> String {
+    let micros = micros.rem_euclid(MICROS_PER_DAY);
+    let seconds = micros / 1_000_000;
+    let mut out = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    let fraction = micros % 1_000_000;
+    if fraction != 0 {
+        let fraction = format!("{:06}", fraction);
+        out.push('.');
+        out.push_str(&fraction[..digits]);
+    }
+    out
+}
+
+fn format_timestamp(micros: i64, digits: usize) -> String {
+    let (year, month, day) = civil_from_days(micros.div_euclid(MICROS_PER_DAY));
+    format!(
+        "{:04}-{:02}-{:02}T{}Z",
+        year,
+        month,
+        day,
+        format_time(micros, digits)
+    )
+}
+
+fn number(digits: &str) -> Option<i64> {
+    if digits.bytes().all(|b| b.is_ascii_digit()) {
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avro::Field;
+
+    fn record(fields: Vec<(&str, AvroSchema)>) -> AvroSchema {
+        AvroSchema::Record(Record {
+            name: "test".to_string(),
+            doc: None,
+            fields: fields
+                .into_iter()
+                .map(|(name, schema)| Field {
+                    name: name.to_string(),
+                    doc: None,
+                    schema,
+                    default: None,
+                })
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_encode_matches_specification() {
+        // Examples from the Avro specification.
+        for (n, expected) in [
+            (0, vec![0x00]),
+            (-1, vec![0x01]),
+            (1, vec![0x02]),
+            (-2, vec![0x03]),
+            (-64, vec![0x7f]),
+            (64, vec![0x80, 0x01]),
+        ] {
+            assert_eq!(AvroSchema::Long.encode(&Value::int64(n)).unwrap(), expected);
+        }
+        assert_eq!(
+            AvroSchema::String.encode(&Value::string("foo")).unwrap(),
+            [0x06, 0x66, 0x6f, 0x6f]
+        );
+
+        let schema = record(vec![("a", AvroSchema::Long), ("b", AvroSchema::String)]);
+        let value = Value::object()
+            .field("a", Value::int64(27))
+            .field("b", Value::string("foo"))
+            .build();
+        let bytes = schema.encode(&value).unwrap();
+        assert_eq!(bytes, [0x36, 0x06, 0x66, 0x6f, 0x6f]);
+        assert_eq!(schema.decode(&bytes).unwrap(), value);
+
+        let union = AvroSchema::Union(vec![AvroSchema::Null, AvroSchema::String]);
+        assert_eq!(union.encode(&Value::Null).unwrap(), [0x00]);
+        assert_eq!(
+            union.encode(&Value::string("a")).unwrap(),
+            [0x02, 0x02, 0x61]
+        );
+        let array = AvroSchema::Array(Box::new(AvroSchema::Long));
+        let value = Value::array(vec![Value::int64(3), Value::int64(27)]);
+        assert_eq!(array.encode(&value).unwrap(), [0x04, 0x06, 0x36, 0x00]);
+        // Blocks with a byte size, as other writers may produce.
+        assert_eq!(
+            array.decode(&[0x03, 0x04, 0x06, 0x36, 0x00]).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_round_trip_logical_types_and_defaults() {
+        let schema = AvroSchema::parse_str(
+            r#"{"type": "record", "name": "Payment", "fields": [
+                {"name": "day", "type": {"type": "int", "logicalType": "date"}},
+                {"name": "at", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+                {"name": "at_micros", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+                {"name": "time", "type": {"type": "int", "logicalType": "time-millis"}},
+                {"name": "amount", "type": {"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}},
+                {"name": "fee", "type": {"type": "fixed", "name": "Fee", "size": 4, "logicalType": "decimal", "precision": 6, "scale": 3}},
+                {"name": "id", "type": {"type": "string", "logicalType": "uuid"}},
+                {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["OK", "FAILED"]}},
+                {"name": "note", "type": ["null", "string"], "default": null},
+                {"name": "tags", "type": {"type": "map", "values": "long"}, "default": {"a": 1}},
+                {"name": "raw", "type": "bytes", "default": "ÿ"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let value = Value::object()
+            .field("day", Value::string("1969-12-31"))
+            .field("at", Value::string("2024-02-29T23:59:59.250+01:00"))
+            .field("at_micros", Value::string("1900-01-01T00:00:00.000001Z"))
+            .field("time", Value::string("12:30:00.250"))
+            .field("amount", Value::string("-1234.5"))
+            .field("fee", Value::string("-0.005"))
+            .field("id", Value::string("5f3c2a4e-0000-4000-8000-000000000000"))
+            .field("status", Value::string("FAILED"))
+            .build();
+        let bytes = schema.encode(&value).unwrap();
+        let decoded = schema.decode(&bytes).unwrap();
+        let expected = Value::object()
+            .field("day", Value::string("1969-12-31"))
+            .field("at", Value::string("2024-02-29T22:59:59.250Z"))
+            .field("at_micros", Value::string("1900-01-01T00:00:00.000001Z"))
+            .field("time", Value::string("12:30:00.250"))
+            .field("amount", Value::string("-1234.50"))
+            .field("fee", Value::string("-0.005"))
+            .field("id", Value::string("5f3c2a4e-0000-4000-8000-000000000000"))
+            .field("status", Value::string("FAILED"))
+            .field("tags", Value::object().field("a", Value::int64(1)).build())
+            .field("raw", Value::bytes(vec![0xff]))
+            .build();
+        assert_eq!(decoded, expected);
+
+        // The date is the day before the epoch; the fee sign-extends.
+        assert_eq!(bytes[0], 0x01);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(11017), (2000, 3, 1));
+        assert_eq!(twos_complement(-123450), [0xfe, 0x1d, 0xc6]);
+        assert_eq!(twos_complement(128), [0x00, 0x80]);
+
+        let error = schema
+            .encode(
+                &Value::object()
+                    .field("day", Value::string("2023-02-29"))
+                    .build(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Cannot encode value at #/day: `2023-02-29` is not a date (YYYY-MM-DD)"
+        );
+    }
+
+    #[test]
+    fn test_single_object_encoding() {
+        let schema = record(vec![("id", AvroSchema::Long)]);
+        let other = record(vec![("id", AvroSchema::Int)]);
+        let value = Value::object().field("id", Value::int64(1)).build();
+
+        let message = schema.encode_single_object(&value).unwrap();
+        assert_eq!(message[..2], [0xc3, 0x01]);
+        assert_eq!(message[2..10], schema.fingerprint().to_le_bytes());
+        assert_eq!(message[10..], [0x02]);
+        assert_eq!(schema.decode_single_object(&message).unwrap(), value);
+
+        assert!(matches!(
+            other.decode_single_object(&message),
+            Err(AvroError::FingerprintMismatch { found, .. }) if found == schema.fingerprint()
+        ));
+        assert!(single_object_fingerprint(&message[1..]).is_err());
+        assert!(schema.decode(&[]).is_err());
+        assert!(schema.decode(&[0x02, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,870 @@
+//! Conversion between [`Schema`] and [`AvroSchema`].
+
+use super::{namespace_of, qualify, valid_name, AvroSchema, Decimal, Enum, Field, Fixed, Record};
+use crate::error::AvroError;
+use crate::registry::{Bundle, SchemaRegistry};
+use crate::schema::{LiteralValue, Schema, SchemaKind, StringFormat};
+use crate::value::Value;
+use crate::SchemaBuilder;
+use indexmap::IndexMap;
+use serde_json::Value as Json;
+use std::collections::{HashMap, HashSet};
+
+impl AvroSchema {
+    /// Converts `schema` to Avro, resolving `$ref`s through `registry`.
+    ///
+    /// `name` names the root when it becomes a named type, and its
+    /// namespace (the part before the last `.`) applies to every named type
+    /// created. Referenced definitions keep their registry names; other
+    /// nested records, enums and fixed types are named after the path to
+    /// them, such as `UserAddress` for the `address` property of `User`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AvroError::Unsupported`] for schemas with no Avro
+    /// equivalent, such as tuples, `Any` and functions, for property names
+    /// and `Enum` values that are not valid Avro names, and for `$ref`s that
+    /// do not resolve.
+    pub fn from_schema(
+        name: &str,
+        schema: &Schema,
+        registry: Option<&SchemaRegistry>,
+    ) -> Result<Self, AvroError> {
+        let mut converter = Converter {
+            registry,
+            namespace: namespace_of(name).to_string(),
+            taken: HashSet::new(),
+            named: HashMap::new(),
+        };
+        let hint = name.rsplit('.').next().unwrap_or(name);
+        converter.convert(schema, hint, "#")
+    }
+
+    /// Converts to a [`Bundle`] with a definition per record, enum and fixed
+    /// type, named by their names without namespace (or with the namespace
+    /// joined in, where two types share a name).
+    ///
+    /// Record fields are required unless their type is a union with `null`
+    /// and their default is `null`. Dates, times and timestamps become
+    /// strings with the matching format, and decimals strings with the
+    /// `decimal` format and a pattern bounding their digits.
+    pub fn to_bundle(&self) -> Bundle {
+        let mut declarations = Vec::new();
+        collect(self, &mut declarations);
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for declaration in &declarations {
+            *counts
+                .entry(simple_name(full_name(declaration)))
+                .or_default() += 1;
+        }
+        let names: HashMap<String, String> = declarations
+            .iter()
+            .map(|declaration| {
+                let full = full_name(declaration);
+                let simple = simple_name(full);
+                let name = if counts[simple] > 1 {
+                    full.replace('.', "")
+                } else {
+                    simple.to_string()
+                };
+                (full.to_string(), name)
+            })
+            .collect();
+
+        let definitions = declarations
+            .iter()
+            .map(|declaration| {
+                (
+                    names[full_name(declaration)].clone(),
+                    definition(declaration, &names),
+                )
+            })
+            .collect();
+        Bundle {
+            root: to_schema(self, &names),
+            definitions,
+        }
+    }
+}
+
+struct Converter<'a> {
+    registry: Option<&'a SchemaRegistry>,
+    /// Namespace of every named type created.
+    namespace: String,
+    /// Full names in use.
+    taken: HashSet<String>,
+    /// Full names of converted definitions, by `$ref` and by `$id`.
+    named: HashMap<String, String>,
+}
+
+impl Converter<'_> {
+    fn convert(
+        &mut self,
+        schema: &Schema,
+        hint: &str,
+        path: &str,
+    ) -> Result<AvroSchema, AvroError> {
+        let avro = match &schema.kind {
+            SchemaKind::Null => AvroSchema::Null,
+            SchemaKind::Bool => AvroSchema::Boolean,
+            SchemaKind::Int8 { .. }
+            | SchemaKind::Int16 { .. }
+            | SchemaKind::Int32 { .. }
+            | SchemaKind::UInt8 { .. }
+            | SchemaKind::UInt16 { .. } => AvroSchema::Int,
+            SchemaKind::Int64 { .. } | SchemaKind::UInt32 { .. } | SchemaKind::UInt64 { .. } => {
+                AvroSchema::Long
+            }
+            SchemaKind::Float32 { .. } => AvroSchema::Float,
+            SchemaKind::Float64 { .. } => AvroSchema::Double,
+            SchemaKind::String {
+                format, pattern, ..
+            } => match format {
+                Some(StringFormat::Date) => AvroSchema::Date,
+                Some(StringFormat::Time) => AvroSchema::TimeMillis,
+                Some(StringFormat::DateTime) => AvroSchema::TimestampMillis,
+                Some(StringFormat::Uuid) => AvroSchema::Uuid,
+                Some(StringFormat::Custom(format)) if format == "decimal" => {
+                    AvroSchema::Decimal(pattern.as_deref().and_then(parse_decimal_pattern).ok_or_else(|| {
+                        unsupported(path, "decimal strings need a pattern bounding their digits, such as `^-?\\d{1,8}(\\.\\d{1,2})?$`")
+                    })?)
+                }
+                _ => AvroSchema::String,
+            },
+            SchemaKind::Literal { value } => match value {
+                LiteralValue::String(_) => AvroSchema::String,
+                LiteralValue::Number(_) => AvroSchema::Long,
+                LiteralValue::Float(_) => AvroSchema::Double,
+                LiteralValue::Boolean(_) => AvroSchema::Boolean,
+                LiteralValue::Null => AvroSchema::Null,
+            },
+            SchemaKind::Array { items, .. } => AvroSchema::Array(Box::new(self.convert(
+                items,
+                &format!("{}Item", hint),
+                &format!("{}/items", path),
+            )?)),
+            SchemaKind::Object {
+                properties,
+                additional_properties: Some(values),
+                ..
+            } if properties.is_empty() => AvroSchema::Map(Box::new(self.convert(
+                values,
+                &format!("{}Value", hint),
+                &format!("{}/additionalProperties", path),
+            )?)),
+            SchemaKind::Union { any_of } => {
+                let mut branches: Vec<AvroSchema> = Vec::new();
+                for (i, variant) in any_of.iter().enumerate() {
+                    let branch = self.convert(
+                        variant,
+                        &format!("{}Variant{}", hint, i + 1),
+                        &format!("{}/anyOf/{}", path, i),
+                    )?;
+                    let flattened = match branch {
+                        AvroSchema::Union(nested) => nested,
+                        branch => vec![branch],
+                    };
+                    for branch in flattened {
+                        match branches.iter().find(|b| same_branch_type(b, &branch)) {
+                            Some(existing) if *existing == branch => {}
+                            Some(_) => {
+                                return Err(unsupported(
+                                    path,
+                                    &format!(
+                                        "Avro unions hold one `{}` branch at most",
+                                        branch.primitive_name()
+                                    ),
+                                ))
+                            }
+                            None => branches.push(branch),
+                        }
+                    }
+                }
+                AvroSchema::Union(branches)
+            }
+            SchemaKind::Ref { reference } => self.reference(reference, path)?,
+            SchemaKind::Named { name, schema } => self.convert(schema, name, path)?,
+            SchemaKind::Recursive { schema: inner } => {
+                let name = self.reserve(hint);
+                if let Some(id) = &schema.id {
+                    self.named.insert(id.clone(), name.clone());
+                }
+                self.named_type(inner, name, path)?
+            }
+            _ if is_named_type(schema) => {
+                let name = self.reserve(hint);
+                self.named_type(schema, name, path)?
+            }
+            kind => {
+                return Err(unsupported(
+                    path,
+                    &format!("`{}` has no Avro equivalent", kind.kind_name()),
+                ))
+            }
+        };
+        Ok(avro)
+    }
+
+    fn reference(&mut self, reference: &str, path: &str) -> Result<AvroSchema, AvroError> {
+        if let Some(name) = self.named.get(reference) {
+            return Ok(AvroSchema::Named(name.clone()));
+        }
+        let registry = self.registry.ok_or_else(|| {
+            unsupported(
+                path,
+                &format!("cannot resolve `{}` without a registry", reference),
+            )
+        })?;
+        let target = registry
+            .resolve_ref(reference, None)
+            .map_err(|e| unsupported(path, &e.to_string()))?;
+        let definition = reference
+            .strip_prefix("#/definitions/")
+            .or_else(|| reference.strip_prefix("#/$defs/"))
+            .unwrap_or(reference);
+        let path = format!("#/definitions/{}", definition);
+
+        let mut body = target;
+        while let SchemaKind::Named { schema, .. } = &body.kind {
+            body = schema;
+        }
+        match &body.kind {
+            SchemaKind::Recursive { schema: inner } => {
+                let name = self.reserve(definition);
+                self.named.insert(reference.to_string(), name.clone());
+                if let Some(id) = &body.id {
+                    self.named.insert(id.clone(), name.clone());
+                }
+                self.named_type(inner, name, &path)
+            }
+            _ if is_named_type(body) => {
+                let name = self.reserve(definition);
+                self.named.insert(reference.to_string(), name.clone());
+                self.named_type(body, name, &path)
+            }
+            _ => self.convert(body, definition, &path),
+        }
+    }
+
+    /// Converts a schema that becomes a record, enum or fixed type.
+    fn named_type(
+        &mut self,
+        schema: &Schema,
+        name: String,
+        path: &str,
+    ) -> Result<AvroSchema, AvroError> {
+        if let SchemaKind::Named { schema: inner, .. } = &schema.kind {
+            return self.named_type(inner, name, path);
+        }
+        match &schema.kind {
+            SchemaKind::Enum { values } => {
+                if let Some(value) = values.iter().find(|v| !valid_name(v)) {
+                    return Err(unsupported(
+                        path,
+                        &format!("`{}` is not a valid Avro enum symbol", value),
+                    ));
+                }
+                Ok(AvroSchema::Enum(Enum {
+                    name,
+                    doc: schema.description.clone(),
+                    symbols: values.clone(),
+                }))
+            }
+            SchemaKind::Bytes {
+                min_length: Some(size),
+                ..
+            } => Ok(AvroSchema::Fixed(Fixed {
+                name,
+                size: *size,
+                decimal: None,
+            })),
+            SchemaKind::Object { .. } | SchemaKind::Intersect { .. } => {
+                self.record(schema, name, path)
+            }
+            kind => Err(unsupported(
+                path,
+                &format!(
+                    "recursive `{}` schemas have no Avro equivalent",
+                    kind.kind_name()
+                ),
+            )),
+        }
+    }
+
+    fn record(
+        &mut self,
+        schema: &Schema,
+        name: String,
+        path: &str,
+    ) -> Result<AvroSchema, AvroError> {
+        let mut properties = IndexMap::new();
+        let mut required = HashSet::new();
+        self.merge(schema, &mut properties, &mut required, path)?;
+
+        let simple = simple_name(&name).to_string();
+        let mut fields = Vec::with_capacity(properties.len());
+        for (prop, prop_schema) in properties {
+            let prop_path = format!("{}/properties/{}", path, prop);
+            if !valid_name(&prop) {
+                return Err(unsupported(
+                    &prop_path,
+                    &format!("`{}` is not a valid Avro field name", prop),
+                ));
+            }
+            let hint = format!("{}{}", simple, pascal_case(&prop));
+            let converted = self.convert(&prop_schema, &hint, &prop_path)?;
+            let (field_schema, default) = if required.contains(&prop) {
+                let default = prop_schema
+                    .default
+                    .as_ref()
+                    .and_then(|value| default_json(value, &converted));
+                (converted, default)
+            } else {
+                let mut branches = match converted {
+                    AvroSchema::Union(branches) => branches,
+                    other => vec![other],
+                };
+                branches.retain(|b| *b != AvroSchema::Null);
+                branches.insert(0, AvroSchema::Null);
+                (AvroSchema::Union(branches), Some(Json::Null))
+            };
+            fields.push(Field {
+                name: prop,
+                doc: prop_schema.description.clone(),
+                schema: field_schema,
+                default,
+            });
+        }
+        Ok(AvroSchema::Record(Record {
+            name,
+            doc: schema.description.clone(),
+            fields,
+        }))
+    }
+
+    /// Collects the properties of an object, or of every object in an
+    /// intersection, keeping the first schema of each property.
+    fn merge(
+        &self,
+        schema: &Schema,
+        properties: &mut IndexMap<String, Schema>,
+        required: &mut HashSet<String>,
+        path: &str,
+    ) -> Result<(), AvroError> {
+        match &schema.kind {
+            SchemaKind::Object {
+                properties: own,
+                required: own_required,
+                ..
+            } => {
+                for (name, prop) in own {
+                    properties
+                        .entry(name.clone())
+                        .or_insert_with(|| prop.clone());
+                }
+                required.extend(own_required.iter().cloned());
+                Ok(())
+            }
+            SchemaKind::Intersect { all_of } => {
+                for (i, member) in all_of.iter().enumerate() {
+                    self.merge(
+                        member,
+                        properties,
+                        required,
+                        &format!("{}/allOf/{}", path, i),
+                    )?;
+                }
+                Ok(())
+            }
+            SchemaKind::Named { schema, .. } | SchemaKind::Recursive { schema } => {
+                self.merge(schema, properties, required, path)
+            }
+            SchemaKind::Ref { reference } => {
+                let target = self
+                    .registry
+                    .ok_or_else(|| {
+                        unsupported(
+                            path,
+                            &format!("cannot resolve `{}` without a registry", reference),
+                        )
+                    })?
+                    .resolve_ref(reference, None)
+                    .map_err(|e| unsupported(path, &e.to_string()))?;
+                self.merge(target, properties, required, path)
+            }
+            kind => Err(unsupported(
+                path,
+                &format!("cannot intersect `{}` into a record", kind.kind_name()),
+            )),
+        }
+    }
+
+    /// Claims a unique full name for a type named after `hint`.
+    fn reserve(&mut self, hint: &str) -> String {
+        let mut simple: String = hint
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !simple.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            simple.insert(0, '_');
+        }
+        let mut candidate = qualify(&simple, &self.namespace);
+        let mut n = 2;
+        while !self.taken.insert(candidate.clone()) {
+            candidate = qualify(&format!("{}{}", simple, n), &self.namespace);
+            n += 1;
+        }
+        candidate
+    }
+}
+
+/// Whether `schema` converts to a record, enum or fixed type.
+fn is_named_type(schema: &Schema) -> bool {
+    match &schema.kind {
+        SchemaKind::Object {
+            properties,
+            additional_properties,
+            ..
+        } => !properties.is_empty() || additional_properties.is_none(),
+        SchemaKind::Intersect { .. } | SchemaKind::Enum { .. } => true,
+        SchemaKind::Bytes {
+            min_length,
+            max_length,
+        } => min_length.is_some() && min_length == max_length,
+        SchemaKind::Named { schema, .. } => is_named_type(schema),
+        _ => false,
+    }
+}
+
+/// Whether Avro forbids `a` and `b` in the same union: unnamed types may
+/// appear once per type, named types once per name.
+fn same_branch_type(a: &AvroSchema, b: &AvroSchema) -> bool {
+    let name = |schema: &AvroSchema| match schema {
+        AvroSchema::Record(Record { name, .. })
+        | AvroSchema::Enum(Enum { name, .. })
+        | AvroSchema::Fixed(Fixed { name, .. })
+        | AvroSchema::Named(name) => Some(name.clone()),
+        _ => None,
+    };
+    match (name(a), name(b)) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => a.primitive_name() == b.primitive_name(),
+        _ => false,
+    }
+}
+
+/// A schema default as an Avro field default, which for a union must
+/// match its first branch.
+fn default_json(value: &Value, schema: &AvroSchema) -> Option<Json> {
+    match (value, schema) {
+        (Value::Bytes(bytes), AvroSchema::Bytes | AvroSchema::Fixed(_)) => {
+            Some(Json::String(bytes.iter().map(|b| *b as char).collect()))
+        }
+        (Value::Null, AvroSchema::Union(branches))
+            if branches.first() == Some(&AvroSchema::Null) =>
+        {
+            Some(Json::Null)
+        }
+        (_, AvroSchema::Union(_)) => None,
+        (value, _) => Some(value.to_json()),
+    }
+}
+
+fn collect<'a>(schema: &'a AvroSchema, out: &mut Vec<&'a AvroSchema>) {
+    match schema {
+        AvroSchema::Array(inner) | AvroSchema::Map(inner) => collect(inner, out),
+        AvroSchema::Union(branches) => branches.iter().for_each(|b| collect(b, out)),
+        AvroSchema::Record(record) => {
+            out.push(schema);
+            record.fields.iter().for_each(|f| collect(&f.schema, out));
+        }
+        AvroSchema::Enum(_) | AvroSchema::Fixed(_) => out.push(schema),
+        _ => {}
+    }
+}
+
+fn full_name(declaration: &AvroSchema) -> &str {
+    match declaration {
+        AvroSchema::Record(Record { name, .. })
+        | AvroSchema::Enum(Enum { name, .. })
+        | AvroSchema::Fixed(Fixed { name, .. })
+        | AvroSchema::Named(name) => name,
+        _ => "",
+    }
+}
+
+fn simple_name(full: &str) -> &str {
+    full.rsplit('.').next().unwrap_or(full)
+}
+
+/// The definition of a record, enum or fixed type.
+fn definition(declaration: &AvroSchema, names: &HashMap<String, String>) -> Schema {
+    match declaration {
+        AvroSchema::Record(record) => {
+            let mut properties = IndexMap::new();
+            let mut required = Vec::new();
+            for field in &record.fields {
+                let mut schema = to_schema(&field.schema, names);
+                let nullable = matches!(
+                    &field.schema,
+                    AvroSchema::Union(branches) if branches.first() == Some(&AvroSchema::Null)
+                );
+                match &field.default {
+                    Some(Json::Null) if nullable => {}
+                    default => {
+                        required.push(field.name.clone());
+                        schema.default = default.as_ref().and_then(|json| match &field.schema {
+                            AvroSchema::Bytes | AvroSchema::Fixed(_) => json
+                                .as_str()
+                                .map(|s| Value::Bytes(s.chars().map(|c| c as u8).collect())),
+                            _ => Value::from_json(json.clone(), &schema).ok(),
+                        });
+                    }
+                }
+                schema.description = field.doc.clone();
+                properties.insert(field.name.clone(), schema);
+            }
+            let mut schema = Schema::new(SchemaKind::Object {
+                properties,
+                required,
+                additional_properties: None,
+            });
+            schema.description = record.doc.clone();
+            schema
+        }
+        AvroSchema::Enum(declaration) => {
+            let mut schema = Schema::new(SchemaKind::Enum {
+                values: declaration.symbols.clone(),
+            });
+            schema.description = declaration.doc.clone();
+            schema
+        }
+        AvroSchema::Fixed(Fixed {
+            decimal: Some(decimal),
+            ..
+        }) => decimal_schema(*decimal),
+        AvroSchema::Fixed(fixed) => Schema::new(SchemaKind::Bytes {
+            min_length: Some(fixed.size),
+            max_length: Some(fixed.size),
+        }),
+        other => to_schema(other, names),
+    }
+}
+
+fn to_schema(schema: &AvroSchema, names: &HashMap<String, String>) -> Schema {
+    let string = |format| SchemaBuilder::string().format(format).build();
+    match schema {
+        AvroSchema::Null => SchemaBuilder::null(),
+        AvroSchema::Boolean => SchemaBuilder::bool(),
+        AvroSchema::Int => SchemaBuilder::int32(),
+        AvroSchema::Long => SchemaBuilder::int64(),
+        AvroSchema::Float => SchemaBuilder::float32(),
+        AvroSchema::Double => SchemaBuilder::float64(),
+        AvroSchema::Bytes => SchemaBuilder::bytes(),
+        AvroSchema::String => SchemaBuilder::string().build(),
+        AvroSchema::Array(items) => SchemaBuilder::array(to_schema(items, names)).build(),
+        AvroSchema::Map(values) => SchemaBuilder::object()
+            .additional_properties(Some(to_schema(values, names)))
+            .build(),
+        AvroSchema::Union(branches) => {
+            SchemaBuilder::union(branches.iter().map(|b| to_schema(b, names)).collect())
+        }
+        AvroSchema::Record(_)
+        | AvroSchema::Enum(_)
+        | AvroSchema::Fixed(_)
+        | AvroSchema::Named(_) => {
+            let full = full_name(schema);
+            SchemaBuilder::r#ref(names.get(full).map_or(full, String::as_str))
+        }
+        AvroSchema::Date => string(StringFormat::Date),
+        AvroSchema::TimeMillis | AvroSchema::TimeMicros => string(StringFormat::Time),
+        AvroSchema::TimestampMillis | AvroSchema::TimestampMicros => string(StringFormat::DateTime),
+        AvroSchema::Uuid => string(StringFormat::Uuid),
+        AvroSchema::Decimal(decimal) => decimal_schema(*decimal),
+    }
+}
+
+fn decimal_schema(decimal: Decimal) -> Schema {
+    SchemaBuilder::string()
+        .format(StringFormat::Custom("decimal".to_string()))
+        .pattern(&decimal_pattern(decimal))
+        .build()
+}
+
+/// The pattern of decimal strings with `decimal`'s precision and scale.
+fn decimal_pattern(decimal: Decimal) -> String {
+    let Decimal { precision, scale } = decimal;
+    if scale == 0 {
+        format!(r"^-?\d{{1,{}}}$", precision)
+    } else if scale == precision {
+        format!(r"^-?0(\.\d{{1,{}}})?$", scale)
+    } else {
+        format!(r"^-?\d{{1,{}}}(\.\d{{1,{}}})?$", precision - scale, scale)
+    }
+}
+
+/// The precision and scale of a pattern written by [`decimal_pattern`].
+fn parse_decimal_pattern(pattern: &str) -> Option<Decimal> {
+    let body = pattern.strip_prefix("^-?")?.strip_suffix('$')?;
+    let fraction = |rest: &str| -> Option<u32> {
+        rest.strip_prefix(r"(\.\d{1,")?
+            .strip_suffix("})?")?
+            .parse()
+            .ok()
+    };
+    if let Some(rest) = body.strip_prefix('0') {
+        let scale = fraction(rest)?;
+        return Some(Decimal {
+            precision: scale,
+            scale,
+        });
+    }
+    let (digits, rest) = body.strip_prefix(r"\d{1,")?.split_once('}')?;
+    let digits: u32 = digits.parse().ok()?;
+    let scale = if rest.is_empty() { 0 } else { fraction(rest)? };
+    Some(Decimal {
+        precision: digits + scale,
+        scale,
+    })
+}
+
+/// `UserAddress`-style names from property names.
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+fn unsupported(path: &str, message: &str) -> AvroError {
+    AvroError::Unsupported {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_schema() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Status",
+            SchemaBuilder::enum_values(vec!["ACTIVE", "BANNED"]),
+        );
+        registry.register(
+            "Node",
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int32())
+                .optional_field("next", SchemaBuilder::r#ref("Node"))
+                .build(),
+        );
+        let user = SchemaBuilder::object()
+            .field("id", SchemaBuilder::uint32())
+            .field(
+                "created",
+                SchemaBuilder::string()
+                    .format(StringFormat::DateTime)
+                    .build(),
+            )
+            .field(
+                "balance",
+                SchemaBuilder::string()
+                    .format(StringFormat::Custom("decimal".into()))
+                    .pattern(r"^-?\d{1,8}(\.\d{1,2})?$")
+                    .build(),
+            )
+            .field("status", SchemaBuilder::r#ref("Status"))
+            .field(
+                "address",
+                SchemaBuilder::object()
+                    .field("city", SchemaBuilder::string().build())
+                    .build(),
+            )
+            .field(
+                "tags",
+                SchemaBuilder::object()
+                    .additional_properties(Some(SchemaBuilder::string().build()))
+                    .build(),
+            )
+            .field("list", SchemaBuilder::r#ref("Node"))
+            .optional_field(
+                "previous",
+                SchemaBuilder::optional(SchemaBuilder::r#ref("Status")),
+            )
+            .build()
+            .with_description("A user.");
+
+        let avro = AvroSchema::from_schema("com.example.User", &user, Some(&registry)).unwrap();
+        assert_eq!(
+            avro.to_json(),
+            json!({
+                "type": "record",
+                "name": "com.example.User",
+                "doc": "A user.",
+                "fields": [
+                    {"name": "id", "type": "long"},
+                    {"name": "created", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+                    {"name": "balance", "type": {"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}},
+                    {"name": "status", "type": {"type": "enum", "name": "com.example.Status", "symbols": ["ACTIVE", "BANNED"]}},
+                    {"name": "address", "type": {
+                        "type": "record",
+                        "name": "com.example.UserAddress",
+                        "fields": [{"name": "city", "type": "string"}]
+                    }},
+                    {"name": "tags", "type": {"type": "map", "values": "string"}},
+                    {"name": "list", "type": {
+                        "type": "record",
+                        "name": "com.example.Node",
+                        "fields": [
+                            {"name": "value", "type": "int"},
+                            {"name": "next", "type": ["null", "com.example.Node"], "default": null}
+                        ]
+                    }},
+                    {"name": "previous", "type": ["null", "com.example.Status"], "default": null}
+                ]
+            })
+        );
+
+        let bundle = avro.to_bundle();
+        assert_eq!(bundle.root, SchemaBuilder::r#ref("User"));
+        let names: Vec<&str> = bundle.definitions.keys().map(String::as_str).collect();
+        assert_eq!(names, ["User", "Status", "UserAddress", "Node"]);
+        assert_eq!(
+            bundle.definitions["Status"],
+            registry.get("Status").unwrap().clone()
+        );
+        let SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } = &bundle.definitions["Node"].kind
+        else {
+            panic!("expected an object");
+        };
+        assert_eq!(required, &["value"]);
+        assert_eq!(
+            properties["next"],
+            SchemaBuilder::union(vec![SchemaBuilder::null(), SchemaBuilder::r#ref("Node")])
+        );
+
+        // Logical types convert back to the formats they came from.
+        let (
+            SchemaKind::Object { properties, .. },
+            SchemaKind::Object {
+                properties: original,
+                ..
+            },
+        ) = (&bundle.definitions["User"].kind, &user.kind)
+        else {
+            panic!("expected objects");
+        };
+        assert_eq!(properties["balance"].kind, original["balance"].kind);
+        assert_eq!(properties["created"].kind, original["created"].kind);
+
+        // Decoded values check against the original schema.
+        let value = Value::object()
+            .field("id", Value::int64(7))
+            .field("created", Value::string("2024-05-01T12:00:00Z"))
+            .field("balance", Value::string("10.25"))
+            .field("status", Value::string("ACTIVE"))
+            .field(
+                "address",
+                Value::object().field("city", Value::string("Oslo")).build(),
+            )
+            .field("tags", Value::object().build())
+            .field(
+                "list",
+                Value::object()
+                    .field("value", Value::int64(1))
+                    .field(
+                        "next",
+                        Value::object().field("value", Value::int64(2)).build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let decoded = avro.decode(&avro.encode(&value).unwrap()).unwrap();
+        assert_eq!(decoded, value);
+        assert!(crate::check_with_registry(&user, &decoded, Some(&registry)));
+    }
+
+    #[test]
+    fn test_to_bundle_optional_fields_and_defaults() {
+        let avro = AvroSchema::parse_str(
+            r#"{"type": "record", "name": "a.Event", "fields": [
+                {"name": "kind", "type": "string", "default": "click"},
+                {"name": "at", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+                {"name": "user", "type": ["null", {"type": "record", "name": "b.Event", "fields": []}], "default": null},
+                {"name": "hash", "type": {"type": "fixed", "name": "Hash", "size": 16}}
+            ]}"#,
+        )
+        .unwrap();
+        let bundle = avro.to_bundle();
+        let names: Vec<&str> = bundle.definitions.keys().map(String::as_str).collect();
+        assert_eq!(names, ["aEvent", "bEvent", "Hash"]);
+
+        let SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } = &bundle.definitions["aEvent"].kind
+        else {
+            panic!("expected an object");
+        };
+        assert_eq!(required, &["kind", "at", "hash"]);
+        assert_eq!(properties["kind"].default, Some(Value::string("click")));
+        assert_eq!(
+            properties["user"].kind,
+            SchemaBuilder::union(vec![SchemaBuilder::null(), SchemaBuilder::r#ref("bEvent")]).kind
+        );
+        assert_eq!(
+            bundle.definitions["Hash"].kind,
+            SchemaKind::Bytes {
+                min_length: Some(16),
+                max_length: Some(16)
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_schema_errors() {
+        let tuple = SchemaBuilder::object()
+            .field(
+                "pair",
+                SchemaBuilder::tuple(vec![SchemaBuilder::int32(), SchemaBuilder::int32()]),
+            )
+            .build();
+        assert_eq!(
+            AvroSchema::from_schema("T", &tuple, None)
+                .unwrap_err()
+                .to_string(),
+            "Unsupported schema at #/properties/pair: `Tuple` has no Avro equivalent"
+        );
+        let union = SchemaBuilder::union(vec![
+            SchemaBuilder::int32(),
+            SchemaBuilder::string().build(),
+            SchemaBuilder::int8(),
+        ]);
+        assert!(AvroSchema::from_schema("U", &union, None).is_ok());
+        let union = SchemaBuilder::union(vec![
+            SchemaBuilder::int64(),
+            SchemaBuilder::string()
+                .format(StringFormat::DateTime)
+                .build(),
+        ]);
+        assert!(AvroSchema::from_schema("U", &union, None).is_err());
+        let dashed = SchemaBuilder::enum_values(vec!["in-progress"]);
+        assert!(AvroSchema::from_schema("E", &dashed, None).is_err());
+    }
+}
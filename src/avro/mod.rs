@@ -0,0 +1,751 @@
+//! Apache Avro schemas and binary encoding.
+//!
+//! An [`AvroSchema`] is built from a [`Schema`](crate::Schema) with
+//! [`AvroSchema::from_schema`] or parsed from Avro schema JSON with
+//! [`AvroSchema::parse`], and converts back with [`AvroSchema::to_bundle`].
+//! It encodes [`Value`](crate::Value)s in Avro's binary format, either bare
+//! ([`AvroSchema::encode`]) or in the single-object encoding, which prefixes
+//! the body with the schema's [`fingerprint`](AvroSchema::fingerprint) so a
+//! reader holding several schemas can tell which one wrote a message.
+//!
+//! | `SchemaKind` | Avro |
+//! |---|---|
+//! | `Null`, `Bool` | `null`, `boolean` |
+//! | `Int8`, `Int16`, `Int32`, `UInt8`, `UInt16` | `int` |
+//! | `Int64`, `UInt32`, `UInt64` | `long` |
+//! | `Float32`, `Float64` | `float`, `double` |
+//! | `String` | `string`; `date`, `time-millis`, `timestamp-millis` and `uuid` logical types for those formats |
+//! | `String` with the `decimal` format | `bytes` with the `decimal` logical type |
+//! | `Bytes` | `bytes`, or `fixed` when its length is exact |
+//! | `Array` | `array` |
+//! | `Object` | `record`, or `map` when it only has additional properties |
+//! | `Enum` | `enum` |
+//! | `Union` | union, in the same order |
+//! | `Intersect` of objects | `record` of the merged properties |
+//! | `Ref`, `Named`, `Recursive` | the named type it resolves to |
+//!
+//! Properties that are not required become fields of a union with `null`
+//! first, defaulting to `null`; they are encoded as `null` when absent, and
+//! decoded `null`s with a `null` default are left out of the decoded object.
+//! Logical types are written as strings in values: dates as `2024-05-01`,
+//! timestamps as RFC 3339 in UTC, times as `12:30:00.250`, and decimals as
+//! `-12.50`. A decimal is a string with the `decimal` format whose pattern,
+//! such as `^-?\d{1,8}(\.\d{1,2})?$` for precision 10 and scale 2, bounds
+//! its digits; [`AvroSchema::to_bundle`] produces that form.
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::avro::AvroSchema;
+//! use typebox::{SchemaBuilder, Value};
+//!
+//! let user = SchemaBuilder::object()
+//!     .field("id", SchemaBuilder::int64())
+//!     .optional_field("email", SchemaBuilder::string().build())
+//!     .build();
+//! let avro = AvroSchema::from_schema("com.example.User", &user, None).unwrap();
+//! assert_eq!(
+//!     avro.canonical_form(),
+//!     r#"{"name":"com.example.User","type":"record","fields":[{"name":"id","type":"long"},{"name":"email","type":["null","string"]}]}"#
+//! );
+//!
+//! let value = Value::object().field("id", Value::int64(1)).build();
+//! let bytes = avro.encode_single_object(&value).unwrap();
+//! assert_eq!(avro.decode_single_object(&bytes).unwrap(), value);
+//! ```
+
+mod binary;
+mod convert;
+
+pub use binary::single_object_fingerprint;
+
+use crate::error::AvroError;
+use serde_json::{json, Map, Value as Json};
+use std::collections::HashSet;
+
+/// An Avro schema.
+///
+/// Names of records, enums and fixed types are full names, including their
+/// namespace. A named type is defined where it first appears and referred
+/// to by [`AvroSchema::Named`] after that.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvroSchema {
+    /// `null`.
+    Null,
+    /// `boolean`.
+    Boolean,
+    /// 32-bit `int`.
+    Int,
+    /// 64-bit `long`.
+    Long,
+    /// 32-bit `float`.
+    Float,
+    /// 64-bit `double`.
+    Double,
+    /// `bytes`.
+    Bytes,
+    /// `string`.
+    String,
+    /// `array` of items.
+    Array(Box<AvroSchema>),
+    /// `map` from strings to values.
+    Map(Box<AvroSchema>),
+    /// Union of branches, tried in order when encoding.
+    Union(Vec<AvroSchema>),
+    /// `record`.
+    Record(Record),
+    /// `enum`.
+    Enum(Enum),
+    /// `fixed`.
+    Fixed(Fixed),
+    /// Reference by full name to a type defined earlier in the schema.
+    Named(String),
+    /// `int` with the `date` logical type: days since 1970-01-01.
+    Date,
+    /// `int` with the `time-millis` logical type.
+    TimeMillis,
+    /// `long` with the `time-micros` logical type.
+    TimeMicros,
+    /// `long` with the `timestamp-millis` logical type.
+    TimestampMillis,
+    /// `long` with the `timestamp-micros` logical type.
+    TimestampMicros,
+    /// `string` with the `uuid` logical type.
+    Uuid,
+    /// `bytes` with the `decimal` logical type.
+    Decimal(Decimal),
+}
+
+/// An Avro `record`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Full name.
+    pub name: String,
+    /// Documentation.
+    pub doc: Option<String>,
+    /// Fields, in encoding order.
+    pub fields: Vec<Field>,
+}
+
+/// A field of a [`Record`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// Field name.
+    pub name: String,
+    /// Documentation.
+    pub doc: Option<String>,
+    /// Field type.
+    pub schema: AvroSchema,
+    /// Default used when the field is missing, as Avro JSON.
+    pub default: Option<Json>,
+}
+
+/// An Avro `enum`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    /// Full name.
+    pub name: String,
+    /// Documentation.
+    pub doc: Option<String>,
+    /// Symbols, in encoding order.
+    pub symbols: Vec<String>,
+}
+
+/// An Avro `fixed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixed {
+    /// Full name.
+    pub name: String,
+    /// Size in bytes.
+    pub size: usize,
+    /// The `decimal` logical type, if the bytes hold one.
+    pub decimal: Option<Decimal>,
+}
+
+/// Precision and scale of a `decimal` logical type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decimal {
+    /// Maximum number of digits.
+    pub precision: u32,
+    /// Number of digits after the decimal point.
+    pub scale: u32,
+}
+
+impl AvroSchema {
+    /// Parses Avro schema JSON.
+    ///
+    /// Unknown attributes are ignored, as are logical types that are not
+    /// listed on [`AvroSchema`] or do not annotate their base type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AvroError::InvalidSchema`] when the JSON is not a valid
+    /// Avro schema, including references to undefined names.
+    pub fn parse(json: &Json) -> Result<Self, AvroError> {
+        Parser::default().schema(json, "", "#")
+    }
+
+    /// Parses `text` and converts it as [`parse`](Self::parse) does.
+    pub fn parse_str(text: &str) -> Result<Self, AvroError> {
+        Self::parse(&serde_json::from_str(text)?)
+    }
+
+    /// The Avro schema JSON.
+    pub fn to_json(&self) -> Json {
+        self.json("")
+    }
+
+    fn json(&self, namespace: &str) -> Json {
+        // Names are written in full, with an empty namespace where a bare
+        // name would otherwise inherit the enclosing one.
+        let named = |kind: &str, name: &str, doc: &Option<String>| {
+            let mut object = Map::new();
+            object.insert("type".into(), json!(kind));
+            object.insert("name".into(), json!(name));
+            if !name.contains('.') && !namespace.is_empty() {
+                object.insert("namespace".into(), json!(""));
+            }
+            if let Some(doc) = doc {
+                object.insert("doc".into(), json!(doc));
+            }
+            object
+        };
+        match self {
+            AvroSchema::Array(items) => json!({"type": "array", "items": items.json(namespace)}),
+            AvroSchema::Map(values) => json!({"type": "map", "values": values.json(namespace)}),
+            AvroSchema::Union(branches) => {
+                Json::Array(branches.iter().map(|b| b.json(namespace)).collect())
+            }
+            AvroSchema::Record(record) => {
+                let mut object = named("record", &record.name, &record.doc);
+                let inner = namespace_of(&record.name);
+                let fields = record
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let mut object = Map::new();
+                        object.insert("name".into(), json!(field.name));
+                        if let Some(doc) = &field.doc {
+                            object.insert("doc".into(), json!(doc));
+                        }
+                        object.insert("type".into(), field.schema.json(inner));
+                        if let Some(default) = &field.default {
+                            object.insert("default".into(), default.clone());
+                        }
+                        Json::Object(object)
+                    })
+                    .collect();
+                object.insert("fields".into(), Json::Array(fields));
+                Json::Object(object)
+            }
+            AvroSchema::Enum(declaration) => {
+                let mut object = named("enum", &declaration.name, &declaration.doc);
+                object.insert("symbols".into(), json!(declaration.symbols));
+                Json::Object(object)
+            }
+            AvroSchema::Fixed(fixed) => {
+                let mut object = named("fixed", &fixed.name, &None);
+                object.insert("size".into(), json!(fixed.size));
+                if let Some(decimal) = fixed.decimal {
+                    object.insert("logicalType".into(), json!("decimal"));
+                    object.insert("precision".into(), json!(decimal.precision));
+                    object.insert("scale".into(), json!(decimal.scale));
+                }
+                Json::Object(object)
+            }
+            AvroSchema::Named(name) if !name.contains('.') && !namespace.is_empty() => {
+                // A bare name would resolve in the enclosing namespace.
+                json!(format!(".{}", name))
+            }
+            AvroSchema::Named(name) => json!(name),
+            AvroSchema::Decimal(decimal) => json!({
+                "type": "bytes",
+                "logicalType": "decimal",
+                "precision": decimal.precision,
+                "scale": decimal.scale,
+            }),
+            _ => match self.logical_type() {
+                Some(logical) => json!({"type": self.primitive_name(), "logicalType": logical}),
+                None => json!(self.primitive_name()),
+            },
+        }
+    }
+
+    /// The Parsing Canonical Form: the schema JSON without whitespace,
+    /// documentation, defaults or logical types, with full names and a
+    /// fixed attribute order. Schemas that read and write data the same
+    /// way share it.
+    pub fn canonical_form(&self) -> String {
+        let mut out = String::new();
+        self.write_canonical(&mut out);
+        out
+    }
+
+    fn write_canonical(&self, out: &mut String) {
+        let quote = |text: &str| Json::from(text).to_string();
+        match self {
+            AvroSchema::Array(items) => {
+                out.push_str(r#"{"type":"array","items":"#);
+                items.write_canonical(out);
+                out.push('}');
+            }
+            AvroSchema::Map(values) => {
+                out.push_str(r#"{"type":"map","values":"#);
+                values.write_canonical(out);
+                out.push('}');
+            }
+            AvroSchema::Union(branches) => {
+                out.push('[');
+                for (i, branch) in branches.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    branch.write_canonical(out);
+                }
+                out.push(']');
+            }
+            AvroSchema::Record(record) => {
+                out.push_str(&format!(
+                    r#"{{"name":{},"type":"record","fields":["#,
+                    quote(&record.name)
+                ));
+                for (i, field) in record.fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(&format!(r#"{{"name":{},"type":"#, quote(&field.name)));
+                    field.schema.write_canonical(out);
+                    out.push('}');
+                }
+                out.push_str("]}");
+            }
+            AvroSchema::Enum(declaration) => {
+                let symbols: Vec<String> = declaration.symbols.iter().map(|s| quote(s)).collect();
+                out.push_str(&format!(
+                    r#"{{"name":{},"type":"enum","symbols":[{}]}}"#,
+                    quote(&declaration.name),
+                    symbols.join(",")
+                ));
+            }
+            AvroSchema::Fixed(fixed) => out.push_str(&format!(
+                r#"{{"name":{},"type":"fixed","size":{}}}"#,
+                quote(&fixed.name),
+                fixed.size
+            )),
+            AvroSchema::Named(name) => out.push_str(&quote(name)),
+            _ => out.push_str(&quote(self.primitive_name())),
+        }
+    }
+
+    /// The CRC-64-AVRO (Rabin) fingerprint of the
+    /// [canonical form](Self::canonical_form), which identifies the schema
+    /// in single-object encoded messages.
+    pub fn fingerprint(&self) -> u64 {
+        rabin(self.canonical_form().as_bytes())
+    }
+
+    /// The Avro name of a primitive or of a logical type's base type.
+    fn primitive_name(&self) -> &'static str {
+        match self {
+            AvroSchema::Null => "null",
+            AvroSchema::Boolean => "boolean",
+            AvroSchema::Int | AvroSchema::Date | AvroSchema::TimeMillis => "int",
+            AvroSchema::Long
+            | AvroSchema::TimeMicros
+            | AvroSchema::TimestampMillis
+            | AvroSchema::TimestampMicros => "long",
+            AvroSchema::Float => "float",
+            AvroSchema::Double => "double",
+            AvroSchema::Bytes | AvroSchema::Decimal(_) => "bytes",
+            AvroSchema::String | AvroSchema::Uuid => "string",
+            AvroSchema::Array(_) => "array",
+            AvroSchema::Map(_) => "map",
+            AvroSchema::Union(_) => "union",
+            AvroSchema::Record(_) => "record",
+            AvroSchema::Enum(_) => "enum",
+            AvroSchema::Fixed(_) => "fixed",
+            AvroSchema::Named(_) => "named",
+        }
+    }
+
+    fn logical_type(&self) -> Option<&'static str> {
+        match self {
+            AvroSchema::Date => Some("date"),
+            AvroSchema::TimeMillis => Some("time-millis"),
+            AvroSchema::TimeMicros => Some("time-micros"),
+            AvroSchema::TimestampMillis => Some("timestamp-millis"),
+            AvroSchema::TimestampMicros => Some("timestamp-micros"),
+            AvroSchema::Uuid => Some("uuid"),
+            AvroSchema::Decimal(_) => Some("decimal"),
+            _ => None,
+        }
+    }
+}
+
+/// Reads Avro schema JSON, tracking the names defined so far.
+#[derive(Default)]
+struct Parser {
+    names: HashSet<String>,
+}
+
+impl Parser {
+    fn schema(
+        &mut self,
+        json: &Json,
+        namespace: &str,
+        path: &str,
+    ) -> Result<AvroSchema, AvroError> {
+        match json {
+            Json::String(name) => self.reference(name, namespace, path),
+            Json::Array(branches) => {
+                let mut parsed = Vec::with_capacity(branches.len());
+                for (i, branch) in branches.iter().enumerate() {
+                    let branch = self.schema(branch, namespace, &format!("{}/{}", path, i))?;
+                    if matches!(branch, AvroSchema::Union(_)) {
+                        return Err(invalid(path, "unions may not immediately contain unions"));
+                    }
+                    parsed.push(branch);
+                }
+                Ok(AvroSchema::Union(parsed))
+            }
+            Json::Object(object) => self.object(object, namespace, path),
+            _ => Err(invalid(path, "expected a name, a union or a schema object")),
+        }
+    }
+
+    fn object(
+        &mut self,
+        object: &Map<String, Json>,
+        namespace: &str,
+        path: &str,
+    ) -> Result<AvroSchema, AvroError> {
+        let kind = match object.get("type") {
+            Some(Json::String(kind)) => kind.as_str(),
+            Some(nested) => return self.schema(nested, namespace, &format!("{}/type", path)),
+            None => return Err(invalid(path, "missing `type`")),
+        };
+        let logical = object.get("logicalType").and_then(Json::as_str);
+        let schema = match (kind, logical) {
+            ("int", Some("date")) => AvroSchema::Date,
+            ("int", Some("time-millis")) => AvroSchema::TimeMillis,
+            ("long", Some("time-micros")) => AvroSchema::TimeMicros,
+            ("long", Some("timestamp-millis")) => AvroSchema::TimestampMillis,
+            ("long", Some("timestamp-micros")) => AvroSchema::TimestampMicros,
+            ("string", Some("uuid")) => AvroSchema::Uuid,
+            ("bytes", Some("decimal")) => match decimal(object) {
+                Some(decimal) => AvroSchema::Decimal(decimal),
+                None => AvroSchema::Bytes,
+            },
+            ("array", _) => {
+                let items = object
+                    .get("items")
+                    .ok_or_else(|| invalid(path, "array without `items`"))?;
+                AvroSchema::Array(Box::new(self.schema(
+                    items,
+                    namespace,
+                    &format!("{}/items", path),
+                )?))
+            }
+            ("map", _) => {
+                let values = object
+                    .get("values")
+                    .ok_or_else(|| invalid(path, "map without `values`"))?;
+                AvroSchema::Map(Box::new(self.schema(
+                    values,
+                    namespace,
+                    &format!("{}/values", path),
+                )?))
+            }
+            ("record" | "error", _) => {
+                let (name, inner) = self.define(object, namespace, path)?;
+                let fields = object
+                    .get("fields")
+                    .and_then(Json::as_array)
+                    .ok_or_else(|| invalid(path, "record without `fields`"))?;
+                let mut parsed = Vec::with_capacity(fields.len());
+                for (i, field) in fields.iter().enumerate() {
+                    let field_path = format!("{}/fields/{}", path, i);
+                    let name = field
+                        .get("name")
+                        .and_then(Json::as_str)
+                        .filter(|name| valid_name(name))
+                        .ok_or_else(|| invalid(&field_path, "field without a valid `name`"))?;
+                    if parsed.iter().any(|f: &Field| f.name == name) {
+                        return Err(invalid(&field_path, &format!("duplicate field `{}`", name)));
+                    }
+                    let schema = field
+                        .get("type")
+                        .ok_or_else(|| invalid(&field_path, "field without `type`"))?;
+                    parsed.push(Field {
+                        name: name.to_string(),
+                        doc: doc(field.get("doc")),
+                        schema: self.schema(schema, &inner, &format!("{}/type", field_path))?,
+                        default: field.get("default").cloned(),
+                    });
+                }
+                AvroSchema::Record(Record {
+                    name,
+                    doc: doc(object.get("doc")),
+                    fields: parsed,
+                })
+            }
+            ("enum", _) => {
+                let (name, _) = self.define(object, namespace, path)?;
+                let symbols = object
+                    .get("symbols")
+                    .and_then(Json::as_array)
+                    .ok_or_else(|| invalid(path, "enum without `symbols`"))?;
+                let mut parsed: Vec<String> = Vec::with_capacity(symbols.len());
+                for symbol in symbols {
+                    match symbol.as_str() {
+                        Some(symbol)
+                            if valid_name(symbol) && !parsed.iter().any(|s| s == symbol) =>
+                        {
+                            parsed.push(symbol.to_string())
+                        }
+                        _ => return Err(invalid(path, &format!("invalid symbol {}", symbol))),
+                    }
+                }
+                AvroSchema::Enum(Enum {
+                    name,
+                    doc: doc(object.get("doc")),
+                    symbols: parsed,
+                })
+            }
+            ("fixed", _) => {
+                let (name, _) = self.define(object, namespace, path)?;
+                let size = object
+                    .get("size")
+                    .and_then(Json::as_u64)
+                    .ok_or_else(|| invalid(path, "fixed without a `size`"))?;
+                AvroSchema::Fixed(Fixed {
+                    name,
+                    size: size as usize,
+                    decimal: decimal(object)
+                        .filter(|_| logical == Some("decimal"))
+                        .filter(|d| d.precision <= max_precision(size as usize)),
+                })
+            }
+            (kind, _) => self.reference(kind, namespace, path)?,
+        };
+        Ok(schema)
+    }
+
+    /// Registers the named type declared by `object`, returning its full
+    /// name and the namespace it sets for the types nested in it.
+    fn define(
+        &mut self,
+        object: &Map<String, Json>,
+        namespace: &str,
+        path: &str,
+    ) -> Result<(String, String), AvroError> {
+        let name = object
+            .get("name")
+            .and_then(Json::as_str)
+            .ok_or_else(|| invalid(path, "named type without a `name`"))?;
+        let namespace = object
+            .get("namespace")
+            .and_then(Json::as_str)
+            .unwrap_or(namespace);
+        let full = qualify(name, namespace);
+        if !full.split('.').all(valid_name) {
+            return Err(invalid(path, &format!("invalid name `{}`", full)));
+        }
+        if primitive(&full).is_some() {
+            return Err(invalid(path, &format!("`{}` is a primitive type", full)));
+        }
+        if !self.names.insert(full.clone()) {
+            return Err(invalid(path, &format!("`{}` is defined twice", full)));
+        }
+        let inner = namespace_of(&full).to_string();
+        Ok((full, inner))
+    }
+
+    fn reference(&self, name: &str, namespace: &str, path: &str) -> Result<AvroSchema, AvroError> {
+        if let Some(schema) = primitive(name) {
+            return Ok(schema);
+        }
+        let full = qualify(name, namespace);
+        if self.names.contains(&full) {
+            Ok(AvroSchema::Named(full))
+        } else if self.names.contains(name) {
+            Ok(AvroSchema::Named(name.to_string()))
+        } else {
+            Err(invalid(path, &format!("unknown type `{}`", name)))
+        }
+    }
+}
+
+fn primitive(name: &str) -> Option<AvroSchema> {
+    Some(match name {
+        "null" => AvroSchema::Null,
+        "boolean" => AvroSchema::Boolean,
+        "int" => AvroSchema::Int,
+        "long" => AvroSchema::Long,
+        "float" => AvroSchema::Float,
+        "double" => AvroSchema::Double,
+        "bytes" => AvroSchema::Bytes,
+        "string" => AvroSchema::String,
+        _ => return None,
+    })
+}
+
+fn decimal(object: &Map<String, Json>) -> Option<Decimal> {
+    let precision = u32::try_from(object.get("precision")?.as_u64()?).ok()?;
+    let scale = match object.get("scale") {
+        Some(scale) => u32::try_from(scale.as_u64()?).ok()?,
+        None => 0,
+    };
+    (precision > 0 && scale <= precision).then_some(Decimal { precision, scale })
+}
+
+/// The most decimal digits a two's-complement number of `size` bytes holds.
+fn max_precision(size: usize) -> u32 {
+    ((8.0 * size as f64 - 1.0) * 2f64.log10()).floor() as u32
+}
+
+fn doc(json: Option<&Json>) -> Option<String> {
+    json.and_then(Json::as_str).map(String::from)
+}
+
+/// The full name of `name` declared in `namespace`.
+fn qualify(name: &str, namespace: &str) -> String {
+    if let Some(name) = name.strip_prefix('.') {
+        return name.to_string();
+    }
+    if name.contains('.') || namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", namespace, name)
+    }
+}
+
+fn namespace_of(full: &str) -> &str {
+    full.rsplit_once('.').map_or("", |(namespace, _)| namespace)
+}
+
+/// Whether `name` is a valid Avro name (one component of a full name).
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn invalid(path: &str, message: &str) -> AvroError {
+    AvroError::InvalidSchema {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// The empty CRC-64-AVRO fingerprint, which is also its polynomial.
+const EMPTY: u64 = 0xc15d_213a_a4d7_a795;
+
+const RABIN_TABLE: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut fp = i as u64;
+        let mut j = 0;
+        while j < 8 {
+            fp = (fp >> 1) ^ (EMPTY & (fp & 1).wrapping_neg());
+            j += 1;
+        }
+        table[i] = fp;
+        i += 1;
+    }
+    table
+};
+
+fn rabin(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(EMPTY, |fp, b| {
+        (fp >> 8) ^ RABIN_TABLE[((fp ^ *b as u64) & 0xff) as usize]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_canonical_form() {
+        let schema = AvroSchema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "User",
+                "namespace": "com.example",
+                "doc": "A user.",
+                "fields": [
+                    {"name": "id", "type": {"type": "long"}, "doc": "Key."},
+                    {"name": "created", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+                    {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["ACTIVE", "BANNED"]}},
+                    {"name": "previous", "type": ["null", "Status"], "default": null},
+                    {"name": "friends", "type": {"type": "array", "items": "User"}},
+                    {"name": "hash", "type": {"type": "fixed", "name": "Hash", "namespace": "", "size": 4}},
+                    {"name": "other", "type": "Hash"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let AvroSchema::Record(record) = &schema else {
+            panic!("expected a record");
+        };
+        assert_eq!(record.name, "com.example.User");
+        assert_eq!(record.fields[1].schema, AvroSchema::TimestampMillis);
+        assert_eq!(
+            record.fields[3].schema,
+            AvroSchema::Union(vec![
+                AvroSchema::Null,
+                AvroSchema::Named("com.example.Status".into())
+            ])
+        );
+        assert_eq!(record.fields[6].schema, AvroSchema::Named("Hash".into()));
+        assert_eq!(
+            schema.canonical_form(),
+            concat!(
+                r#"{"name":"com.example.User","type":"record","fields":["#,
+                r#"{"name":"id","type":"long"},"#,
+                r#"{"name":"created","type":"long"},"#,
+                r#"{"name":"status","type":{"name":"com.example.Status","type":"enum","symbols":["ACTIVE","BANNED"]}},"#,
+                r#"{"name":"previous","type":["null","com.example.Status"]},"#,
+                r#"{"name":"friends","type":{"type":"array","items":"com.example.User"}},"#,
+                r#"{"name":"hash","type":{"name":"Hash","type":"fixed","size":4}},"#,
+                r#"{"name":"other","type":"Hash"}]}"#
+            )
+        );
+
+        // The JSON written back parses to the same schema.
+        assert_eq!(AvroSchema::parse(&schema.to_json()).unwrap(), schema);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        // Values from the Avro specification's test suite.
+        assert_eq!(AvroSchema::Null.fingerprint(), 7195948357588979594);
+        assert_eq!(AvroSchema::Int.fingerprint(), 8247732601305521295);
+        assert_eq!(
+            AvroSchema::Date.fingerprint(),
+            AvroSchema::Int.fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = AvroSchema::parse_str(
+            r#"{"type": "record", "name": "A", "fields": [{"name": "b", "type": "B"}]}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid Avro schema at #/fields/0/type: unknown type `B`"
+        );
+        assert!(AvroSchema::parse_str(r#"[["null"]]"#).is_err());
+        assert!(AvroSchema::parse_str(
+            r#"{"type": "enum", "name": "E", "symbols": ["in-progress"]}"#
+        )
+        .is_err());
+    }
+}
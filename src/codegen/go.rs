@@ -0,0 +1,1390 @@
+//! Go source generation.
+//!
+//! Objects become structs with `encoding/json` tags, optional fields become
+//! pointers (slices, maps and interfaces are nil-able already), and `Enum`s
+//! become string types with a constant per value:
+//!
+//! ```text
+//! type Person struct {
+//!     ID    int64   `json:"id"`
+//!     Email *string `json:"email,omitempty"`
+//!     Tags  []Tag   `json:"tags"`
+//! }
+//! ```
+//!
+//! A union becomes an interface implemented by a type per variant, with an
+//! `UnmarshalX` function that picks the variant by its string literal tag
+//! when the variants share one, and tries each in turn otherwise. Structs
+//! with union fields get an `UnmarshalJSON` method that uses it. With
+//! [`GoGenerator::with_validation`], structs also get a `Validate() error`
+//! method checking the bounds, lengths and required fields Go's types do not
+//! capture.
+
+use super::hoist::{optional_inner, pascal_case, ref_name, string_literals, Hoister};
+use super::rust::{collect_refs, reaches, snake_case};
+use crate::codegen::SchemaRegistry;
+use crate::schema::{LiteralValue, Schema, SchemaKind};
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+pub struct GoGenerator {
+    registry: Handlebars<'static>,
+    package: String,
+    validation: bool,
+}
+
+impl GoGenerator {
+    pub fn new() -> Self {
+        let mut registry = Handlebars::new();
+        registry
+            .register_template_string("struct", STRUCT_TEMPLATE)
+            .unwrap();
+        registry
+            .register_template_string("enum", ENUM_TEMPLATE)
+            .unwrap();
+        registry
+            .register_template_string("union", UNION_TEMPLATE)
+            .unwrap();
+        registry
+            .register_template_string("type", TYPE_TEMPLATE)
+            .unwrap();
+
+        Self {
+            registry,
+            package: "models".to_string(),
+            validation: false,
+        }
+    }
+
+    /// Sets the package clause of generated modules; `models` by default.
+    pub fn with_package(mut self, package: impl Into<String>) -> Self {
+        self.package = package.into();
+        self
+    }
+
+    /// Generates a `Validate() error` method for every struct.
+    pub fn with_validation(mut self) -> Self {
+        self.validation = true;
+        self
+    }
+
+    /// Generates the definition `name` and the types hoisted out of it,
+    /// without the package clause and imports.
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(None).with_enums();
+        hoister.add_root(name, schema);
+        let (code, _) = self.render(&hoister)?;
+        Ok(code.join("\n"))
+    }
+
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(Some(registry)).with_enums();
+
+        // Dependencies first, in a stable order, so output diffs cleanly.
+        for name in registry.dependency_graph().topological_order() {
+            if let Some(schema) = registry.get(name) {
+                hoister.add_root(name, schema);
+            }
+        }
+
+        let (code, imports) = self.render(&hoister)?;
+
+        // Go tools recognize generated files by this exact form.
+        let mut output = String::new();
+        output.push_str("// Code generated by typebox-rs. DO NOT EDIT.\n\n");
+        output.push_str(&format!("package {}\n", self.package));
+        match imports.len() {
+            0 => {}
+            1 => output.push_str(&format!("\nimport {:?}\n", imports.first().unwrap())),
+            _ => {
+                output.push_str("\nimport (\n");
+                for import in &imports {
+                    output.push_str(&format!("\t{:?}\n", import));
+                }
+                output.push_str(")\n");
+            }
+        }
+
+        for code in code {
+            output.push('\n');
+            output.push_str(&code);
+        }
+
+        Ok(output)
+    }
+
+    /// Renders the hoisted definitions and returns them with the packages
+    /// they import. Struct fields through which a type contains itself are
+    /// pointers.
+    fn render(&self, hoister: &Hoister) -> Result<(Vec<String>, BTreeSet<&str>), crate::Error> {
+        let mut module = Module::new(hoister, self.validation);
+
+        let mut direct: HashMap<&str, HashSet<String>> = HashMap::new();
+        for def in &hoister.defs {
+            let by_value = direct.entry(&def.name).or_default();
+            if module.kind(&def.name) == DefKind::Struct {
+                collect_refs(&def.schema, &hoister.refs, true, by_value);
+            }
+        }
+
+        let mut code = Vec::new();
+        for def in &hoister.defs {
+            let boxed: HashSet<String> = direct[def.name.as_str()]
+                .iter()
+                .filter(|target| reaches(&direct, target, &def.name))
+                .cloned()
+                .collect();
+            code.push(self.render_def(&def.name, &def.schema, &mut module, &boxed)?);
+        }
+        if module.helpers {
+            code.push(DECODE_STRICT.to_string());
+        }
+        Ok((code, module.imports))
+    }
+
+    fn render_def(
+        &self,
+        name: &str,
+        schema: &Schema,
+        module: &mut Module,
+        boxed: &HashSet<String>,
+    ) -> Result<String, crate::Error> {
+        let doc = doc_lines(schema, "");
+        match module.kind(name) {
+            DefKind::Struct => {
+                let ctx = module.structure(name, schema, doc, boxed);
+                Ok(self.registry.render("struct", &ctx)?)
+            }
+            DefKind::Enum => {
+                let values: Vec<&str> = match &schema.kind {
+                    SchemaKind::Enum { values } => values.iter().map(String::as_str).collect(),
+                    SchemaKind::Union { any_of } => string_literals(any_of).unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+                let mut used = HashSet::new();
+                let names: Vec<String> = values
+                    .iter()
+                    .map(|value| match go_ident(value) {
+                        ident if ident.is_empty() => format!("{}Empty", name),
+                        ident => dedupe(format!("{}{}", name, ident), &mut used),
+                    })
+                    .collect();
+                let width = names.iter().map(String::len).max().unwrap_or(0);
+                let ctx = EnumContext {
+                    name: name.to_string(),
+                    doc,
+                    constants: if names.is_empty() {
+                        String::new()
+                    } else {
+                        let constants: String = names
+                            .iter()
+                            .zip(&values)
+                            .map(|(constant, value)| {
+                                format!("\t{:width$} {} = {}\n", constant, name, go_string(value))
+                            })
+                            .collect();
+                        format!("\nconst (\n{})\n", constants)
+                    },
+                };
+                Ok(self.registry.render("enum", &ctx)?)
+            }
+            DefKind::Union => {
+                let SchemaKind::Union { any_of } = &schema.kind else {
+                    unreachable!("only unions are interfaces");
+                };
+                let ctx = module.union(name, any_of, doc);
+                Ok(self.registry.render("union", &ctx)?)
+            }
+            DefKind::Type => {
+                let mut doc = doc;
+                let go_type = match &schema.kind {
+                    SchemaKind::Union { any_of } if optional_inner(any_of).is_none() => {
+                        if doc.is_empty() {
+                            doc.push(format!(
+                                "// {} is any of its variants; they cannot all carry a method.",
+                                name
+                            ));
+                        }
+                        "any".to_string()
+                    }
+                    _ => module.go_type(schema, boxed),
+                };
+                let ctx = TypeContext {
+                    name: name.to_string(),
+                    doc,
+                    go_type,
+                };
+                Ok(self.registry.render("type", &ctx)?)
+            }
+        }
+    }
+}
+
+impl Default for GoGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a definition is declared.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DefKind {
+    Struct,
+    /// A string type with a constant per value.
+    Enum,
+    /// An interface implemented by a type per variant.
+    Union,
+    /// A defined type over another type.
+    Type,
+}
+
+#[derive(Serialize)]
+struct StructContext {
+    name: String,
+    doc: Vec<String>,
+    fields: Vec<String>,
+    /// Methods, each preceded by a blank line.
+    methods: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct EnumContext {
+    name: String,
+    doc: Vec<String>,
+    /// The `const` block, preceded by a blank line; empty without values.
+    constants: String,
+}
+
+#[derive(Serialize)]
+struct UnionContext {
+    name: String,
+    doc: Vec<String>,
+    /// Declarations of the types wrapping variants that are not named
+    /// types, preceded by a blank line; empty if there are none.
+    wrappers: String,
+    variants: Vec<String>,
+    decoder: String,
+}
+
+#[derive(Serialize)]
+struct TypeContext {
+    name: String,
+    doc: Vec<String>,
+    go_type: String,
+}
+
+/// A struct field.
+struct Field<'a> {
+    prop: &'a str,
+    ident: String,
+    go_type: String,
+    schema: &'a Schema,
+    required: bool,
+}
+
+/// A union variant: its Go type, and the value it must equal if it is a
+/// literal.
+struct Variant {
+    go_type: String,
+    literal: Option<String>,
+    schema: Schema,
+}
+
+/// Rendering state of one module.
+struct Module<'h> {
+    defs: HashMap<&'h str, &'h Schema>,
+    refs: &'h HashMap<String, String>,
+    kinds: HashMap<&'h str, DefKind>,
+    validation: bool,
+    imports: BTreeSet<&'static str>,
+    /// Whether `decodeStrict` is used.
+    helpers: bool,
+}
+
+impl<'h> Module<'h> {
+    fn new(hoister: &'h Hoister, validation: bool) -> Self {
+        let defs: HashMap<&str, &Schema> = hoister
+            .defs
+            .iter()
+            .map(|def| (def.name.as_str(), &def.schema))
+            .collect();
+        let mut module = Self {
+            defs,
+            refs: &hoister.refs,
+            kinds: HashMap::new(),
+            validation,
+            imports: BTreeSet::new(),
+            helpers: false,
+        };
+        // Unions depend on the kinds of their variants, so they come last.
+        for def in &hoister.defs {
+            let kind = match &def.schema.kind {
+                SchemaKind::Object { properties, .. } if !properties.is_empty() => DefKind::Struct,
+                SchemaKind::Enum { .. } => DefKind::Enum,
+                SchemaKind::Union { any_of } if string_literals(any_of).is_some() => DefKind::Enum,
+                _ => DefKind::Type,
+            };
+            module.kinds.insert(&def.name, kind);
+        }
+        for def in &hoister.defs {
+            if let SchemaKind::Intersect { all_of } = &def.schema.kind {
+                if all_of
+                    .iter()
+                    .all(|part| module.ref_kind(part) == Some(DefKind::Struct))
+                {
+                    module.kinds.insert(&def.name, DefKind::Struct);
+                }
+            }
+        }
+        for def in &hoister.defs {
+            if let SchemaKind::Union { any_of } = &def.schema.kind {
+                let interface = optional_inner(any_of).is_none()
+                    && string_literals(any_of).is_none()
+                    && any_of.iter().any(|v| !matches!(v.kind, SchemaKind::Null))
+                    && any_of.iter().all(|variant| module.can_implement(variant));
+                if interface {
+                    module.kinds.insert(&def.name, DefKind::Union);
+                }
+            }
+        }
+        module
+    }
+
+    fn kind(&self, name: &str) -> DefKind {
+        self.kinds.get(name).copied().unwrap_or(DefKind::Type)
+    }
+
+    /// The kind of the definition `schema` refers to, if it is a `Ref`.
+    fn ref_kind(&self, schema: &Schema) -> Option<DefKind> {
+        match &schema.kind {
+            SchemaKind::Ref { reference } => {
+                let name = ref_name(reference, self.refs);
+                self.kinds.get(name.as_str()).copied()
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether a union variant can be given the union's marker method,
+    /// directly or through a wrapper type. `null` is the nil interface.
+    fn can_implement(&self, variant: &Schema) -> bool {
+        match self.ref_kind(variant) {
+            Some(DefKind::Struct | DefKind::Enum) => true,
+            Some(DefKind::Union) => false,
+            Some(DefKind::Type) => {
+                let SchemaKind::Ref { reference } = &variant.kind else {
+                    return false;
+                };
+                let target = self.defs.get(ref_name(reference, self.refs).as_str());
+                target.is_some_and(|target| self.wrappable(target))
+            }
+            None => matches!(variant.kind, SchemaKind::Null) || self.wrappable(variant),
+        }
+    }
+
+    /// Whether a defined type over `schema` can have methods: it is neither
+    /// a pointer nor an interface.
+    fn wrappable(&self, schema: &Schema) -> bool {
+        match &schema.kind {
+            SchemaKind::Union { any_of } => string_literals(any_of).is_some(),
+            SchemaKind::Ref { .. } => {
+                matches!(self.ref_kind(schema), Some(DefKind::Struct | DefKind::Enum))
+            }
+            kind => {
+                go_scalar(kind).is_some_and(|t| t != "any")
+                    || matches!(kind, SchemaKind::Array { .. } | SchemaKind::Object { .. })
+            }
+        }
+    }
+
+    /// The Go type for `schema`. References to the structs in `boxed` are
+    /// pointers unless already behind a slice or map.
+    fn go_type(&self, schema: &Schema, boxed: &HashSet<String>) -> String {
+        if let Some(scalar) = go_scalar(&schema.kind) {
+            return scalar.to_string();
+        }
+        match &schema.kind {
+            SchemaKind::Array { items, .. } => {
+                format!("[]{}", self.go_type(items, &HashSet::new()))
+            }
+            SchemaKind::Object {
+                additional_properties: Some(values),
+                ..
+            } => format!("map[string]{}", self.go_type(values, &HashSet::new())),
+            SchemaKind::Union { any_of } => match optional_inner(any_of) {
+                Some(inner) => self.optional(self.go_type(inner, boxed)),
+                None if string_literals(any_of).is_some() => "string".to_string(),
+                None => "any".to_string(),
+            },
+            SchemaKind::Ref { reference } => {
+                let name = ref_name(reference, self.refs);
+                if boxed.contains(&name) {
+                    format!("*{}", name)
+                } else {
+                    name
+                }
+            }
+            SchemaKind::Named { name, .. } => name.clone(),
+            SchemaKind::Recursive { schema: inner } => self.go_type(inner, boxed),
+            _ => "map[string]any".to_string(),
+        }
+    }
+
+    /// `go_type` as the type of an optional value: a pointer, unless it is
+    /// nil-able already.
+    fn optional(&self, go_type: String) -> String {
+        let nilable = go_type.starts_with(['*', '['])
+            || go_type.starts_with("map[")
+            || go_type == "any"
+            || self.kind(&go_type) == DefKind::Union
+            || (self.kind(&go_type) == DefKind::Type
+                && self.defs.get(go_type.as_str()).is_some_and(|schema| {
+                    let inner = self.go_type(schema, &HashSet::new());
+                    inner.starts_with(['*', '[']) || inner.starts_with("map[") || inner == "any"
+                }));
+        if nilable {
+            go_type
+        } else {
+            format!("*{}", go_type)
+        }
+    }
+
+    fn structure(
+        &mut self,
+        name: &str,
+        schema: &Schema,
+        doc: Vec<String>,
+        boxed: &HashSet<String>,
+    ) -> StructContext {
+        let mut lines = Vec::new();
+        let mut methods = Vec::new();
+
+        if let SchemaKind::Intersect { all_of } = &schema.kind {
+            let parts: Vec<String> = all_of
+                .iter()
+                .map(|part| self.go_type(part, &HashSet::new()))
+                .collect();
+            lines.extend(parts.iter().map(|part| format!("\t{}", part)));
+            if self.validation {
+                let receiver = receiver(name);
+                let checks: Vec<String> = parts
+                    .iter()
+                    .map(|part| {
+                        format!(
+                            "\tif err := {}.{}.Validate(); err != nil {{\n\t\treturn err\n\t}}\n",
+                            receiver, part
+                        )
+                    })
+                    .collect();
+                methods.push(self.validate_method(name, checks));
+            }
+            return StructContext {
+                name: name.to_string(),
+                doc,
+                fields: lines,
+                methods,
+            };
+        }
+
+        let SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } = &schema.kind
+        else {
+            unreachable!("structs are objects or intersections");
+        };
+        let mut used = HashSet::new();
+        let fields: Vec<Field> = properties
+            .iter()
+            .map(|(prop, prop_schema)| {
+                let required = required.contains(prop);
+                let go_type = self.go_type(prop_schema, boxed);
+                Field {
+                    prop,
+                    ident: dedupe(go_ident_or(prop, "Field"), &mut used),
+                    go_type: if required {
+                        go_type
+                    } else {
+                        self.optional(go_type)
+                    },
+                    schema: prop_schema,
+                    required,
+                }
+            })
+            .collect();
+
+        // gofmt aligns the fields between doc comments.
+        let mut block: Vec<&Field> = Vec::new();
+        let flush = |block: &mut Vec<&Field>, lines: &mut Vec<String>| {
+            let name_width = block.iter().map(|f| f.ident.len()).max().unwrap_or(0);
+            let type_width = block.iter().map(|f| f.go_type.len()).max().unwrap_or(0);
+            for field in block.drain(..) {
+                let omitempty = if field.required { "" } else { ",omitempty" };
+                lines.push(format!(
+                    "\t{:nw$} {:tw$} `json:\"{}{}\"`",
+                    field.ident,
+                    field.go_type,
+                    tag_name(field.prop),
+                    omitempty,
+                    nw = name_width,
+                    tw = type_width,
+                ));
+            }
+        };
+        for field in &fields {
+            let field_doc = doc_lines(field.schema, "\t");
+            if !field_doc.is_empty() {
+                flush(&mut block, &mut lines);
+                lines.extend(field_doc);
+            }
+            block.push(field);
+        }
+        flush(&mut block, &mut lines);
+
+        let unions: Vec<&Field> = fields
+            .iter()
+            .filter(|field| self.union_element(&field.go_type).is_some())
+            .collect();
+        if !unions.is_empty() {
+            methods.push(self.unmarshal_method(name, &unions));
+        }
+        if self.validation {
+            let checks: Vec<String> = fields
+                .iter()
+                .filter_map(|field| self.field_checks(&receiver(name), field))
+                .collect();
+            methods.push(self.validate_method(name, checks));
+        }
+
+        StructContext {
+            name: name.to_string(),
+            doc,
+            fields: lines,
+            methods,
+        }
+    }
+
+    /// The union interface `go_type` holds directly or as slice elements,
+    /// with whether it is a slice.
+    fn union_element<'t>(&self, go_type: &'t str) -> Option<(&'t str, bool)> {
+        let (element, slice) = match go_type.strip_prefix("[]") {
+            Some(element) => (element, true),
+            None => (go_type, false),
+        };
+        (self.kind(element) == DefKind::Union).then_some((element, slice))
+    }
+
+    /// `UnmarshalJSON` decoding the union fields of the struct `name`
+    /// through their `UnmarshalX` functions.
+    fn unmarshal_method(&mut self, name: &str, unions: &[&Field]) -> String {
+        self.imports.insert("encoding/json");
+        self.imports.insert("fmt");
+        let r = receiver(name);
+        let mut out = format!(
+            "\n// UnmarshalJSON decodes {} with its union fields.\nfunc ({} *{}) UnmarshalJSON(data []byte) error {{\n\ttype plain {}\n\taux := struct {{\n\t\t*plain\n",
+            name, r, name, name
+        );
+        let raws: Vec<&str> = unions
+            .iter()
+            .map(|field| match self.union_element(&field.go_type) {
+                Some((_, true)) => "[]json.RawMessage",
+                _ => "json.RawMessage",
+            })
+            .collect();
+        let name_width = unions.iter().map(|f| f.ident.len()).max().unwrap_or(0);
+        let type_width = raws.iter().map(|raw| raw.len()).max().unwrap_or(0);
+        for (field, raw) in unions.iter().zip(&raws) {
+            out.push_str(&format!(
+                "\t\t{:nw$} {:tw$} `json:\"{},omitempty\"`\n",
+                field.ident,
+                raw,
+                tag_name(field.prop),
+                nw = name_width,
+                tw = type_width,
+            ));
+        }
+        out.push_str(&format!(
+            "\t}}{{plain: (*plain)({})}}\n\tif err := json.Unmarshal(data, &aux); err != nil {{\n\t\treturn err\n\t}}\n",
+            r
+        ));
+        for field in unions {
+            let (union, slice) = self.union_element(&field.go_type).unwrap();
+            let label = format_escape(field.prop);
+            if slice {
+                out.push_str(&format!(
+                    "\tif aux.{f} != nil {{\n\t\t{r}.{f} = make([]{u}, len(aux.{f}))\n\t\tfor i, raw := range aux.{f} {{\n\t\t\tv, err := Unmarshal{u}(raw)\n\t\t\tif err != nil {{\n\t\t\t\treturn fmt.Errorf(\"{l}[%d]: %w\", i, err)\n\t\t\t}}\n\t\t\t{r}.{f}[i] = v\n\t\t}}\n\t}}\n",
+                    f = field.ident,
+                    r = r,
+                    u = union,
+                    l = label
+                ));
+            } else {
+                out.push_str(&format!(
+                    "\tif aux.{f} != nil {{\n\t\tv, err := Unmarshal{u}(aux.{f})\n\t\tif err != nil {{\n\t\t\treturn fmt.Errorf(\"{l}: %w\", err)\n\t\t}}\n\t\t{r}.{f} = v\n\t}}\n",
+                    f = field.ident,
+                    r = r,
+                    u = union,
+                    l = label
+                ));
+            }
+        }
+        out.push_str("\treturn nil\n}\n");
+        out
+    }
+
+    fn validate_method(&mut self, name: &str, checks: Vec<String>) -> String {
+        let receiver = if checks.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", receiver(name))
+        };
+        format!(
+            "\n// Validate checks the constraints of {} that its Go type does not.\nfunc ({}{}) Validate() error {{\n{}\treturn nil\n}}\n",
+            name,
+            receiver,
+            name,
+            checks.concat()
+        )
+    }
+
+    /// The checks of `Validate` for `field`.
+    fn field_checks(&mut self, receiver: &str, field: &Field) -> Option<String> {
+        let access = format!("{}.{}", receiver, field.ident);
+        let label = format_escape(field.prop);
+        let pointer = field.go_type.starts_with('*');
+        let value = if pointer {
+            format!("*{}", access)
+        } else {
+            access.clone()
+        };
+        let mut out = String::new();
+
+        let schema = match &field.schema.kind {
+            SchemaKind::Union { any_of } => optional_inner(any_of).unwrap_or(field.schema),
+            _ => field.schema,
+        };
+        // A nil union is its `null` variant, if it has one.
+        let nilable = field.go_type.starts_with('[')
+            || field.go_type.starts_with("map[")
+            || (self.kind(&field.go_type) == DefKind::Union
+                && !self.defs.get(field.go_type.as_str()).is_some_and(|schema| {
+                    matches!(&schema.kind, SchemaKind::Union { any_of }
+                        if any_of.iter().any(|v| matches!(v.kind, SchemaKind::Null)))
+                }));
+        if field.required && nilable && !matches!(field.schema.kind, SchemaKind::Union { .. }) {
+            self.imports.insert("fmt");
+            out.push_str(&format!(
+                "\tif {} == nil {{\n\t\treturn fmt.Errorf(\"{}: is required\")\n\t}}\n",
+                access, label
+            ));
+        }
+
+        let mut conditions: Vec<(String, String)> = Vec::new();
+        let bound =
+            |conditions: &mut Vec<(String, String)>, min: Option<String>, max: Option<String>| {
+                if let Some(min) = min {
+                    conditions.push((
+                        format!("{} < {}", value, min),
+                        format!("must be at least {}", min),
+                    ));
+                }
+                if let Some(max) = max {
+                    conditions.push((
+                        format!("{} > {}", value, max),
+                        format!("must be at most {}", max),
+                    ));
+                }
+            };
+        match &schema.kind {
+            SchemaKind::Int8 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            SchemaKind::Int16 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            SchemaKind::Int32 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            SchemaKind::Int64 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            // Unsigned values are never below 0.
+            SchemaKind::UInt8 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.filter(|n| *n > 0).map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            SchemaKind::UInt16 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.filter(|n| *n > 0).map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            SchemaKind::UInt32 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.filter(|n| *n > 0).map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            SchemaKind::UInt64 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.filter(|n| *n > 0).map(|n| n.to_string()),
+                maximum.map(|n| n.to_string()),
+            ),
+            SchemaKind::Float32 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.map(|n| go_float(n.into())),
+                maximum.map(|n| go_float(n.into())),
+            ),
+            SchemaKind::Float64 { minimum, maximum } => bound(
+                &mut conditions,
+                minimum.map(go_float),
+                maximum.map(go_float),
+            ),
+            SchemaKind::String {
+                min_length,
+                max_length,
+                ..
+            } if min_length.is_some() || max_length.is_some() => {
+                self.imports.insert("unicode/utf8");
+                let length = format!("utf8.RuneCountInString({})", value);
+                lengths(
+                    &mut conditions,
+                    &length,
+                    *min_length,
+                    *max_length,
+                    "characters",
+                );
+            }
+            SchemaKind::Bytes {
+                min_length,
+                max_length,
+            } => {
+                let length = format!("len({})", value);
+                lengths(&mut conditions, &length, *min_length, *max_length, "bytes");
+            }
+            SchemaKind::Array {
+                min_items,
+                max_items,
+                ..
+            } => {
+                let length = format!("len({})", value);
+                lengths(&mut conditions, &length, *min_items, *max_items, "items");
+            }
+            _ => {}
+        }
+        for (condition, message) in conditions {
+            self.imports.insert("fmt");
+            let guard = if pointer {
+                format!("{} != nil && ", access)
+            } else {
+                String::new()
+            };
+            out.push_str(&format!(
+                "\tif {}{} {{\n\t\treturn fmt.Errorf(\"{}: {}\")\n\t}}\n",
+                guard, condition, label, message
+            ));
+        }
+
+        // Nested structs validate themselves.
+        let element = field.go_type.trim_start_matches('*');
+        if let Some(items) = element.strip_prefix("[]") {
+            if self.validates(items.trim_start_matches('*')) {
+                self.imports.insert("fmt");
+                out.push_str(&format!(
+                    "\tfor i, item := range {} {{\n\t\tif err := item.Validate(); err != nil {{\n\t\t\treturn fmt.Errorf(\"{}[%d]: %w\", i, err)\n\t\t}}\n\t}}\n",
+                    access, label
+                ));
+            }
+        } else if self.validates(element) {
+            self.imports.insert("fmt");
+            let check = format!(
+                "if err := {}.Validate(); err != nil {{\n\t\treturn fmt.Errorf(\"{}: %w\", err)\n\t}}",
+                access, label
+            );
+            if pointer {
+                out.push_str(&format!(
+                    "\tif {} != nil {{\n\t\t{}\n\t}}\n",
+                    access,
+                    check.replace("\n\t", "\n\t\t")
+                ));
+            } else {
+                out.push_str(&format!("\t{}\n", check));
+            }
+        } else if self.kind(element) == DefKind::Union {
+            self.imports.insert("fmt");
+            out.push_str(&format!(
+                "\tif v, ok := {}.(interface{{ Validate() error }}); ok {{\n\t\tif err := v.Validate(); err != nil {{\n\t\t\treturn fmt.Errorf(\"{}: %w\", err)\n\t\t}}\n\t}}\n",
+                access, label
+            ));
+        }
+
+        (!out.is_empty()).then_some(out)
+    }
+
+    /// Whether the type `name` has a `Validate` method.
+    fn validates(&self, name: &str) -> bool {
+        self.validation && self.kind(name) == DefKind::Struct
+    }
+
+    fn union(&mut self, name: &str, any_of: &[Schema], mut doc: Vec<String>) -> UnionContext {
+        let mut wrappers = String::new();
+        let mut variants: Vec<Variant> = Vec::new();
+        let mut used = HashSet::new();
+        let mut nullable = false;
+        for variant in any_of {
+            if matches!(variant.kind, SchemaKind::Null) {
+                nullable = true;
+                continue;
+            }
+            let go_type = match &variant.kind {
+                SchemaKind::Ref { reference } => ref_name(reference, self.refs),
+                _ => {
+                    let wrapper = dedupe(format!("{}{}", name, variant_suffix(variant)), &mut used);
+                    let inner = self.go_type(variant, &HashSet::new());
+                    if wrappers.is_empty() {
+                        wrappers.push('\n');
+                    }
+                    wrappers.push_str(&format!("type {} {}\n", wrapper, inner));
+                    wrapper
+                }
+            };
+            if variants.iter().any(|v| v.go_type == go_type) {
+                continue;
+            }
+            let literal = match &variant.kind {
+                SchemaKind::Literal { value } => Some(go_literal(value)),
+                _ => None,
+            };
+            variants.push(Variant {
+                go_type,
+                literal,
+                schema: variant.clone(),
+            });
+        }
+        if doc.is_empty() {
+            let names: Vec<&str> = variants.iter().map(|v| v.go_type.as_str()).collect();
+            doc.push(format!("// {} is one of {}.", name, names.join(", ")));
+        }
+
+        let decoder = match self.discriminator(&variants) {
+            Some((tag, values)) => self.tagged_decoder(name, &tag, &variants, &values),
+            None => self.untagged_decoder(name, &variants, nullable),
+        };
+        UnionContext {
+            name: name.to_string(),
+            doc,
+            wrappers,
+            variants: variants.into_iter().map(|v| v.go_type).collect(),
+            decoder,
+        }
+    }
+
+    /// The property every variant struct has as a distinct, required
+    /// string literal, with each variant's value.
+    fn discriminator(&self, variants: &[Variant]) -> Option<(String, Vec<String>)> {
+        let objects = variants
+            .iter()
+            .map(|variant| match self.ref_kind(&variant.schema) {
+                Some(DefKind::Struct) => self.defs.get(variant.go_type.as_str()).copied(),
+                _ => None,
+            })
+            .collect::<Option<Vec<&Schema>>>()?;
+        let SchemaKind::Object { properties, .. } = &objects.first()?.kind else {
+            return None;
+        };
+        properties.keys().find_map(|tag| {
+            let values = objects
+                .iter()
+                .map(|object| match &object.kind {
+                    SchemaKind::Object {
+                        properties,
+                        required,
+                        ..
+                    } if required.contains(tag) => match properties.get(tag) {
+                        Some(Schema {
+                            kind:
+                                SchemaKind::Literal {
+                                    value: LiteralValue::String(value),
+                                },
+                            ..
+                        }) => Some(value.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Option<Vec<String>>>()?;
+            let distinct: HashSet<&String> = values.iter().collect();
+            (distinct.len() == values.len()).then(|| (tag.clone(), values))
+        })
+    }
+
+    fn tagged_decoder(
+        &mut self,
+        name: &str,
+        tag: &str,
+        variants: &[Variant],
+        values: &[String],
+    ) -> String {
+        self.imports.insert("encoding/json");
+        self.imports.insert("fmt");
+        let mut out = format!(
+            "// Unmarshal{n} decodes a {n} from JSON by its {t} property.\nfunc Unmarshal{n}(data []byte) ({n}, error) {{\n\tvar probe struct {{\n\t\tTag string `json:\"{tn}\"`\n\t}}\n\tif err := json.Unmarshal(data, &probe); err != nil {{\n\t\treturn nil, err\n\t}}\n\tswitch probe.Tag {{\n",
+            n = name,
+            t = go_string(tag),
+            tn = tag_name(tag)
+        );
+        for (variant, value) in variants.iter().zip(values) {
+            out.push_str(&format!(
+                "\tcase {}:\n\t\tvar v {}\n\t\terr := json.Unmarshal(data, &v)\n\t\treturn v, err\n",
+                go_string(value),
+                variant.go_type
+            ));
+        }
+        out.push_str(&format!(
+            "\t}}\n\treturn nil, fmt.Errorf(\"unknown {} {}: %q\", probe.Tag)\n}}\n",
+            name,
+            format_escape(tag)
+        ));
+        out
+    }
+
+    fn untagged_decoder(&mut self, name: &str, variants: &[Variant], nullable: bool) -> String {
+        self.imports.insert("encoding/json");
+        self.imports.insert("fmt");
+        let mut out = format!(
+            "// Unmarshal{n} decodes a {n} from JSON as the first variant it matches.\nfunc Unmarshal{n}(data []byte) ({n}, error) {{\n",
+            n = name
+        );
+        if nullable {
+            self.imports.insert("bytes");
+            out.push_str(
+                "\tif string(bytes.TrimSpace(data)) == \"null\" {\n\t\treturn nil, nil\n\t}\n",
+            );
+        }
+        // Literals first, so a literal is not taken for its plain type.
+        let (literals, others): (Vec<&Variant>, Vec<&Variant>) =
+            variants.iter().partition(|v| v.literal.is_some());
+        for variant in literals {
+            out.push_str(&format!(
+                "\t{{\n\t\tvar v {}\n\t\tif err := json.Unmarshal(data, &v); err == nil && v == {} {{\n\t\t\treturn v, nil\n\t\t}}\n\t}}\n",
+                variant.go_type,
+                variant.literal.as_deref().unwrap()
+            ));
+        }
+        for variant in others {
+            self.helpers = true;
+            out.push_str(&format!(
+                "\t{{\n\t\tvar v {}\n\t\tif err := decodeStrict(data, &v); err == nil {{\n\t\t\treturn v, nil\n\t\t}}\n\t}}\n",
+                variant.go_type
+            ));
+        }
+        out.push_str(&format!(
+            "\treturn nil, fmt.Errorf(\"no variant of {} matches %s\", data)\n}}\n",
+            name
+        ));
+        if self.helpers {
+            self.imports.insert("bytes");
+        }
+        out
+    }
+}
+
+/// The Go type of the kinds that map to one directly.
+fn go_scalar(kind: &SchemaKind) -> Option<&'static str> {
+    Some(match kind {
+        SchemaKind::Bool => "bool",
+        SchemaKind::Int8 { .. } => "int8",
+        SchemaKind::Int16 { .. } => "int16",
+        SchemaKind::Int32 { .. } => "int32",
+        SchemaKind::Int64 { .. } => "int64",
+        SchemaKind::UInt8 { .. } => "uint8",
+        SchemaKind::UInt16 { .. } => "uint16",
+        SchemaKind::UInt32 { .. } => "uint32",
+        SchemaKind::UInt64 { .. } => "uint64",
+        SchemaKind::Float32 { .. } => "float32",
+        SchemaKind::Float64 { .. } => "float64",
+        SchemaKind::String { .. } | SchemaKind::Enum { .. } => "string",
+        SchemaKind::Bytes { .. } => "[]byte",
+        SchemaKind::Literal { value } => match value {
+            LiteralValue::String(_) => "string",
+            LiteralValue::Number(_) => "int64",
+            LiteralValue::Float(_) => "float64",
+            LiteralValue::Boolean(_) => "bool",
+            LiteralValue::Null => "any",
+        },
+        // Go has no tuples; elements keep their JSON types.
+        SchemaKind::Tuple { .. } => "[]any",
+        SchemaKind::Null
+        | SchemaKind::Function { .. }
+        | SchemaKind::Void
+        | SchemaKind::Never
+        | SchemaKind::Any
+        | SchemaKind::Unknown
+        | SchemaKind::Undefined => "any",
+        _ => return None,
+    })
+}
+
+/// The wrapper type name suffix for an inline union variant.
+fn variant_suffix(schema: &Schema) -> String {
+    match &schema.kind {
+        SchemaKind::Literal {
+            value: LiteralValue::String(s),
+        } => go_ident_or(s, "Empty"),
+        SchemaKind::Literal { value } => match value {
+            LiteralValue::Boolean(b) => pascal_case(&b.to_string()),
+            _ => "Literal".to_string(),
+        },
+        SchemaKind::Array { .. } => "List".to_string(),
+        SchemaKind::Object { .. } => "Map".to_string(),
+        kind => go_scalar(kind)
+            .map(|scalar| match scalar {
+                "[]byte" => "Bytes".to_string(),
+                scalar => pascal_case(scalar),
+            })
+            .unwrap_or_else(|| "Value".to_string()),
+    }
+}
+
+fn lengths(
+    conditions: &mut Vec<(String, String)>,
+    length: &str,
+    minimum: Option<usize>,
+    maximum: Option<usize>,
+    unit: &str,
+) {
+    if let Some(min) = minimum.filter(|n| *n > 0) {
+        conditions.push((
+            format!("{} < {}", length, min),
+            format!("must have at least {} {}", min, unit),
+        ));
+    }
+    if let Some(max) = maximum {
+        conditions.push((
+            format!("{} > {}", length, max),
+            format!("must have at most {} {}", max, unit),
+        ));
+    }
+}
+
+/// The receiver name for methods of `name`, avoiding the names of the
+/// generated methods' locals.
+fn receiver(name: &str) -> String {
+    match name.chars().next().map(|c| c.to_ascii_lowercase()) {
+        Some(c) if c.is_ascii_lowercase() && c != 'i' && c != 'v' => c.to_string(),
+        _ => "x".to_string(),
+    }
+}
+
+/// Words Go spells in capitals.
+const INITIALISMS: &[&str] = &[
+    "api", "ascii", "cpu", "css", "dns", "eof", "guid", "html", "http", "https", "id", "ip",
+    "json", "sql", "ssh", "tcp", "tls", "ttl", "udp", "ui", "uri", "url", "utf8", "uuid", "xml",
+];
+
+/// An exported identifier for `name`, such as `UserID` for `user_id`;
+/// empty if `name` has no letters or digits.
+fn go_ident(name: &str) -> String {
+    if !name.chars().any(char::is_alphanumeric) {
+        return String::new();
+    }
+    let mut ident = String::new();
+    for word in snake_case(name).split('_').filter(|w| !w.is_empty()) {
+        if INITIALISMS.contains(&word) {
+            ident.push_str(&word.to_uppercase());
+        } else {
+            ident.push_str(&pascal_case(word));
+        }
+    }
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, 'X');
+    }
+    ident
+}
+
+fn go_ident_or(name: &str, fallback: &str) -> String {
+    match go_ident(name) {
+        ident if ident.is_empty() => fallback.to_string(),
+        ident => ident,
+    }
+}
+
+/// `name`, or `name` with the first free numeric suffix.
+fn dedupe(name: String, used: &mut HashSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut suffix = 1;
+    while !used.insert(unique.clone()) {
+        suffix += 1;
+        unique = format!("{}{}", name, suffix);
+    }
+    unique
+}
+
+/// Comment lines for the description and deprecation of `schema`.
+fn doc_lines(schema: &Schema, indent: &str) -> Vec<String> {
+    let mut lines: Vec<String> = schema
+        .description
+        .iter()
+        .flat_map(|description| description.trim().lines())
+        .map(|line| format!("{}// {}", indent, line).trim_end().to_string())
+        .collect();
+    if schema.deprecated == Some(true) {
+        if !lines.is_empty() {
+            lines.push(format!("{}//", indent));
+        }
+        lines.push(format!("{}// Deprecated: do not use.", indent));
+    }
+    lines
+}
+
+/// A property name as written inside a `json:"..."` struct tag.
+fn tag_name(prop: &str) -> String {
+    prop.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// `s` as a constant `fmt.Errorf` format string contents.
+fn format_escape(s: &str) -> String {
+    go_string(s).trim_matches('"').replace('%', "%%")
+}
+
+fn go_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn go_float(n: f64) -> String {
+    format!("{:?}", n)
+}
+
+fn go_literal(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::String(s) => go_string(s),
+        LiteralValue::Number(n) => n.to_string(),
+        LiteralValue::Float(f) => go_float(*f),
+        LiteralValue::Boolean(b) => b.to_string(),
+        LiteralValue::Null => "nil".to_string(),
+    }
+}
+
+const STRUCT_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}type {{name}} struct {
+{{#each fields}}{{{this}}}
+{{/each}}}
+{{#each methods}}{{{this}}}{{/each}}"#;
+
+const ENUM_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}type {{name}} string
+{{{constants}}}"#;
+
+const UNION_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}type {{name}} interface {
+	is{{name}}()
+}
+{{{wrappers}}}
+{{#each variants}}func ({{this}}) is{{../name}}() {}
+{{/each}}
+
+{{{decoder}}}"#;
+
+const TYPE_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}type {{name}} {{{go_type}}}
+"#;
+
+const DECODE_STRICT: &str = r#"// decodeStrict decodes data into v, rejecting unknown fields.
+func decodeStrict(data []byte, v any) error {
+	dec := json.NewDecoder(bytes.NewReader(data))
+	dec.DisallowUnknownFields()
+	return dec.Decode(v)
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::schema::StringFormat;
+
+    #[test]
+    fn test_generate_struct() {
+        let gen = GoGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field("user_id", SchemaBuilder::uint32())
+            .optional_field(
+                "email",
+                SchemaBuilder::string().format(StringFormat::Email).build(),
+            )
+            .field(
+                "tags",
+                SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+            )
+            .optional_field(
+                "avatar",
+                Schema::new(SchemaKind::Bytes {
+                    min_length: None,
+                    max_length: None,
+                }),
+            )
+            .optional_field(
+                "nickname",
+                SchemaBuilder::optional(SchemaBuilder::string().build())
+                    .with_description("Shown instead of the name."),
+            )
+            .build()
+            .with_description("A person.");
+
+        let output = gen.generate("Person", &schema).unwrap();
+        assert_eq!(
+            output,
+            "// A person.\ntype Person struct {\n\tUserID uint32   `json:\"user_id\"`\n\tEmail  *string  `json:\"email,omitempty\"`\n\tTags   []string `json:\"tags\"`\n\tAvatar []byte   `json:\"avatar,omitempty\"`\n\t// Shown instead of the name.\n\tNickname *string `json:\"nickname,omitempty\"`\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_hoists_inline_enums() {
+        let gen = GoGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "status",
+                SchemaBuilder::enum_values(vec!["active", "in-progress"]),
+            )
+            .build();
+
+        let output = gen.generate("Task", &schema).unwrap();
+        assert!(output.contains(
+            "type TaskStatus string\n\nconst (\n\tTaskStatusActive     TaskStatus = \"active\"\n\tTaskStatusInProgress TaskStatus = \"in-progress\"\n)"
+        ));
+        assert!(output.contains("\tStatus TaskStatus `json:\"status\"`"));
+    }
+
+    #[test]
+    fn test_generate_enum_and_union() {
+        let gen = GoGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Status",
+            SchemaBuilder::enum_values(vec!["active", "in-progress"]),
+        );
+        registry.register(
+            "Circle",
+            SchemaBuilder::object()
+                .field("kind", SchemaBuilder::literal("circle"))
+                .field("radius", SchemaBuilder::float64())
+                .build(),
+        );
+        registry.register(
+            "Square",
+            SchemaBuilder::object()
+                .field("kind", SchemaBuilder::literal("square"))
+                .field("side", SchemaBuilder::float64())
+                .build(),
+        );
+        registry.register(
+            "Shape",
+            SchemaBuilder::union(vec![
+                SchemaBuilder::r#ref("Circle"),
+                SchemaBuilder::r#ref("Square"),
+            ]),
+        );
+        registry.register(
+            "Size",
+            SchemaBuilder::union(vec![SchemaBuilder::literal("auto"), SchemaBuilder::int64()]),
+        );
+        registry.register(
+            "Canvas",
+            SchemaBuilder::object()
+                .field(
+                    "shapes",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Shape")).build(),
+                )
+                .optional_field("size", SchemaBuilder::r#ref("Size"))
+                .field("status", SchemaBuilder::r#ref("Status"))
+                .build(),
+        );
+
+        let output = gen
+            .with_package("shapes")
+            .generate_module(&registry)
+            .unwrap();
+        assert!(output.starts_with(
+            "// Code generated by typebox-rs. DO NOT EDIT.\n\npackage shapes\n\nimport (\n\t\"bytes\"\n\t\"encoding/json\"\n\t\"fmt\"\n)\n"
+        ));
+        assert!(output.contains(
+            "type Status string\n\nconst (\n\tStatusActive     Status = \"active\"\n\tStatusInProgress Status = \"in-progress\"\n)\n"
+        ));
+        assert!(output.contains(
+            "// Shape is one of Circle, Square.\ntype Shape interface {\n\tisShape()\n}\n\nfunc (Circle) isShape() {}\nfunc (Square) isShape() {}\n"
+        ));
+        assert!(output.contains("\tswitch probe.Tag {\n\tcase \"circle\":\n\t\tvar v Circle\n"));
+        assert!(output.contains("type Size interface {\n\tisSize()\n}\n\ntype SizeAuto string\ntype SizeInt64 int64\n\nfunc (SizeAuto) isSize() {}\n"));
+        assert!(output.contains(
+            "\t\tvar v SizeAuto\n\t\tif err := json.Unmarshal(data, &v); err == nil && v == \"auto\" {\n"
+        ));
+        assert!(output.contains(
+            "type Canvas struct {\n\tShapes []Shape `json:\"shapes\"`\n\tSize   Size    `json:\"size,omitempty\"`\n\tStatus Status  `json:\"status\"`\n}\n"
+        ));
+        assert!(output.contains("\t\tShapes []json.RawMessage `json:\"shapes,omitempty\"`\n"));
+        assert!(output.contains("\t\t\tv, err := UnmarshalShape(raw)\n"));
+        assert!(output.contains("func decodeStrict(data []byte, v any) error {"));
+    }
+
+    #[test]
+    fn test_generate_validate_and_recursion() {
+        let gen = GoGenerator::new().with_validation();
+        let node = SchemaBuilder::recursive("Node", |this| {
+            SchemaBuilder::object()
+                .field(
+                    "name",
+                    SchemaBuilder::string().min_length(1).max_length(32).build(),
+                )
+                .optional_field(
+                    "weight",
+                    Schema::new(SchemaKind::Int32 {
+                        minimum: Some(0),
+                        maximum: Some(100),
+                    }),
+                )
+                .optional_field("next", this.clone())
+                .field("children", SchemaBuilder::array(this).build())
+                .build()
+        });
+
+        let output = gen.generate("Node", &node).unwrap();
+        assert!(output.contains(
+            "type Node struct {\n\tName     string `json:\"name\"`\n\tWeight   *int32 `json:\"weight,omitempty\"`\n\tNext     *Node  `json:\"next,omitempty\"`\n\tChildren []Node `json:\"children\"`\n}\n\n// Validate"
+        ));
+        assert!(output.contains(
+            "\tif utf8.RuneCountInString(n.Name) < 1 {\n\t\treturn fmt.Errorf(\"name: must have at least 1 characters\")\n\t}\n"
+        ));
+        assert!(output.contains(
+            "\tif n.Weight != nil && *n.Weight > 100 {\n\t\treturn fmt.Errorf(\"weight: must be at most 100\")\n\t}\n"
+        ));
+        assert!(output.contains(
+            "\tif n.Next != nil {\n\t\tif err := n.Next.Validate(); err != nil {\n\t\t\treturn fmt.Errorf(\"next: %w\", err)\n\t\t}\n\t}\n"
+        ));
+        assert!(output.contains(
+            "\tif n.Children == nil {\n\t\treturn fmt.Errorf(\"children: is required\")\n\t}\n"
+        ));
+        assert!(output.contains("\tfor i, item := range n.Children {\n"));
+    }
+}
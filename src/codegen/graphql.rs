@@ -0,0 +1,981 @@
+//! GraphQL SDL generation.
+//!
+//! Objects become an output `type` and an `input` of the same fields:
+//!
+//! ```text
+//! """A person."""
+//! type Person {
+//!   id: Int64!
+//!   email: String
+//!   status: Status! @deprecated
+//! }
+//!
+//! """A person."""
+//! input PersonInput {
+//!   email: String
+//!   password: String!
+//! }
+//! ```
+//!
+//! Fields marked `read_only` appear on the type only and fields marked
+//! `write_only` on the input only, and objects marked either way get only
+//! the one side. Required fields are non-null (`!`) unless their schema
+//! admits `null`. `Enum`s and unions of string literals become `enum`s, and
+//! unions of objects become `union`s on the output side. GraphQL's `Int` is
+//! 32 bits wide, so `Int64` and `UInt32` use an `Int64` scalar and `UInt64`
+//! a `UInt64` scalar; schemas with no GraphQL type, such as maps, tuples
+//! and union inputs, use a `JSON` scalar. [`GraphQLGenerator::generate_module`]
+//! declares the scalars it uses.
+//!
+//! Names GraphQL does not allow, such as `in-progress`, are rewritten
+//! (`in_progress`), and definitions with no GraphQL type of their own, such
+//! as aliases of lists and unions of numbers, are written out where they
+//! are used. [`GraphQLGenerator::diagnostics`] reports both as warnings.
+
+use super::hoist::{optional_inner, pascal_case, ref_name, string_literals, Hoister};
+use crate::codegen::SchemaRegistry;
+use crate::lint::{Diagnostic, Severity};
+use crate::schema::{LiteralValue, Schema, SchemaKind};
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Built-in and generated scalars, which type names must not shadow.
+const RESERVED: &[&str] = &[
+    "String", "Int", "Float", "Boolean", "ID", "JSON", "Int64", "UInt64",
+];
+
+pub struct GraphQLGenerator {
+    registry: Handlebars<'static>,
+}
+
+impl GraphQLGenerator {
+    pub fn new() -> Self {
+        let mut registry = Handlebars::new();
+        registry
+            .register_template_string("block", BLOCK_TEMPLATE)
+            .unwrap();
+        registry
+            .register_template_string("union", UNION_TEMPLATE)
+            .unwrap();
+
+        Self { registry }
+    }
+
+    /// Generates the definition `name` and the types hoisted out of it,
+    /// without scalar declarations.
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(None).with_reserved(RESERVED);
+        hoister.add_root(name, schema);
+        let (code, _) = self.render(&hoister)?;
+        Ok(code.join("\n\n"))
+    }
+
+    /// Generates the SDL for every definition in `registry`. Names are
+    /// rewritten and definitions left out where GraphQL requires it; see
+    /// [`diagnostics`](Self::diagnostics).
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
+        let hoister = module_hoister(registry);
+        let (code, module) = self.render(&hoister)?;
+        let scalars = module.scalars;
+
+        let mut blocks: Vec<String> = scalars
+            .iter()
+            .map(|scalar| {
+                let doc = match *scalar {
+                    INT64 => "A 64-bit signed integer.",
+                    UINT64 => "A 64-bit unsigned integer.",
+                    _ => "Any JSON value.",
+                };
+                format!("\"\"\"{}\"\"\"\nscalar {}", doc, scalar)
+            })
+            .collect();
+        blocks.extend(code);
+
+        let mut output = String::new();
+        output.push_str("# Code generated by typebox-rs. DO NOT EDIT.\n");
+        for block in blocks {
+            output.push('\n');
+            output.push_str(&block);
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    /// Warnings about what [`generate_module`](Self::generate_module) does
+    /// to `registry` without failing: names GraphQL does not allow that it
+    /// rewrites (rule `renamed`), definitions with no GraphQL type of their
+    /// own, which are written out where they are used (rule `inlined`), and
+    /// objects and unions left with nothing to hold (rule `dropped`). Paths
+    /// are those of the definitions in the registry.
+    pub fn diagnostics(&self, registry: &SchemaRegistry) -> Result<Vec<Diagnostic>, crate::Error> {
+        let hoister = module_hoister(registry);
+        let (_, module) = self.render(&hoister)?;
+        Ok(module.diagnostics)
+    }
+
+    /// Renders the hoisted definitions and returns them with the module
+    /// state: the custom scalars they use and the diagnostics.
+    fn render<'h>(&self, hoister: &'h Hoister) -> Result<(Vec<String>, Module<'h>), crate::Error> {
+        let mut module = Module::new(hoister);
+        let mut code = Vec::new();
+        for def in &hoister.defs {
+            let path = format!(
+                "#/definitions/{}",
+                def.original.as_deref().unwrap_or(&def.name)
+            );
+            if let Some(original) = &def.original {
+                module.warn(
+                    "renamed",
+                    &path,
+                    format!("{} is named {}", original, def.name),
+                );
+            }
+            module.path = path;
+            let blocks = self.render_def(&def.name, &def.schema, &mut module)?;
+            if def.root && blocks.is_empty() {
+                let name = def.original.as_deref().unwrap_or(&def.name);
+                let (rule, message) = match module.kind(&def.name) {
+                    DefKind::Alias => (
+                        "inlined",
+                        format!(
+                            "{} has no GraphQL type and is written out where it is used",
+                            name
+                        ),
+                    ),
+                    _ => (
+                        "dropped",
+                        format!("{} has no fields GraphQL can hold and is left out", name),
+                    ),
+                };
+                let path = module.path.clone();
+                module.warn(rule, &path, message);
+            }
+            // Enums named after the fields using them come first.
+            for (name, values) in std::mem::take(&mut module.pending) {
+                code.push(self.render_enum(&name, &values, None, &mut module)?);
+            }
+            code.extend(blocks);
+        }
+        Ok((code, module))
+    }
+
+    fn render_def(
+        &self,
+        name: &str,
+        schema: &Schema,
+        module: &mut Module,
+    ) -> Result<Vec<String>, crate::Error> {
+        let mut blocks = Vec::new();
+        match module.kind(name) {
+            DefKind::Object => {
+                for side in [Side::Output, Side::Input] {
+                    if !module.available_def(name, side) {
+                        continue;
+                    }
+                    let fields = module
+                        .fields(schema)
+                        .into_iter()
+                        .filter_map(|(prop, field, required)| {
+                            let hint = format!("{}{}", name, pascal_case(&prop));
+                            module.field(&prop, &field, required, side, &hint)
+                        })
+                        .collect();
+                    let ctx = BlockContext {
+                        doc: description(schema.description.as_deref(), ""),
+                        keyword: side.keyword(),
+                        name: side.type_name(name),
+                        fields,
+                    };
+                    blocks.push(self.registry.render("block", &ctx)?);
+                }
+            }
+            DefKind::Enum => {
+                let values: Vec<String> = match &schema.kind {
+                    SchemaKind::Enum { values } => values.clone(),
+                    SchemaKind::Union { any_of } => string_literals(any_of)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                blocks.push(self.render_enum(
+                    name,
+                    &values,
+                    schema.description.as_deref(),
+                    module,
+                )?);
+            }
+            DefKind::Union => {
+                if module.available_def(name, Side::Output) {
+                    let members = module.members(schema);
+                    let ctx = UnionContext {
+                        doc: description(schema.description.as_deref(), ""),
+                        name: name.to_string(),
+                        members: members.join(" | "),
+                    };
+                    blocks.push(self.registry.render("union", &ctx)?);
+                }
+            }
+            // Other definitions are written out where they are used.
+            DefKind::Alias => {}
+        }
+        Ok(blocks
+            .into_iter()
+            .map(|block| block.trim_end().to_string())
+            .collect())
+    }
+
+    fn render_enum(
+        &self,
+        name: &str,
+        values: &[String],
+        doc: Option<&str>,
+        module: &mut Module,
+    ) -> Result<String, crate::Error> {
+        let mut used = HashSet::new();
+        let mut fields = Vec::new();
+        for value in values {
+            let ident = dedupe(enum_value(value), &mut used);
+            if ident != *value {
+                let path = module.path.clone();
+                module.warn(
+                    "renamed",
+                    &path,
+                    format!("enum value '{}' of {} is written as {}", value, name, ident),
+                );
+            }
+            fields.push(format!("  {}", ident));
+        }
+        let ctx = BlockContext {
+            doc: description(doc, ""),
+            keyword: "enum",
+            name: name.to_string(),
+            fields,
+        };
+        Ok(self.registry.render("block", &ctx)?.trim_end().to_string())
+    }
+}
+
+impl Default for GraphQLGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The definitions of `registry`, dependencies first in a stable order, so
+/// output diffs cleanly.
+fn module_hoister(registry: &SchemaRegistry) -> Hoister<'_> {
+    let mut hoister = Hoister::new(Some(registry)).with_reserved(RESERVED);
+    for name in registry.dependency_graph().topological_order() {
+        if let Some(schema) = registry.get(name) {
+            hoister.add_root(name, schema);
+        }
+    }
+    hoister
+}
+
+const BLOCK_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}{{keyword}} {{name}} {
+{{#each fields}}{{{this}}}
+{{/each}}}
+"#;
+
+const UNION_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}union {{name}} = {{{members}}}
+"#;
+
+const INT64: &str = "Int64";
+const UINT64: &str = "UInt64";
+const JSON: &str = "JSON";
+
+#[derive(Serialize)]
+struct BlockContext {
+    doc: Vec<String>,
+    keyword: &'static str,
+    name: String,
+    /// Rendered fields or values, with their descriptions.
+    fields: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct UnionContext {
+    doc: Vec<String>,
+    name: String,
+    members: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Side {
+    Output,
+    Input,
+}
+
+impl Side {
+    fn keyword(self) -> &'static str {
+        match self {
+            Side::Output => "type",
+            Side::Input => "input",
+        }
+    }
+
+    fn type_name(self, name: &str) -> String {
+        match self {
+            Side::Output => name.to_string(),
+            Side::Input => format!("{}Input", name),
+        }
+    }
+
+    /// Whether a field with `schema`'s annotations belongs on this side.
+    fn includes(self, schema: &Schema) -> bool {
+        match self {
+            Side::Output => schema.write_only != Some(true),
+            Side::Input => schema.read_only != Some(true),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DefKind {
+    Object,
+    Enum,
+    Union,
+    Alias,
+}
+
+/// Rendering state of one module.
+struct Module<'h> {
+    defs: HashMap<&'h str, &'h Schema>,
+    refs: &'h HashMap<String, String>,
+    /// Object and union definitions rendered on each side.
+    available: HashSet<(String, Side)>,
+    /// Enums created for inline `Enum`s, by name.
+    enums: HashMap<String, Vec<String>>,
+    /// Enums created while rendering the current definition.
+    pending: Vec<(String, Vec<String>)>,
+    scalars: BTreeSet<&'static str>,
+    /// Path of the definition being rendered, for diagnostics.
+    path: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'h> Module<'h> {
+    fn new(hoister: &'h Hoister) -> Self {
+        let mut module = Self {
+            defs: hoister
+                .defs
+                .iter()
+                .map(|def| (def.name.as_str(), &def.schema))
+                .collect(),
+            refs: &hoister.refs,
+            available: HashSet::new(),
+            enums: HashMap::new(),
+            pending: Vec::new(),
+            scalars: BTreeSet::new(),
+            path: "#".to_string(),
+            diagnostics: Vec::new(),
+        };
+        module.resolve_available();
+        module
+    }
+
+    /// Records a warning, once.
+    fn warn(&mut self, rule: &'static str, path: &str, message: String) {
+        let diagnostic = Diagnostic {
+            severity: Severity::Warning,
+            rule,
+            path: path.to_string(),
+            message,
+        };
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn kind(&self, name: &str) -> DefKind {
+        let Some(schema) = self.defs.get(name) else {
+            return DefKind::Alias;
+        };
+        match &schema.kind {
+            SchemaKind::Object { properties, .. } if !properties.is_empty() => DefKind::Object,
+            SchemaKind::Intersect { all_of } if all_of.iter().all(|part| self.is_object(part)) => {
+                DefKind::Object
+            }
+            SchemaKind::Enum { .. } => DefKind::Enum,
+            SchemaKind::Union { any_of } if string_literals(any_of).is_some() => DefKind::Enum,
+            SchemaKind::Union { any_of }
+                if any_of.iter().any(|v| !matches!(v.kind, SchemaKind::Null))
+                    && any_of.iter().all(|v| {
+                        matches!(v.kind, SchemaKind::Null)
+                            || self
+                                .ref_target(v)
+                                .is_some_and(|t| self.kind(t) == DefKind::Object)
+                    }) =>
+            {
+                DefKind::Union
+            }
+            _ => DefKind::Alias,
+        }
+    }
+
+    fn is_object(&self, schema: &Schema) -> bool {
+        match &schema.kind {
+            SchemaKind::Object { .. } => true,
+            SchemaKind::Ref { .. } => self
+                .ref_target(schema)
+                .is_some_and(|target| self.kind(target) == DefKind::Object),
+            _ => false,
+        }
+    }
+
+    /// The definition a `Ref` points at.
+    fn ref_target(&self, schema: &Schema) -> Option<&'h str> {
+        let SchemaKind::Ref { reference } = &schema.kind else {
+            return None;
+        };
+        let name = ref_name(reference, self.refs);
+        self.defs
+            .get_key_value(name.as_str())
+            .map(|(name, _)| *name)
+    }
+
+    /// The properties of an object, or of the objects an intersection
+    /// merges (the first schema of a property wins), with whether each is
+    /// required.
+    fn fields(&self, schema: &Schema) -> Vec<(String, Schema, bool)> {
+        let mut fields: Vec<(String, Schema, bool)> = Vec::new();
+        self.collect_fields(schema, &mut fields, &mut HashSet::new());
+        fields
+    }
+
+    fn collect_fields(
+        &self,
+        schema: &Schema,
+        fields: &mut Vec<(String, Schema, bool)>,
+        visited: &mut HashSet<&'h str>,
+    ) {
+        match &schema.kind {
+            SchemaKind::Object {
+                properties,
+                required,
+                ..
+            } => {
+                for (prop, prop_schema) in properties {
+                    if !fields.iter().any(|(name, ..)| name == prop) {
+                        fields.push((prop.clone(), prop_schema.clone(), required.contains(prop)));
+                    }
+                }
+            }
+            SchemaKind::Intersect { all_of } => {
+                for part in all_of {
+                    self.collect_fields(part, fields, visited);
+                }
+            }
+            SchemaKind::Ref { .. } => {
+                if let Some(target) = self.ref_target(schema) {
+                    if visited.insert(target) {
+                        self.collect_fields(self.defs[target], fields, visited);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The object types of a union's variants that are rendered.
+    fn members(&self, schema: &Schema) -> Vec<String> {
+        let SchemaKind::Union { any_of } = &schema.kind else {
+            return Vec::new();
+        };
+        any_of
+            .iter()
+            .filter_map(|variant| self.ref_target(variant))
+            .filter(|target| self.available_def(target, Side::Output))
+            .map(String::from)
+            .collect()
+    }
+
+    fn available_def(&self, name: &str, side: Side) -> bool {
+        self.available.contains(&(name.to_string(), side))
+    }
+
+    /// Works out which objects and unions are rendered on each side: those
+    /// left with a field (or member) once fields of the other side, and
+    /// fields of types not rendered, are dropped.
+    fn resolve_available(&mut self) {
+        for (name, schema) in &self.defs {
+            match self.kind(name) {
+                DefKind::Object => {
+                    for side in [Side::Output, Side::Input] {
+                        if side.includes(schema) {
+                            self.available.insert((name.to_string(), side));
+                        }
+                    }
+                }
+                DefKind::Union => {
+                    self.available.insert((name.to_string(), Side::Output));
+                }
+                DefKind::Enum | DefKind::Alias => {}
+            }
+        }
+        loop {
+            let unavailable: Vec<(String, Side)> = self
+                .available
+                .iter()
+                .filter(|(name, side)| {
+                    let schema = self.defs[name.as_str()];
+                    match self.kind(name) {
+                        DefKind::Union => self.members(schema).is_empty(),
+                        _ => !self.fields(schema).iter().any(|(_, field, _)| {
+                            side.includes(field) && self.renders(field, *side, 0)
+                        }),
+                    }
+                })
+                .cloned()
+                .collect();
+            if unavailable.is_empty() {
+                break;
+            }
+            for entry in unavailable {
+                self.available.remove(&entry);
+            }
+        }
+    }
+
+    /// Whether `schema` has a type on `side`.
+    fn renders(&self, schema: &Schema, side: Side, depth: usize) -> bool {
+        match &schema.kind {
+            SchemaKind::Null
+            | SchemaKind::Void
+            | SchemaKind::Undefined
+            | SchemaKind::Never
+            | SchemaKind::Function { .. }
+            | SchemaKind::Literal {
+                value: LiteralValue::Null,
+            } => false,
+            SchemaKind::Array { items, .. } => self.renders(items, side, depth),
+            SchemaKind::Union { any_of } => match optional_inner(any_of) {
+                Some(inner) => self.renders(inner, side, depth),
+                None => true,
+            },
+            SchemaKind::Ref { .. } => match self.ref_target(schema) {
+                Some(target) => match self.kind(target) {
+                    DefKind::Object => self.available_def(target, side),
+                    DefKind::Union => side == Side::Input || self.available_def(target, side),
+                    DefKind::Enum => true,
+                    DefKind::Alias => {
+                        depth < MAX_ALIAS_DEPTH && self.renders(self.defs[target], side, depth + 1)
+                    }
+                },
+                None => true,
+            },
+            _ => true,
+        }
+    }
+
+    /// The field `prop` on `side`, with its description, if it belongs
+    /// there and has a type.
+    fn field(
+        &mut self,
+        prop: &str,
+        schema: &Schema,
+        required: bool,
+        side: Side,
+        hint: &str,
+    ) -> Option<String> {
+        if !side.includes(schema) || !self.renders(schema, side, 0) {
+            return None;
+        }
+        let (gql_type, nullable) = self.gql_type(schema, side, hint, 0)?;
+        let non_null = required && !nullable;
+        let name = graphql_name(prop);
+        if name != prop {
+            let path = format!("{}/properties/{}", self.path, prop);
+            self.warn(
+                "renamed",
+                &path,
+                format!("field '{}' is written as {}", prop, name),
+            );
+        }
+        let mut line = format!(
+            "  {}: {}{}",
+            name,
+            gql_type,
+            if non_null { "!" } else { "" }
+        );
+        // Required input fields cannot be deprecated.
+        if schema.deprecated == Some(true) && (side == Side::Output || !non_null) {
+            line.push_str(" @deprecated");
+        }
+        let mut lines = description(schema.description.as_deref(), "  ");
+        lines.push(line);
+        Some(lines.join("\n"))
+    }
+
+    /// The GraphQL type of `schema` on `side` and whether it admits `null`.
+    fn gql_type(
+        &mut self,
+        schema: &Schema,
+        side: Side,
+        hint: &str,
+        depth: usize,
+    ) -> Option<(String, bool)> {
+        let gql_type = match &schema.kind {
+            SchemaKind::Bool => "Boolean".to_string(),
+            SchemaKind::Int8 { .. }
+            | SchemaKind::Int16 { .. }
+            | SchemaKind::Int32 { .. }
+            | SchemaKind::UInt8 { .. }
+            | SchemaKind::UInt16 { .. } => "Int".to_string(),
+            SchemaKind::Int64 { .. } | SchemaKind::UInt32 { .. } => self.scalar(INT64),
+            SchemaKind::UInt64 { .. } => self.scalar(UINT64),
+            SchemaKind::Float32 { .. } | SchemaKind::Float64 { .. } => "Float".to_string(),
+            SchemaKind::String { .. } | SchemaKind::Bytes { .. } => "String".to_string(),
+            SchemaKind::Literal { value } => match value {
+                LiteralValue::String(_) => "String".to_string(),
+                LiteralValue::Number(n) if i32::try_from(*n).is_ok() => "Int".to_string(),
+                LiteralValue::Number(_) => self.scalar(INT64),
+                LiteralValue::Float(_) => "Float".to_string(),
+                LiteralValue::Boolean(_) => "Boolean".to_string(),
+                LiteralValue::Null => return None,
+            },
+            SchemaKind::Enum { values } => self.inline_enum(hint, values),
+            SchemaKind::Array { items, .. } => {
+                let (item, nullable) =
+                    self.gql_type(items, side, &format!("{}Item", hint), depth)?;
+                format!("[{}{}]", item, if nullable { "" } else { "!" })
+            }
+            SchemaKind::Union { any_of } => {
+                if let Some(inner) = optional_inner(any_of) {
+                    let (gql_type, _) = self.gql_type(inner, side, hint, depth)?;
+                    return Some((gql_type, true));
+                }
+                match string_literals(any_of) {
+                    Some(values) => {
+                        let values: Vec<String> = values.into_iter().map(String::from).collect();
+                        self.inline_enum(hint, &values)
+                    }
+                    None => self.scalar(JSON),
+                }
+            }
+            SchemaKind::Ref { reference } => {
+                let Some(target) = self.ref_target(schema) else {
+                    return Some((ref_name(reference, self.refs), false));
+                };
+                let target_schema = self.defs[target];
+                match self.kind(target) {
+                    DefKind::Object if self.available_def(target, side) => side.type_name(target),
+                    DefKind::Object => return None,
+                    DefKind::Enum => target.to_string(),
+                    DefKind::Union if side == Side::Input => self.scalar(JSON),
+                    DefKind::Union if self.available_def(target, side) => {
+                        let nullable = matches!(
+                            &target_schema.kind,
+                            SchemaKind::Union { any_of } if any_of.iter().any(|v| matches!(v.kind, SchemaKind::Null))
+                        );
+                        return Some((target.to_string(), nullable));
+                    }
+                    DefKind::Union => return None,
+                    DefKind::Alias if depth < MAX_ALIAS_DEPTH => {
+                        return self.gql_type(target_schema, side, target, depth + 1);
+                    }
+                    DefKind::Alias => self.scalar(JSON),
+                }
+            }
+            SchemaKind::Null
+            | SchemaKind::Void
+            | SchemaKind::Undefined
+            | SchemaKind::Never
+            | SchemaKind::Function { .. } => return None,
+            _ => self.scalar(JSON),
+        };
+        Some((gql_type, false))
+    }
+
+    fn scalar(&mut self, name: &'static str) -> String {
+        self.scalars.insert(name);
+        name.to_string()
+    }
+
+    /// The enum for an inline `Enum` at `hint`, created on first use.
+    fn inline_enum(&mut self, hint: &str, values: &[String]) -> String {
+        let mut name = hint.to_string();
+        let mut suffix = 1;
+        loop {
+            match self.enums.get(&name) {
+                Some(existing) if existing == values => return name,
+                None if !self.defs.contains_key(name.as_str()) => break,
+                _ => {
+                    suffix += 1;
+                    name = format!("{}{}", hint, suffix);
+                }
+            }
+        }
+        self.enums.insert(name.clone(), values.to_vec());
+        self.pending.push((name.clone(), values.to_vec()));
+        name
+    }
+}
+
+/// How many alias definitions a type is looked up through.
+const MAX_ALIAS_DEPTH: usize = 16;
+
+/// A GraphQL name for `name`: characters outside `[_0-9A-Za-z]` become
+/// `_`, and a leading digit gets a `_` prefix.
+fn graphql_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// An enum value for `value`; `true`, `false` and `null` are not allowed.
+fn enum_value(value: &str) -> String {
+    let name = graphql_name(value);
+    match name.as_str() {
+        "true" | "false" | "null" => format!("{}_", name),
+        _ => name,
+    }
+}
+
+fn dedupe(name: String, used: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut suffix = 1;
+    while !used.insert(candidate.clone()) {
+        suffix += 1;
+        candidate = format!("{}{}", name, suffix);
+    }
+    candidate
+}
+
+/// A description as block string lines indented by `indent`.
+fn description(text: Option<&str>, indent: &str) -> Vec<String> {
+    let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) else {
+        return Vec::new();
+    };
+    let text = text.replace("\"\"\"", "\\\"\"\"");
+    // A quote just before the closing quotes would end the string early.
+    if text.contains('\n') || text.ends_with('"') {
+        let mut lines = vec![format!("{}\"\"\"", indent)];
+        lines.extend(
+            text.lines()
+                .map(|line| format!("{}{}", indent, line).trim_end().to_string()),
+        );
+        lines.push(format!("{}\"\"\"", indent));
+        lines
+    } else {
+        vec![format!("{}\"\"\"{}\"\"\"", indent, text)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SchemaBuilder;
+
+    #[test]
+    fn test_generate_type_and_input() {
+        let gen = GraphQLGenerator::new();
+        let mut id = SchemaBuilder::int64();
+        id.read_only = Some(true);
+        let mut password = SchemaBuilder::string().build();
+        password.write_only = Some(true);
+        let mut legacy = SchemaBuilder::optional(SchemaBuilder::uint8());
+        legacy.deprecated = Some(true);
+        let schema = SchemaBuilder::object()
+            .field("id", id)
+            .field(
+                "name",
+                SchemaBuilder::string()
+                    .build()
+                    .with_description("Full name."),
+            )
+            .optional_field("email", SchemaBuilder::string().build())
+            .field("password", password)
+            .field(
+                "roles",
+                SchemaBuilder::array(SchemaBuilder::enum_values(vec!["admin", "in-review"]))
+                    .build(),
+            )
+            .field("legacy", legacy)
+            .field(
+                "settings",
+                SchemaBuilder::object()
+                    .additional_properties(Some(SchemaBuilder::string().build()))
+                    .build(),
+            )
+            .build()
+            .with_description("A user.\nCreated on sign-up.");
+
+        let output = gen.generate("User", &schema).unwrap();
+        assert_eq!(
+            output,
+            r#"enum UserRolesItem {
+  admin
+  in_review
+}
+
+"""
+A user.
+Created on sign-up.
+"""
+type User {
+  id: Int64!
+  """Full name."""
+  name: String!
+  email: String
+  roles: [UserRolesItem!]!
+  legacy: Int @deprecated
+  settings: JSON!
+}
+
+"""
+A user.
+Created on sign-up.
+"""
+input UserInput {
+  """Full name."""
+  name: String!
+  email: String
+  password: String!
+  roles: [UserRolesItem!]!
+  legacy: Int @deprecated
+  settings: JSON!
+}"#
+        );
+    }
+
+    #[test]
+    fn test_generate_module_with_unions_and_scalars() {
+        let gen = GraphQLGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Status",
+            SchemaBuilder::enum_values(vec!["active", "banned"]).with_description("Account state."),
+        );
+        registry.register(
+            "Circle",
+            SchemaBuilder::object()
+                .field("radius", SchemaBuilder::float64())
+                .build(),
+        );
+        registry.register(
+            "Square",
+            SchemaBuilder::object()
+                .field("side", SchemaBuilder::uint64())
+                .build(),
+        );
+        registry.register(
+            "Shape",
+            SchemaBuilder::union(vec![
+                SchemaBuilder::r#ref("Circle"),
+                SchemaBuilder::r#ref("Square"),
+            ]),
+        );
+        let mut audit = SchemaBuilder::object()
+            .field("by", SchemaBuilder::string().build())
+            .build();
+        audit.read_only = Some(true);
+        registry.register("Audit", audit);
+        registry.register(
+            "Canvas",
+            SchemaBuilder::object()
+                .field(
+                    "shapes",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Shape")).build(),
+                )
+                .optional_field(
+                    "main",
+                    SchemaBuilder::optional(SchemaBuilder::r#ref("Shape")),
+                )
+                .field("status", SchemaBuilder::r#ref("Status"))
+                .field("audit", SchemaBuilder::r#ref("Audit"))
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.starts_with(
+            "# Code generated by typebox-rs. DO NOT EDIT.\n\n\"\"\"Any JSON value.\"\"\"\nscalar JSON\n\n\"\"\"A 64-bit unsigned integer.\"\"\"\nscalar UInt64\n"
+        ));
+        assert!(
+            output.contains("\n\"\"\"Account state.\"\"\"\nenum Status {\n  active\n  banned\n}\n")
+        );
+        assert!(output.contains("\nunion Shape = Circle | Square\n"));
+        assert!(output.contains("\ntype Audit {\n  by: String!\n}\n"));
+        assert!(!output.contains("AuditInput"));
+        assert!(output.contains(
+            "\ntype Canvas {\n  shapes: [Shape!]!\n  main: Shape\n  status: Status!\n  audit: Audit!\n}\n"
+        ));
+        assert!(output.contains(
+            "\ninput CanvasInput {\n  shapes: [JSON!]!\n  main: JSON\n  status: Status!\n}\n"
+        ));
+        assert!(output.contains("\ninput SquareInput {\n  side: UInt64!\n}\n"));
+    }
+
+    #[test]
+    fn test_generate_intersection_and_empty_sides() {
+        let gen = GraphQLGenerator::new();
+        let mut secret = SchemaBuilder::string().build();
+        secret.write_only = Some(true);
+        let schema = SchemaBuilder::intersect(vec![
+            SchemaBuilder::object()
+                .field("a", SchemaBuilder::int32())
+                .build(),
+            SchemaBuilder::object()
+                .field("a", SchemaBuilder::string().build())
+                .field("secret", secret)
+                .build(),
+        ]);
+        let output = gen.generate("Merged", &schema).unwrap();
+        assert!(output.contains("type Merged {\n  a: Int!\n}"));
+        assert!(output.contains("input MergedInput {\n  a: Int!\n  secret: String!\n}"));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let gen = GraphQLGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Order",
+            SchemaBuilder::object()
+                .field("name-with-dash", SchemaBuilder::string().build())
+                .field(
+                    "status",
+                    SchemaBuilder::enum_values(vec!["active", "in-progress"]),
+                )
+                .build(),
+        );
+        registry.register(
+            "List",
+            SchemaBuilder::array(SchemaBuilder::r#ref("Order")).build(),
+        );
+        registry.register(
+            "Nums",
+            SchemaBuilder::union(vec![
+                SchemaBuilder::literal(1i64),
+                SchemaBuilder::literal(2i64),
+            ]),
+        );
+
+        let diagnostics: Vec<String> = gen
+            .diagnostics(&registry)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "warning[inlined] at #/definitions/Nums: Nums has no GraphQL type and is written out where it is used",
+                "warning[renamed] at #/definitions/Order/properties/name-with-dash: field 'name-with-dash' is written as name_with_dash",
+                "warning[renamed] at #/definitions/Order: enum value 'in-progress' of OrderStatus is written as in_progress",
+                "warning[inlined] at #/definitions/List: List has no GraphQL type and is written out where it is used",
+            ]
+        );
+    }
+}
@@ -0,0 +1,628 @@
+//! Hoisting of anonymous nested types into named definitions.
+//!
+//! Generators can only refer to structured types by name, so before
+//! rendering, every anonymous object, union and recursive schema nested in
+//! a definition is lifted into a definition of its own, named after its
+//! position: the `connectivity` items of `TableInfo` become
+//! `TableInfoConnectivityItem`. The nested schema is replaced by a `Ref` to
+//! the new definition, and `$ref`s are resolved through the registry to the
+//! name of the definition they point at. Generators that need a named type
+//! to hold a literal or an enum can have those hoisted too.
+//!
+//! Registry names need not be identifiers: schemas loaded from files are
+//! registered under their `$id`. Root definitions are named by [`TypeNames`]
+//! instead: `https://example.com/schemas/order.json` becomes `Order`.
+
+use crate::registry::SchemaRegistry;
+use crate::schema::{LiteralValue, Schema, SchemaKind};
+use std::collections::{HashMap, HashSet};
+
+/// A type to render: its name and its schema, in which nested structured
+/// types have been replaced by `Ref`s.
+pub(crate) struct TypeDef {
+    pub name: String,
+    pub schema: Schema,
+    /// Whether the definition was added with [`Hoister::add_root`] rather
+    /// than hoisted out of another.
+    pub root: bool,
+    /// The name a root definition was added under, if `name` differs.
+    pub original: Option<String>,
+}
+
+/// Type names for registry names, which may be URIs, keywords or names
+/// clashing with the target language's own types.
+pub(crate) struct TypeNames {
+    /// Names generated code uses for its own purposes.
+    reserved: &'static [&'static str],
+    names: HashMap<String, String>,
+    used: HashSet<String>,
+}
+
+impl TypeNames {
+    /// Names every schema in `registry`, in dependency order so the names
+    /// are stable.
+    pub fn new(registry: Option<&SchemaRegistry>, reserved: &'static [&'static str]) -> Self {
+        let mut names = Self {
+            reserved,
+            names: HashMap::new(),
+            used: HashSet::new(),
+        };
+        if let Some(registry) = registry {
+            for name in registry.dependency_graph().topological_order() {
+                names.get(name);
+            }
+        }
+        names
+    }
+
+    /// The type name for `name`, allocating one on first use.
+    pub fn get(&mut self, name: &str) -> String {
+        if let Some(type_name) = self.names.get(name) {
+            return type_name.clone();
+        }
+        let base = self.escape(type_name(name));
+        let mut type_name = base.clone();
+        let mut suffix = 1;
+        while self.used.contains(&type_name) {
+            suffix += 1;
+            type_name = format!("{}{}", base, suffix);
+        }
+        self.used.insert(type_name.clone());
+        self.names.insert(name.to_string(), type_name.clone());
+        type_name
+    }
+
+    /// `name`, with an underscore appended if it is reserved.
+    pub fn escape(&self, name: String) -> String {
+        if self.reserved.contains(&name.as_str()) {
+            format!("{}_", name)
+        } else {
+            name
+        }
+    }
+}
+
+/// Collects the named definitions of one module.
+pub(crate) struct Hoister<'r> {
+    registry: Option<&'r SchemaRegistry>,
+    /// Base URI of the definition being hoisted, for resolving `$ref`s.
+    base: Option<String>,
+    /// Schemas of the names in use, so identical hoisted types are shared
+    /// and different ones get a numeric suffix.
+    taken: HashMap<String, Schema>,
+    /// Whether non-null literals in type positions are hoisted.
+    literals: bool,
+    /// Whether `Enum`s in type positions are hoisted.
+    enums: bool,
+    /// Type names of the root definitions.
+    names: TypeNames,
+    /// Definitions in dependency order.
+    pub defs: Vec<TypeDef>,
+    /// Type name for each `$ref` string.
+    pub refs: HashMap<String, String>,
+}
+
+impl<'r> Hoister<'r> {
+    pub fn new(registry: Option<&'r SchemaRegistry>) -> Self {
+        let mut hoister = Self {
+            registry,
+            base: None,
+            taken: HashMap::new(),
+            literals: false,
+            enums: false,
+            names: TypeNames::new(None, &[]),
+            defs: Vec::new(),
+            refs: HashMap::new(),
+        };
+        hoister.name_registry(&[]);
+        hoister
+    }
+
+    /// Also hoists `Enum`s found in type positions.
+    pub fn with_enums(mut self) -> Self {
+        self.enums = true;
+        self
+    }
+
+    /// Keeps type names off `reserved`, the names generated code uses for
+    /// its own purposes, by appending an underscore.
+    pub fn with_reserved(mut self, reserved: &'static [&'static str]) -> Self {
+        self.name_registry(reserved);
+        self
+    }
+
+    /// Names the registry's schemas, reserving their names for them.
+    fn name_registry(&mut self, reserved: &'static [&'static str]) {
+        self.names = TypeNames::new(self.registry, reserved);
+        self.taken.clear();
+        self.refs.clear();
+        for (name, schema) in self.registry.into_iter().flat_map(SchemaRegistry::schemas) {
+            let type_name = self.names.get(name);
+            self.taken.insert(type_name.clone(), schema.clone());
+            self.refs
+                .insert(format!("#/definitions/{}", name), type_name);
+        }
+    }
+
+    /// Also hoists non-null literals found in type positions, except the
+    /// variants of a union of string literals, which reads as an enum.
+    pub fn with_literals(mut self) -> Self {
+        self.literals = true;
+        self
+    }
+
+    /// Adds the definition `name` and everything hoisted out of it, the
+    /// hoisted types first.
+    pub fn add_root(&mut self, name: &str, schema: &Schema) {
+        self.base = self.registry.and_then(|r| r.document_base(name));
+        let type_name = self.names.get(name);
+        self.taken.insert(type_name.clone(), schema.clone());
+        self.refs
+            .insert(format!("#/definitions/{}", name), type_name.clone());
+        let schema = self.hoist_children(&type_name, schema);
+        self.defs.push(TypeDef {
+            original: (type_name != name).then(|| name.to_string()),
+            name: type_name,
+            schema,
+            root: true,
+        });
+    }
+
+    /// Defines `schema` under a name derived from `base`, returning the
+    /// name.
+    fn define(&mut self, base: &str, schema: &Schema) -> String {
+        let name = self.unique_name(base, schema);
+        let reference = format!("#/definitions/{}", name);
+        if !self.refs.contains_key(&reference) {
+            self.taken.insert(name.clone(), schema.clone());
+            self.refs.insert(reference, name.clone());
+            let hoisted = self.hoist_children(&name, schema);
+            self.defs.push(TypeDef {
+                name: name.clone(),
+                schema: hoisted,
+                root: false,
+                original: None,
+            });
+        }
+        name
+    }
+
+    /// Like [`define`](Self::define), returning a `Ref` to the definition
+    /// that keeps the annotations of `schema` for the referring field.
+    fn define_ref(&mut self, base: &str, schema: &Schema) -> Schema {
+        let name = self.define(base, schema);
+        definition_ref(&name, schema)
+    }
+
+    fn unique_name(&self, base: &str, schema: &Schema) -> String {
+        let base = self.names.escape(base.to_string());
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while let Some(existing) = self.taken.get(&name) {
+            if existing == schema {
+                break;
+            }
+            suffix += 1;
+            name = format!("{}{}", base, suffix);
+        }
+        name
+    }
+
+    /// Hoists the structured types nested in the definition `name`.
+    fn hoist_children(&mut self, name: &str, schema: &Schema) -> Schema {
+        let kind = match &schema.kind {
+            SchemaKind::Named { schema: inner, .. } => {
+                return self.hoist_children(name, inner);
+            }
+            SchemaKind::Recursive { schema: inner } => {
+                if let Some(id) = &schema.id {
+                    self.refs.insert(id.clone(), name.to_string());
+                }
+                return self.hoist_children(name, inner);
+            }
+            SchemaKind::Object {
+                properties,
+                required,
+                additional_properties,
+            } => SchemaKind::Object {
+                properties: properties
+                    .iter()
+                    .map(|(prop, schema)| {
+                        let base = format!("{}{}", name, pascal_case(prop));
+                        (prop.clone(), self.hoist_type(&base, schema))
+                    })
+                    .collect(),
+                required: required.clone(),
+                additional_properties: additional_properties
+                    .as_ref()
+                    .map(|a| Box::new(self.hoist_type(&format!("{}Value", name), a))),
+            },
+            SchemaKind::Union { any_of } if string_literals(any_of).is_some() => {
+                return schema.clone();
+            }
+            SchemaKind::Union { any_of } => SchemaKind::Union {
+                any_of: any_of
+                    .iter()
+                    .enumerate()
+                    .map(|(i, variant)| self.hoist_type(&format!("{}Variant{}", name, i), variant))
+                    .collect(),
+            },
+            SchemaKind::Intersect { all_of } => SchemaKind::Intersect {
+                all_of: all_of
+                    .iter()
+                    .enumerate()
+                    .map(|(i, member)| self.hoist_type(&format!("{}Part{}", name, i), member))
+                    .collect(),
+            },
+            SchemaKind::Literal { .. } | SchemaKind::Enum { .. } => return schema.clone(),
+            _ => return self.hoist_type(name, schema),
+        };
+        Schema {
+            kind,
+            ..schema.clone()
+        }
+    }
+
+    /// Hoists `schema`, found in a type position, if it needs a name of its
+    /// own, and the structured types nested in it otherwise.
+    fn hoist_type(&mut self, base: &str, schema: &Schema) -> Schema {
+        let kind = match &schema.kind {
+            SchemaKind::Object { properties, .. } if !properties.is_empty() => {
+                return self.define_ref(base, schema);
+            }
+            SchemaKind::Union { any_of } if optional_inner(any_of).is_none() => {
+                return self.define_ref(base, schema);
+            }
+            SchemaKind::Enum { values } if self.enums && !values.is_empty() => {
+                return self.define_ref(base, schema);
+            }
+            SchemaKind::Recursive { .. } => return self.define_ref(base, schema),
+            SchemaKind::Literal { value } if self.literals && *value != LiteralValue::Null => {
+                return self.define_ref(base, schema);
+            }
+            SchemaKind::Named {
+                name,
+                schema: inner,
+            } => {
+                if self.registry.is_some_and(|r| r.contains(name)) {
+                    return definition_ref(name, schema);
+                }
+                return self.define_ref(&type_name(name), inner);
+            }
+            SchemaKind::Ref { reference } => {
+                self.resolve(base, reference);
+                return schema.clone();
+            }
+
+            SchemaKind::Object {
+                properties,
+                required,
+                additional_properties,
+            } => SchemaKind::Object {
+                properties: properties.clone(),
+                required: required.clone(),
+                additional_properties: additional_properties
+                    .as_ref()
+                    .map(|a| Box::new(self.hoist_type(&format!("{}Value", base), a))),
+            },
+            SchemaKind::Union { any_of } => SchemaKind::Union {
+                any_of: any_of.iter().map(|v| self.hoist_type(base, v)).collect(),
+            },
+            SchemaKind::Array {
+                items,
+                min_items,
+                max_items,
+                unique_items,
+            } => SchemaKind::Array {
+                items: Box::new(self.hoist_type(&format!("{}Item", base), items)),
+                min_items: *min_items,
+                max_items: *max_items,
+                unique_items: *unique_items,
+            },
+            SchemaKind::Tuple { items } => SchemaKind::Tuple {
+                items: items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| self.hoist_type(&format!("{}Item{}", base, i), item))
+                    .collect(),
+            },
+            SchemaKind::Intersect { .. } => return self.define_ref(base, schema),
+            SchemaKind::Function {
+                parameters,
+                returns,
+            } => SchemaKind::Function {
+                parameters: parameters
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| self.hoist_type(&format!("{}Param{}", base, i), p))
+                    .collect(),
+                returns: Box::new(self.hoist_type(&format!("{}Return", base), returns)),
+            },
+            _ => return schema.clone(),
+        };
+        Schema {
+            kind,
+            ..schema.clone()
+        }
+    }
+
+    /// Records the type name a `$ref` stands for, hoisting its target when
+    /// it is not a registered definition (e.g. a pointer into one).
+    fn resolve(&mut self, base: &str, reference: &str) {
+        if self.refs.contains_key(reference) {
+            return;
+        }
+        let Some(registry) = self.registry else {
+            return;
+        };
+        let Ok(target) = registry.resolve_ref(reference, self.base.as_deref()) else {
+            return;
+        };
+        let registered = registry
+            .schemas()
+            .find(|(_, schema)| std::ptr::eq(*schema, target))
+            .map(|(name, _)| name.clone());
+        let name = match (registered, &target.kind) {
+            (Some(name), _) => self.names.get(&name),
+            (None, SchemaKind::Named { name, schema }) => self.define(&type_name(name), schema),
+            (None, _) => {
+                let derived = reference
+                    .rsplit(['/', '#'])
+                    .find(|token| !token.is_empty())
+                    .map(pascal_case)
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| base.to_string());
+                self.define(&derived, target)
+            }
+        };
+        self.refs.insert(reference.to_string(), name);
+    }
+}
+
+/// The PascalCase type name for a registry name, taking the stem of the
+/// last path segment of a URI: `https://example.com/order.json` becomes
+/// `Order`.
+pub(crate) fn type_name(name: &str) -> String {
+    let stem = if name.contains([':', '/']) {
+        let path = name.split(['#', '?']).next().unwrap_or(name);
+        let segment = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(path);
+        match segment.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => stem,
+            _ => segment,
+        }
+    } else {
+        name
+    };
+    match pascal_case(stem) {
+        type_name if type_name.is_empty() => "Type".to_string(),
+        type_name => type_name,
+    }
+}
+
+/// A `Ref` to the definition `name` with the annotations of `schema`.
+fn definition_ref(name: &str, schema: &Schema) -> Schema {
+    Schema {
+        kind: SchemaKind::Ref {
+            reference: format!("#/definitions/{}", name),
+        },
+        id: None,
+        schema_version: None,
+        ..schema.clone()
+    }
+}
+
+/// The non-null variant of an optional union (`T | null`).
+pub(crate) fn optional_inner(any_of: &[Schema]) -> Option<&Schema> {
+    match any_of {
+        [a, b] if matches!(b.kind, SchemaKind::Null) => Some(a),
+        [a, b] if matches!(a.kind, SchemaKind::Null) => Some(b),
+        _ => None,
+    }
+}
+
+/// The values of a union whose variants are all string literals.
+pub(crate) fn string_literals(any_of: &[Schema]) -> Option<Vec<&str>> {
+    any_of
+        .iter()
+        .map(|variant| match &variant.kind {
+            SchemaKind::Literal {
+                value: LiteralValue::String(s),
+            } => Some(s.as_str()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|values| !values.is_empty())
+}
+
+/// Converts a property name such as `is_primary-key` or `isPrimaryKey` to
+/// `IsPrimaryKey`.
+pub(crate) fn pascal_case(name: &str) -> String {
+    let mut out = String::new();
+    for part in name.split(|c: char| !c.is_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// The type name for a `$ref`: the hoisted or registered definition it
+/// resolves to, else the last token of a local definitions pointer.
+pub(crate) fn ref_name(reference: &str, refs: &HashMap<String, String>) -> String {
+    if let Some(name) = refs.get(reference) {
+        return name.clone();
+    }
+    reference
+        .strip_prefix("#/definitions/")
+        .or_else(|| reference.strip_prefix("#/$defs/"))
+        .unwrap_or(reference)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+
+    fn names<'a>(hoister: &'a Hoister) -> Vec<&'a str> {
+        hoister.defs.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_hoists_nested_types_with_derived_names() {
+        let point = SchemaBuilder::object()
+            .field("x", SchemaBuilder::float64())
+            .build();
+        let schema = SchemaBuilder::object()
+            .field("connectivity", SchemaBuilder::array(point.clone()).build())
+            .field("pair", SchemaBuilder::tuple(vec![point.clone(), point]))
+            .field(
+                "shape",
+                SchemaBuilder::union(vec![
+                    SchemaBuilder::string().build(),
+                    SchemaBuilder::object()
+                        .field("radius", SchemaBuilder::float64())
+                        .build(),
+                ]),
+            )
+            .build();
+
+        let mut hoister = Hoister::new(None);
+        hoister.add_root("TableInfo", &schema);
+        assert_eq!(
+            names(&hoister),
+            [
+                "TableInfoConnectivityItem",
+                "TableInfoPairItem0",
+                "TableInfoPairItem1",
+                "TableInfoShapeVariant1",
+                "TableInfoShape",
+                "TableInfo",
+            ]
+        );
+
+        // A different type under a derived name that is taken gets a suffix.
+        let mut again = Hoister::new(None);
+        again.add_root("Other", &SchemaBuilder::string().build());
+        assert_eq!(
+            again.unique_name("Other", &SchemaBuilder::int32()),
+            "Other2"
+        );
+    }
+
+    #[test]
+    fn test_resolves_refs_through_registry() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .field("address", SchemaBuilder::r#ref("Address"))
+                .field(
+                    "street",
+                    Schema::new(SchemaKind::Ref {
+                        reference: "address.json#/properties/street".to_string(),
+                    }),
+                )
+                .build()
+                .with_id("https://example.com/person.json"),
+        );
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field(
+                    "street",
+                    SchemaBuilder::object()
+                        .field("line", SchemaBuilder::string().build())
+                        .build(),
+                )
+                .build()
+                .with_id("https://example.com/address.json"),
+        );
+
+        let mut hoister = Hoister::new(Some(&registry));
+        hoister.add_root("Person", registry.get("Person").unwrap());
+        assert_eq!(
+            ref_name("address.json#/properties/street", &hoister.refs),
+            "Street"
+        );
+        assert_eq!(ref_name("#/definitions/Address", &hoister.refs), "Address");
+    }
+
+    #[test]
+    fn test_hoists_literals_on_request() {
+        let literal = |s: &str| {
+            Schema::new(SchemaKind::Literal {
+                value: LiteralValue::String(s.to_string()),
+            })
+        };
+        let mode = SchemaBuilder::union(vec![literal("a"), literal("b")]);
+        let schema = SchemaBuilder::object()
+            .field("version", literal("v1"))
+            .field("mode", mode.clone())
+            .build();
+
+        let mut plain = Hoister::new(None);
+        plain.add_root("Doc", &schema);
+        assert_eq!(names(&plain), ["DocMode", "Doc"]);
+
+        let mut hoister = Hoister::new(None).with_literals();
+        hoister.add_root("Doc", &schema);
+        assert_eq!(names(&hoister), ["DocVersion", "DocMode", "Doc"]);
+        assert_eq!(hoister.defs[0].schema, literal("v1"));
+        // A union of string literals keeps its variants, to read as an enum.
+        assert_eq!(hoister.defs[1].schema, mode);
+    }
+
+    #[test]
+    fn test_names_roots_as_types() {
+        let mut registry = SchemaRegistry::new();
+        let order = SchemaBuilder::object()
+            .field("billing", SchemaBuilder::r#ref("address"))
+            .build();
+        registry.register("https://example.com/schemas/order.json", order);
+        registry.register(
+            "https://example.com/legacy/order.json",
+            SchemaBuilder::int32(),
+        );
+        registry.register("address", SchemaBuilder::string().build());
+        registry.register("Self", SchemaBuilder::bool());
+
+        let mut hoister = Hoister::new(Some(&registry)).with_reserved(&["Self"]);
+        for name in registry.dependency_graph().topological_order() {
+            hoister.add_root(name, registry.get(name).unwrap());
+        }
+        let mut defs: Vec<(&str, Option<&str>)> = hoister
+            .defs
+            .iter()
+            .map(|def| (def.name.as_str(), def.original.as_deref()))
+            .collect();
+        defs.sort();
+        assert_eq!(
+            defs,
+            [
+                ("Address", Some("address")),
+                ("Order", Some("https://example.com/legacy/order.json")),
+                ("Order2", Some("https://example.com/schemas/order.json")),
+                ("Self_", Some("Self")),
+            ]
+        );
+        assert_eq!(ref_name("#/definitions/address", &hoister.refs), "Address");
+    }
+
+    #[test]
+    fn test_pascal_case() {
+        assert_eq!(pascal_case("isPrimaryKey"), "IsPrimaryKey");
+        assert_eq!(pascal_case("rel_groups"), "RelGroups");
+        assert_eq!(pascal_case("in-progress"), "InProgress");
+        assert_eq!(pascal_case("2xx"), "_2xx");
+    }
+}
@@ -1,7 +1,20 @@
+mod go;
+mod graphql;
+mod hoist;
+mod proto;
+mod pydantic;
 mod rust;
+mod typebox;
 mod typescript;
+mod zod;
 
+pub use go::GoGenerator;
+pub use graphql::GraphQLGenerator;
+pub use proto::{FieldNumbers, ProtoGenerator};
+pub use pydantic::PydanticGenerator;
 pub use rust::RustGenerator;
+pub use typebox::TypeBoxGenerator;
 pub use typescript::TypeScriptGenerator;
+pub use zod::ZodGenerator;
 
 pub use crate::registry::SchemaRegistry;
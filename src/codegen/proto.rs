@@ -0,0 +1,1307 @@
+//! Protocol Buffers (proto3) generation.
+//!
+//! Objects become messages, `Enum`s and unions of string literals become
+//! enums whose zero value is `{NAME}_UNSPECIFIED`, arrays become `repeated`
+//! fields and objects with only `additional_properties` become
+//! `map<string, V>` fields. A union held by a single field becomes a `oneof`
+//! of that field's message; any other union becomes a message holding just a
+//! `oneof`:
+//!
+//! ```text
+//! message Person {
+//!   int64 id = 1;
+//!   optional string email = 2;
+//!   repeated string tags = 3;
+//!   oneof contact {
+//!     Phone phone = 4;
+//!     Address address = 5;
+//!   }
+//! }
+//! ```
+//!
+//! Field numbers come from the [`FieldNumbers`] given with
+//! [`ProtoGenerator::with_field_numbers`], by message and field name; other
+//! fields are numbered after the highest number given for their message, in
+//! declaration order. Storing what [`ProtoGenerator::field_numbers`] returns
+//! and passing it back on the next run keeps the numbers stable as fields
+//! are added, removed and reordered.
+//!
+//! Proto3 has no type aliases, so definitions other than objects, enums and
+//! unions are inlined where they are used, and lists or maps nested in one
+//! another are wrapped in a message with a single `values` field. `Any` and
+//! other untyped schemas become `google.protobuf.Value`, and `date-time`
+//! strings become `google.protobuf.Timestamp`, whose JSON form is the same
+//! RFC 3339 string. Property names that protobuf's JSON mapping would not
+//! derive from the field name are kept with `json_name`.
+//!
+//! An `Intersect` of objects becomes a single message holding the fields
+//! of each; merging anything else is an error. A union of literals of one
+//! type other than string, such as `1 | 2`, becomes a field of that type.
+
+use super::hoist::{optional_inner, pascal_case, ref_name, string_literals, Hoister};
+use super::rust::snake_case;
+use crate::codegen::SchemaRegistry;
+pub use crate::import::FieldNumbers;
+use crate::schema::{LiteralValue, Schema, SchemaKind, StringFormat};
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::RangeInclusive;
+
+/// The highest field number protobuf allows.
+const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+/// Field numbers reserved for the protobuf implementation.
+const RESERVED_NUMBERS: RangeInclusive<u32> = 19_000..=19_999;
+
+/// How many alias definitions are followed before giving up on a cycle.
+const MAX_ALIAS_DEPTH: usize = 32;
+
+pub struct ProtoGenerator {
+    registry: Handlebars<'static>,
+    package: Option<String>,
+    field_numbers: FieldNumbers,
+}
+
+impl ProtoGenerator {
+    pub fn new() -> Self {
+        let mut registry = Handlebars::new();
+        registry
+            .register_template_string("block", BLOCK_TEMPLATE)
+            .unwrap();
+
+        Self {
+            registry,
+            package: None,
+            field_numbers: FieldNumbers::new(),
+        }
+    }
+
+    /// Sets the `package` of generated modules.
+    pub fn with_package(mut self, package: impl Into<String>) -> Self {
+        self.package = Some(package.into());
+        self
+    }
+
+    /// Numbers the fields named in `numbers` as given. A message's other
+    /// fields are numbered after the highest number given for it, so the
+    /// numbers of removed fields are not reused.
+    pub fn with_field_numbers(mut self, numbers: FieldNumbers) -> Self {
+        self.field_numbers = numbers;
+        self
+    }
+
+    /// Generates the messages and enums for `name` and the types hoisted
+    /// out of it, without the `syntax` line and imports.
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(None);
+        hoister.add_root(name, schema);
+        let (blocks, _) = self.render(&hoister)?;
+        Ok(blocks.join("\n"))
+    }
+
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
+        let hoister = module_hoister(registry);
+        let (blocks, module) = self.render(&hoister)?;
+        let imports = module.imports;
+
+        let mut output = String::new();
+        output.push_str("// Code generated by typebox-rs. DO NOT EDIT.\n\n");
+        output.push_str("syntax = \"proto3\";\n");
+        if let Some(package) = &self.package {
+            output.push_str(&format!("\npackage {};\n", package));
+        }
+        if !imports.is_empty() {
+            output.push('\n');
+            for import in &imports {
+                output.push_str(&format!("import \"{}\";\n", import));
+            }
+        }
+
+        for block in blocks {
+            output.push('\n');
+            output.push_str(&block);
+        }
+
+        Ok(output)
+    }
+
+    /// The field numbers [`generate_module`](Self::generate_module) gives
+    /// `registry`: those set with
+    /// [`with_field_numbers`](Self::with_field_numbers), including the ones
+    /// of fields no longer generated, and the numbers given to the other
+    /// fields.
+    pub fn field_numbers(&self, registry: &SchemaRegistry) -> Result<FieldNumbers, crate::Error> {
+        let hoister = module_hoister(registry);
+        let (_, module) = self.render(&hoister)?;
+        let mut numbers = self.field_numbers.clone();
+        for (message, fields) in module.numbers {
+            numbers.entry(message).or_default().extend(fields);
+        }
+        Ok(numbers)
+    }
+
+    /// Renders the messages and enums of the hoisted definitions and
+    /// returns them with the module state: the files they import and the
+    /// field numbers given.
+    fn render<'h>(
+        &'h self,
+        hoister: &'h Hoister,
+    ) -> Result<(Vec<String>, Module<'h>), crate::Error> {
+        let mut module = Module::new(hoister, &self.field_numbers);
+        let mut blocks = Vec::new();
+        for def in &hoister.defs {
+            let block = match module.kind(&def.name) {
+                DefKind::Message => Some(module.message(&def.name, &def.schema)?),
+                DefKind::Enum => {
+                    let values: Vec<&str> = match &def.schema.kind {
+                        SchemaKind::Enum { values } => values.iter().map(String::as_str).collect(),
+                        SchemaKind::Union { any_of } => string_literals(any_of).unwrap_or_default(),
+                        _ => Vec::new(),
+                    };
+                    Some(enum_block(&def.name, &values, &def.schema))
+                }
+                DefKind::Wrapper => Some(module.wrapper(&def.name, &def.schema)?),
+                DefKind::Oneof | DefKind::Alias => None,
+            };
+            if let Some(block) = block {
+                blocks.push(self.render_block(&block)?);
+            }
+        }
+        Ok((blocks, module))
+    }
+
+    fn render_block(&self, block: &Block) -> Result<String, crate::Error> {
+        let mut sections: Vec<Vec<String>> = Vec::new();
+        if !block.options.is_empty() {
+            sections.push(block.options.clone());
+        }
+        for nested in &block.nested {
+            let code = self.render_block(nested)?;
+            sections.push(code.lines().map(String::from).collect());
+        }
+        if !block.lines.is_empty() {
+            sections.push(block.lines.clone());
+        }
+
+        let mut body = Vec::new();
+        for (i, section) in sections.iter().enumerate() {
+            if i > 0 {
+                body.push(String::new());
+            }
+            body.extend(indent(section));
+        }
+        let ctx = BlockContext {
+            keyword: block.keyword,
+            name: &block.name,
+            doc: &block.doc,
+            body,
+        };
+        Ok(self.registry.render("block", &ctx)?)
+    }
+}
+
+impl Default for ProtoGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The definitions of `registry`, dependencies first in a stable order, so
+/// output diffs cleanly.
+fn module_hoister(registry: &SchemaRegistry) -> Hoister<'_> {
+    let mut hoister = Hoister::new(Some(registry));
+    for name in registry.dependency_graph().topological_order() {
+        if let Some(schema) = registry.get(name) {
+            hoister.add_root(name, schema);
+        }
+    }
+    hoister
+}
+
+/// How a definition is declared.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DefKind {
+    Message,
+    Enum,
+    /// A union inlined as a `oneof` of the one message field holding it.
+    Oneof,
+    /// A union declared as a message holding a `oneof`.
+    Wrapper,
+    /// A definition inlined where it is used.
+    Alias,
+}
+
+#[derive(Serialize)]
+struct BlockContext<'a> {
+    keyword: &'static str,
+    name: &'a str,
+    doc: &'a [String],
+    body: Vec<String>,
+}
+
+/// A message or enum declaration.
+struct Block {
+    keyword: &'static str,
+    name: String,
+    doc: Vec<String>,
+    options: Vec<String>,
+    nested: Vec<Block>,
+    /// Field, `oneof` or enum value lines.
+    lines: Vec<String>,
+}
+
+/// A message field or `oneof` member.
+struct Field {
+    /// `repeated `, `optional ` or empty.
+    label: &'static str,
+    proto_type: String,
+    name: String,
+    number: Option<u32>,
+    options: Vec<String>,
+    doc: Vec<String>,
+}
+
+impl Field {
+    fn lines(&self) -> Vec<String> {
+        let options = if self.options.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", self.options.join(", "))
+        };
+        let mut lines = self.doc.clone();
+        lines.push(format!(
+            "{}{} {} = {}{};",
+            self.label,
+            self.proto_type,
+            self.name,
+            self.number.unwrap_or_default(),
+            options
+        ));
+        lines
+    }
+}
+
+enum Entry {
+    Field(Field),
+    Oneof {
+        name: String,
+        doc: Vec<String>,
+        members: Vec<Field>,
+    },
+}
+
+/// Types declared inside the message being rendered.
+#[derive(Default)]
+struct Scope {
+    nested: Vec<Block>,
+    names: HashSet<String>,
+}
+
+/// Rendering state of one module.
+struct Module<'h> {
+    defs: HashMap<&'h str, &'h Schema>,
+    refs: &'h HashMap<String, String>,
+    kinds: HashMap<&'h str, DefKind>,
+    imports: BTreeSet<&'static str>,
+    /// Field numbers to keep.
+    stored: &'h FieldNumbers,
+    /// Field numbers given, by message.
+    numbers: FieldNumbers,
+}
+
+impl<'h> Module<'h> {
+    fn new(hoister: &'h Hoister, stored: &'h FieldNumbers) -> Self {
+        // A union can be a `oneof` if its only use is as a message field,
+        // and an object merged into others needs no message of its own if
+        // that is its only use.
+        let mut uses: HashMap<String, usize> = HashMap::new();
+        let mut field_uses: HashMap<String, usize> = HashMap::new();
+        let mut merged_uses: HashMap<String, usize> = HashMap::new();
+        for def in &hoister.defs {
+            count_refs(&def.schema, &hoister.refs, &mut uses);
+            if let SchemaKind::Intersect { all_of } = &def.schema.kind {
+                for part in all_of {
+                    if let SchemaKind::Ref { reference } = &part.kind {
+                        *merged_uses
+                            .entry(ref_name(reference, &hoister.refs))
+                            .or_default() += 1;
+                    }
+                }
+            }
+            if let SchemaKind::Object { properties, .. } = &def.schema.kind {
+                for property in properties.values() {
+                    if let SchemaKind::Ref { reference } = &unwrap_optional(property).kind {
+                        *field_uses
+                            .entry(ref_name(reference, &hoister.refs))
+                            .or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        let mut kinds = HashMap::new();
+        for def in &hoister.defs {
+            let merged = merged_uses.get(&def.name);
+            let merged_only = !def.root && merged.is_some() && uses.get(&def.name) == merged;
+            let kind = match &def.schema.kind {
+                SchemaKind::Object { .. } | SchemaKind::Intersect { .. } if merged_only => {
+                    DefKind::Alias
+                }
+                SchemaKind::Object {
+                    properties,
+                    additional_properties,
+                    ..
+                } if !properties.is_empty() || additional_properties.is_none() => DefKind::Message,
+                SchemaKind::Intersect { .. } => DefKind::Message,
+                SchemaKind::Enum { .. } => DefKind::Enum,
+                SchemaKind::Union { any_of } if string_literals(any_of).is_some() => DefKind::Enum,
+                SchemaKind::Union { any_of } if literal_type(any_of).is_some() => DefKind::Alias,
+                SchemaKind::Union { any_of } if optional_inner(any_of).is_none() => {
+                    let single_field =
+                        uses.get(&def.name) == Some(&1) && field_uses.get(&def.name) == Some(&1);
+                    if !def.root && single_field {
+                        DefKind::Oneof
+                    } else {
+                        DefKind::Wrapper
+                    }
+                }
+                _ => DefKind::Alias,
+            };
+            kinds.insert(def.name.as_str(), kind);
+        }
+
+        Self {
+            defs: hoister
+                .defs
+                .iter()
+                .map(|def| (def.name.as_str(), &def.schema))
+                .collect(),
+            refs: &hoister.refs,
+            kinds,
+            imports: BTreeSet::new(),
+            stored,
+            numbers: FieldNumbers::new(),
+        }
+    }
+
+    fn kind(&self, name: &str) -> DefKind {
+        self.kinds.get(name).copied().unwrap_or(DefKind::Alias)
+    }
+
+    /// `schema`, or the schema of the alias definition it refers to.
+    fn resolve<'a>(&self, schema: &'a Schema) -> &'a Schema
+    where
+        'h: 'a,
+    {
+        let mut schema = schema;
+        for _ in 0..MAX_ALIAS_DEPTH {
+            match &schema.kind {
+                SchemaKind::Ref { reference } => {
+                    let name = ref_name(reference, self.refs);
+                    match self.defs.get(name.as_str()) {
+                        Some(target) if self.kind(&name) == DefKind::Alias => schema = target,
+                        _ => return schema,
+                    }
+                }
+                _ => return schema,
+            }
+        }
+        schema
+    }
+
+    fn message(&mut self, name: &str, schema: &Schema) -> Result<Block, crate::Error> {
+        let mut properties = Vec::new();
+        self.collect_properties(name, schema, &mut properties, 0)?;
+
+        let mut scope = Scope::default();
+        let mut used = HashSet::new();
+        let mut entries = Vec::new();
+        for (prop, property, required) in properties {
+            let inner = unwrap_optional(property);
+            let oneof = match &inner.kind {
+                SchemaKind::Ref { reference } => {
+                    let target = ref_name(reference, self.refs);
+                    match (self.kind(&target), self.defs.get(target.as_str())) {
+                        (DefKind::Oneof, Some(union)) => Some(*union),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match oneof {
+                Some(union) => {
+                    let SchemaKind::Union { any_of } = &union.kind else {
+                        unreachable!("only unions are oneofs");
+                    };
+                    let name = dedupe(snake_case(prop), &mut used);
+                    let members = self.members(any_of, &pascal_case(prop), &mut scope, &mut used);
+                    entries.push(Entry::Oneof {
+                        name,
+                        doc: doc_lines(property, inner),
+                        members,
+                    });
+                }
+                None => {
+                    let optional = !required || !std::ptr::eq(inner, property);
+                    let field = self.field(prop, property, optional, &mut scope, &mut used);
+                    entries.push(Entry::Field(field));
+                }
+            }
+        }
+        self.number(name, &mut entries)?;
+
+        let mut block = Block {
+            keyword: "message",
+            name: name.to_string(),
+            doc: doc_lines(schema, schema),
+            options: Vec::new(),
+            nested: scope.nested,
+            lines: Vec::new(),
+        };
+        if schema.deprecated == Some(true) {
+            block.options.push("option deprecated = true;".to_string());
+        }
+        for entry in &entries {
+            match entry {
+                Entry::Field(field) => block.lines.extend(field.lines()),
+                Entry::Oneof { name, doc, members } => {
+                    block.lines.extend(doc.iter().cloned());
+                    block.lines.push(format!("oneof {} {{", name));
+                    for member in members {
+                        block.lines.extend(indent(&member.lines()));
+                    }
+                    block.lines.push("}".to_string());
+                }
+            }
+        }
+        Ok(block)
+    }
+
+    /// A message holding the union `name` as a `oneof`.
+    fn wrapper(&mut self, name: &str, schema: &Schema) -> Result<Block, crate::Error> {
+        let SchemaKind::Union { any_of } = &schema.kind else {
+            unreachable!("only unions are wrapped");
+        };
+        let mut scope = Scope::default();
+        let mut used = HashSet::from(["value".to_string()]);
+        let members = self.members(any_of, "Value", &mut scope, &mut used);
+        let mut entries = vec![Entry::Oneof {
+            name: "value".to_string(),
+            doc: Vec::new(),
+            members,
+        }];
+        self.number(name, &mut entries)?;
+
+        let Some(Entry::Oneof { members, .. }) = entries.pop() else {
+            unreachable!();
+        };
+        let mut lines = vec!["oneof value {".to_string()];
+        for member in &members {
+            lines.extend(indent(&member.lines()));
+        }
+        lines.push("}".to_string());
+        Ok(Block {
+            keyword: "message",
+            name: name.to_string(),
+            doc: doc_lines(schema, schema),
+            options: Vec::new(),
+            nested: scope.nested,
+            lines,
+        })
+    }
+
+    /// The properties of an object, or of the objects an `Intersect`
+    /// merges, with whether each is required. The first of several
+    /// properties with the same name wins.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `message` merges anything but objects.
+    fn collect_properties<'a>(
+        &self,
+        message: &str,
+        schema: &'a Schema,
+        out: &mut Vec<(&'a str, &'a Schema, bool)>,
+        depth: usize,
+    ) -> Result<(), crate::Error>
+    where
+        'h: 'a,
+    {
+        if depth > MAX_ALIAS_DEPTH {
+            return Ok(());
+        }
+        match &schema.kind {
+            SchemaKind::Object {
+                properties,
+                required,
+                ..
+            } => {
+                for (prop, property) in properties {
+                    if out.iter().all(|(name, _, _)| name != prop) {
+                        out.push((prop, property, required.contains(prop)));
+                    }
+                }
+            }
+            SchemaKind::Intersect { all_of } => {
+                for part in all_of {
+                    let part = match &part.kind {
+                        SchemaKind::Ref { reference } => {
+                            let name = ref_name(reference, self.refs);
+                            match self.defs.get(name.as_str()) {
+                                Some(target) => *target,
+                                None => part,
+                            }
+                        }
+                        _ => part,
+                    };
+                    if !matches!(
+                        part.kind,
+                        SchemaKind::Object { .. } | SchemaKind::Intersect { .. }
+                    ) {
+                        return Err(crate::Error::Codegen(format!(
+                            "{}: only objects can be merged into a message, not {}",
+                            message,
+                            part.kind.kind_name()
+                        )));
+                    }
+                    self.collect_properties(message, part, out, depth + 1)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The `oneof` members for the non-null variants of a union.
+    fn members(
+        &mut self,
+        any_of: &[Schema],
+        hint: &str,
+        scope: &mut Scope,
+        used: &mut HashSet<String>,
+    ) -> Vec<Field> {
+        any_of
+            .iter()
+            .filter(|variant| !matches!(variant.kind, SchemaKind::Null))
+            .enumerate()
+            .map(|(i, variant)| {
+                let proto_type = self.single_type(variant, &format!("{}{}", hint, i), scope);
+                let base = match &variant.kind {
+                    SchemaKind::Ref { reference } => snake_case(&ref_name(reference, self.refs)),
+                    _ => {
+                        let short = proto_type.rsplit('.').next().unwrap_or(&proto_type);
+                        format!("{}_value", snake_case(short))
+                    }
+                };
+                Field {
+                    label: "",
+                    proto_type,
+                    name: dedupe(base, used),
+                    number: None,
+                    options: field_options(None, variant),
+                    doc: doc_lines(variant, variant),
+                }
+            })
+            .collect()
+    }
+
+    fn field(
+        &mut self,
+        prop: &str,
+        property: &Schema,
+        optional: bool,
+        scope: &mut Scope,
+        used: &mut HashSet<String>,
+    ) -> Field {
+        let inner = unwrap_optional(property);
+        let name = dedupe(snake_case(prop), used);
+        let hint = pascal_case(prop);
+        let (label, proto_type) = match &self.resolve(inner).kind {
+            SchemaKind::Array { items, .. } => (
+                "repeated ",
+                self.element_type(items, &format!("{}Item", hint), scope),
+            ),
+            SchemaKind::Object {
+                properties,
+                additional_properties: Some(values),
+                ..
+            } if properties.is_empty() => (
+                "",
+                format!(
+                    "map<string, {}>",
+                    self.element_type(values, &format!("{}Value", hint), scope)
+                ),
+            ),
+            _ => (
+                if optional { "optional " } else { "" },
+                self.single_type(inner, &hint, scope),
+            ),
+        };
+        let json_name = (json_name(&name) != prop).then_some(prop);
+        Field {
+            label,
+            proto_type,
+            number: None,
+            options: field_options(json_name, property),
+            doc: doc_lines(property, inner),
+            name,
+        }
+    }
+
+    /// The type of a list item or map value, which cannot itself be a list
+    /// or map.
+    fn element_type(&mut self, schema: &Schema, hint: &str, scope: &mut Scope) -> String {
+        let resolved = self.resolve(schema);
+        let values = match &resolved.kind {
+            SchemaKind::Array { items, .. } => {
+                let item = self.element_type(items, &format!("{}Item", hint), scope);
+                format!("repeated {} values = 1;", item)
+            }
+            SchemaKind::Object {
+                properties,
+                additional_properties: Some(values),
+                ..
+            } if properties.is_empty() => {
+                let value = self.element_type(values, &format!("{}Value", hint), scope);
+                format!("map<string, {}> values = 1;", value)
+            }
+            SchemaKind::Union { any_of } => match optional_inner(any_of) {
+                Some(inner) => return self.element_type(inner, hint, scope),
+                None => return self.single_type(schema, hint, scope),
+            },
+            _ => return self.single_type(schema, hint, scope),
+        };
+        let name = dedupe(hint.to_string(), &mut scope.names);
+        scope.nested.push(Block {
+            keyword: "message",
+            name: name.clone(),
+            doc: Vec::new(),
+            options: Vec::new(),
+            nested: Vec::new(),
+            lines: vec![values],
+        });
+        name
+    }
+
+    /// The type of a singular field.
+    fn single_type(&mut self, schema: &Schema, hint: &str, scope: &mut Scope) -> String {
+        let resolved = self.resolve(schema);
+        if let Some(scalar) = self.scalar(&resolved.kind) {
+            return scalar.to_string();
+        }
+        match &resolved.kind {
+            SchemaKind::Ref { reference } => {
+                let name = ref_name(reference, self.refs);
+                if self.kind(&name) == DefKind::Alias {
+                    // An alias cycle, or a reference outside the module.
+                    self.value()
+                } else {
+                    name
+                }
+            }
+            SchemaKind::Enum { values } => {
+                let values: Vec<&str> = values.iter().map(String::as_str).collect();
+                self.nested_enum(&values, resolved, hint, scope)
+            }
+            SchemaKind::Union { any_of } => {
+                if let Some(inner) = optional_inner(any_of) {
+                    self.single_type(inner, hint, scope)
+                } else if let Some(values) = string_literals(any_of) {
+                    self.nested_enum(&values, resolved, hint, scope)
+                } else if let Some(literal) = literal_type(any_of) {
+                    self.single_type(literal, hint, scope)
+                } else {
+                    self.value()
+                }
+            }
+            SchemaKind::Array { .. } => self.element_type(resolved, hint, scope),
+            SchemaKind::Object {
+                properties,
+                additional_properties,
+                ..
+            } if properties.is_empty() => match additional_properties {
+                Some(_) => self.element_type(resolved, hint, scope),
+                None => {
+                    self.imports.insert("google/protobuf/struct.proto");
+                    "google.protobuf.Struct".to_string()
+                }
+            },
+            SchemaKind::Named { name, .. } => name.clone(),
+            _ => self.value(),
+        }
+    }
+
+    fn nested_enum(
+        &mut self,
+        values: &[&str],
+        schema: &Schema,
+        hint: &str,
+        scope: &mut Scope,
+    ) -> String {
+        let name = dedupe(hint.to_string(), &mut scope.names);
+        let mut block = enum_block(&name, values, schema);
+        block.doc.clear();
+        scope.nested.push(block);
+        name
+    }
+
+    /// The scalar or well-known type for `kind`, if it maps to one.
+    fn scalar(&mut self, kind: &SchemaKind) -> Option<&'static str> {
+        let scalar = match kind {
+            SchemaKind::Bool => "bool",
+            SchemaKind::Int8 { .. } | SchemaKind::Int16 { .. } | SchemaKind::Int32 { .. } => {
+                "int32"
+            }
+            SchemaKind::Int64 { .. } => "int64",
+            SchemaKind::UInt8 { .. } | SchemaKind::UInt16 { .. } | SchemaKind::UInt32 { .. } => {
+                "uint32"
+            }
+            SchemaKind::UInt64 { .. } => "uint64",
+            SchemaKind::Float32 { .. } => "float",
+            SchemaKind::Float64 { .. } => "double",
+            SchemaKind::String {
+                format: Some(StringFormat::DateTime),
+                ..
+            } => {
+                self.imports.insert("google/protobuf/timestamp.proto");
+                "google.protobuf.Timestamp"
+            }
+            SchemaKind::String { .. } => "string",
+            SchemaKind::Bytes { .. } => "bytes",
+            SchemaKind::Literal { value } => match value {
+                LiteralValue::String(_) => "string",
+                LiteralValue::Number(_) => "int64",
+                LiteralValue::Float(_) => "double",
+                LiteralValue::Boolean(_) => "bool",
+                LiteralValue::Null => return Some(self.null()),
+            },
+            SchemaKind::Null => return Some(self.null()),
+            SchemaKind::Tuple { .. } => {
+                self.imports.insert("google/protobuf/struct.proto");
+                "google.protobuf.ListValue"
+            }
+            _ => return None,
+        };
+        Some(scalar)
+    }
+
+    fn null(&mut self) -> &'static str {
+        self.imports.insert("google/protobuf/struct.proto");
+        "google.protobuf.NullValue"
+    }
+
+    /// Numbers the fields of `message`, keeping the stored numbers, and
+    /// records the numbers given.
+    fn number(&mut self, message: &str, entries: &mut [Entry]) -> Result<(), crate::Error> {
+        let stored = self.stored.get(message);
+        let mut taken: HashMap<u32, String> = HashMap::new();
+        for field in fields_mut(entries) {
+            let Some(&number) = stored.and_then(|numbers| numbers.get(&field.name)) else {
+                continue;
+            };
+            if number == 0 || number > MAX_FIELD_NUMBER || RESERVED_NUMBERS.contains(&number) {
+                return Err(crate::Error::Codegen(format!(
+                    "{}.{}: {} is not a valid field number",
+                    message, field.name, number
+                )));
+            }
+            if let Some(other) = taken.insert(number, field.name.clone()) {
+                return Err(crate::Error::Codegen(format!(
+                    "{}: fields {} and {} are both numbered {}",
+                    message, other, field.name, number
+                )));
+            }
+            field.number = Some(number);
+        }
+
+        // Numbers of removed fields are not reused.
+        let highest = stored.and_then(|numbers| numbers.values().max().copied());
+        let mut numbers: Vec<Option<u32>> = fields_mut(entries).map(|field| field.number).collect();
+        fill_numbers(&mut numbers, highest);
+        let given = self.numbers.entry(message.to_string()).or_default();
+        for (field, number) in fields_mut(entries).zip(numbers) {
+            field.number = number;
+            given.extend(number.map(|number| (field.name.clone(), number)));
+        }
+        Ok(())
+    }
+
+    /// `google.protobuf.Value`, which holds any JSON value.
+    fn value(&mut self) -> String {
+        self.imports.insert("google/protobuf/struct.proto");
+        "google.protobuf.Value".to_string()
+    }
+}
+
+fn fields_mut(entries: &mut [Entry]) -> impl Iterator<Item = &mut Field> {
+    entries.iter_mut().flat_map(|entry| match entry {
+        Entry::Field(field) => std::slice::from_mut(field),
+        Entry::Oneof { members, .. } => members.as_mut_slice(),
+    })
+}
+
+/// Numbers the unnumbered slots in order, after the highest number in use
+/// and `highest`.
+fn fill_numbers(numbers: &mut [Option<u32>], highest: Option<u32>) {
+    let mut next = numbers
+        .iter()
+        .flatten()
+        .chain(&highest)
+        .max()
+        .map_or(1, |n| n + 1);
+    for number in numbers.iter_mut().filter(|n| n.is_none()) {
+        if RESERVED_NUMBERS.contains(&next) {
+            next = RESERVED_NUMBERS.end() + 1;
+        }
+        *number = Some(next);
+        next += 1;
+    }
+}
+
+/// The first variant of a union of literals of one scalar type other than
+/// string, such as `1 | 2`, which is held as that scalar.
+fn literal_type(any_of: &[Schema]) -> Option<&Schema> {
+    let (first, rest) = any_of.split_first()?;
+    let SchemaKind::Literal { value } = &first.kind else {
+        return None;
+    };
+    if matches!(value, LiteralValue::String(_) | LiteralValue::Null) {
+        return None;
+    }
+    rest.iter()
+        .all(|variant| match &variant.kind {
+            SchemaKind::Literal { value: other } => {
+                std::mem::discriminant(value) == std::mem::discriminant(other)
+            }
+            _ => false,
+        })
+        .then_some(first)
+}
+
+/// The value of an optional union (`T | null`), or `schema` itself.
+fn unwrap_optional(schema: &Schema) -> &Schema {
+    match &schema.kind {
+        SchemaKind::Union { any_of } => optional_inner(any_of).unwrap_or(schema),
+        _ => schema,
+    }
+}
+
+/// Counts the references to each definition.
+fn count_refs(schema: &Schema, refs: &HashMap<String, String>, uses: &mut HashMap<String, usize>) {
+    match &schema.kind {
+        SchemaKind::Ref { reference } => *uses.entry(ref_name(reference, refs)).or_default() += 1,
+        SchemaKind::Object {
+            properties,
+            additional_properties,
+            ..
+        } => {
+            for property in properties.values() {
+                count_refs(property, refs, uses);
+            }
+            if let Some(values) = additional_properties {
+                count_refs(values, refs, uses);
+            }
+        }
+        SchemaKind::Union { any_of: schemas }
+        | SchemaKind::Intersect { all_of: schemas }
+        | SchemaKind::Tuple { items: schemas } => {
+            for schema in schemas {
+                count_refs(schema, refs, uses);
+            }
+        }
+        SchemaKind::Function {
+            parameters,
+            returns,
+        } => {
+            for schema in parameters.iter().chain([returns.as_ref()]) {
+                count_refs(schema, refs, uses);
+            }
+        }
+        SchemaKind::Array { items: schema, .. }
+        | SchemaKind::Named { schema, .. }
+        | SchemaKind::Recursive { schema } => count_refs(schema, refs, uses),
+        _ => {}
+    }
+}
+
+fn enum_block(name: &str, values: &[&str], schema: &Schema) -> Block {
+    let prefix = screaming_snake(name);
+    let zero = format!("{}_UNSPECIFIED", prefix);
+    let mut used = HashSet::from([zero.clone()]);
+    let mut lines = vec![format!("{} = 0;", zero)];
+    for (i, value) in values.iter().enumerate() {
+        let suffix = match screaming_snake(value) {
+            suffix if value.chars().any(char::is_alphanumeric) => suffix,
+            _ => "EMPTY".to_string(),
+        };
+        let ident = dedupe(format!("{}_{}", prefix, suffix), &mut used);
+        lines.push(format!("{} = {};", ident, i + 1));
+    }
+    let mut options = Vec::new();
+    if schema.deprecated == Some(true) {
+        options.push("option deprecated = true;".to_string());
+    }
+    Block {
+        keyword: "enum",
+        name: name.to_string(),
+        doc: doc_lines(schema, schema),
+        options,
+        nested: Vec::new(),
+        lines,
+    }
+}
+
+fn field_options(json_name: Option<&str>, schema: &Schema) -> Vec<String> {
+    let mut options = Vec::new();
+    if let Some(json_name) = json_name {
+        options.push(format!("json_name = {}", proto_string(json_name)));
+    }
+    if schema.deprecated == Some(true) {
+        options.push("deprecated = true".to_string());
+    }
+    options
+}
+
+/// Comment lines for the description of `schema`, or of `fallback`.
+fn doc_lines(schema: &Schema, fallback: &Schema) -> Vec<String> {
+    schema
+        .description
+        .as_ref()
+        .or(fallback.description.as_ref())
+        .iter()
+        .flat_map(|description| description.trim().lines())
+        .map(|line| format!("// {}", line).trim_end().to_string())
+        .collect()
+}
+
+/// The JSON name protobuf derives from a field name: lowerCamelCase.
+fn json_name(field: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in field.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// `STATUS_IN_PROGRESS` for `StatusInProgress` or `status-in-progress`.
+fn screaming_snake(name: &str) -> String {
+    snake_case(name).trim_start_matches('_').to_uppercase()
+}
+
+/// `name`, or `name` with the first free numeric suffix.
+fn dedupe(name: String, used: &mut HashSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut suffix = 1;
+    while !used.insert(unique.clone()) {
+        suffix += 1;
+        unique = format!("{}{}", name, suffix);
+    }
+    unique
+}
+
+fn indent(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("  {}", line)
+            }
+        })
+        .collect()
+}
+
+fn proto_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+const BLOCK_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}{{keyword}} {{name}} {
+{{#each body}}{{{this}}}
+{{/each}}}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_generate_message() {
+        let gen = ProtoGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field("id", SchemaBuilder::int64())
+            .optional_field("emailAddress", SchemaBuilder::string().build())
+            .field(
+                "created_at",
+                SchemaBuilder::string()
+                    .format(StringFormat::DateTime)
+                    .build(),
+            )
+            .field(
+                "tags",
+                SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+            )
+            .field(
+                "labels",
+                SchemaBuilder::object()
+                    .additional_properties(Some(SchemaBuilder::uint8()))
+                    .build(),
+            )
+            .field(
+                "matrix",
+                SchemaBuilder::array(SchemaBuilder::array(SchemaBuilder::float64()).build())
+                    .build(),
+            )
+            .field(
+                "status",
+                SchemaBuilder::enum_values(vec!["active", "in-progress"])
+                    .with_description("Lifecycle state."),
+            )
+            .optional_field("avatar", SchemaBuilder::bytes().with_deprecated(true))
+            .build()
+            .with_description("A person.");
+
+        let output = gen.generate("Person", &schema).unwrap();
+        assert_eq!(
+            output,
+            "// A person.
+message Person {
+  message MatrixItem {
+    repeated double values = 1;
+  }
+
+  enum Status {
+    STATUS_UNSPECIFIED = 0;
+    STATUS_ACTIVE = 1;
+    STATUS_IN_PROGRESS = 2;
+  }
+
+  int64 id = 1;
+  optional string email_address = 2;
+  google.protobuf.Timestamp created_at = 3 [json_name = \"created_at\"];
+  repeated string tags = 4;
+  map<string, uint32> labels = 5;
+  repeated MatrixItem matrix = 6;
+  // Lifecycle state.
+  Status status = 7;
+  optional bytes avatar = 8 [deprecated = true];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_generate_module_with_oneofs_and_numbers() {
+        let numbers = FieldNumbers::from([
+            (
+                "Circle".to_string(),
+                BTreeMap::from([("radius".to_string(), 2)]),
+            ),
+            (
+                "Drawing".to_string(),
+                BTreeMap::from([("square".to_string(), 7)]),
+            ),
+        ]);
+        let gen = ProtoGenerator::new()
+            .with_package("shapes.v1")
+            .with_field_numbers(numbers);
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Circle",
+            SchemaBuilder::object()
+                .field("radius", SchemaBuilder::float64())
+                .field("center", SchemaBuilder::string().build())
+                .build(),
+        );
+        registry.register(
+            "Square",
+            SchemaBuilder::object()
+                .field("side", SchemaBuilder::float64())
+                .build(),
+        );
+        registry.register(
+            "Shape",
+            SchemaBuilder::union(vec![
+                SchemaBuilder::r#ref("Circle"),
+                SchemaBuilder::r#ref("Square"),
+            ]),
+        );
+        registry.register(
+            "Drawing",
+            SchemaBuilder::object()
+                .field(
+                    "shapes",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Shape")).build(),
+                )
+                .optional_field(
+                    "fill",
+                    SchemaBuilder::union(vec![
+                        SchemaBuilder::string().build(),
+                        SchemaBuilder::r#ref("Square"),
+                    ]),
+                )
+                .field("meta", SchemaBuilder::any())
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.starts_with(
+            "// Code generated by typebox-rs. DO NOT EDIT.\n\nsyntax = \"proto3\";\n\npackage shapes.v1;\n\nimport \"google/protobuf/struct.proto\";\n"
+        ));
+        assert!(
+            output.contains("message Circle {\n  double radius = 2;\n  string center = 3;\n}\n")
+        );
+        assert!(output.contains(
+            "message Shape {\n  oneof value {\n    Circle circle = 1;\n    Square square = 2;\n  }\n}\n"
+        ));
+        assert!(output.contains(
+            "message Drawing {\n  repeated Shape shapes = 8;\n  oneof fill {\n    string string_value = 9;\n    Square square = 7;\n  }\n  google.protobuf.Value meta = 10;\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_module_merges_intersections() {
+        let gen = ProtoGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "TableInfo",
+            SchemaBuilder::object()
+                .field("name", SchemaBuilder::string().build())
+                .build(),
+        );
+        registry.register(
+            "TableInfoBoth",
+            SchemaBuilder::intersect(vec![
+                SchemaBuilder::r#ref("TableInfo"),
+                SchemaBuilder::object()
+                    .field("extra", SchemaBuilder::int32())
+                    .build(),
+            ]),
+        );
+        registry.register(
+            "Nums",
+            SchemaBuilder::union(vec![
+                SchemaBuilder::literal(1i64),
+                SchemaBuilder::literal(2i64),
+            ]),
+        );
+        registry.register(
+            "Holder",
+            SchemaBuilder::object()
+                .field("n", SchemaBuilder::r#ref("Nums"))
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(
+            output.contains("message TableInfoBoth {\n  string name = 1;\n  int32 extra = 2;\n}\n")
+        );
+        assert!(output.contains("message Holder {\n  int64 n = 1;\n}\n"));
+        assert!(!output.contains("TableInfoBothPart1"));
+        assert!(!output.contains("message Nums"));
+
+        registry.register(
+            "Either",
+            SchemaBuilder::union(vec![
+                SchemaBuilder::r#ref("TableInfo"),
+                SchemaBuilder::r#ref("Holder"),
+            ]),
+        );
+        registry.register(
+            "EitherBoth",
+            SchemaBuilder::intersect(vec![
+                SchemaBuilder::r#ref("Either"),
+                SchemaBuilder::r#ref("TableInfo"),
+            ]),
+        );
+        let err = gen.generate_module(&registry).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Code generation error: EitherBoth: only objects can be merged into a message, not Union"
+        );
+    }
+
+    #[test]
+    fn test_field_numbers() {
+        let gen = ProtoGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        let mut thing = SchemaBuilder::object()
+            .field("a", SchemaBuilder::string().build())
+            .field("b", SchemaBuilder::int32())
+            .optional_field(
+                "c",
+                SchemaBuilder::union(vec![
+                    SchemaBuilder::string().build(),
+                    SchemaBuilder::int64(),
+                ]),
+            )
+            .build();
+        registry.register("Thing", thing.clone());
+        let numbers = gen.field_numbers(&registry).unwrap();
+        assert_eq!(
+            numbers["Thing"],
+            BTreeMap::from([
+                ("a".to_string(), 1),
+                ("b".to_string(), 2),
+                ("string_value".to_string(), 3),
+                ("int64_value".to_string(), 4),
+            ])
+        );
+
+        // Reordering, removing and adding fields keeps the numbers and
+        // does not reuse the removed one.
+        let SchemaKind::Object { properties, .. } = &mut thing.kind else {
+            unreachable!();
+        };
+        properties.shift_remove("a");
+        properties.insert("d".to_string(), SchemaBuilder::bool());
+        properties.move_index(0, 2);
+        registry.register("Thing", thing);
+        let gen = gen.with_field_numbers(numbers);
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains(
+            "message Thing {\n  oneof c {\n    string string_value = 3;\n    int64 int64_value = 4;\n  }\n  optional bool d = 5;\n  int32 b = 2;\n}\n"
+        ));
+        let numbers = gen.field_numbers(&registry).unwrap();
+        assert_eq!(numbers["Thing"]["a"], 1);
+        assert_eq!(numbers["Thing"]["d"], 5);
+
+        let numbers = FieldNumbers::from([(
+            "Thing".to_string(),
+            BTreeMap::from([("b".to_string(), 3), ("d".to_string(), 3)]),
+        )]);
+        let err = ProtoGenerator::new()
+            .with_field_numbers(numbers)
+            .generate_module(&registry)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Code generation error: Thing: fields d and b are both numbered 3"
+        );
+    }
+}
@@ -0,0 +1,1187 @@
+//! Pydantic v2 model generation.
+//!
+//! Objects become `BaseModel` classes whose constraints are `Field`
+//! arguments, `Enum`s become `str` enums, intersections of models become
+//! classes deriving from them, and unions of objects sharing a string
+//! literal tag become discriminated unions:
+//!
+//! ```text
+//! class Person(BaseModel):
+//!     """A person."""
+//!
+//!     model_config = ConfigDict(extra='forbid', populate_by_name=True)
+//!
+//!     id: int = Field(ge=0)
+//!     email: Optional[str] = None
+//!     content_type: str = Field(alias='content-type', max_length=64)
+//!
+//!
+//! Shape = Annotated[Union[Circle, Square], Field(discriminator='kind')]
+//! ```
+//!
+//! The module starts with `from __future__ import annotations`, so models
+//! may refer to models defined after them; those are completed with
+//! `model_rebuild()` at the end of the module.
+
+use super::hoist::{optional_inner, ref_name, string_literals, Hoister, TypeDef};
+use super::rust::snake_case;
+use crate::codegen::SchemaRegistry;
+use crate::schema::{LiteralValue, Schema, SchemaKind, StringFormat};
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+pub struct PydanticGenerator {
+    registry: Handlebars<'static>,
+}
+
+impl PydanticGenerator {
+    pub fn new() -> Self {
+        let mut registry = Handlebars::new();
+        registry
+            .register_template_string("class", CLASS_TEMPLATE)
+            .unwrap();
+        registry
+            .register_template_string("enum", ENUM_TEMPLATE)
+            .unwrap();
+        registry
+            .register_template_string("alias", ALIAS_TEMPLATE)
+            .unwrap();
+
+        Self { registry }
+    }
+
+    /// Generates the definition `name` and the types hoisted out of it,
+    /// without imports.
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(None).with_reserved(RESERVED);
+        hoister.add_root(name, schema);
+        let mut module = Module::new(&hoister);
+        Ok(self.render(&hoister, &mut module)?.join("\n\n"))
+    }
+
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(Some(registry)).with_reserved(RESERVED);
+
+        // Dependencies first, so few models need rebuilding.
+        for name in registry.dependency_graph().topological_order() {
+            if let Some(schema) = registry.get(name) {
+                hoister.add_root(name, schema);
+            }
+        }
+
+        let mut module = Module::new(&hoister);
+        let defs = self.render(&hoister, &mut module)?;
+
+        let mut output = String::new();
+        output.push_str("# Auto-generated by typebox-rs. DO NOT EDIT.\n\n");
+        output.push_str("from __future__ import annotations\n\n");
+        // Standard library imports, then pydantic's.
+        let (pydantic, std): (Vec<_>, Vec<_>) = module
+            .imports
+            .iter()
+            .partition(|(from, _)| **from == "pydantic");
+        for (from, names) in &std {
+            let names: Vec<&str> = names.iter().copied().collect();
+            output.push_str(&format!("from {} import {}\n", from, names.join(", ")));
+        }
+        for (from, names) in &pydantic {
+            let names: Vec<&str> = names.iter().copied().collect();
+            output.push_str(&format!("\nfrom {} import {}\n", from, names.join(", ")));
+        }
+
+        for code in defs {
+            output.push_str("\n\n");
+            output.push_str(&code);
+        }
+
+        if !module.rebuilds.is_empty() {
+            output.push_str("\n\n");
+            for name in &module.rebuilds {
+                output.push_str(&format!("{}.model_rebuild()\n", name));
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn render(&self, hoister: &Hoister, module: &mut Module) -> Result<Vec<String>, crate::Error> {
+        hoister
+            .defs
+            .iter()
+            .map(|def| self.render_def(def, module))
+            .collect()
+    }
+
+    fn render_def(&self, def: &TypeDef, module: &mut Module) -> Result<String, crate::Error> {
+        let (name, schema) = (def.name.as_str(), &def.schema);
+        let original = def.original.as_deref();
+        let code = match &schema.kind {
+            SchemaKind::Object { properties, .. } if !properties.is_empty() => {
+                let ctx = module.class(name, original, schema, Vec::new());
+                self.registry.render("class", &ctx)?
+            }
+            SchemaKind::Enum { values } => {
+                module.import("enum", "Enum");
+                module.defined.insert(name.to_string());
+                let mut used = HashSet::new();
+                let ctx = EnumContext {
+                    name: name.to_string(),
+                    doc: docstring(schema, INDENT),
+                    members: values
+                        .iter()
+                        .map(|value| MemberContext {
+                            name: dedupe(member_name(value), &mut used),
+                            value: py_string(value),
+                        })
+                        .collect(),
+                };
+                self.registry.render("enum", &ctx)?
+            }
+            SchemaKind::Intersect { all_of } if module.all_models(all_of) => {
+                let bases = all_of
+                    .iter()
+                    .map(|part| module.py_type(part))
+                    .collect::<Vec<_>>();
+                // The bases carry the fields and their configuration.
+                let ctx = module.class(name, original, schema, bases);
+                self.registry.render("class", &ctx)?
+            }
+            SchemaKind::Intersect { .. } => {
+                return Err(crate::Error::Codegen(format!(
+                    "{}: only an intersection of models can become a class",
+                    original.unwrap_or(name)
+                )));
+            }
+            _ => {
+                module.quote = true;
+                let mut py_type = module.py_type(schema);
+                if let SchemaKind::Union { any_of } = &schema.kind {
+                    if let Some(tag) = module.discriminator(any_of) {
+                        module.import("typing", "Annotated");
+                        module.import("pydantic", "Field");
+                        py_type = format!(
+                            "Annotated[{}, Field(discriminator={})]",
+                            py_type,
+                            py_string(&tag)
+                        );
+                    }
+                }
+                module.quote = false;
+                module.defined.insert(name.to_string());
+                let ctx = AliasContext {
+                    name: name.to_string(),
+                    doc: docstring(schema, ""),
+                    py_type,
+                };
+                self.registry.render("alias", &ctx)?
+            }
+        };
+        Ok(code.trim_end().to_string() + "\n")
+    }
+}
+
+impl Default for PydanticGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const INDENT: &str = "    ";
+
+/// Names the generated code uses, which class names must not shadow.
+const RESERVED: &[&str] = &[
+    "None",
+    "True",
+    "False",
+    "Annotated",
+    "Any",
+    "BaseModel",
+    "Callable",
+    "ConfigDict",
+    "Dict",
+    "Enum",
+    "Field",
+    "IPv4Address",
+    "IPv6Address",
+    "List",
+    "Literal",
+    "NoReturn",
+    "Optional",
+    "Tuple",
+    "UUID",
+    "Union",
+];
+
+#[derive(Serialize)]
+struct ClassContext {
+    name: String,
+    bases: String,
+    /// Docstring, configuration and fields, separated by blank lines.
+    sections: Vec<String>,
+}
+
+struct FieldContext {
+    name: String,
+    py_type: String,
+    value: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EnumContext {
+    name: String,
+    doc: Option<String>,
+    members: Vec<MemberContext>,
+}
+
+#[derive(Serialize)]
+struct MemberContext {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct AliasContext {
+    name: String,
+    doc: Option<String>,
+    py_type: String,
+}
+
+/// Rendering state of one module.
+struct Module<'h> {
+    defs: HashMap<&'h str, &'h Schema>,
+    refs: &'h HashMap<String, String>,
+    /// Names to import, by module.
+    imports: BTreeMap<&'static str, BTreeSet<&'static str>>,
+    /// Definitions rendered so far.
+    defined: HashSet<String>,
+    /// Whether to quote names not defined yet, for expressions Python
+    /// evaluates immediately.
+    quote: bool,
+    /// Whether a name not defined yet was referenced.
+    forward: bool,
+    /// Models to complete once every definition exists.
+    rebuilds: Vec<String>,
+}
+
+impl<'h> Module<'h> {
+    fn new(hoister: &'h Hoister) -> Self {
+        Self {
+            defs: hoister
+                .defs
+                .iter()
+                .map(|def| (def.name.as_str(), &def.schema))
+                .collect(),
+            refs: &hoister.refs,
+            imports: BTreeMap::new(),
+            defined: HashSet::new(),
+            quote: false,
+            forward: false,
+            rebuilds: Vec::new(),
+        }
+    }
+
+    fn import(&mut self, from: &'static str, name: &'static str) {
+        self.imports.entry(from).or_default().insert(name);
+    }
+
+    /// The class `name` for the object `schema`, deriving from `bases`.
+    /// A class renamed from the registry name `original` keeps it as its
+    /// title.
+    fn class(
+        &mut self,
+        name: &str,
+        original: Option<&str>,
+        schema: &Schema,
+        bases: Vec<String>,
+    ) -> ClassContext {
+        self.import("pydantic", "BaseModel");
+        // A model may refer to itself without rebuilding.
+        self.defined.insert(name.to_string());
+        self.forward = false;
+
+        let mut fields = Vec::new();
+        let mut extra = None;
+        let mut config: Vec<String> = original
+            .map(|original| format!("title={}", py_string(original)))
+            .into_iter()
+            .collect();
+        if let SchemaKind::Object {
+            properties,
+            required,
+            additional_properties,
+        } = &schema.kind
+        {
+            let mut used = HashSet::new();
+            for (prop, prop_schema) in properties {
+                fields.push(self.field(prop, prop_schema, required.contains(prop), &mut used));
+            }
+            match additional_properties.as_deref() {
+                None => config.push("extra='forbid'".to_string()),
+                Some(values) => {
+                    config.push("extra='allow'".to_string());
+                    if !matches!(values.kind, SchemaKind::Any | SchemaKind::Unknown) {
+                        self.import("typing", "Dict");
+                        self.import("pydantic", "Field");
+                        extra = Some(format!("Dict[str, {}]", self.py_type(values)));
+                    }
+                }
+            }
+            if fields
+                .iter()
+                .any(|f| f.value.as_deref().is_some_and(|v| v.contains("alias=")))
+            {
+                config.push("populate_by_name=True".to_string());
+            }
+            // Bytes travel as base64 strings in JSON.
+            if properties.values().any(contains_bytes) {
+                config.push("ser_json_bytes='base64'".to_string());
+                config.push("val_json_bytes='base64'".to_string());
+            }
+        }
+        if !config.is_empty() {
+            self.import("pydantic", "ConfigDict");
+        }
+
+        if self.forward {
+            self.rebuilds.push(name.to_string());
+        }
+        let mut sections: Vec<String> = docstring(schema, INDENT)
+            .map(|doc| format!("{}{}", INDENT, doc))
+            .into_iter()
+            .collect();
+        if !config.is_empty() {
+            sections.push(format!(
+                "{}model_config = ConfigDict({})",
+                INDENT,
+                config.join(", ")
+            ));
+        }
+        if let Some(extra) = extra {
+            sections.push(format!(
+                "{}__pydantic_extra__: {} = Field(init=False)",
+                INDENT, extra
+            ));
+        }
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|field| match field.value {
+                Some(value) => format!("{}{}: {} = {}", INDENT, field.name, field.py_type, value),
+                None => format!("{}{}: {}", INDENT, field.name, field.py_type),
+            })
+            .collect();
+        if !fields.is_empty() {
+            sections.push(fields.join("\n"));
+        }
+        if sections.is_empty() {
+            sections.push(format!("{}pass", INDENT));
+        }
+        ClassContext {
+            name: name.to_string(),
+            bases: if bases.is_empty() {
+                "BaseModel".to_string()
+            } else {
+                bases.join(", ")
+            },
+            sections,
+        }
+    }
+
+    fn field(
+        &mut self,
+        prop: &str,
+        schema: &Schema,
+        required: bool,
+        used: &mut HashSet<String>,
+    ) -> FieldContext {
+        let name = dedupe(field_name(prop), used);
+        let mut args = Vec::new();
+        let mut constraints = Vec::new();
+
+        // Constraints go into `Field` unless the type is wrapped in `Optional`.
+        let py_type = if required || schema.default.is_some() {
+            let py_type;
+            (py_type, constraints) = self.py_parts(schema);
+            if let Some(default) = &schema.default {
+                args.push(format!("default={}", py_value(&default.to_json())));
+            }
+            py_type
+        } else {
+            args.push("default=None".to_string());
+            let py_type = self.py_type(schema);
+            if py_type.starts_with("Optional[") || matches!(py_type.as_str(), "None" | "Any") {
+                py_type
+            } else {
+                self.import("typing", "Optional");
+                format!("Optional[{}]", py_type)
+            }
+        };
+
+        if name != prop {
+            args.push(format!("alias={}", py_string(prop)));
+        }
+        args.extend(constraints);
+        if let Some(title) = &schema.title {
+            args.push(format!("title={}", py_string(title)));
+        }
+        if let Some(description) = &schema.description {
+            args.push(format!("description={}", py_string(description)));
+        }
+        if let Some(examples) = &schema.examples {
+            let json = serde_json::Value::Array(examples.iter().map(|v| v.to_json()).collect());
+            args.push(format!("examples={}", py_value(&json)));
+        }
+        if schema.deprecated == Some(true) {
+            args.push("deprecated=True".to_string());
+        }
+        // Defaults are not validated otherwise, so a default for a model,
+        // enum or date stays a plain JSON value.
+        if schema.default.is_some() && !plain_type(&py_type) {
+            args.push("validate_default=True".to_string());
+        }
+
+        let value = match args.as_slice() {
+            [] => None,
+            [default] if default.starts_with("default=") => {
+                Some(default.trim_start_matches("default=").to_string())
+            }
+            _ => {
+                self.import("pydantic", "Field");
+                Some(format!("Field({})", args.join(", ")))
+            }
+        };
+        FieldContext {
+            name,
+            py_type,
+            value,
+        }
+    }
+
+    /// The type annotation for `schema`, constraints included.
+    fn py_type(&mut self, schema: &Schema) -> String {
+        let (py_type, constraints) = self.py_parts(schema);
+        if constraints.is_empty() {
+            return py_type;
+        }
+        self.import("typing", "Annotated");
+        self.import("pydantic", "Field");
+        format!("Annotated[{}, Field({})]", py_type, constraints.join(", "))
+    }
+
+    /// The type annotation for `schema` and its `Field` constraints.
+    fn py_parts(&mut self, schema: &Schema) -> (String, Vec<String>) {
+        let simple = |py_type: &str| (py_type.to_string(), Vec::new());
+        match &schema.kind {
+            SchemaKind::Null
+            | SchemaKind::Void
+            | SchemaKind::Undefined
+            | SchemaKind::Literal {
+                value: LiteralValue::Null,
+            } => simple("None"),
+            SchemaKind::Bool => simple("bool"),
+            SchemaKind::Int8 { minimum, maximum } => integer(
+                minimum.unwrap_or(i8::MIN).into(),
+                maximum.unwrap_or(i8::MAX).into(),
+            ),
+            SchemaKind::Int16 { minimum, maximum } => integer(
+                minimum.unwrap_or(i16::MIN).into(),
+                maximum.unwrap_or(i16::MAX).into(),
+            ),
+            SchemaKind::Int32 { minimum, maximum } => integer(
+                minimum.unwrap_or(i32::MIN).into(),
+                maximum.unwrap_or(i32::MAX).into(),
+            ),
+            SchemaKind::UInt8 { minimum, maximum } => integer(
+                minimum.unwrap_or(0).into(),
+                maximum.unwrap_or(u8::MAX).into(),
+            ),
+            SchemaKind::UInt16 { minimum, maximum } => integer(
+                minimum.unwrap_or(0).into(),
+                maximum.unwrap_or(u16::MAX).into(),
+            ),
+            SchemaKind::UInt32 { minimum, maximum } => integer(
+                minimum.unwrap_or(0).into(),
+                maximum.unwrap_or(u32::MAX).into(),
+            ),
+            SchemaKind::Int64 { minimum, maximum } => {
+                ("int".to_string(), bounds(*minimum, *maximum))
+            }
+            SchemaKind::UInt64 { minimum, maximum } => (
+                "int".to_string(),
+                bounds(Some(minimum.unwrap_or(0)), *maximum),
+            ),
+            SchemaKind::Float32 { minimum, maximum } => (
+                "float".to_string(),
+                bounds(
+                    minimum.map(|n| py_float(n.into())),
+                    maximum.map(|n| py_float(n.into())),
+                ),
+            ),
+            SchemaKind::Float64 { minimum, maximum } => (
+                "float".to_string(),
+                bounds(minimum.map(py_float), maximum.map(py_float)),
+            ),
+            SchemaKind::String {
+                format,
+                pattern,
+                min_length,
+                max_length,
+            } => {
+                let py_type = match format {
+                    Some(StringFormat::DateTime) => self.imported("datetime", "datetime"),
+                    Some(StringFormat::Date) => self.imported("datetime", "date"),
+                    Some(StringFormat::Time) => self.imported("datetime", "time"),
+                    Some(StringFormat::Uuid) => self.imported("uuid", "UUID"),
+                    Some(StringFormat::Ipv4) => self.imported("ipaddress", "IPv4Address"),
+                    Some(StringFormat::Ipv6) => self.imported("ipaddress", "IPv6Address"),
+                    _ => "str",
+                };
+                // Length and pattern constraints only apply to `str`.
+                let mut constraints = Vec::new();
+                if py_type == "str" {
+                    constraints = lengths(*min_length, *max_length);
+                    if let Some(pattern) = pattern {
+                        constraints.push(format!("pattern={}", py_raw_string(pattern)));
+                    }
+                }
+                (py_type.to_string(), constraints)
+            }
+            SchemaKind::Bytes {
+                min_length,
+                max_length,
+            } => ("bytes".to_string(), lengths(*min_length, *max_length)),
+
+            SchemaKind::Array {
+                items,
+                min_items,
+                max_items,
+                ..
+            } => {
+                self.import("typing", "List");
+                let items = self.py_type(items);
+                (format!("List[{}]", items), lengths(*min_items, *max_items))
+            }
+            SchemaKind::Tuple { items } => {
+                self.import("typing", "Tuple");
+                if items.is_empty() {
+                    return simple("Tuple[()]");
+                }
+                (format!("Tuple[{}]", self.py_list(items)), Vec::new())
+            }
+            SchemaKind::Object {
+                additional_properties,
+                ..
+            } => {
+                self.import("typing", "Dict");
+                let values = match additional_properties.as_deref() {
+                    Some(values) => self.py_type(values),
+                    None => self.imported("typing", "Any").to_string(),
+                };
+                (format!("Dict[str, {}]", values), Vec::new())
+            }
+
+            SchemaKind::Union { any_of } => {
+                if let Some(inner) = optional_inner(any_of) {
+                    self.import("typing", "Optional");
+                    return (format!("Optional[{}]", self.py_type(inner)), Vec::new());
+                }
+                if let Some(values) = string_literals(any_of) {
+                    return (self.literal(values.into_iter().map(py_string)), Vec::new());
+                }
+                match any_of.as_slice() {
+                    [] => simple(self.imported("typing", "NoReturn")),
+                    [only] => self.py_parts(only),
+                    _ => {
+                        self.import("typing", "Union");
+                        (format!("Union[{}]", self.py_list(any_of)), Vec::new())
+                    }
+                }
+            }
+            SchemaKind::Literal { value } => {
+                let value = match value {
+                    LiteralValue::String(s) => py_string(s),
+                    LiteralValue::Number(n) => n.to_string(),
+                    LiteralValue::Float(f) => py_float(*f),
+                    LiteralValue::Boolean(b) => py_bool(*b).to_string(),
+                    LiteralValue::Null => "None".to_string(),
+                };
+                (self.literal([value]), Vec::new())
+            }
+            SchemaKind::Enum { values } if values.is_empty() => {
+                simple(self.imported("typing", "NoReturn"))
+            }
+            SchemaKind::Enum { values } => (
+                self.literal(values.iter().map(|v| py_string(v))),
+                Vec::new(),
+            ),
+
+            SchemaKind::Ref { reference } => {
+                let name = ref_name(reference, self.refs);
+                (self.name(name), Vec::new())
+            }
+            SchemaKind::Named { name, .. } => (self.name(name.clone()), Vec::new()),
+
+            SchemaKind::Function {
+                parameters,
+                returns,
+            } => {
+                self.import("typing", "Callable");
+                let params = self.py_list(parameters);
+                let returns = self.py_type(returns);
+                (format!("Callable[[{}], {}]", params, returns), Vec::new())
+            }
+
+            SchemaKind::Never => simple(self.imported("typing", "NoReturn")),
+            SchemaKind::Any | SchemaKind::Unknown => simple(self.imported("typing", "Any")),
+            SchemaKind::Recursive { schema: inner } => self.py_parts(inner),
+            // Intersections of models are hoisted into classes.
+            SchemaKind::Intersect { .. } => {
+                self.import("typing", "Dict");
+                let any = self.imported("typing", "Any");
+                (format!("Dict[str, {}]", any), Vec::new())
+            }
+        }
+    }
+
+    fn py_list(&mut self, schemas: &[Schema]) -> String {
+        let types: Vec<String> = schemas.iter().map(|s| self.py_type(s)).collect();
+        types.join(", ")
+    }
+
+    fn literal(&mut self, values: impl IntoIterator<Item = String>) -> String {
+        self.import("typing", "Literal");
+        let values: Vec<String> = values.into_iter().collect();
+        format!("Literal[{}]", values.join(", "))
+    }
+
+    fn imported(&mut self, from: &'static str, name: &'static str) -> &'static str {
+        self.import(from, name);
+        name
+    }
+
+    /// A reference to the definition `name`.
+    fn name(&mut self, name: String) -> String {
+        if self.defined.contains(&name) {
+            return name;
+        }
+        self.forward = true;
+        if self.quote {
+            py_string(&name)
+        } else {
+            name
+        }
+    }
+
+    /// Whether every schema refers to a model class.
+    fn all_models(&self, schemas: &[Schema]) -> bool {
+        !schemas.is_empty() && schemas.iter().all(|schema| self.model(schema).is_some())
+    }
+
+    /// The object definition `schema` refers to, if it is a model class.
+    fn model(&self, schema: &Schema) -> Option<&'h Schema> {
+        let SchemaKind::Ref { reference } = &schema.kind else {
+            return None;
+        };
+        let target = *self.defs.get(ref_name(reference, self.refs).as_str())?;
+        match &target.kind {
+            SchemaKind::Object { properties, .. } if !properties.is_empty() => Some(target),
+            SchemaKind::Intersect { all_of } if self.all_models(all_of) => Some(target),
+            _ => None,
+        }
+    }
+
+    /// The field every variant model has as a distinct, required string
+    /// literal, by its Python name.
+    fn discriminator(&self, any_of: &[Schema]) -> Option<String> {
+        if any_of.len() < 2 {
+            return None;
+        }
+        let objects = any_of
+            .iter()
+            .map(|variant| self.model(variant))
+            .collect::<Option<Vec<_>>>()?;
+        let SchemaKind::Object { properties, .. } = &objects[0].kind else {
+            return None;
+        };
+        properties.keys().find_map(|tag| {
+            let mut seen = HashSet::new();
+            objects
+                .iter()
+                .all(|object| match &object.kind {
+                    SchemaKind::Object {
+                        properties,
+                        required,
+                        ..
+                    } if required.contains(tag) => match properties.get(tag) {
+                        Some(Schema {
+                            kind:
+                                SchemaKind::Literal {
+                                    value: LiteralValue::String(value),
+                                },
+                            ..
+                        }) => seen.insert(value.as_str()),
+                        _ => false,
+                    },
+                    _ => false,
+                })
+                .then(|| field_name(tag))
+        })
+    }
+}
+
+/// `int` bounded to `minimum..=maximum`.
+fn integer(minimum: i64, maximum: i64) -> (String, Vec<String>) {
+    ("int".to_string(), bounds(Some(minimum), Some(maximum)))
+}
+
+fn bounds<T: ToString>(minimum: Option<T>, maximum: Option<T>) -> Vec<String> {
+    let mut constraints = Vec::new();
+    if let Some(min) = minimum {
+        constraints.push(format!("ge={}", min.to_string()));
+    }
+    if let Some(max) = maximum {
+        constraints.push(format!("le={}", max.to_string()));
+    }
+    constraints
+}
+
+fn lengths(minimum: Option<usize>, maximum: Option<usize>) -> Vec<String> {
+    let mut constraints = Vec::new();
+    if let Some(min) = minimum {
+        constraints.push(format!("min_length={}", min));
+    }
+    if let Some(max) = maximum {
+        constraints.push(format!("max_length={}", max));
+    }
+    constraints
+}
+
+/// Whether a default of type `py_type` is used as written.
+fn plain_type(py_type: &str) -> bool {
+    let inner = py_type
+        .strip_prefix("Optional[")
+        .and_then(|t| t.strip_suffix(']'))
+        .unwrap_or(py_type);
+    matches!(inner, "bool" | "int" | "float" | "str" | "Any" | "None")
+        || ["List[", "Dict[", "Tuple[", "Literal["]
+            .iter()
+            .any(|prefix| inner.starts_with(prefix))
+}
+
+fn contains_bytes(schema: &Schema) -> bool {
+    match &schema.kind {
+        SchemaKind::Bytes { .. } => true,
+        SchemaKind::Array { items, .. } => contains_bytes(items),
+        SchemaKind::Tuple { items } => items.iter().any(contains_bytes),
+        SchemaKind::Union { any_of } => any_of.iter().any(contains_bytes),
+        SchemaKind::Object {
+            additional_properties: Some(values),
+            ..
+        } => contains_bytes(values),
+        _ => false,
+    }
+}
+
+/// A field name for the JSON property `prop`.
+fn field_name(prop: &str) -> String {
+    let mut name = snake_case(prop);
+    // Leading underscores make private attributes.
+    if name.starts_with('_') {
+        name = format!("field{}", name);
+    }
+    if is_keyword(&name)
+        || name.starts_with("model_")
+        || BASE_MODEL_ATTRIBUTES.contains(&name.as_str())
+        || SHADOWED.contains(&name.as_str())
+    {
+        name.push('_');
+    }
+    name
+}
+
+/// An enum member name for `value`, such as `IN_PROGRESS`.
+fn member_name(value: &str) -> String {
+    if !value.chars().any(char::is_alphanumeric) {
+        return "EMPTY".to_string();
+    }
+    let name = snake_case(value).to_uppercase();
+    if name.starts_with('_') {
+        format!("V{}", name)
+    } else {
+        name
+    }
+}
+
+/// `name`, or `name` with the first free numeric suffix.
+fn dedupe(name: String, used: &mut HashSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut suffix = 1;
+    while !used.insert(unique.clone()) {
+        suffix += 1;
+        unique = format!("{}_{}", name, suffix);
+    }
+    unique
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "and"
+            | "as"
+            | "assert"
+            | "async"
+            | "await"
+            | "break"
+            | "class"
+            | "continue"
+            | "def"
+            | "del"
+            | "elif"
+            | "else"
+            | "except"
+            | "finally"
+            | "for"
+            | "from"
+            | "global"
+            | "if"
+            | "import"
+            | "in"
+            | "is"
+            | "lambda"
+            | "nonlocal"
+            | "not"
+            | "or"
+            | "pass"
+            | "raise"
+            | "return"
+            | "try"
+            | "while"
+            | "with"
+            | "yield"
+    )
+}
+
+/// Attributes of `BaseModel` a field must not shadow.
+const BASE_MODEL_ATTRIBUTES: &[&str] = &[
+    "construct",
+    "copy",
+    "dict",
+    "from_orm",
+    "json",
+    "parse_file",
+    "parse_obj",
+    "parse_raw",
+    "schema",
+    "schema_json",
+    "update_forward_refs",
+    "validate",
+];
+
+/// Names annotations may use, which a field of the same name would
+/// shadow within its class.
+const SHADOWED: &[&str] = &[
+    "any", "bool", "bytes", "date", "datetime", "dict", "float", "int", "list", "str", "time",
+    "tuple", "type",
+];
+
+/// The docstring for the description of `schema`, at `indent`.
+fn docstring(schema: &Schema, indent: &str) -> Option<String> {
+    let description = schema.description.as_deref()?.trim();
+    let mut escaped = description
+        .replace('\\', "\\\\")
+        .replace("\"\"\"", "\\\"\\\"\\\"");
+    // A quote just before the closing quotes would end the string early.
+    if escaped.ends_with('"') && !escaped.ends_with("\\\"") {
+        escaped.insert(escaped.len() - 1, '\\');
+    }
+    let lines: Vec<&str> = escaped.lines().collect();
+    Some(match lines.as_slice() {
+        [line] => format!("\"\"\"{}\"\"\"", line),
+        _ => {
+            let mut doc = format!("\"\"\"{}", lines[0]);
+            for line in &lines[1..] {
+                doc.push('\n');
+                if !line.is_empty() {
+                    doc.push_str(indent);
+                    doc.push_str(line);
+                }
+            }
+            doc.push_str(&format!("\n{}\"\"\"", indent));
+            doc
+        }
+    })
+}
+
+fn py_string(s: &str) -> String {
+    let mut out = String::from("'");
+    for c in s.chars() {
+        match c {
+            '\'' => out.push_str("\\'"),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+/// A regular expression as a raw string, when it can be one.
+fn py_raw_string(pattern: &str) -> String {
+    let raw = !pattern.contains(['\'', '\n', '\r']) && !pattern.ends_with('\\');
+    if raw {
+        format!("r'{}'", pattern)
+    } else {
+        py_string(pattern)
+    }
+}
+
+fn py_float(n: f64) -> String {
+    if n.is_nan() {
+        "float('nan')".to_string()
+    } else if n.is_infinite() {
+        format!("float('{}inf')", if n < 0.0 { "-" } else { "" })
+    } else {
+        format!("{:?}", n)
+    }
+}
+
+fn py_bool(b: bool) -> &'static str {
+    if b {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+/// A JSON value as a Python literal.
+fn py_value(json: &serde_json::Value) -> String {
+    match json {
+        serde_json::Value::Null => "None".to_string(),
+        serde_json::Value::Bool(b) => py_bool(*b).to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => py_string(s),
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(py_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        serde_json::Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{}: {}", py_string(key), py_value(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+    }
+}
+
+const CLASS_TEMPLATE: &str = r#"class {{name}}({{{bases}}}):
+{{#each sections}}{{#unless @first}}
+
+{{/unless}}{{{this}}}{{/each}}
+"#;
+
+const ENUM_TEMPLATE: &str = r#"class {{name}}(str, Enum):
+{{#if doc}}    {{{doc}}}
+{{#if members}}
+{{/if}}{{/if}}{{#each members}}    {{name}} = {{{value}}}
+{{/each}}{{#unless doc}}{{#unless members}}    pass
+{{/unless}}{{/unless}}"#;
+
+const ALIAS_TEMPLATE: &str = r#"{{name}} = {{{py_type}}}
+{{#if doc}}{{{doc}}}
+{{/if}}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::value::Value;
+
+    #[test]
+    fn test_generate_model() {
+        let gen = PydanticGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "id",
+                Schema::new(SchemaKind::Int64 {
+                    minimum: Some(0),
+                    maximum: None,
+                }),
+            )
+            .optional_field(
+                "email",
+                SchemaBuilder::string().format(StringFormat::Email).build(),
+            )
+            .field(
+                "content-type",
+                SchemaBuilder::string()
+                    .pattern("^[a-z]+/[a-z]+$")
+                    .max_length(64)
+                    .build(),
+            )
+            .optional_field(
+                "retries",
+                SchemaBuilder::uint8()
+                    .with_default(Value::Int64(3))
+                    .with_description("How often to retry."),
+            )
+            .optional_field(
+                "status",
+                SchemaBuilder::enum_values(vec!["active", "banned"]),
+            )
+            .field(
+                "avatar",
+                Schema::new(SchemaKind::Bytes {
+                    min_length: None,
+                    max_length: None,
+                }),
+            )
+            .field(
+                "bytes",
+                SchemaBuilder::int64().with_default(Value::Int64(0)),
+            )
+            .build()
+            .with_description("A person.");
+
+        let output = gen.generate("Person", &schema).unwrap();
+        assert_eq!(
+            output,
+            r#"class Person(BaseModel):
+    """A person."""
+
+    model_config = ConfigDict(extra='forbid', populate_by_name=True, ser_json_bytes='base64', val_json_bytes='base64')
+
+    id: int = Field(ge=0)
+    email: Optional[str] = None
+    content_type: str = Field(alias='content-type', max_length=64, pattern=r'^[a-z]+/[a-z]+$')
+    retries: int = Field(default=3, ge=0, le=255, description='How often to retry.')
+    status: Optional[Literal['active', 'banned']] = None
+    avatar: bytes
+    bytes_: int = Field(default=0, alias='bytes')
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_enum_and_names() {
+        let gen = PydanticGenerator::new();
+        let output = gen
+            .generate(
+                "Status",
+                &SchemaBuilder::enum_values(vec!["in-progress", "2xx", "", "class"]),
+            )
+            .unwrap();
+        assert_eq!(
+            output,
+            "class Status(str, Enum):\n    IN_PROGRESS = 'in-progress'\n    V_2XX = '2xx'\n    EMPTY = ''\n    CLASS = 'class'\n"
+        );
+
+        assert_eq!(field_name("class"), "class_");
+        assert_eq!(field_name("_id"), "id");
+        assert_eq!(field_name("1st"), "field_1st");
+        assert_eq!(field_name("model_name"), "model_name_");
+        assert_eq!(field_name("isPrimaryKey"), "is_primary_key");
+        assert_eq!(field_name("str"), "str_");
+
+        let order = SchemaBuilder::object()
+            .field("id", SchemaBuilder::int64())
+            .build()
+            .with_description("Quoted \"order\"");
+        let output = gen
+            .generate("https://example.com/schemas/order.json", &order)
+            .unwrap();
+        assert!(output.starts_with(
+            "class Order(BaseModel):\n    \"\"\"Quoted \"order\\\"\"\"\"\n\n    model_config = ConfigDict(title='https://example.com/schemas/order.json', extra='forbid')\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_module_unions_and_recursion() {
+        let gen = PydanticGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Shape",
+            SchemaBuilder::union(vec![
+                SchemaBuilder::r#ref("Circle"),
+                SchemaBuilder::r#ref("Square"),
+            ]),
+        );
+        registry.register(
+            "Circle",
+            SchemaBuilder::object()
+                .field("kind", SchemaBuilder::literal("circle"))
+                .field("radius", SchemaBuilder::float64())
+                .build(),
+        );
+        registry.register(
+            "Square",
+            SchemaBuilder::object()
+                .field("kind", SchemaBuilder::literal("square"))
+                .field("side", SchemaBuilder::float64())
+                .build(),
+        );
+        registry.register(
+            "Node",
+            SchemaBuilder::recursive("Node", |this| {
+                SchemaBuilder::object()
+                    .field("children", SchemaBuilder::array(this).build())
+                    .optional_field("shape", SchemaBuilder::r#ref("Shape"))
+                    .build()
+            }),
+        );
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .optional_field("pet", SchemaBuilder::r#ref("Pet"))
+                .build(),
+        );
+        registry.register(
+            "Pet",
+            SchemaBuilder::object()
+                .field("owner", SchemaBuilder::r#ref("Person"))
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.starts_with(
+            "# Auto-generated by typebox-rs. DO NOT EDIT.\n\nfrom __future__ import annotations\n\nfrom typing import Annotated, List, Literal, Optional, Union\n\nfrom pydantic import BaseModel, ConfigDict, Field\n"
+        ));
+        assert!(output
+            .contains("Shape = Annotated[Union[Circle, Square], Field(discriminator='kind')]\n"));
+        assert!(output.contains("    kind: Literal['circle']\n"));
+        assert!(output.contains(
+            "class Node(BaseModel):\n    model_config = ConfigDict(extra='forbid')\n\n    children: List[Node]\n    shape: Optional[Shape] = None\n"
+        ));
+        // `Person` refers to `Pet`, defined after it.
+        assert!(output.contains("    pet: Optional[Pet] = None\n"));
+        assert!(output.ends_with("\n\nPerson.model_rebuild()\n"));
+
+        // Only models can be combined into a class.
+        registry.register(
+            "Labelled",
+            SchemaBuilder::intersect(vec![
+                SchemaBuilder::r#ref("Circle"),
+                SchemaBuilder::object()
+                    .field("label", SchemaBuilder::string().build())
+                    .build(),
+            ]),
+        );
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains("class Labelled(Circle, LabelledPart1):\n    pass\n"));
+        registry.register(
+            "LabelledShape",
+            SchemaBuilder::intersect(vec![
+                SchemaBuilder::r#ref("Shape"),
+                SchemaBuilder::r#ref("Labelled"),
+            ]),
+        );
+        assert!(matches!(
+            gen.generate_module(&registry),
+            Err(crate::Error::Codegen(message)) if message.starts_with("LabelledShape:")
+        ));
+    }
+}
@@ -1,11 +1,33 @@
+use super::hoist::{optional_inner, pascal_case, ref_name, string_literals, Hoister, TypeDef};
 use crate::codegen::SchemaRegistry;
 use crate::schema::{LiteralValue, Schema, SchemaKind};
+use crate::value::Value;
 use handlebars::Handlebars;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Names the generated code uses, which type names must not shadow.
+const RESERVED: &[&str] = &[
+    "Self",
+    "Option",
+    "Some",
+    "None",
+    "Box",
+    "Vec",
+    "String",
+    "Result",
+    "Ok",
+    "Err",
+    "Serialize",
+    "Deserialize",
+];
+
+/// Derives every generated struct and enum gets.
+const DEFAULT_DERIVES: [&str; 4] = ["Debug", "Clone", "Serialize", "Deserialize"];
 
 pub struct RustGenerator {
     registry: Handlebars<'static>,
+    derives: Vec<String>,
 }
 
 impl RustGenerator {
@@ -20,48 +42,403 @@ impl RustGenerator {
         registry
             .register_template_string("enum", ENUM_TEMPLATE)
             .unwrap();
+        registry
+            .register_template_string("alias", ALIAS_TEMPLATE)
+            .unwrap();
+        registry
+            .register_template_string("literal", LITERAL_TEMPLATE)
+            .unwrap();
 
-        Self { registry }
+        Self {
+            registry,
+            derives: DEFAULT_DERIVES.iter().map(|d| d.to_string()).collect(),
+        }
     }
 
-    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
-        let context = SchemaContext::from_schema(name, schema);
-
-        match &schema.kind {
-            SchemaKind::Enum { values } => {
-                let ctx = EnumContext {
-                    name: name.to_string(),
-                    values: values.clone(),
-                };
-                Ok(self.registry.render("enum", &ctx)?)
-            }
-            SchemaKind::Object { .. } => Ok(self.registry.render("struct", &context)?),
-            SchemaKind::Named { schema, .. } => self.generate(name, schema),
-            _ => {
-                let rust_type = schema_to_rust_type(schema, &HashMap::new());
-                Ok(format!("pub type {} = {};\n", name, rust_type))
+    /// Adds derives, such as `PartialEq`, `Eq` or `Hash`, to every generated
+    /// struct and enum.
+    pub fn with_derives<I, S>(mut self, derives: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for derive in derives {
+            let derive = derive.into();
+            if !self.derives.contains(&derive) {
+                self.derives.push(derive);
             }
         }
+        self
+    }
+
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(None)
+            .with_reserved(RESERVED)
+            .with_literals()
+            .with_enums();
+        hoister.add_root(name, schema);
+        Ok(self.render(&hoister)?.join("\n"))
     }
 
     pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
-        let mut rendered: Vec<String> = Vec::new();
+        let mut hoister = Hoister::new(Some(registry))
+            .with_reserved(RESERVED)
+            .with_literals()
+            .with_enums();
 
-        for (name, schema) in registry.schemas() {
-            rendered.push(self.generate(name, schema)?);
+        // Dependencies first, in a stable order, so output diffs cleanly.
+        for name in registry.dependency_graph().topological_order() {
+            if let Some(schema) = registry.get(name) {
+                hoister.add_root(name, schema);
+            }
         }
 
         let mut output = String::new();
         output.push_str("// Auto-generated by typebox-rs. DO NOT EDIT.\n\n");
         output.push_str("use serde::{Deserialize, Serialize};\n\n");
 
-        for code in rendered {
+        for code in self.render(&hoister)? {
             output.push_str(&code);
             output.push('\n');
         }
 
         Ok(output)
     }
+
+    /// Renders the hoisted definitions, boxing the fields through which a
+    /// type contains itself. Tagged unions take the fields of their variants
+    /// inline, so hoisted definitions used only by them are left out.
+    fn render(&self, hoister: &Hoister) -> Result<Vec<String>, crate::Error> {
+        let module = Module {
+            defs: hoister
+                .defs
+                .iter()
+                .map(|def| (def.name.as_str(), &def.schema))
+                .collect(),
+            refs: &hoister.refs,
+        };
+        let unions: HashMap<&str, UnionRepr> = hoister
+            .defs
+            .iter()
+            .filter_map(|def| match &def.schema.kind {
+                SchemaKind::Union { any_of }
+                    if optional_inner(any_of).is_none() && string_literals(any_of).is_none() =>
+                {
+                    Some((def.name.as_str(), module.union_repr(any_of)))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut direct: HashMap<&str, HashSet<String>> = HashMap::new();
+        let mut uses: HashMap<&str, HashSet<String>> = HashMap::new();
+        for def in &hoister.defs {
+            let parts: Vec<&Schema> = match unions.get(def.name.as_str()) {
+                Some(
+                    UnionRepr::Internal { variants, .. } | UnionRepr::Adjacent { variants, .. },
+                ) => variants.iter().map(|variant| &variant.body).collect(),
+                _ => vec![&def.schema],
+            };
+            let (by_value, all) = (
+                direct.entry(&def.name).or_default(),
+                uses.entry(&def.name).or_default(),
+            );
+            for part in parts {
+                collect_refs(part, &hoister.refs, true, by_value);
+                collect_refs(part, &hoister.refs, false, all);
+            }
+        }
+
+        let mut reachable = HashSet::new();
+        let mut stack: Vec<&str> = hoister
+            .defs
+            .iter()
+            .filter(|def| def.root)
+            .map(|def| def.name.as_str())
+            .collect();
+        while let Some(name) = stack.pop() {
+            if reachable.insert(name) {
+                stack.extend(uses.get(name).into_iter().flatten().map(String::as_str));
+            }
+        }
+
+        hoister
+            .defs
+            .iter()
+            .filter(|def| reachable.contains(def.name.as_str()))
+            .map(|def| {
+                let boxed: HashSet<String> = direct[def.name.as_str()]
+                    .iter()
+                    .filter(|target| reaches(&direct, target, &def.name))
+                    .cloned()
+                    .collect();
+                let union = unions.get(def.name.as_str());
+                self.render_def(def, union, &module, &boxed)
+            })
+            .collect()
+    }
+
+    fn render_def(
+        &self,
+        def: &TypeDef,
+        union: Option<&UnionRepr>,
+        module: &Module,
+        boxed: &HashSet<String>,
+    ) -> Result<String, crate::Error> {
+        let (name, schema) = (def.name.as_str(), &def.schema);
+        let original = def.original.as_deref();
+        let refs = module.refs;
+        match (&schema.kind, union) {
+            (_, Some(repr)) => self.render_union(name, schema, original, repr, module, boxed),
+            (SchemaKind::Enum { values }, _) => {
+                let values: Vec<&str> = values.iter().map(String::as_str).collect();
+                self.render_enum(name, schema, original, &values)
+            }
+            (SchemaKind::Union { any_of }, _) if string_literals(any_of).is_some() => {
+                let values = string_literals(any_of).unwrap_or_default();
+                self.render_enum(name, schema, original, &values)
+            }
+            (SchemaKind::Object { properties, .. }, _) if !properties.is_empty() => {
+                let context = SchemaContext::from_schema(name, schema, refs, boxed);
+                let mut attributes = self.type_attributes(schema, original);
+                if let Some(rename_all) = &context.rename_all {
+                    attributes.push(format!("#[serde(rename_all = \"{}\")]", rename_all));
+                }
+                Ok(self.registry.render(
+                    "struct",
+                    &StructContext {
+                        attributes,
+                        context,
+                    },
+                )?)
+            }
+            (SchemaKind::Intersect { all_of }, _) => {
+                let mut used = HashSet::new();
+                let properties = all_of
+                    .iter()
+                    .map(|member| {
+                        let field = format_ident(&dedupe(
+                            snake_case(&module.variant_name(name, member)),
+                            "_",
+                            &mut used,
+                        ));
+                        PropertyContext {
+                            name: unraw(&field).to_string(),
+                            rust_name: field,
+                            rust_type: schema_to_rust_type(member, refs, boxed),
+                            optional: false,
+                            doc: doc_lines(member, "    "),
+                            attributes: vec!["#[serde(flatten)]".to_string()],
+                            has_default: false,
+                            default_value: None,
+                        }
+                    })
+                    .collect();
+                let context = SchemaContext {
+                    name: name.to_string(),
+                    doc: doc_lines(schema, ""),
+                    rename_all: None,
+                    properties,
+                    default_fns: Vec::new(),
+                };
+                Ok(self.registry.render(
+                    "struct",
+                    &StructContext {
+                        attributes: self.type_attributes(schema, original),
+                        context,
+                    },
+                )?)
+            }
+            (SchemaKind::Literal { value }, _) => match literal_const(value) {
+                Some((value_type, owned_type, value)) => {
+                    let mut derives: Vec<&str> =
+                        vec!["Debug", "Clone", "Copy", "Default", "PartialEq"];
+                    for derive in &self.derives {
+                        if !derives.contains(&derive.as_str())
+                            && !matches!(derive.as_str(), "Serialize" | "Deserialize")
+                        {
+                            derives.push(derive);
+                        }
+                    }
+                    let mut attributes = vec![format!("#[derive({})]", derives.join(", "))];
+                    attributes.extend(deprecated_attribute(schema));
+                    let ctx = LiteralContext {
+                        name: name.to_string(),
+                        doc: doc_lines(schema, ""),
+                        attributes,
+                        value_type: value_type.to_string(),
+                        owned_type: owned_type.to_string(),
+                        value,
+                    };
+                    Ok(self.registry.render("literal", &ctx)?)
+                }
+                None => self.render_alias(name, schema, refs, boxed),
+            },
+            _ => self.render_alias(name, schema, refs, boxed),
+        }
+    }
+
+    /// Renders a union as an enum: tagged by a literal property all variants
+    /// share if there is one, untagged otherwise.
+    fn render_union(
+        &self,
+        name: &str,
+        schema: &Schema,
+        original: Option<&str>,
+        repr: &UnionRepr,
+        module: &Module,
+        boxed: &HashSet<String>,
+    ) -> Result<String, crate::Error> {
+        let refs = module.refs;
+        let mut attributes = self.type_attributes(schema, original);
+        let mut used = HashSet::new();
+        let mut default_fns = Vec::new();
+        let variants = match repr {
+            UnionRepr::Internal { tag, variants } => {
+                attributes.push(format!("#[serde(tag = {:?})]", tag));
+                variants
+                    .iter()
+                    .map(|variant| {
+                        let ident = dedupe(variant_ident(variant.value), "", &mut used);
+                        let context = SchemaContext::from_schema(
+                            &format!("{}{}", name, ident),
+                            &variant.body,
+                            refs,
+                            boxed,
+                        );
+                        default_fns.extend(context.default_fns);
+                        let mut serde = rename(variant.value, &ident);
+                        serde.extend(
+                            context
+                                .rename_all
+                                .map(|rename_all| format!("rename_all = {:?}", rename_all)),
+                        );
+                        VariantContext {
+                            doc: doc_lines(variant.schema, "    "),
+                            attributes: variant_attributes(serde, variant.schema),
+                            name: ident,
+                            payload: None,
+                            fields: context.properties,
+                        }
+                    })
+                    .collect()
+            }
+            UnionRepr::Adjacent {
+                tag,
+                content,
+                variants,
+            } => {
+                attributes.push(format!(
+                    "#[serde(tag = {:?}, content = {:?})]",
+                    tag, content
+                ));
+                variants
+                    .iter()
+                    .map(|variant| {
+                        let ident = dedupe(variant_ident(variant.value), "", &mut used);
+                        VariantContext {
+                            doc: doc_lines(variant.schema, "    "),
+                            attributes: variant_attributes(
+                                rename(variant.value, &ident),
+                                variant.schema,
+                            ),
+                            name: ident,
+                            payload: Some(schema_to_rust_type(&variant.body, refs, boxed)),
+                            fields: Vec::new(),
+                        }
+                    })
+                    .collect()
+            }
+            UnionRepr::Untagged { any_of } => {
+                attributes.push("#[serde(untagged)]".to_string());
+                // serde tries the variants in order, so literals go before
+                // the types that would also accept them.
+                let (literals, others): (Vec<&Schema>, Vec<&Schema>) =
+                    any_of.iter().partition(|variant| {
+                        matches!(module.resolve(variant).kind, SchemaKind::Literal { .. })
+                    });
+                literals
+                    .into_iter()
+                    .chain(others)
+                    .map(|variant| {
+                        let ident = variant_ident(&module.variant_name(name, variant));
+                        VariantContext {
+                            name: dedupe(ident, "", &mut used),
+                            doc: doc_lines(variant, "    "),
+                            attributes: variant_attributes(Vec::new(), variant),
+                            payload: (!matches!(variant.kind, SchemaKind::Null))
+                                .then(|| schema_to_rust_type(variant, refs, boxed)),
+                            fields: Vec::new(),
+                        }
+                    })
+                    .collect()
+            }
+        };
+        let ctx = EnumContext {
+            name: name.to_string(),
+            doc: doc_lines(schema, ""),
+            attributes,
+            variants,
+            default_fns,
+        };
+        Ok(self.registry.render("enum", &ctx)?)
+    }
+
+    /// Renders string values as a fieldless enum.
+    fn render_enum(
+        &self,
+        name: &str,
+        schema: &Schema,
+        original: Option<&str>,
+        values: &[&str],
+    ) -> Result<String, crate::Error> {
+        let mut used = HashSet::new();
+        let variants = values
+            .iter()
+            .map(|value| {
+                let ident = dedupe(variant_ident(value), "", &mut used);
+                VariantContext {
+                    attributes: serde_attribute(rename(value, &ident)).into_iter().collect(),
+                    name: ident,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let ctx = EnumContext {
+            name: name.to_string(),
+            doc: doc_lines(schema, ""),
+            attributes: self.type_attributes(schema, original),
+            variants,
+            default_fns: Vec::new(),
+        };
+        Ok(self.registry.render("enum", &ctx)?)
+    }
+
+    fn render_alias(
+        &self,
+        name: &str,
+        schema: &Schema,
+        refs: &HashMap<String, String>,
+        boxed: &HashSet<String>,
+    ) -> Result<String, crate::Error> {
+        let ctx = AliasContext {
+            name: name.to_string(),
+            doc: doc_lines(schema, ""),
+            attributes: deprecated_attribute(schema).into_iter().collect(),
+            rust_type: schema_to_rust_type(schema, refs, boxed),
+        };
+        Ok(self.registry.render("alias", &ctx)?)
+    }
+
+    /// The `#[derive]`, `#[deprecated]` and, for a type named after a
+    /// registry name that is not an identifier, `#[serde(rename)]`
+    /// attributes of a generated type.
+    fn type_attributes(&self, schema: &Schema, original: Option<&str>) -> Vec<String> {
+        let mut attributes = vec![format!("#[derive({})]", self.derives.join(", "))];
+        attributes.extend(original.map(|original| format!("#[serde(rename = {:?})]", original)));
+        attributes.extend(deprecated_attribute(schema));
+        attributes
+    }
 }
 
 impl Default for RustGenerator {
@@ -73,9 +450,17 @@ impl Default for RustGenerator {
 #[derive(Serialize)]
 struct SchemaContext {
     name: String,
-    description: Option<String>,
+    doc: Vec<String>,
+    rename_all: Option<String>,
     properties: Vec<PropertyContext>,
-    type_refs: HashMap<String, String>,
+    default_fns: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct StructContext {
+    attributes: Vec<String>,
+    #[serde(flatten)]
+    context: SchemaContext,
 }
 
 #[derive(Serialize)]
@@ -84,7 +469,8 @@ struct PropertyContext {
     rust_name: String,
     rust_type: String,
     optional: bool,
-    description: Option<String>,
+    doc: Vec<String>,
+    attributes: Vec<String>,
     has_default: bool,
     default_value: Option<String>,
 }
@@ -92,12 +478,50 @@ struct PropertyContext {
 #[derive(Serialize)]
 struct EnumContext {
     name: String,
-    values: Vec<String>,
+    doc: Vec<String>,
+    attributes: Vec<String>,
+    variants: Vec<VariantContext>,
+    default_fns: Vec<String>,
+}
+
+/// A unit, newtype (`payload`) or struct (`fields`) enum variant.
+#[derive(Serialize, Default)]
+struct VariantContext {
+    name: String,
+    doc: Vec<String>,
+    attributes: Vec<String>,
+    payload: Option<String>,
+    fields: Vec<PropertyContext>,
+}
+
+#[derive(Serialize)]
+struct LiteralContext {
+    name: String,
+    doc: Vec<String>,
+    attributes: Vec<String>,
+    value_type: String,
+    owned_type: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct AliasContext {
+    name: String,
+    doc: Vec<String>,
+    attributes: Vec<String>,
+    rust_type: String,
 }
 
 impl SchemaContext {
-    fn from_schema(name: &str, schema: &Schema) -> Self {
+    fn from_schema(
+        name: &str,
+        schema: &Schema,
+        refs: &HashMap<String, String>,
+        boxed: &HashSet<String>,
+    ) -> Self {
         let mut properties = Vec::new();
+        let mut default_fns = Vec::new();
+        let mut rename_all = None;
 
         if let SchemaKind::Object {
             properties: props,
@@ -105,30 +529,535 @@ impl SchemaContext {
             ..
         } = &schema.kind
         {
-            for (prop_name, prop_schema) in props {
+            let mut used = HashSet::new();
+            let fields: Vec<(&String, String)> = props
+                .keys()
+                .map(|prop| {
+                    let mut field = format_ident(&snake_case(prop));
+                    let mut suffix = 1;
+                    while !used.insert(field.clone()) {
+                        suffix += 1;
+                        field = format!("{}_{}", snake_case(prop), suffix);
+                    }
+                    (prop, field)
+                })
+                .collect();
+
+            // One `rename_all` instead of a `rename` per field when every
+            // JSON name is the camelCase form of its field name.
+            let renamed = fields.iter().any(|(prop, field)| *prop != field);
+            if renamed
+                && fields
+                    .iter()
+                    .all(|(prop, field)| **prop == camel_case(field))
+            {
+                rename_all = Some("camelCase".to_string());
+            }
+
+            for ((prop_name, field), prop_schema) in fields.into_iter().zip(props.values()) {
                 let is_optional = !required.contains(prop_name);
+                let mut rust_type = schema_to_rust_type(prop_schema, refs, boxed);
+                if is_optional {
+                    rust_type = format!("Option<{}>", rust_type);
+                }
+
+                let mut serde = Vec::new();
+                if rename_all.is_none() && *prop_name != unraw(&field) {
+                    serde.push(format!("rename = {:?}", prop_name));
+                }
+                let default_value = prop_schema.default.as_ref().map(|default| {
+                    let fn_name = format!("default_{}_{}", snake_case(name), unraw(&field));
+                    let value = default_expr(default, prop_schema, is_optional);
+                    default_fns.push(format!(
+                        "fn {}() -> {} {{\n    {}\n}}\n",
+                        fn_name, rust_type, value
+                    ));
+                    fn_name
+                });
+                if let Some(fn_name) = &default_value {
+                    serde.push(format!("default = {:?}", fn_name));
+                }
+                if is_optional {
+                    serde.push("skip_serializing_if = \"Option::is_none\"".to_string());
+                }
+
+                let mut attributes = Vec::new();
+                if !serde.is_empty() {
+                    attributes.push(format!("#[serde({})]", serde.join(", ")));
+                }
+                attributes.extend(deprecated_attribute(prop_schema));
+
                 properties.push(PropertyContext {
                     name: prop_name.clone(),
-                    rust_name: format_ident(prop_name),
-                    rust_type: schema_to_rust_type(prop_schema, &HashMap::new()),
+                    rust_name: field,
+                    rust_type,
                     optional: is_optional,
-                    description: None,
-                    has_default: false,
-                    default_value: None,
+                    doc: doc_lines(prop_schema, "    "),
+                    attributes,
+                    has_default: default_value.is_some(),
+                    default_value,
                 });
             }
         }
 
         Self {
             name: name.to_string(),
-            description: schema.description.clone(),
+            doc: doc_lines(schema, ""),
+            rename_all,
+            properties,
+            default_fns,
+        }
+    }
+}
+
+/// The hoisted definitions of one generated module.
+struct Module<'a> {
+    defs: HashMap<&'a str, &'a Schema>,
+    refs: &'a HashMap<String, String>,
+}
+
+/// How a union is represented in Rust.
+enum UnionRepr<'a> {
+    /// `#[serde(tag = "..")]`: each variant object is a struct variant with
+    /// the fields other than the tag.
+    Internal {
+        tag: &'a str,
+        variants: Vec<TaggedVariant<'a>>,
+    },
+    /// `#[serde(tag = "..", content = "..")]`: each variant object holds
+    /// only the tag and the content.
+    Adjacent {
+        tag: &'a str,
+        content: &'a str,
+        variants: Vec<TaggedVariant<'a>>,
+    },
+    /// `#[serde(untagged)]`: the first variant that deserializes wins.
+    Untagged { any_of: &'a [Schema] },
+}
+
+struct TaggedVariant<'a> {
+    /// The tag value that selects the variant.
+    value: &'a str,
+    /// The variant object.
+    schema: &'a Schema,
+    /// The variant's fields without the tag, or its content.
+    body: Schema,
+}
+
+impl<'a> Module<'a> {
+    /// `schema`, or the definition it refers to.
+    fn resolve(&self, mut schema: &'a Schema) -> &'a Schema {
+        for _ in 0..=self.defs.len() {
+            let SchemaKind::Ref { reference } = &schema.kind else {
+                break;
+            };
+            match self.defs.get(ref_name(reference, self.refs).as_str()) {
+                Some(target) => schema = target,
+                None => break,
+            }
+        }
+        schema
+    }
+
+    fn union_repr(&self, any_of: &'a [Schema]) -> UnionRepr<'a> {
+        let objects: Option<Vec<&'a Schema>> = any_of
+            .iter()
+            .map(|variant| {
+                Some(self.resolve(variant))
+                    .filter(|schema| matches!(schema.kind, SchemaKind::Object { .. }))
+            })
+            .collect();
+        let Some(objects) = objects else {
+            return UnionRepr::Untagged { any_of };
+        };
+        let Some(SchemaKind::Object { properties, .. }) = objects.first().map(|o| &o.kind) else {
+            return UnionRepr::Untagged { any_of };
+        };
+
+        for tag in properties.keys() {
+            let Some(values) = objects
+                .iter()
+                .map(|object| self.tag_value(object, tag))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            if values.iter().collect::<HashSet<_>>().len() != values.len() {
+                continue;
+            }
+            let content = adjacent_content(&objects, tag);
+            let variants = objects
+                .iter()
+                .zip(values)
+                .map(|(object, value)| TaggedVariant {
+                    value,
+                    schema: object,
+                    body: variant_body(object, tag, content),
+                })
+                .collect();
+            return match content {
+                Some(content) => UnionRepr::Adjacent {
+                    tag,
+                    content,
+                    variants,
+                },
+                None => UnionRepr::Internal { tag, variants },
+            };
+        }
+        UnionRepr::Untagged { any_of }
+    }
+
+    /// The string literal the required property `tag` of `object` is fixed
+    /// to.
+    fn tag_value(&self, object: &'a Schema, tag: &str) -> Option<&'a str> {
+        let SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } = &object.kind
+        else {
+            return None;
+        };
+        if !required.iter().any(|r| r == tag) {
+            return None;
+        }
+        match &self.resolve(properties.get(tag)?).kind {
+            SchemaKind::Literal {
+                value: LiteralValue::String(value),
+            } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// A name for the variant `schema` of the union `union`, from the type
+    /// it holds.
+    fn variant_name(&self, union: &str, schema: &Schema) -> String {
+        match &schema.kind {
+            SchemaKind::Ref { reference } => {
+                if let SchemaKind::Literal { value } = &self.resolve(schema).kind {
+                    return literal_name(value);
+                }
+                let name = ref_name(reference, self.refs);
+                match name.strip_prefix(union) {
+                    Some(rest) if rest.starts_with(char::is_uppercase) => rest.to_string(),
+                    _ => name,
+                }
+            }
+            SchemaKind::Literal { value } => literal_name(value),
+            SchemaKind::Array { items, .. } => format!("{}List", self.variant_name(union, items)),
+            SchemaKind::Union { any_of } => match optional_inner(any_of) {
+                Some(inner) => format!("Optional{}", self.variant_name(union, inner)),
+                None => "Union".to_string(),
+            },
+            SchemaKind::Null => "Null".to_string(),
+            SchemaKind::Bool => "Bool".to_string(),
+            SchemaKind::Int8 { .. } => "I8".to_string(),
+            SchemaKind::Int16 { .. } => "I16".to_string(),
+            SchemaKind::Int32 { .. } => "I32".to_string(),
+            SchemaKind::Int64 { .. } => "I64".to_string(),
+            SchemaKind::UInt8 { .. } => "U8".to_string(),
+            SchemaKind::UInt16 { .. } => "U16".to_string(),
+            SchemaKind::UInt32 { .. } => "U32".to_string(),
+            SchemaKind::UInt64 { .. } => "U64".to_string(),
+            SchemaKind::Float32 { .. } => "F32".to_string(),
+            SchemaKind::Float64 { .. } => "F64".to_string(),
+            SchemaKind::String { .. } | SchemaKind::Enum { .. } => "String".to_string(),
+            SchemaKind::Bytes { .. } => "Bytes".to_string(),
+            SchemaKind::Tuple { .. } => "Tuple".to_string(),
+            SchemaKind::Object { .. } => "Map".to_string(),
+            SchemaKind::Function { .. } => "Function".to_string(),
+            _ => "Value".to_string(),
+        }
+    }
+}
+
+/// The property that holds the content of every variant when each variant
+/// object has exactly the required properties `tag` and that property.
+fn adjacent_content<'a>(objects: &[&'a Schema], tag: &str) -> Option<&'a str> {
+    let mut content = None;
+    for object in objects {
+        let SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } = &object.kind
+        else {
+            return None;
+        };
+        if properties.len() != 2 {
+            return None;
+        }
+        let other = properties.keys().find(|p| *p != tag)?;
+        if !required.contains(other) || content.is_some_and(|c| c != other) {
+            return None;
+        }
+        content = Some(other.as_str());
+    }
+    content
+}
+
+/// The body of the variant `object` of a union tagged by `tag`: its content
+/// if adjacently tagged, else the object without the tag.
+fn variant_body(object: &Schema, tag: &str, content: Option<&str>) -> Schema {
+    match (&object.kind, content) {
+        (SchemaKind::Object { properties, .. }, Some(content)) => properties
+            .get(content)
+            .cloned()
+            .unwrap_or_else(|| object.clone()),
+        (
+            SchemaKind::Object {
+                properties,
+                required,
+                additional_properties,
+            },
+            None,
+        ) => Schema {
+            kind: SchemaKind::Object {
+                properties: properties
+                    .iter()
+                    .filter(|(prop, _)| *prop != tag)
+                    .map(|(prop, schema)| (prop.clone(), schema.clone()))
+                    .collect(),
+                required: required.iter().filter(|r| *r != tag).cloned().collect(),
+                additional_properties: additional_properties.clone(),
+            },
+            ..object.clone()
+        },
+        _ => object.clone(),
+    }
+}
+
+fn literal_name(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::String(s) => s.clone(),
+        LiteralValue::Boolean(b) => b.to_string(),
+        LiteralValue::Null => "Null".to_string(),
+        LiteralValue::Number(_) | LiteralValue::Float(_) => "Literal".to_string(),
+    }
+}
+
+/// The constant type, the type to deserialize and the constant expression
+/// of a literal; `None` for `null`, which is `()`.
+fn literal_const(value: &LiteralValue) -> Option<(&'static str, &'static str, String)> {
+    match value {
+        LiteralValue::String(s) => Some(("&'static str", "String", format!("{:?}", s))),
+        LiteralValue::Number(n) => Some(("i64", "i64", n.to_string())),
+        LiteralValue::Float(f) => Some(("f64", "f64", format!("{:?}", f))),
+        LiteralValue::Boolean(b) => Some(("bool", "bool", b.to_string())),
+        LiteralValue::Null => None,
+    }
+}
+
+/// An enum variant name for the JSON value or type name `value`.
+fn variant_ident(value: &str) -> String {
+    match pascal_case(value) {
+        ident if ident.is_empty() => "Empty".to_string(),
+        ident if ident == "Self" => "Self_".to_string(),
+        ident => ident,
+    }
+}
+
+/// `ident`, or `ident` with the first free numeric suffix.
+fn dedupe(ident: String, separator: &str, used: &mut HashSet<String>) -> String {
+    let mut unique = ident.clone();
+    let mut suffix = 1;
+    while !used.insert(unique.clone()) {
+        suffix += 1;
+        unique = format!("{}{}{}", ident, separator, suffix);
+    }
+    unique
+}
+
+/// A `rename` for the variant `ident` if it differs from the JSON `value`.
+fn rename(value: &str, ident: &str) -> Vec<String> {
+    if value == ident {
+        Vec::new()
+    } else {
+        vec![format!("rename = {:?}", value)]
+    }
+}
+
+fn serde_attribute(args: Vec<String>) -> Option<String> {
+    (!args.is_empty()).then(|| format!("#[serde({})]", args.join(", ")))
+}
+
+fn variant_attributes(serde: Vec<String>, schema: &Schema) -> Vec<String> {
+    serde_attribute(serde)
+        .into_iter()
+        .chain(deprecated_attribute(schema))
+        .collect()
+}
+
+/// `///` lines from the description and examples of `schema`, each
+/// prefixed with `indent`.
+fn doc_lines(schema: &Schema, indent: &str) -> Vec<String> {
+    let mut lines: Vec<String> = schema
+        .description
+        .iter()
+        .flat_map(|description| description.lines())
+        .map(str::to_string)
+        .collect();
+    if let Some(examples) = schema.examples.as_ref().filter(|e| !e.is_empty()) {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push("# Examples".to_string());
+        for example in examples {
+            lines.push(String::new());
+            lines.push("```json".to_string());
+            let json = serde_json::to_string_pretty(&example.to_json()).unwrap_or_default();
+            lines.extend(json.lines().map(str::to_string));
+            lines.push("```".to_string());
+        }
+    }
+    lines
+        .into_iter()
+        .map(|line| {
+            if line.is_empty() {
+                format!("{}///", indent)
+            } else {
+                format!("{}/// {}", indent, line)
+            }
+        })
+        .collect()
+}
+
+fn deprecated_attribute(schema: &Schema) -> Option<String> {
+    (schema.deprecated == Some(true)).then(|| "#[deprecated]".to_string())
+}
+
+/// A Rust expression for the default `value` of a field of type `schema`.
+fn default_expr(value: &Value, schema: &Schema, optional: bool) -> String {
+    let schema = match &schema.kind {
+        SchemaKind::Union { any_of } => match optional_inner(any_of) {
+            Some(inner) => {
+                return match value {
+                    Value::Null => "None".to_string(),
+                    value => wrap_some(default_expr(value, inner, false), optional),
+                };
+            }
+            None => schema,
+        },
+        _ => schema,
+    };
+    if optional {
+        return match value {
+            Value::Null => "None".to_string(),
+            value => format!("Some({})", default_expr(value, schema, false)),
+        };
+    }
+    match (&schema.kind, value) {
+        (SchemaKind::Bool, Value::Bool(b)) => b.to_string(),
+        (
+            SchemaKind::Int8 { .. }
+            | SchemaKind::Int16 { .. }
+            | SchemaKind::Int32 { .. }
+            | SchemaKind::Int64 { .. }
+            | SchemaKind::UInt8 { .. }
+            | SchemaKind::UInt16 { .. }
+            | SchemaKind::UInt32 { .. }
+            | SchemaKind::UInt64 { .. },
+            Value::Int64(n),
+        ) => n.to_string(),
+        (SchemaKind::Float32 { .. } | SchemaKind::Float64 { .. }, Value::Float64(f)) => {
+            format!("{:?}", f)
+        }
+        (SchemaKind::Float32 { .. } | SchemaKind::Float64 { .. }, Value::Int64(n)) => {
+            format!("{:?}", *n as f64)
+        }
+        (SchemaKind::String { .. }, Value::String(s)) => format!("{:?}.to_string()", s),
+        (SchemaKind::Array { items, .. }, Value::Array(values)) => {
+            let values: Vec<_> = values
+                .iter()
+                .map(|v| default_expr(v, items, false))
+                .collect();
+            format!("vec![{}]", values.join(", "))
+        }
+        _ => format!(
+            "serde_json::from_str({:?}).expect(\"valid default\")",
+            value.to_json().to_string()
+        ),
+    }
+}
+
+fn wrap_some(expr: String, optional: bool) -> String {
+    if optional {
+        format!("Some(Some({}))", expr)
+    } else {
+        format!("Some({})", expr)
+    }
+}
+
+/// Names of the types `schema` refers to; with `by_value`, only those it
+/// contains by value, i.e. not behind a `Vec`, map or function.
+pub(super) fn collect_refs(
+    schema: &Schema,
+    refs: &HashMap<String, String>,
+    by_value: bool,
+    names: &mut HashSet<String>,
+) {
+    match &schema.kind {
+        SchemaKind::Ref { reference } => {
+            names.insert(ref_name(reference, refs));
+        }
+        SchemaKind::Object {
             properties,
-            type_refs: HashMap::new(),
+            additional_properties,
+            ..
+        } => {
+            for property in properties.values() {
+                collect_refs(property, refs, by_value, names);
+            }
+            if let Some(values) = additional_properties.as_ref().filter(|_| !by_value) {
+                collect_refs(values, refs, by_value, names);
+            }
+        }
+        SchemaKind::Union { any_of: schemas }
+        | SchemaKind::Intersect { all_of: schemas }
+        | SchemaKind::Tuple { items: schemas } => {
+            for schema in schemas {
+                collect_refs(schema, refs, by_value, names);
+            }
+        }
+        SchemaKind::Named { schema, .. } | SchemaKind::Recursive { schema } => {
+            collect_refs(schema, refs, by_value, names)
+        }
+        SchemaKind::Array { items, .. } if !by_value => collect_refs(items, refs, by_value, names),
+        SchemaKind::Function {
+            parameters,
+            returns,
+        } if !by_value => {
+            for schema in parameters.iter().chain([returns.as_ref()]) {
+                collect_refs(schema, refs, by_value, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `to` is reachable from `from` along by-value containment.
+pub(super) fn reaches(direct: &HashMap<&str, HashSet<String>>, from: &str, to: &str) -> bool {
+    let mut stack = vec![from];
+    let mut seen = HashSet::new();
+    while let Some(name) = stack.pop() {
+        if name == to {
+            return true;
+        }
+        if seen.insert(name) {
+            stack.extend(direct.get(name).into_iter().flatten().map(String::as_str));
         }
     }
+    false
 }
 
-fn schema_to_rust_type(schema: &Schema, refs: &HashMap<String, String>) -> String {
+/// Rust type for `schema`. References to the types in `boxed` are boxed
+/// unless already behind a `Vec` or map.
+fn schema_to_rust_type(
+    schema: &Schema,
+    refs: &HashMap<String, String>,
+    boxed: &HashSet<String>,
+) -> String {
+    let unboxed = &HashSet::new();
     match &schema.kind {
         SchemaKind::Null => "()".to_string(),
         SchemaKind::Bool => "bool".to_string(),
@@ -146,51 +1075,56 @@ fn schema_to_rust_type(schema: &Schema, refs: &HashMap<String, String>) -> Strin
         SchemaKind::Bytes { .. } => "Vec<u8>".to_string(),
 
         SchemaKind::Array { items, .. } => {
-            format!("Vec<{}>", schema_to_rust_type(items, refs))
+            format!("Vec<{}>", schema_to_rust_type(items, refs, unboxed))
         }
 
         SchemaKind::Tuple { items } => {
-            let types: Vec<_> = items.iter().map(|s| schema_to_rust_type(s, refs)).collect();
+            let types: Vec<_> = items
+                .iter()
+                .map(|s| schema_to_rust_type(s, refs, boxed))
+                .collect();
             format!("({})", types.join(", "))
         }
 
+        SchemaKind::Object {
+            properties,
+            additional_properties: Some(values),
+            ..
+        } if properties.is_empty() => format!(
+            "std::collections::HashMap<String, {}>",
+            schema_to_rust_type(values, refs, unboxed)
+        ),
         SchemaKind::Object { .. } => "serde_json::Value".to_string(),
 
+        // Other unions, literals and intersections are hoisted and rendered
+        // as definitions; these are fallbacks for when they are not.
         SchemaKind::Union { any_of } => {
-            if any_of.len() == 2 {
-                let is_optional = any_of.iter().any(|s| matches!(&s.kind, SchemaKind::Null));
-                if is_optional {
-                    let non_null: Vec<_> = any_of
-                        .iter()
-                        .filter(|s| !matches!(&s.kind, SchemaKind::Null))
-                        .collect();
-                    if non_null.len() == 1 {
-                        return format!("Option<{}>", schema_to_rust_type(non_null[0], refs));
-                    }
-                }
+            if let Some(inner) = optional_inner(any_of) {
+                return format!("Option<{}>", schema_to_rust_type(inner, refs, boxed));
             }
-            let types: Vec<_> = any_of
-                .iter()
-                .map(|s| schema_to_rust_type(s, refs))
-                .collect();
-            format!("({})", types.join(" | "))
+            if string_literals(any_of).is_some() {
+                return "String".to_string();
+            }
+            "serde_json::Value".to_string()
         }
 
         SchemaKind::Literal { value } => match value {
-            LiteralValue::String(s) => format!("&'static str /* \"{}\" */", s),
-            LiteralValue::Number(n) => format!("{}", n),
-            LiteralValue::Float(f) => format!("{}", f),
-            LiteralValue::Boolean(b) => format!("{}", b),
+            LiteralValue::String(_) => "String".to_string(),
+            LiteralValue::Number(_) => "i64".to_string(),
+            LiteralValue::Float(_) => "f64".to_string(),
+            LiteralValue::Boolean(_) => "bool".to_string(),
             LiteralValue::Null => "()".to_string(),
         },
 
         SchemaKind::Enum { .. } => "String".to_string(),
 
         SchemaKind::Ref { reference } => {
-            let name = reference
-                .strip_prefix("#/definitions/")
-                .unwrap_or(reference);
-            refs.get(name).cloned().unwrap_or_else(|| name.to_string())
+            let name = ref_name(reference, refs);
+            if boxed.contains(&name) {
+                format!("Box<{}>", name)
+            } else {
+                name
+            }
         }
 
         SchemaKind::Named { name, .. } => name.clone(),
@@ -201,9 +1135,9 @@ fn schema_to_rust_type(schema: &Schema, refs: &HashMap<String, String>) -> Strin
         } => {
             let params: Vec<_> = parameters
                 .iter()
-                .map(|s| schema_to_rust_type(s, refs))
+                .map(|s| schema_to_rust_type(s, refs, unboxed))
                 .collect();
-            let ret = schema_to_rust_type(returns, refs);
+            let ret = schema_to_rust_type(returns, refs, unboxed);
             format!("fn({}) -> {}", params.join(", "), ret)
         }
 
@@ -212,24 +1146,69 @@ fn schema_to_rust_type(schema: &Schema, refs: &HashMap<String, String>) -> Strin
         SchemaKind::Any => "serde_json::Value".to_string(),
         SchemaKind::Unknown => "serde_json::Value".to_string(),
         SchemaKind::Undefined => "()".to_string(),
-        SchemaKind::Recursive { schema } => schema_to_rust_type(schema, refs),
-        SchemaKind::Intersect { all_of } => {
-            let types: Vec<_> = all_of
-                .iter()
-                .map(|s| schema_to_rust_type(s, refs))
-                .collect();
-            types.join(" + ")
+        SchemaKind::Recursive { schema } => schema_to_rust_type(schema, refs, boxed),
+        SchemaKind::Intersect { .. } => "serde_json::Value".to_string(),
+    }
+}
+
+/// Converts a JSON property name such as `isPrimaryKey` or `content-type`
+/// to a snake_case field name.
+pub(super) fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            if c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit())
+            {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+        previous = Some(c);
+    }
+    let out = out.trim_end_matches('_').to_string();
+    if out.is_empty() {
+        "field".to_string()
+    } else if out.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", out)
+    } else {
+        out
+    }
+}
+
+/// The camelCase form serde's `rename_all = "camelCase"` gives a field.
+fn camel_case(field: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in field.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
         }
     }
+    out
+}
+
+/// An identifier without its `r#` prefix, i.e. the name serde sees.
+fn unraw(ident: &str) -> &str {
+    ident.strip_prefix("r#").unwrap_or(ident)
 }
 
 fn format_ident(name: &str) -> String {
     match name {
-        "type" => "r#type".to_string(),
-        "match" => "r#match".to_string(),
-        "fn" => "r#fn".to_string(),
-        "mod" => "r#mod".to_string(),
-        "use" => "r#use".to_string(),
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        "as" | "break" | "const" | "continue" | "else" | "enum" | "extern" | "false" | "fn"
+        | "for" | "if" | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut"
+        | "pub" | "ref" | "return" | "static" | "struct" | "trait" | "true" | "type" | "unsafe"
+        | "use" | "where" | "while" | "async" | "await" | "dyn" | "abstract" | "become" | "box"
+        | "do" | "final" | "macro" | "override" | "priv" | "typeof" | "unsized" | "virtual"
+        | "yield" | "try" | "gen" => format!("r#{}", name),
         s => s.to_string(),
     }
 }
@@ -242,29 +1221,77 @@ use serde::{Deserialize, Serialize};
 {{/each}}
 "#;
 
-const STRUCT_TEMPLATE: &str = r#"#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct {{name}} {
+const STRUCT_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}{{#each attributes}}{{{this}}}
+{{/each}}pub struct {{name}} {
 {{#each properties}}
-    {{#if description}}/// {{description}}
-    {{/if}}{{#if optional}}#[serde(skip_serializing_if = "Option::is_none")]
-    {{/if}}pub {{rust_name}}: {{#if optional}}Option<{{/if}}{{rust_type}}{{#if optional}}>{{/if}},
+{{#each doc}}{{{this}}}
+{{/each}}{{#each attributes}}    {{{this}}}
+{{/each}}    pub {{rust_name}}: {{{rust_type}}},
 {{/each}}
 }
-"#;
+{{#each default_fns}}
+
+{{{this}}}{{/each}}"#;
 
-const ENUM_TEMPLATE: &str = r#"#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum {{name}} {
-{{#each values}}
-    #[serde(rename = "{{this}}")]
-    {{this}},
+const ENUM_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}{{#each attributes}}{{{this}}}
+{{/each}}pub enum {{name}} {
+{{#each variants}}
+{{#each doc}}{{{this}}}
+{{/each}}{{#each attributes}}    {{{this}}}
+{{/each}}    {{name}}{{#if payload}}({{{payload}}}){{/if}}{{#if fields}} {
+{{#each fields}}
+{{#each doc}}    {{{this}}}
+{{/each}}{{#each attributes}}        {{{this}}}
+{{/each}}        {{rust_name}}: {{{rust_type}}},
+{{/each}}
+    }{{/if}},
 {{/each}}
 }
+{{#each default_fns}}
+
+{{{this}}}{{/each}}"#;
+
+const ALIAS_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}{{#each attributes}}{{{this}}}
+{{/each}}pub type {{name}} = {{{rust_type}}};
+"#;
+
+const LITERAL_TEMPLATE: &str = r#"{{#each doc}}{{{this}}}
+{{/each}}{{#each attributes}}{{{this}}}
+{{/each}}pub struct {{name}};
+
+impl {{name}} {
+    pub const VALUE: {{{value_type}}} = {{{value}}};
+}
+
+impl Serialize for {{name}} {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Self::VALUE.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for {{name}} {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = {{{owned_type}}}::deserialize(deserializer)?;
+        if value == Self::VALUE {
+            Ok(Self)
+        } else {
+            Err(serde::de::Error::custom(format!(
+                "expected {:?}, found {:?}",
+                Self::VALUE, value
+            )))
+        }
+    }
+}
 "#;
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::builder::SchemaBuilder;
+    use crate::value::Value;
 
     #[test]
     fn test_generate_struct() {
@@ -289,9 +1316,118 @@ mod tests {
 
         let output = gen.generate("Color", &schema).unwrap();
         assert!(output.contains("pub enum Color"));
-        assert!(output.contains("Red"));
-        assert!(output.contains("Green"));
-        assert!(output.contains("Blue"));
+        assert!(output.contains("    Red,\n    Green,\n    Blue,"));
+        assert!(!output.contains("rename"));
+
+        let status = SchemaBuilder::enum_values(vec!["in-progress", "2xx", "done", "Done"]);
+        let output = gen.generate("Status", &status).unwrap();
+        assert!(output.contains("    #[serde(rename = \"in-progress\")]\n    InProgress,"));
+        assert!(output.contains("    #[serde(rename = \"2xx\")]\n    _2xx,"));
+        assert!(output.contains("    #[serde(rename = \"done\")]\n    Done,"));
+        assert!(output.contains("    #[serde(rename = \"Done\")]\n    Done2,"));
+    }
+
+    fn literal(value: &str) -> Schema {
+        Schema::new(SchemaKind::Literal {
+            value: LiteralValue::String(value.to_string()),
+        })
+    }
+
+    #[test]
+    fn test_generate_tagged_unions() {
+        let gen = RustGenerator::new();
+        let shape = SchemaBuilder::union(vec![
+            SchemaBuilder::object()
+                .field("kind", literal("circle"))
+                .field("radius", SchemaBuilder::float64())
+                .build(),
+            SchemaBuilder::object()
+                .field("kind", literal("point"))
+                .build(),
+        ]);
+        let output = gen.generate("Shape", &shape).unwrap();
+        assert!(output.starts_with(
+            "#[derive(Debug, Clone, Serialize, Deserialize)]\n#[serde(tag = \"kind\")]\npub enum Shape {\n"
+        ));
+        assert!(output.contains(
+            "    #[serde(rename = \"circle\")]\n    Circle {\n        radius: f64,\n    },\n"
+        ));
+        assert!(output.contains("    #[serde(rename = \"point\")]\n    Point,\n"));
+        // The variant objects and their tags are not generated on their own.
+        assert_eq!(output.matches("pub ").count(), 1);
+
+        let event = SchemaBuilder::union(vec![
+            SchemaBuilder::object()
+                .field("type", literal("moved"))
+                .field("data", SchemaBuilder::r#ref("Event"))
+                .build(),
+            SchemaBuilder::object()
+                .field("type", literal("closed"))
+                .field("data", SchemaBuilder::int64())
+                .build(),
+        ]);
+        let output = gen.generate("Event", &event).unwrap();
+        assert!(output.contains("#[serde(tag = \"type\", content = \"data\")]\npub enum Event {"));
+        assert!(output.contains("    Moved(Box<Event>),\n"));
+        assert!(output.contains("    Closed(i64),\n"));
+    }
+
+    #[test]
+    fn test_generate_untagged_union_and_literals() {
+        let gen = RustGenerator::new();
+        let id = SchemaBuilder::union(vec![
+            SchemaBuilder::string().build(),
+            SchemaBuilder::int64(),
+            literal("auto"),
+            SchemaBuilder::array(SchemaBuilder::int64()).build(),
+        ]);
+        let output = gen.generate("Id", &id).unwrap();
+        assert!(output.contains(
+            "#[serde(untagged)]\npub enum Id {\n    Auto(IdVariant2),\n    String(String),\n    I64(i64),\n    I64List(Vec<i64>),\n}"
+        ));
+        assert!(output.contains("pub struct IdVariant2;"));
+        assert!(output.contains("    pub const VALUE: &'static str = \"auto\";"));
+
+        let versioned = SchemaBuilder::object()
+            .field("version", literal("v1"))
+            .build();
+        let output = gen.generate("Doc", &versioned).unwrap();
+        assert!(output
+            .contains("#[derive(Debug, Clone, Copy, Default, PartialEq)]\npub struct DocVersion;"));
+        assert!(output.contains("    pub version: DocVersion,"));
+        assert!(!output.contains("&'static str /*"));
+
+        let mode = SchemaBuilder::union(vec![literal("fast"), literal("slow")]);
+        let output = gen.generate("Mode", &mode).unwrap();
+        assert!(output.contains("    #[serde(rename = \"fast\")]\n    Fast,"));
+    }
+
+    #[test]
+    fn test_generate_intersect() {
+        let gen = RustGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Audited",
+            SchemaBuilder::object()
+                .field("createdAt", SchemaBuilder::string().build())
+                .build(),
+        );
+        registry.register(
+            "Post",
+            Schema::new(SchemaKind::Intersect {
+                all_of: vec![
+                    SchemaBuilder::r#ref("Audited"),
+                    SchemaBuilder::object()
+                        .field("title", SchemaBuilder::string().build())
+                        .build(),
+                ],
+            }),
+        );
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains(
+            "pub struct Post {\n    #[serde(flatten)]\n    pub audited: Audited,\n    #[serde(flatten)]\n    pub part1: PostPart1,\n}"
+        ));
+        assert!(!output.contains(" + "));
     }
 
     #[test]
@@ -312,6 +1448,230 @@ mod tests {
         assert!(output.contains("pub struct Person"));
     }
 
+    #[test]
+    fn test_generate_module_order_is_deterministic() {
+        let gen = RustGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        for name in ["Zeta", "Alpha", "Mid"] {
+            registry.register(
+                name,
+                SchemaBuilder::object()
+                    .field("id", SchemaBuilder::int64())
+                    .build(),
+            );
+        }
+        registry.register(
+            "Order",
+            SchemaBuilder::object()
+                .field("zeta", SchemaBuilder::r#ref("Zeta"))
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        let position = |name: &str| output.find(&format!("pub struct {} ", name)).unwrap();
+        assert!(position("Alpha") < position("Mid"));
+        assert!(position("Mid") < position("Zeta"));
+        assert!(position("Zeta") < position("Order"));
+        assert_eq!(output, gen.generate_module(&registry.clone()).unwrap());
+    }
+
+    #[test]
+    fn test_generate_hoists_nested_types() {
+        let gen = RustGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "connectivity",
+                SchemaBuilder::array(
+                    SchemaBuilder::object()
+                        .field("src", SchemaBuilder::string().build())
+                        .build(),
+                )
+                .build(),
+            )
+            .field(
+                "labels",
+                SchemaBuilder::object()
+                    .additional_properties(Some(SchemaBuilder::string().build()))
+                    .build(),
+            )
+            .build();
+
+        let output = gen.generate("TableInfo", &schema).unwrap();
+        assert!(output.contains("pub struct TableInfoConnectivityItem {\n    pub src: String,"));
+        assert!(output.contains("pub connectivity: Vec<TableInfoConnectivityItem>,"));
+        assert!(output.contains("pub labels: std::collections::HashMap<String, String>,"));
+        assert!(output.find("TableInfoConnectivityItem {") < output.find("TableInfo {"));
+        assert!(!output.contains("serde_json::Value"));
+    }
+
+    #[test]
+    fn test_generate_hoists_inline_enums() {
+        let gen = RustGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "status",
+                SchemaBuilder::enum_values(vec!["active", "in-progress"]),
+            )
+            .build();
+
+        let output = gen.generate("Task", &schema).unwrap();
+        assert!(output.contains(
+            "pub enum TaskStatus {\n    #[serde(rename = \"active\")]\n    Active,\n    #[serde(rename = \"in-progress\")]\n    InProgress,\n}"
+        ));
+        assert!(output.contains("    pub status: TaskStatus,"));
+    }
+
+    #[test]
+    fn test_generate_boxes_recursive_fields() {
+        let gen = RustGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Node",
+            SchemaBuilder::object()
+                .optional_field("next", SchemaBuilder::r#ref("Node"))
+                .field(
+                    "children",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Node")).build(),
+                )
+                .field("owner", SchemaBuilder::r#ref("Owner"))
+                .build(),
+        );
+        registry.register(
+            "Owner",
+            SchemaBuilder::object()
+                .field(
+                    "home",
+                    SchemaBuilder::optional(SchemaBuilder::r#ref("Node")),
+                )
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains("pub next: Option<Box<Node>>,"));
+        assert!(output.contains("pub children: Vec<Node>,"));
+        assert!(output.contains("pub owner: Box<Owner>,"));
+        assert!(output.contains("pub home: Option<Box<Node>>,"));
+
+        let list = SchemaBuilder::recursive("List", |this| {
+            SchemaBuilder::object()
+                .field("head", SchemaBuilder::int64())
+                .field("tail", SchemaBuilder::optional(this))
+                .build()
+        });
+        let output = gen.generate("List", &list).unwrap();
+        assert!(output.contains("pub tail: Option<Box<List>>,"));
+    }
+
+    #[test]
+    fn test_generate_module_resolves_refs_through_registry() {
+        let gen = RustGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .field(
+                    "address",
+                    Schema::new(SchemaKind::Ref {
+                        reference: "address.json".to_string(),
+                    }),
+                )
+                .build()
+                .with_id("https://example.com/person.json"),
+        );
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .build()
+                .with_id("https://example.com/address.json"),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains("pub address: Address,"));
+
+        // Registry names that are URIs or clash with std become identifiers.
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "https://example.com/schemas/order.json",
+            SchemaBuilder::object()
+                .field("label", SchemaBuilder::r#ref("string"))
+                .build(),
+        );
+        registry.register("string", SchemaBuilder::string().build());
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains(
+            "#[serde(rename = \"https://example.com/schemas/order.json\")]\npub struct Order {"
+        ));
+        assert!(output.contains("pub type String_ = String;"));
+        assert!(output.contains("    pub label: String_,"));
+    }
+
+    #[test]
+    fn test_generate_serde_renames() {
+        let gen = RustGenerator::new();
+        let camel = SchemaBuilder::object()
+            .field("isPrimaryKey", SchemaBuilder::bool())
+            .field("name", SchemaBuilder::string().build())
+            .build();
+        let output = gen.generate("PropertyInfo", &camel).unwrap();
+        assert!(output.contains("#[serde(rename_all = \"camelCase\")]\npub struct PropertyInfo"));
+        assert!(output.contains("    pub is_primary_key: bool,"));
+
+        let mixed = SchemaBuilder::object()
+            .field("content-type", SchemaBuilder::string().build())
+            .field("type", SchemaBuilder::string().build())
+            .field("self", SchemaBuilder::string().build())
+            .build();
+        let output = gen.generate("Header", &mixed).unwrap();
+        assert!(!output.contains("rename_all"));
+        assert!(output
+            .contains("    #[serde(rename = \"content-type\")]\n    pub content_type: String,"));
+        assert!(output.contains("    pub r#type: String,"));
+        assert!(output.contains("    #[serde(rename = \"self\")]\n    pub self_: String,"));
+    }
+
+    #[test]
+    fn test_generate_docs_deprecated_and_defaults() {
+        let gen = RustGenerator::new().with_derives(["PartialEq", "Eq", "Hash"]);
+        let schema = SchemaBuilder::object()
+            .field(
+                "retries",
+                SchemaBuilder::int32()
+                    .with_description("How often to retry.")
+                    .with_default(Value::int64(3)),
+            )
+            .optional_field(
+                "mode",
+                SchemaBuilder::string()
+                    .build()
+                    .with_default(Value::string("fast"))
+                    .with_deprecated(true),
+            )
+            .build()
+            .with_description("Client settings.")
+            .with_examples(vec![Value::object()
+                .field("retries", Value::int64(1))
+                .build()]);
+
+        let output = gen.generate("Settings", &schema).unwrap();
+        assert!(output.starts_with(
+            "/// Client settings.\n///\n/// # Examples\n///\n/// ```json\n/// {\n///   \"retries\": 1\n/// }\n/// ```\n"
+        ));
+        assert!(output.contains(
+            "#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]\npub struct Settings"
+        ));
+        assert!(output.contains(
+            "    /// How often to retry.\n    #[serde(default = \"default_settings_retries\")]\n    pub retries: i32,"
+        ));
+        assert!(output.contains("fn default_settings_retries() -> i32 {\n    3\n}"));
+        assert!(output.contains(
+            "    #[serde(default = \"default_settings_mode\", skip_serializing_if = \"Option::is_none\")]\n    #[deprecated]\n    pub mode: Option<String>,"
+        ));
+        assert!(output.contains(
+            "fn default_settings_mode() -> Option<String> {\n    Some(\"fast\".to_string())\n}"
+        ));
+    }
+
     #[test]
     fn test_generate_function_type() {
         let gen = RustGenerator::new();
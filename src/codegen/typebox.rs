@@ -0,0 +1,782 @@
+//! TypeBox source generation.
+//!
+//! Emits a `const` built with TypeBox's `Type` builders and a matching
+//! `Static` type for each definition, so a TypeScript frontend validates
+//! with the same constraints as the Rust side:
+//!
+//! ```text
+//! export const Person = Type.Object({
+//!   id: Type.Integer({ minimum: 0 }),
+//!   email: Type.Optional(Type.String({ format: 'email' })),
+//! }, { $id: 'Person', additionalProperties: false });
+//! export type Person = Static<typeof Person>;
+//! ```
+//!
+//! Definitions carry a `$id`, so references to them become `Type.Ref`; a
+//! reference to a definition that is not declared yet (a cycle between
+//! definitions) becomes `Type.Unsafe<T>({ $ref })`, and a reference from a
+//! `Recursive` schema to itself becomes `This`. A registered definition that
+//! refers to itself is emitted as `Type.Recursive` as well.
+//!
+//! `Bytes` become `Type.Uint8Array`, which is what `from_typebox` reads back
+//! as `Bytes`.
+
+use super::hoist::TypeNames;
+use crate::codegen::SchemaRegistry;
+use crate::schema::{LiteralValue, Schema, SchemaKind};
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+const INDENT: &str = "  ";
+
+/// Names the generated code imports, which definitions must not shadow.
+const RESERVED: &[&str] = &["Type", "Static"];
+
+pub struct TypeBoxGenerator {
+    registry: Handlebars<'static>,
+}
+
+impl TypeBoxGenerator {
+    pub fn new() -> Self {
+        let mut registry = Handlebars::new();
+        registry
+            .register_template_string("definition", DEFINITION_TEMPLATE)
+            .unwrap();
+
+        Self { registry }
+    }
+
+    /// Generates the definition `name`, preceded by the definitions of the
+    /// `Named` schemas nested in it.
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
+        let mut emitter = Emitter::new(None);
+        emitter.define(name, schema);
+        Ok(self.render(&emitter)?.join("\n"))
+    }
+
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
+        let mut emitter = Emitter::new(Some(registry));
+
+        // Dependencies first, so most references can use `Type.Ref`.
+        for name in registry.dependency_graph().topological_order() {
+            if let Some(schema) = registry.get(name) {
+                emitter.define(name, schema);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str("// Auto-generated by typebox-rs. DO NOT EDIT.\n\n");
+        output.push_str("import { Static, Type } from '@sinclair/typebox';\n\n");
+
+        for code in self.render(&emitter)? {
+            output.push_str(&code);
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
+
+    fn render(&self, emitter: &Emitter) -> Result<Vec<String>, crate::Error> {
+        emitter
+            .output
+            .iter()
+            .map(|(name, expr)| {
+                let ctx = DefinitionContext {
+                    name: name.clone(),
+                    expr: expr.clone(),
+                };
+                Ok(self.registry.render("definition", &ctx)?)
+            })
+            .collect()
+    }
+}
+
+impl Default for TypeBoxGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize)]
+struct DefinitionContext {
+    name: String,
+    expr: String,
+}
+
+/// Builds the `Type` expressions of one module.
+struct Emitter<'r> {
+    registry: Option<&'r SchemaRegistry>,
+    /// Base URI of the definition being emitted, for resolving `$ref`s.
+    base: Option<String>,
+    /// `$id` of each declared definition.
+    declared: HashMap<String, String>,
+    /// Enclosing `Recursive` schemas: their `$id` and the parameter that
+    /// stands for them.
+    this: Vec<(String, String)>,
+    /// References being inlined, to stop at cycles.
+    inlining: HashSet<String>,
+    /// Registered definitions that refer to themselves.
+    self_referential: HashSet<String>,
+    /// Definitions in declaration order: name and expression.
+    output: Vec<(String, String)>,
+    /// The identifier of each definition.
+    names: TypeNames,
+}
+
+impl<'r> Emitter<'r> {
+    fn new(registry: Option<&'r SchemaRegistry>) -> Self {
+        Self {
+            registry,
+            base: None,
+            declared: HashMap::new(),
+            this: Vec::new(),
+            inlining: HashSet::new(),
+            self_referential: registry
+                .map(|r| {
+                    r.dependency_graph()
+                        .edges()
+                        .filter(|(from, to)| from == to)
+                        .map(|(name, _)| name.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            output: Vec::new(),
+            names: TypeNames::new(registry, RESERVED),
+        }
+    }
+
+    /// Declares the definition `name`, after any `Named` schemas nested in
+    /// it. The name need not be an identifier; it is kept as the `$id`.
+    fn define(&mut self, name: &str, schema: &Schema) {
+        let schema = match &schema.kind {
+            SchemaKind::Named { schema: inner, .. } => inner,
+            _ => schema,
+        };
+        let id = schema.id.clone().unwrap_or_else(|| name.to_string());
+        let saved = self.base.take();
+        self.base = self.registry.and_then(|r| r.document_base(name));
+        let recursive = self.self_referential.contains(name)
+            && !matches!(schema.kind, SchemaKind::Recursive { .. });
+        let expr = if recursive {
+            // Its references to itself become the `This` of a `Recursive`.
+            let wrapped = Schema {
+                kind: SchemaKind::Recursive {
+                    schema: Box::new(Schema::new(schema.kind.clone())),
+                },
+                ..schema.clone()
+            };
+            self.expr(&wrapped, Some(&id), 0)
+        } else {
+            self.expr(schema, Some(&id), 0)
+        };
+        self.base = saved;
+        self.declared.insert(name.to_string(), id);
+        let ident = self.names.get(name);
+        self.output.push((ident, expr));
+    }
+
+    /// The `Type` expression for `schema` at nesting level `depth`, with
+    /// `$id` set to `id`.
+    fn expr(&mut self, schema: &Schema, id: Option<&str>, depth: usize) -> String {
+        let id = id.or(schema.id.as_deref());
+        let mut options = Options::default();
+        if let Some(id) = id {
+            options.push("$id", js_string(id));
+        }
+
+        let (function, args) = match &schema.kind {
+            SchemaKind::Null
+            | SchemaKind::Literal {
+                value: LiteralValue::Null,
+            } => ("Type.Null", Vec::new()),
+            SchemaKind::Bool => ("Type.Boolean", Vec::new()),
+            SchemaKind::Int8 { minimum, maximum } => integer(
+                &mut options,
+                minimum.unwrap_or(i8::MIN),
+                maximum.unwrap_or(i8::MAX),
+            ),
+            SchemaKind::Int16 { minimum, maximum } => integer(
+                &mut options,
+                minimum.unwrap_or(i16::MIN),
+                maximum.unwrap_or(i16::MAX),
+            ),
+            SchemaKind::Int32 { minimum, maximum } => integer(
+                &mut options,
+                minimum.unwrap_or(i32::MIN),
+                maximum.unwrap_or(i32::MAX),
+            ),
+            SchemaKind::UInt8 { minimum, maximum } => integer(
+                &mut options,
+                minimum.unwrap_or(0),
+                maximum.unwrap_or(u8::MAX),
+            ),
+            SchemaKind::UInt16 { minimum, maximum } => integer(
+                &mut options,
+                minimum.unwrap_or(0),
+                maximum.unwrap_or(u16::MAX),
+            ),
+            SchemaKind::UInt32 { minimum, maximum } => integer(
+                &mut options,
+                minimum.unwrap_or(0),
+                maximum.unwrap_or(u32::MAX),
+            ),
+            // 64-bit bounds are beyond what a JavaScript number holds exactly.
+            SchemaKind::Int64 { minimum, maximum } => {
+                options.push_opt("minimum", *minimum);
+                options.push_opt("maximum", *maximum);
+                ("Type.Integer", Vec::new())
+            }
+            SchemaKind::UInt64 { minimum, maximum } => {
+                options.push("minimum", minimum.unwrap_or(0).to_string());
+                options.push_opt("maximum", *maximum);
+                ("Type.Integer", Vec::new())
+            }
+            SchemaKind::Float32 { minimum, maximum } => {
+                options.push_opt("minimum", minimum.map(f64::from).map(js_number));
+                options.push_opt("maximum", maximum.map(f64::from).map(js_number));
+                ("Type.Number", Vec::new())
+            }
+            SchemaKind::Float64 { minimum, maximum } => {
+                options.push_opt("minimum", minimum.map(js_number));
+                options.push_opt("maximum", maximum.map(js_number));
+                ("Type.Number", Vec::new())
+            }
+            SchemaKind::String {
+                format,
+                pattern,
+                min_length,
+                max_length,
+            } => {
+                options.push_opt("format", format.as_ref().map(|f| js_string(f.as_str())));
+                options.push_opt("pattern", pattern.as_deref().map(js_string));
+                options.push_opt("minLength", *min_length);
+                options.push_opt("maxLength", *max_length);
+                ("Type.String", Vec::new())
+            }
+            SchemaKind::Bytes {
+                min_length,
+                max_length,
+            } => {
+                options.push_opt("minByteLength", *min_length);
+                options.push_opt("maxByteLength", *max_length);
+                ("Type.Uint8Array", Vec::new())
+            }
+
+            SchemaKind::Array {
+                items,
+                min_items,
+                max_items,
+                unique_items,
+            } => {
+                options.push_opt("minItems", *min_items);
+                options.push_opt("maxItems", *max_items);
+                options.push_opt("uniqueItems", *unique_items);
+                ("Type.Array", vec![self.expr(items, None, depth)])
+            }
+
+            SchemaKind::Tuple { items } => ("Type.Tuple", vec![self.list(items, depth)]),
+
+            SchemaKind::Object {
+                properties,
+                additional_properties: Some(values),
+                ..
+            } if properties.is_empty() => (
+                "Type.Record",
+                vec!["Type.String()".to_string(), self.expr(values, None, depth)],
+            ),
+            SchemaKind::Object {
+                properties,
+                required,
+                additional_properties,
+            } => {
+                let pad = INDENT.repeat(depth + 1);
+                let mut fields = String::new();
+                for (prop, prop_schema) in properties {
+                    let mut value = self.expr(prop_schema, None, depth + 1);
+                    if !required.contains(prop) {
+                        value = format!("Type.Optional({})", value);
+                    }
+                    fields.push_str(&format!("{}{}: {},\n", pad, js_key(prop), value));
+                }
+                let fields = if fields.is_empty() {
+                    "{}".to_string()
+                } else {
+                    format!("{{\n{}{}}}", fields, INDENT.repeat(depth))
+                };
+                // Unknown properties are rejected unless a schema allows them.
+                let additional = match additional_properties {
+                    Some(values) => self.expr(values, None, depth),
+                    None => "false".to_string(),
+                };
+                options.push("additionalProperties", additional);
+                ("Type.Object", vec![fields])
+            }
+
+            SchemaKind::Union { any_of } => ("Type.Union", vec![self.list(any_of, depth)]),
+            SchemaKind::Intersect { all_of } => ("Type.Intersect", vec![self.list(all_of, depth)]),
+            SchemaKind::Literal { value } => ("Type.Literal", vec![js_literal(value)]),
+            SchemaKind::Enum { values } => {
+                let literals: Vec<String> = values
+                    .iter()
+                    .map(|v| format!("Type.Literal({})", js_string(v)))
+                    .collect();
+                ("Type.Union", vec![format!("[{}]", literals.join(", "))])
+            }
+
+            SchemaKind::Ref { reference } => {
+                options.annotate(schema);
+                return self.reference(reference, options, schema, depth);
+            }
+            SchemaKind::Named {
+                name,
+                schema: inner,
+            } => {
+                let registered = self.registry.is_some_and(|r| r.contains(name));
+                if !registered && !self.declared.contains_key(name) {
+                    self.define(name, inner);
+                }
+                options.annotate(schema);
+                let reference = format!("#/definitions/{}", name);
+                return self.reference(&reference, options, schema, depth);
+            }
+
+            SchemaKind::Function {
+                parameters,
+                returns,
+            } => (
+                "Type.Function",
+                vec![
+                    self.list(parameters, depth),
+                    self.expr(returns, None, depth),
+                ],
+            ),
+
+            SchemaKind::Void => ("Type.Void", Vec::new()),
+            SchemaKind::Never => ("Type.Never", Vec::new()),
+            SchemaKind::Any => ("Type.Any", Vec::new()),
+            SchemaKind::Unknown => ("Type.Unknown", Vec::new()),
+            SchemaKind::Undefined => ("Type.Undefined", Vec::new()),
+
+            SchemaKind::Recursive { schema: inner } => {
+                let param = match self.this.len() {
+                    0 => "This".to_string(),
+                    n => format!("This{}", n + 1),
+                };
+                if let Some(id) = id {
+                    self.this.push((id.to_string(), param.clone()));
+                }
+                let body = self.expr(inner, None, depth);
+                self.this.retain(|(_, p)| *p != param);
+                ("Type.Recursive", vec![format!("({}) => {}", param, body)])
+            }
+        };
+        options.annotate(schema);
+        call(function, args, options)
+    }
+
+    /// `[a, b, ...]` of the expressions of `schemas`.
+    fn list(&mut self, schemas: &[Schema], depth: usize) -> String {
+        let exprs: Vec<String> = schemas
+            .iter()
+            .map(|schema| self.expr(schema, None, depth))
+            .collect();
+        format!("[{}]", exprs.join(", "))
+    }
+
+    /// The expression for the `$ref` of `schema`, with the annotations in
+    /// `options`.
+    fn reference(
+        &mut self,
+        reference: &str,
+        mut options: Options,
+        schema: &Schema,
+        depth: usize,
+    ) -> String {
+        let target = self.target(reference);
+        let target_id = target.as_ref().map(|name| {
+            self.registry
+                .and_then(|r| r.get(name))
+                .and_then(|schema| schema.id.clone())
+                .unwrap_or_else(|| name.clone())
+        });
+        // `This` takes no options.
+        if let Some((_, param)) = self
+            .this
+            .iter()
+            .rev()
+            .find(|(id, _)| id == reference || target_id.as_ref() == Some(id))
+        {
+            return param.clone();
+        }
+        if let Some(name) = target {
+            let ident = self.names.get(&name);
+            if self.declared.contains_key(&name) {
+                return call("Type.Ref", vec![ident], options);
+            }
+            let id = target_id.unwrap_or_else(|| name.clone());
+            options
+                .entries
+                .insert(0, ("$ref".to_string(), js_string(&id)));
+            return format!("Type.Unsafe<{}>({})", ident, options.render());
+        }
+
+        // A pointer into a definition: inline its target, annotated as the
+        // reference is.
+        let target = self
+            .registry
+            .and_then(|r| r.resolve_ref(reference, self.base.as_deref()).ok());
+        match target {
+            Some(target) if self.inlining.insert(reference.to_string()) => {
+                let annotated = Schema {
+                    kind: target.kind.clone(),
+                    id: None,
+                    schema_version: None,
+                    title: schema.title.clone().or_else(|| target.title.clone()),
+                    description: schema
+                        .description
+                        .clone()
+                        .or_else(|| target.description.clone()),
+                    default: schema.default.clone().or_else(|| target.default.clone()),
+                    examples: schema.examples.clone().or_else(|| target.examples.clone()),
+                    read_only: schema.read_only.or(target.read_only),
+                    write_only: schema.write_only.or(target.write_only),
+                    deprecated: schema.deprecated.or(target.deprecated),
+                };
+                let expr = self.expr(&annotated, None, depth);
+                self.inlining.remove(reference);
+                expr
+            }
+            _ => {
+                options
+                    .entries
+                    .insert(0, ("$ref".to_string(), js_string(reference)));
+                format!("Type.Unsafe<unknown>({})", options.render())
+            }
+        }
+    }
+
+    /// The definition `reference` points at, if it is one.
+    fn target(&self, reference: &str) -> Option<String> {
+        if let Some(name) = reference.strip_prefix("#/definitions/") {
+            if self.declared.contains_key(name) || self.registry.is_some_and(|r| r.contains(name)) {
+                return Some(name.to_string());
+            }
+        }
+        if let Some((name, _)) = self.declared.iter().find(|(_, id)| *id == reference) {
+            return Some(name.clone());
+        }
+        let registry = self.registry?;
+        let target = registry.resolve_ref(reference, self.base.as_deref()).ok()?;
+        registry
+            .schemas()
+            .find(|(_, schema)| std::ptr::eq(*schema, target))
+            .map(|(name, _)| name.clone())
+    }
+}
+
+/// The options object of a `Type` call: constraints, then annotations.
+#[derive(Default)]
+struct Options {
+    entries: Vec<(String, String)>,
+}
+
+impl Options {
+    fn push(&mut self, key: &str, value: String) {
+        self.entries.push((key.to_string(), value));
+    }
+
+    fn push_opt<T: ToString>(&mut self, key: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.push(key, value.to_string());
+        }
+    }
+
+    /// Adds the title, description, default, examples and flags of
+    /// `schema`.
+    fn annotate(&mut self, schema: &Schema) {
+        self.push_opt("title", schema.title.as_deref().map(js_string));
+        self.push_opt("description", schema.description.as_deref().map(js_string));
+        self.push_opt(
+            "default",
+            schema.default.as_ref().map(|v| js_value(&v.to_json())),
+        );
+        if let Some(examples) = &schema.examples {
+            let json = serde_json::Value::Array(examples.iter().map(|v| v.to_json()).collect());
+            self.push("examples", js_value(&json));
+        }
+        self.push_opt("readOnly", schema.read_only);
+        self.push_opt("writeOnly", schema.write_only);
+        self.push_opt("deprecated", schema.deprecated);
+    }
+
+    fn render(&self) -> String {
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(key, value)| format!("{}: {}", js_key(key), value))
+            .collect();
+        format!("{{ {} }}", entries.join(", "))
+    }
+}
+
+/// `Type.Integer` bounded to `minimum..=maximum`.
+fn integer<T: ToString>(
+    options: &mut Options,
+    minimum: T,
+    maximum: T,
+) -> (&'static str, Vec<String>) {
+    options.push("minimum", minimum.to_string());
+    options.push("maximum", maximum.to_string());
+    ("Type.Integer", Vec::new())
+}
+
+/// `function(args..., options)`.
+fn call(function: &str, mut args: Vec<String>, options: Options) -> String {
+    if !options.entries.is_empty() {
+        args.push(options.render());
+    }
+    format!("{}({})", function, args.join(", "))
+}
+
+pub(super) fn js_string(s: &str) -> String {
+    let mut out = String::from("'");
+    for c in s.chars() {
+        match c {
+            '\'' => out.push_str("\\'"),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+pub(super) fn js_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+pub(super) fn js_literal(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::String(s) => js_string(s),
+        LiteralValue::Number(n) => n.to_string(),
+        LiteralValue::Float(f) => js_number(*f),
+        LiteralValue::Boolean(b) => b.to_string(),
+        LiteralValue::Null => "null".to_string(),
+    }
+}
+
+/// A JSON value as a JavaScript literal.
+pub(super) fn js_value(json: &serde_json::Value) -> String {
+    match json {
+        serde_json::Value::String(s) => js_string(s),
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(js_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        serde_json::Value::Object(map) if map.is_empty() => "{}".to_string(),
+        serde_json::Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{}: {}", js_key(key), js_value(value)))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+        scalar => scalar.to_string(),
+    }
+}
+
+/// An object key, quoted unless it is an identifier.
+pub(super) fn js_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        key.to_string()
+    } else {
+        js_string(key)
+    }
+}
+
+const DEFINITION_TEMPLATE: &str = r#"export const {{name}} = {{{expr}}};
+export type {{name}} = Static<typeof {{name}}>;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::schema::StringFormat;
+    use crate::value::Value;
+
+    #[test]
+    fn test_generate_object() {
+        let gen = TypeBoxGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "id",
+                Schema::new(SchemaKind::Int64 {
+                    minimum: Some(0),
+                    maximum: None,
+                }),
+            )
+            .optional_field(
+                "email",
+                SchemaBuilder::string().format(StringFormat::Email).build(),
+            )
+            .field(
+                "content-type",
+                SchemaBuilder::string().max_length(64).build(),
+            )
+            .build()
+            .with_description("A person.");
+
+        let output = gen.generate("Person", &schema).unwrap();
+        assert_eq!(
+            output,
+            "export const Person = Type.Object({\n  id: Type.Integer({ minimum: 0 }),\n  email: Type.Optional(Type.String({ format: 'email' })),\n  'content-type': Type.String({ maxLength: 64 }),\n}, { $id: 'Person', additionalProperties: false, description: 'A person.' });\nexport type Person = Static<typeof Person>;\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_kinds() {
+        let gen = TypeBoxGenerator::new();
+        let expr = |schema: Schema| {
+            let mut emitter = Emitter::new(None);
+            emitter.expr(&schema, None, 0)
+        };
+
+        assert_eq!(
+            expr(SchemaBuilder::uint8()),
+            "Type.Integer({ minimum: 0, maximum: 255 })"
+        );
+        assert_eq!(
+            expr(
+                SchemaBuilder::array(SchemaBuilder::bool())
+                    .min_items(1)
+                    .build()
+            ),
+            "Type.Array(Type.Boolean(), { minItems: 1 })"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::optional(SchemaBuilder::string().build())),
+            "Type.Union([Type.String(), Type.Null()])"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::enum_values(vec!["a", "it's"])),
+            "Type.Union([Type.Literal('a'), Type.Literal('it\\'s')])"
+        );
+        assert_eq!(
+            expr(
+                SchemaBuilder::object()
+                    .additional_properties(Some(SchemaBuilder::float64()))
+                    .build()
+            ),
+            "Type.Record(Type.String(), Type.Number())"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::bool().with_default(Value::Bool(true))),
+            "Type.Boolean({ default: true })"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::function(
+                vec![SchemaBuilder::int64()],
+                SchemaBuilder::void()
+            )),
+            "Type.Function([Type.Integer()], Type.Void())"
+        );
+
+        let list = SchemaBuilder::recursive("List", |this| {
+            SchemaBuilder::object()
+                .field("head", SchemaBuilder::int64())
+                .optional_field("tail", this)
+                .build()
+        });
+        let output = gen.generate("List", &list).unwrap();
+        assert!(output.starts_with(
+            "export const List = Type.Recursive((This) => Type.Object({\n  head: Type.Integer(),\n  tail: Type.Optional(This),\n}, { additionalProperties: false }), { $id: 'List' });"
+        ));
+    }
+
+    #[test]
+    fn test_generate_module_references() {
+        let gen = TypeBoxGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .build(),
+        );
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .field("address", SchemaBuilder::r#ref("Address"))
+                .optional_field("friend", SchemaBuilder::r#ref("Pet"))
+                .build(),
+        );
+        registry.register(
+            "Pet",
+            SchemaBuilder::object()
+                .field("owner", SchemaBuilder::r#ref("Person"))
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains("import { Static, Type } from '@sinclair/typebox';"));
+        assert!(output.contains("  address: Type.Ref(Address),\n"));
+        // `Person` and `Pet` refer to each other: one of them comes first.
+        assert!(
+            output.contains("  friend: Type.Optional(Type.Unsafe<Pet>({ $ref: 'Pet' })),\n")
+                || output.contains("  owner: Type.Unsafe<Person>({ $ref: 'Person' }),\n")
+        );
+        assert!(output.contains("export type Pet = Static<typeof Pet>;"));
+    }
+
+    #[test]
+    fn test_generate_module_self_reference() {
+        let gen = TypeBoxGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Tree",
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int64())
+                .field(
+                    "children",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Tree")).build(),
+                )
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains(
+            "export const Tree = Type.Recursive((This) => Type.Object({\n  value: Type.Integer(),\n  children: Type.Array(This),\n}, { additionalProperties: false }), { $id: 'Tree' });"
+        ));
+        assert!(!output.contains("Type.Unsafe"));
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let bytes = Schema::new(SchemaKind::Bytes {
+            min_length: None,
+            max_length: Some(16),
+        });
+        let expr = Emitter::new(None).expr(&bytes, None, 0);
+        assert_eq!(expr, "Type.Uint8Array({ maxByteLength: 16 })");
+
+        // The JSON `Type.Uint8Array({ maxByteLength: 16 })` serializes to.
+        let json = serde_json::json!({"type": "Uint8Array", "maxByteLength": 16});
+        let imported = crate::import::from_typebox(&json).unwrap();
+        assert_eq!(imported.kind, bytes.kind);
+    }
+}
@@ -1,9 +1,17 @@
+use super::hoist::{optional_inner, ref_name, Hoister};
+use super::typebox::{js_key, js_literal, js_string};
 use crate::codegen::SchemaRegistry;
-use crate::schema::{LiteralValue, Schema, SchemaKind};
-use handlebars::Handlebars;
+use crate::schema::{Schema, SchemaKind};
+use handlebars::{handlebars_helper, Handlebars};
 use serde::Serialize;
 use std::collections::HashMap;
 
+/// Names the generated code uses, which type names must not shadow.
+const RESERVED: &[&str] = &["Array", "Record", "Uint8Array"];
+
+// Text for a `/** */` comment, which `*/` would end early.
+handlebars_helper!(doc: |text: str| text.replace("*/", "*\\/"));
+
 pub struct TypeScriptGenerator {
     registry: Handlebars<'static>,
 }
@@ -23,33 +31,68 @@ impl TypeScriptGenerator {
         registry
             .register_template_string("type", TYPE_TEMPLATE)
             .unwrap();
+        registry.register_helper("doc", Box::new(doc));
 
         Self { registry }
     }
 
     pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
-        let context = SchemaContext::from_schema(name, schema);
+        let mut hoister = Hoister::new(None).with_reserved(RESERVED).with_enums();
+        hoister.add_root(name, schema);
+        Ok(self.render(&hoister)?.join("\n"))
+    }
+
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
+        let mut hoister = Hoister::new(Some(registry))
+            .with_reserved(RESERVED)
+            .with_enums();
+
+        // Dependencies first, in a stable order, so output diffs cleanly.
+        for name in registry.dependency_graph().topological_order() {
+            if let Some(schema) = registry.get(name) {
+                hoister.add_root(name, schema);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str("// Auto-generated by typebox-rs. DO NOT EDIT.\n\n");
+
+        for code in self.render(&hoister)? {
+            output.push_str(&code);
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
 
+    fn render(&self, hoister: &Hoister) -> Result<Vec<String>, crate::Error> {
+        hoister
+            .defs
+            .iter()
+            .map(|def| self.render_def(&def.name, &def.schema, &hoister.refs))
+            .collect()
+    }
+
+    fn render_def(
+        &self,
+        name: &str,
+        schema: &Schema,
+        refs: &HashMap<String, String>,
+    ) -> Result<String, crate::Error> {
         match &schema.kind {
             SchemaKind::Enum { values } => {
                 let ctx = EnumContext {
                     name: name.to_string(),
-                    values: values.clone(),
+                    values: values.iter().map(|value| js_string(value)).collect(),
                 };
                 Ok(self.registry.render("enum", &ctx)?)
             }
-            SchemaKind::Object { .. } => Ok(self.registry.render("interface", &context)?),
-            SchemaKind::Union { .. } => {
-                let ts_type = schema_to_ts_type(schema, &HashMap::new());
-                let ctx = TypeContext {
-                    name: name.to_string(),
-                    ts_type,
-                };
-                Ok(self.registry.render("type", &ctx)?)
+            SchemaKind::Object { properties, .. } if !properties.is_empty() => {
+                let context = SchemaContext::from_schema(name, schema, refs);
+                Ok(self.registry.render("interface", &context)?)
             }
-            SchemaKind::Named { schema, .. } => self.generate(name, schema),
             _ => {
-                let ts_type = schema_to_ts_type(schema, &HashMap::new());
+                let ts_type = schema_to_ts_type(schema, refs);
                 let ctx = TypeContext {
                     name: name.to_string(),
                     ts_type,
@@ -58,24 +101,6 @@ impl TypeScriptGenerator {
             }
         }
     }
-
-    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
-        let mut rendered: Vec<String> = Vec::new();
-
-        for (name, schema) in registry.schemas() {
-            rendered.push(self.generate(name, schema)?);
-        }
-
-        let mut output = String::new();
-        output.push_str("// Auto-generated by typebox-rs. DO NOT EDIT.\n\n");
-
-        for code in rendered {
-            output.push_str(&code);
-            output.push('\n');
-        }
-
-        Ok(output)
-    }
 }
 
 impl Default for TypeScriptGenerator {
@@ -94,7 +119,8 @@ struct SchemaContext {
 
 #[derive(Serialize)]
 struct PropertyContext {
-    name: String,
+    /// The property name, quoted unless it is an identifier.
+    key: String,
     ts_type: String,
     optional: bool,
     description: Option<String>,
@@ -113,7 +139,7 @@ struct TypeContext {
 }
 
 impl SchemaContext {
-    fn from_schema(name: &str, schema: &Schema) -> Self {
+    fn from_schema(name: &str, schema: &Schema, refs: &HashMap<String, String>) -> Self {
         let mut properties = Vec::new();
 
         if let SchemaKind::Object {
@@ -125,10 +151,10 @@ impl SchemaContext {
             for (prop_name, prop_schema) in props {
                 let is_optional = !required.contains(prop_name);
                 properties.push(PropertyContext {
-                    name: prop_name.clone(),
-                    ts_type: schema_to_ts_type(prop_schema, &HashMap::new()),
+                    key: js_key(prop_name),
+                    ts_type: schema_to_ts_type(prop_schema, refs),
                     optional: is_optional,
-                    description: None,
+                    description: prop_schema.description.clone(),
                 });
             }
         }
@@ -137,7 +163,7 @@ impl SchemaContext {
             name: name.to_string(),
             description: schema.description.clone(),
             properties,
-            type_refs: HashMap::new(),
+            type_refs: refs.clone(),
         }
     }
 }
@@ -167,41 +193,28 @@ fn schema_to_ts_type(schema: &Schema, refs: &HashMap<String, String>) -> String
             format!("[{}]", types.join(", "))
         }
 
+        SchemaKind::Object {
+            properties,
+            additional_properties: Some(values),
+            ..
+        } if properties.is_empty() => {
+            format!("Record<string, {}>", schema_to_ts_type(values, refs))
+        }
         SchemaKind::Object { .. } => "Record<string, unknown>".to_string(),
 
         SchemaKind::Union { any_of } => {
-            if any_of.len() == 2 {
-                let is_optional = any_of.iter().any(|s| matches!(&s.kind, SchemaKind::Null));
-                if is_optional {
-                    let non_null: Vec<_> = any_of
-                        .iter()
-                        .filter(|s| !matches!(&s.kind, SchemaKind::Null))
-                        .collect();
-                    if non_null.len() == 1 {
-                        return format!("{} | null", schema_to_ts_type(non_null[0], refs));
-                    }
-                }
+            if let Some(inner) = optional_inner(any_of) {
+                return format!("{} | null", schema_to_ts_type(inner, refs));
             }
             let types: Vec<_> = any_of.iter().map(|s| schema_to_ts_type(s, refs)).collect();
             types.join(" | ")
         }
 
-        SchemaKind::Literal { value } => match value {
-            LiteralValue::String(s) => format!("'{}'", s),
-            LiteralValue::Number(n) => format!("{}", n),
-            LiteralValue::Float(f) => format!("{}", f),
-            LiteralValue::Boolean(b) => format!("{}", b),
-            LiteralValue::Null => "null".to_string(),
-        },
+        SchemaKind::Literal { value } => js_literal(value),
 
         SchemaKind::Enum { .. } => "string".to_string(),
 
-        SchemaKind::Ref { reference } => {
-            let name = reference
-                .strip_prefix("#/definitions/")
-                .unwrap_or(reference);
-            refs.get(name).cloned().unwrap_or_else(|| name.to_string())
-        }
+        SchemaKind::Ref { reference } => ref_name(reference, refs),
 
         SchemaKind::Named { name, .. } => name.clone(),
 
@@ -234,16 +247,16 @@ const MODULE_TEMPLATE: &str = r#"{{preamble}}{{#each schemas}}
 {{{this}}}
 {{/each}}"#;
 
-const INTERFACE_TEMPLATE: &str = r#"{{#if description}}/** {{description}} */
+const INTERFACE_TEMPLATE: &str = r#"{{#if description}}/** {{{doc description}}} */
 {{/if}}export interface {{name}} {
 {{#each properties}}
-  {{#if description}}/** {{description}} */
-  {{/if}}{{name}}{{#if optional}}?{{/if}}: {{ts_type}};
+  {{#if description}}/** {{{doc description}}} */
+  {{/if}}{{{key}}}{{#if optional}}?{{/if}}: {{{ts_type}}};
 {{/each}}
 }
 "#;
 
-const ENUM_TEMPLATE: &str = r#"export type {{name}} = {{#each values}}'{{this}}'{{#unless @last}} | {{/unless}}{{/each}};
+const ENUM_TEMPLATE: &str = r#"export type {{name}} = {{#each values}}{{{this}}}{{#unless @last}} | {{/unless}}{{/each}};
 "#;
 
 const TYPE_TEMPLATE: &str = r#"export type {{name}} = {{{ts_type}}};
@@ -300,6 +313,54 @@ mod tests {
         assert!(output.contains("export interface Person"));
     }
 
+    #[test]
+    fn test_generate_hoists_nested_types() {
+        let gen = TypeScriptGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "connectivity",
+                SchemaBuilder::array(
+                    SchemaBuilder::object()
+                        .field("src", SchemaBuilder::string().build())
+                        .build(),
+                )
+                .build(),
+            )
+            .field(
+                "shape",
+                SchemaBuilder::union(vec![
+                    SchemaBuilder::string().build(),
+                    SchemaBuilder::object()
+                        .field("radius", SchemaBuilder::float64())
+                        .build(),
+                ]),
+            )
+            .build();
+
+        let output = gen.generate("TableInfo", &schema).unwrap();
+        assert!(output.contains("export interface TableInfoConnectivityItem {\n  src: string;"));
+        assert!(output.contains("connectivity: Array<TableInfoConnectivityItem>;"));
+        assert!(output.contains("export interface TableInfoShapeVariant1 {"));
+        assert!(output.contains("export type TableInfoShape = string | TableInfoShapeVariant1;"));
+        assert!(output.contains("shape: TableInfoShape;"));
+        assert!(!output.contains("Record<string, unknown>"));
+    }
+
+    #[test]
+    fn test_generate_hoists_inline_enums() {
+        let gen = TypeScriptGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "status",
+                SchemaBuilder::enum_values(vec!["active", "in-progress"]),
+            )
+            .build();
+
+        let output = gen.generate("Task", &schema).unwrap();
+        assert!(output.contains("export type TaskStatus = 'active' | 'in-progress';"));
+        assert!(output.contains("  status: TaskStatus;"));
+    }
+
     #[test]
     fn test_generate_function_type() {
         let gen = TypeScriptGenerator::new();
@@ -312,4 +373,30 @@ mod tests {
         assert!(output.contains("export type Callback"));
         assert!(output.contains("=> void"));
     }
+
+    #[test]
+    fn test_generate_quotes_keys_and_escapes_docs() {
+        let gen = TypeScriptGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "https://example.com/schemas/order.json",
+            SchemaBuilder::object()
+                .field(
+                    "name-with-dash",
+                    SchemaBuilder::string()
+                        .build()
+                        .with_description("Ends a comment: */ & <b>"),
+                )
+                .field("status", SchemaBuilder::literal("it's"))
+                .build()
+                .with_description("An order"),
+        );
+        registry.register("record", SchemaBuilder::string().build());
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains("export interface Order {"));
+        assert!(output.contains("  /** Ends a comment: *\\/ & <b> */\n  'name-with-dash': string;"));
+        assert!(output.contains("  status: 'it\\'s';"));
+        assert!(output.contains("export type Record_ = string;"));
+    }
 }
@@ -0,0 +1,937 @@
+//! Zod source generation.
+//!
+//! Emits a Zod schema `const` and its `z.infer` type for each definition:
+//!
+//! ```text
+//! export const Person = z.object({
+//!   id: z.number().int().min(0),
+//!   email: z.string().email().optional(),
+//! }).strict().describe('A person.');
+//! export type Person = z.infer<typeof Person>;
+//! ```
+//!
+//! `z.infer` cannot see through a schema that refers to itself, so
+//! `Recursive` definitions and definitions in a reference cycle are written
+//! as an explicit TypeScript type plus a `z.ZodType<T>`-annotated `const`,
+//! with `z.lazy` breaking the cycle:
+//!
+//! ```text
+//! export type Node = { children: Array<Node> };
+//! export const Node: z.ZodType<Node, z.ZodTypeDef, unknown> = z.lazy(() => z.object({
+//!   children: z.array(Node),
+//! }).strict());
+//! ```
+
+use super::hoist::{optional_inner, pascal_case, TypeNames};
+use super::typebox::{js_key, js_literal, js_number, js_string, js_value};
+use crate::codegen::SchemaRegistry;
+use crate::schema::{LiteralValue, Schema, SchemaKind, StringFormat};
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+const INDENT: &str = "  ";
+
+pub struct ZodGenerator {
+    registry: Handlebars<'static>,
+}
+
+impl ZodGenerator {
+    pub fn new() -> Self {
+        let mut registry = Handlebars::new();
+        registry
+            .register_template_string("definition", DEFINITION_TEMPLATE)
+            .unwrap();
+
+        Self { registry }
+    }
+
+    /// Generates the definition `name`, preceded by the definitions of the
+    /// `Named` and nested `Recursive` schemas in it.
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, crate::Error> {
+        let mut emitter = Emitter::new(None);
+        emitter.define(name, schema);
+        Ok(self.render(&emitter)?.join("\n"))
+    }
+
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, crate::Error> {
+        let mut emitter = Emitter::new(Some(registry));
+        let graph = registry.dependency_graph();
+        emitter.cyclic = graph
+            .recursive_groups()
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .collect();
+
+        // Dependencies first, so only cycles need `z.lazy`.
+        for name in graph.topological_order() {
+            if let Some(schema) = registry.get(name) {
+                emitter.define(name, schema);
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str("// Auto-generated by typebox-rs. DO NOT EDIT.\n\n");
+        output.push_str("import { z } from 'zod';\n\n");
+
+        for code in self.render(&emitter)? {
+            output.push_str(&code);
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
+
+    fn render(&self, emitter: &Emitter) -> Result<Vec<String>, crate::Error> {
+        emitter
+            .output
+            .iter()
+            .map(|ctx| Ok(self.registry.render("definition", ctx)?))
+            .collect()
+    }
+}
+
+impl Default for ZodGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize)]
+struct DefinitionContext {
+    name: String,
+    expr: String,
+    /// The explicit TypeScript type of a self-referencing definition.
+    ts_type: Option<String>,
+}
+
+/// A declared definition.
+struct Declared {
+    id: String,
+    schema: Schema,
+    /// Whether it is typed `z.ZodType<T>` rather than by its builder.
+    annotated: bool,
+}
+
+/// Builds the Zod expressions of one module.
+struct Emitter<'r> {
+    registry: Option<&'r SchemaRegistry>,
+    /// Base URI of the definition being emitted, for resolving `$ref`s.
+    base: Option<String>,
+    /// Registered definitions that take part in a reference cycle.
+    cyclic: HashSet<String>,
+    declared: HashMap<String, Declared>,
+    /// Enclosing `Recursive` schemas: their `$id` and definition name.
+    this: Vec<(String, String)>,
+    /// References being inlined, to stop at cycles.
+    inlining: HashSet<String>,
+    /// Definitions in declaration order.
+    output: Vec<DefinitionContext>,
+    /// The identifier of each definition.
+    names: TypeNames,
+}
+
+impl<'r> Emitter<'r> {
+    fn new(registry: Option<&'r SchemaRegistry>) -> Self {
+        Self {
+            registry,
+            base: None,
+            cyclic: HashSet::new(),
+            declared: HashMap::new(),
+            this: Vec::new(),
+            inlining: HashSet::new(),
+            output: Vec::new(),
+            names: TypeNames::new(registry, &[]),
+        }
+    }
+
+    /// Declares the definition `name`, after any definitions nested in it.
+    /// The name need not be an identifier.
+    fn define(&mut self, name: &str, schema: &Schema) {
+        let schema = match &schema.kind {
+            SchemaKind::Named { schema: inner, .. } => inner,
+            _ => schema,
+        };
+        let id = schema.id.clone().unwrap_or_else(|| name.to_string());
+        let saved = self.base.take();
+        self.base = self.registry.and_then(|r| r.document_base(name));
+
+        let (expr, ts_type) = match &schema.kind {
+            SchemaKind::Recursive { schema: inner } => {
+                let ident = self.names.get(name);
+                self.this.push((id.clone(), ident));
+                let body = self.expr(inner, 0);
+                let ts_type = self.ts_type(inner);
+                self.this.pop();
+                let expr = annotate(format!("z.lazy(() => {})", body), schema);
+                (expr, Some(ts_type))
+            }
+            _ if self.cyclic.contains(name) => (self.expr(schema, 0), Some(self.ts_type(schema))),
+            _ => (self.expr(schema, 0), None),
+        };
+
+        self.base = saved;
+        self.declared.insert(
+            name.to_string(),
+            Declared {
+                id,
+                schema: schema.clone(),
+                annotated: ts_type.is_some(),
+            },
+        );
+        self.output.push(DefinitionContext {
+            name: self.names.get(name),
+            expr,
+            ts_type,
+        });
+    }
+
+    /// The Zod expression for `schema` at nesting level `depth`.
+    fn expr(&mut self, schema: &Schema, depth: usize) -> String {
+        let expr = match &schema.kind {
+            SchemaKind::Null
+            | SchemaKind::Literal {
+                value: LiteralValue::Null,
+            } => "z.null()".to_string(),
+            SchemaKind::Bool => "z.boolean()".to_string(),
+            SchemaKind::Int8 { minimum, maximum } => {
+                integer(minimum.unwrap_or(i8::MIN), maximum.unwrap_or(i8::MAX))
+            }
+            SchemaKind::Int16 { minimum, maximum } => {
+                integer(minimum.unwrap_or(i16::MIN), maximum.unwrap_or(i16::MAX))
+            }
+            SchemaKind::Int32 { minimum, maximum } => {
+                integer(minimum.unwrap_or(i32::MIN), maximum.unwrap_or(i32::MAX))
+            }
+            SchemaKind::UInt8 { minimum, maximum } => {
+                integer(minimum.unwrap_or(0), maximum.unwrap_or(u8::MAX))
+            }
+            SchemaKind::UInt16 { minimum, maximum } => {
+                integer(minimum.unwrap_or(0), maximum.unwrap_or(u16::MAX))
+            }
+            SchemaKind::UInt32 { minimum, maximum } => {
+                integer(minimum.unwrap_or(0), maximum.unwrap_or(u32::MAX))
+            }
+            // 64-bit bounds are beyond what a JavaScript number holds exactly.
+            SchemaKind::Int64 { minimum, maximum } => {
+                bounded("z.number().int()", *minimum, *maximum)
+            }
+            SchemaKind::UInt64 { minimum, maximum } => {
+                bounded("z.number().int()", Some(minimum.unwrap_or(0)), *maximum)
+            }
+            SchemaKind::Float32 { minimum, maximum } => bounded(
+                "z.number()",
+                minimum.map(|n| js_number(n.into())),
+                maximum.map(|n| js_number(n.into())),
+            ),
+            SchemaKind::Float64 { minimum, maximum } => {
+                bounded("z.number()", minimum.map(js_number), maximum.map(js_number))
+            }
+            SchemaKind::String {
+                format,
+                pattern,
+                min_length,
+                max_length,
+            } => {
+                let mut expr = "z.string()".to_string();
+                if let Some(method) = format.as_ref().and_then(format_method) {
+                    expr.push_str(method);
+                }
+                if let Some(pattern) = pattern {
+                    expr.push_str(&format!(".regex({})", js_regex(pattern)));
+                }
+                bounded(&expr, *min_length, *max_length)
+            }
+            SchemaKind::Bytes {
+                min_length,
+                max_length,
+            } => {
+                let mut expr = "z.instanceof(Uint8Array)".to_string();
+                if let Some(min) = min_length {
+                    expr.push_str(&format!(".refine((bytes) => bytes.length >= {})", min));
+                }
+                if let Some(max) = max_length {
+                    expr.push_str(&format!(".refine((bytes) => bytes.length <= {})", max));
+                }
+                expr
+            }
+
+            SchemaKind::Array {
+                items,
+                min_items,
+                max_items,
+                unique_items,
+            } => {
+                let items = self.expr(items, depth);
+                let mut expr = bounded(&format!("z.array({})", items), *min_items, *max_items);
+                if *unique_items == Some(true) {
+                    expr.push_str(
+                        ".refine((items) => new Set(items).size === items.length, { message: 'Items must be unique' })",
+                    );
+                }
+                expr
+            }
+
+            SchemaKind::Tuple { items } => format!("z.tuple({})", self.list(items, depth)),
+
+            SchemaKind::Object {
+                properties,
+                additional_properties: Some(values),
+                ..
+            } if properties.is_empty() => format!("z.record({})", self.expr(values, depth)),
+            SchemaKind::Object {
+                properties,
+                required,
+                additional_properties,
+            } => {
+                let pad = INDENT.repeat(depth + 1);
+                let mut fields = String::new();
+                for (prop, prop_schema) in properties {
+                    let mut value = self.expr(prop_schema, depth + 1);
+                    if !required.contains(prop) {
+                        value.push_str(".optional()");
+                    }
+                    fields.push_str(&format!("{}{}: {},\n", pad, js_key(prop), value));
+                }
+                let fields = if fields.is_empty() {
+                    "{}".to_string()
+                } else {
+                    format!("{{\n{}{}}}", fields, INDENT.repeat(depth))
+                };
+                // Unknown properties are rejected unless a schema allows them.
+                let unknown = match additional_properties.as_deref() {
+                    None => ".strict()".to_string(),
+                    Some(Schema {
+                        kind: SchemaKind::Any,
+                        ..
+                    }) => ".passthrough()".to_string(),
+                    Some(values) => format!(".catchall({})", self.expr(values, depth)),
+                };
+                format!("z.object({}){}", fields, unknown)
+            }
+
+            SchemaKind::Union { any_of } => {
+                if let Some(inner) = optional_inner(any_of) {
+                    format!("{}.nullable()", self.expr(inner, depth))
+                } else {
+                    self.union(any_of, depth)
+                }
+            }
+            SchemaKind::Intersect { all_of } => match all_of.split_first() {
+                Some((first, rest)) => {
+                    let mut expr = self.expr(first, depth);
+                    for member in rest {
+                        expr = format!("{}.and({})", expr, self.expr(member, depth));
+                    }
+                    expr
+                }
+                None => "z.unknown()".to_string(),
+            },
+            SchemaKind::Literal { value } => format!("z.literal({})", js_literal(value)),
+            SchemaKind::Enum { values } if values.is_empty() => "z.never()".to_string(),
+            SchemaKind::Enum { values } => {
+                let values: Vec<String> = values.iter().map(|v| js_string(v)).collect();
+                format!("z.enum([{}])", values.join(", "))
+            }
+
+            SchemaKind::Ref { reference } => self.reference(reference, schema, depth),
+            SchemaKind::Named {
+                name,
+                schema: inner,
+            } => {
+                let registered = self.registry.is_some_and(|r| r.contains(name));
+                if !registered && !self.declared.contains_key(name) {
+                    self.define(name, inner);
+                }
+                let reference = format!("#/definitions/{}", name);
+                self.reference(&reference, schema, depth)
+            }
+
+            SchemaKind::Function {
+                parameters,
+                returns,
+            } => {
+                let params: Vec<String> = parameters
+                    .iter()
+                    .map(|param| self.expr(param, depth))
+                    .collect();
+                format!(
+                    "z.function().args({}).returns({})",
+                    params.join(", "),
+                    self.expr(returns, depth)
+                )
+            }
+
+            SchemaKind::Void => "z.void()".to_string(),
+            SchemaKind::Never => "z.never()".to_string(),
+            SchemaKind::Any => "z.any()".to_string(),
+            SchemaKind::Unknown => "z.unknown()".to_string(),
+            SchemaKind::Undefined => "z.undefined()".to_string(),
+
+            // A nested recursive schema needs a name to refer to itself by.
+            SchemaKind::Recursive { schema: inner } => match &schema.id {
+                Some(id) => return self.recursive(id, schema),
+                None => self.expr(inner, depth),
+            },
+        };
+        match &schema.kind {
+            SchemaKind::Ref { .. } | SchemaKind::Named { .. } => expr,
+            _ => annotate(expr, schema),
+        }
+    }
+
+    /// `[a, b, ...]` of the expressions of `schemas`.
+    fn list(&mut self, schemas: &[Schema], depth: usize) -> String {
+        let exprs: Vec<String> = schemas
+            .iter()
+            .map(|schema| self.expr(schema, depth))
+            .collect();
+        format!("[{}]", exprs.join(", "))
+    }
+
+    /// `z.discriminatedUnion` when the variants are objects sharing a
+    /// string literal tag, else `z.union`.
+    fn union(&mut self, any_of: &[Schema], depth: usize) -> String {
+        let variants: Vec<String> = any_of
+            .iter()
+            .map(|variant| self.expr(variant, depth))
+            .collect();
+        match variants.len() {
+            0 => return "z.never()".to_string(),
+            1 => return variants[0].clone(),
+            _ => {}
+        }
+        match self.discriminator(any_of) {
+            Some(tag) => format!(
+                "z.discriminatedUnion({}, [{}])",
+                js_string(&tag),
+                variants.join(", ")
+            ),
+            None => format!("z.union([{}])", variants.join(", ")),
+        }
+    }
+
+    /// The property every variant has as a distinct, required string
+    /// literal. Variants must be plain `z.object`s, inline or declared.
+    fn discriminator(&self, any_of: &[Schema]) -> Option<String> {
+        let objects = any_of
+            .iter()
+            .map(|variant| self.object(variant))
+            .collect::<Option<Vec<_>>>()?;
+        let SchemaKind::Object { properties, .. } = &objects.first()?.kind else {
+            return None;
+        };
+        properties.keys().find_map(|tag| {
+            let mut seen = HashSet::new();
+            objects
+                .iter()
+                .all(|object| match &object.kind {
+                    SchemaKind::Object {
+                        properties,
+                        required,
+                        ..
+                    } if required.contains(tag) => match properties.get(tag) {
+                        Some(Schema {
+                            kind:
+                                SchemaKind::Literal {
+                                    value: LiteralValue::String(value),
+                                },
+                            default: None,
+                            ..
+                        }) => seen.insert(value.as_str()),
+                        _ => false,
+                    },
+                    _ => false,
+                })
+                .then(|| tag.clone())
+        })
+    }
+
+    /// The object schema `variant` is emitted as, if it is a `z.object`.
+    fn object<'a>(&'a self, variant: &'a Schema) -> Option<&'a Schema> {
+        let schema = match &variant.kind {
+            SchemaKind::Ref { .. } | SchemaKind::Named { .. } if variant.description.is_none() => {
+                let reference = match &variant.kind {
+                    SchemaKind::Ref { reference } => reference.clone(),
+                    SchemaKind::Named { name, .. } => format!("#/definitions/{}", name),
+                    _ => unreachable!(),
+                };
+                let declared = self.declared.get(&self.target(&reference)?)?;
+                if declared.annotated {
+                    return None;
+                }
+                &declared.schema
+            }
+            _ => variant,
+        };
+        let plain = schema.default.is_none()
+            && matches!(&schema.kind, SchemaKind::Object { properties, .. } if !properties.is_empty());
+        plain.then_some(schema)
+    }
+
+    /// The expression for the `$ref` of `schema`, with its annotations.
+    fn reference(&mut self, reference: &str, schema: &Schema, depth: usize) -> String {
+        if let Some((_, name)) = self.this.iter().rev().find(|(id, _)| id == reference) {
+            return annotate(name.clone(), schema);
+        }
+        if let Some(name) = self.target(reference) {
+            // A definition declared later is part of a cycle with this one.
+            let ident = self.names.get(&name);
+            let expr = if self.declared.contains_key(&name) {
+                ident
+            } else {
+                format!("z.lazy(() => {})", ident)
+            };
+            return annotate(expr, schema);
+        }
+
+        // A pointer into a definition: inline its target, annotated as the
+        // reference is.
+        let target = self
+            .registry
+            .and_then(|r| r.resolve_ref(reference, self.base.as_deref()).ok());
+        match target {
+            Some(target) if self.inlining.insert(reference.to_string()) => {
+                let annotated = Schema {
+                    kind: target.kind.clone(),
+                    id: None,
+                    description: schema
+                        .description
+                        .clone()
+                        .or_else(|| target.description.clone()),
+                    default: schema.default.clone().or_else(|| target.default.clone()),
+                    ..Schema::new(SchemaKind::Unknown)
+                };
+                let expr = self.expr(&annotated, depth);
+                self.inlining.remove(reference);
+                expr
+            }
+            _ => annotate("z.unknown()".to_string(), schema),
+        }
+    }
+
+    /// Declares the nested `Recursive` schema with `$id` `id` as its own
+    /// definition, and refers to it.
+    fn recursive(&mut self, id: &str, schema: &Schema) -> String {
+        let existing = self
+            .declared
+            .iter()
+            .find(|(_, declared)| declared.id == id)
+            .map(|(name, _)| name.clone());
+        let name = match existing {
+            Some(name) => name,
+            None => {
+                let segment = id.rsplit(['/', '#']).find(|s| !s.is_empty()).unwrap_or(id);
+                let base = pascal_case(segment.trim_end_matches(".json"));
+                let base = if base.is_empty() {
+                    "Recursive".to_string()
+                } else {
+                    base
+                };
+                let mut name = base.clone();
+                let mut n = 2;
+                while self.declared.contains_key(&name)
+                    || self.registry.is_some_and(|r| r.contains(&name))
+                {
+                    name = format!("{}{}", base, n);
+                    n += 1;
+                }
+                self.define(&name, schema);
+                name
+            }
+        };
+        self.names.get(&name)
+    }
+
+    /// The TypeScript type `schema` infers to, for definitions that must
+    /// spell it out.
+    fn ts_type(&mut self, schema: &Schema) -> String {
+        let ts_type = match &schema.kind {
+            SchemaKind::Null => "null".to_string(),
+            SchemaKind::Bool => "boolean".to_string(),
+            SchemaKind::Int8 { .. }
+            | SchemaKind::Int16 { .. }
+            | SchemaKind::Int32 { .. }
+            | SchemaKind::Int64 { .. }
+            | SchemaKind::UInt8 { .. }
+            | SchemaKind::UInt16 { .. }
+            | SchemaKind::UInt32 { .. }
+            | SchemaKind::UInt64 { .. }
+            | SchemaKind::Float32 { .. }
+            | SchemaKind::Float64 { .. } => "number".to_string(),
+            SchemaKind::String { .. } => "string".to_string(),
+            SchemaKind::Bytes { .. } => "Uint8Array".to_string(),
+            SchemaKind::Array { items, .. } => format!("Array<{}>", self.ts_type(items)),
+            SchemaKind::Tuple { items } => format!("[{}]", self.ts_list(items, ", ")),
+            SchemaKind::Object {
+                properties,
+                additional_properties: Some(values),
+                ..
+            } if properties.is_empty() => format!("Record<string, {}>", self.ts_type(values)),
+            SchemaKind::Object {
+                properties,
+                required,
+                additional_properties,
+            } => {
+                let fields: Vec<String> = properties
+                    .iter()
+                    .map(|(prop, prop_schema)| {
+                        let optional = if required.contains(prop) { "" } else { "?" };
+                        format!(
+                            "{}{}: {}",
+                            js_key(prop),
+                            optional,
+                            self.ts_type(prop_schema)
+                        )
+                    })
+                    .collect();
+                let fields = if fields.is_empty() {
+                    "{}".to_string()
+                } else {
+                    format!("{{ {} }}", fields.join("; "))
+                };
+                match additional_properties {
+                    Some(values) => {
+                        format!("{} & {{ [key: string]: {} }}", fields, self.ts_type(values))
+                    }
+                    None => fields,
+                }
+            }
+            SchemaKind::Union { any_of } if any_of.is_empty() => "never".to_string(),
+            SchemaKind::Union { any_of } => self.ts_list(any_of, " | "),
+            SchemaKind::Intersect { all_of } if all_of.is_empty() => "unknown".to_string(),
+            SchemaKind::Intersect { all_of } => self.ts_list(all_of, " & "),
+            SchemaKind::Literal { value } => js_literal(value),
+            SchemaKind::Enum { values } if values.is_empty() => "never".to_string(),
+            SchemaKind::Enum { values } => {
+                let values: Vec<String> = values.iter().map(|v| js_string(v)).collect();
+                values.join(" | ")
+            }
+            SchemaKind::Ref { reference } => self.ts_reference(reference),
+            SchemaKind::Named { name, .. } => self.names.get(name),
+            SchemaKind::Function {
+                parameters,
+                returns,
+            } => format!(
+                "(...args: [{}]) => {}",
+                self.ts_list(parameters, ", "),
+                self.ts_type(returns)
+            ),
+            SchemaKind::Void => "void".to_string(),
+            SchemaKind::Never => "never".to_string(),
+            SchemaKind::Any => "any".to_string(),
+            SchemaKind::Unknown => "unknown".to_string(),
+            SchemaKind::Undefined => "undefined".to_string(),
+            SchemaKind::Recursive { schema: inner } => match &schema.id {
+                Some(id) => self.recursive(id, schema),
+                None => self.ts_type(inner),
+            },
+        };
+        match &schema.kind {
+            SchemaKind::Union { .. }
+            | SchemaKind::Intersect { .. }
+            | SchemaKind::Function { .. }
+                if ts_type.contains(' ') =>
+            {
+                format!("({})", ts_type)
+            }
+            _ => ts_type,
+        }
+    }
+
+    fn ts_list(&mut self, schemas: &[Schema], separator: &str) -> String {
+        let types: Vec<String> = schemas.iter().map(|s| self.ts_type(s)).collect();
+        types.join(separator)
+    }
+
+    fn ts_reference(&mut self, reference: &str) -> String {
+        if let Some((_, name)) = self.this.iter().rev().find(|(id, _)| id == reference) {
+            return name.clone();
+        }
+        if let Some(name) = self.target(reference) {
+            return self.names.get(&name);
+        }
+        let target = self
+            .registry
+            .and_then(|r| r.resolve_ref(reference, self.base.as_deref()).ok());
+        match target {
+            Some(target) if self.inlining.insert(reference.to_string()) => {
+                let ts_type = self.ts_type(target);
+                self.inlining.remove(reference);
+                ts_type
+            }
+            _ => "unknown".to_string(),
+        }
+    }
+
+    /// The definition `reference` points at, if it is one.
+    fn target(&self, reference: &str) -> Option<String> {
+        if let Some(name) = reference.strip_prefix("#/definitions/") {
+            if self.declared.contains_key(name) || self.registry.is_some_and(|r| r.contains(name)) {
+                return Some(name.to_string());
+            }
+        }
+        if let Some((name, _)) = self
+            .declared
+            .iter()
+            .find(|(_, declared)| declared.id == reference)
+        {
+            return Some(name.clone());
+        }
+        let registry = self.registry?;
+        let target = registry.resolve_ref(reference, self.base.as_deref()).ok()?;
+        registry
+            .schemas()
+            .find(|(_, schema)| std::ptr::eq(*schema, target))
+            .map(|(name, _)| name.clone())
+    }
+}
+
+/// Appends `.default()` and `.describe()` for the annotations of `schema`.
+fn annotate(mut expr: String, schema: &Schema) -> String {
+    if let Some(default) = &schema.default {
+        expr.push_str(&format!(".default({})", js_value(&default.to_json())));
+    }
+    if let Some(description) = &schema.description {
+        expr.push_str(&format!(".describe({})", js_string(description)));
+    }
+    expr
+}
+
+/// `z.number().int()` bounded to `minimum..=maximum`.
+fn integer<T: ToString>(minimum: T, maximum: T) -> String {
+    bounded("z.number().int()", Some(minimum), Some(maximum))
+}
+
+/// `expr` followed by `.min()` and `.max()` for the bounds that are set.
+fn bounded<T: ToString>(expr: &str, minimum: Option<T>, maximum: Option<T>) -> String {
+    let mut expr = expr.to_string();
+    if let Some(min) = minimum {
+        expr.push_str(&format!(".min({})", min.to_string()));
+    }
+    if let Some(max) = maximum {
+        expr.push_str(&format!(".max({})", max.to_string()));
+    }
+    expr
+}
+
+/// The `z.string()` method checking `format`, if Zod has one.
+fn format_method(format: &StringFormat) -> Option<&'static str> {
+    match format {
+        StringFormat::Email => Some(".email()"),
+        StringFormat::Uuid => Some(".uuid()"),
+        StringFormat::Uri => Some(".url()"),
+        StringFormat::DateTime => Some(".datetime({ offset: true })"),
+        StringFormat::Date => Some(".date()"),
+        StringFormat::Time => Some(".time()"),
+        StringFormat::Ipv4 => Some(".ip({ version: 'v4' })"),
+        StringFormat::Ipv6 => Some(".ip({ version: 'v6' })"),
+        StringFormat::Hostname | StringFormat::Custom(_) => None,
+    }
+}
+
+/// `pattern` as a JavaScript regular expression literal.
+fn js_regex(pattern: &str) -> String {
+    if pattern.is_empty() {
+        return "/(?:)/".to_string();
+    }
+    let mut out = String::from("/");
+    let mut escaped = false;
+    for c in pattern.chars() {
+        match c {
+            '/' if !escaped => out.push_str("\\/"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    out.push('/');
+    out
+}
+
+const DEFINITION_TEMPLATE: &str = r#"{{#if ts_type}}export type {{name}} = {{{ts_type}}};
+export const {{name}}: z.ZodType<{{name}}, z.ZodTypeDef, unknown> = {{{expr}}};
+{{else}}export const {{name}} = {{{expr}}};
+export type {{name}} = z.infer<typeof {{name}}>;
+{{/if}}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::value::Value;
+
+    fn expr(schema: Schema) -> String {
+        Emitter::new(None).expr(&schema, 0)
+    }
+
+    #[test]
+    fn test_generate_object() {
+        let gen = ZodGenerator::new();
+        let schema = SchemaBuilder::object()
+            .field(
+                "id",
+                Schema::new(SchemaKind::Int64 {
+                    minimum: Some(0),
+                    maximum: None,
+                }),
+            )
+            .optional_field(
+                "email",
+                SchemaBuilder::string().format(StringFormat::Email).build(),
+            )
+            .field(
+                "content-type",
+                SchemaBuilder::string()
+                    .pattern("^[a-z]+/[a-z]+$")
+                    .max_length(64)
+                    .build(),
+            )
+            .optional_field(
+                "nickname",
+                SchemaBuilder::optional(SchemaBuilder::string().build()),
+            )
+            .build()
+            .with_description("A person.");
+
+        let output = gen.generate("Person", &schema).unwrap();
+        assert_eq!(
+            output,
+            "export const Person = z.object({\n  id: z.number().int().min(0),\n  email: z.string().email().optional(),\n  'content-type': z.string().regex(/^[a-z]+\\/[a-z]+$/).max(64),\n  nickname: z.string().nullable().optional(),\n}).strict().describe('A person.');\nexport type Person = z.infer<typeof Person>;\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_kinds() {
+        assert_eq!(
+            expr(SchemaBuilder::uint8()),
+            "z.number().int().min(0).max(255)"
+        );
+        assert_eq!(
+            expr(
+                SchemaBuilder::array(SchemaBuilder::bool())
+                    .min_items(1)
+                    .build()
+            ),
+            "z.array(z.boolean()).min(1)"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::tuple(vec![
+                SchemaBuilder::float64(),
+                SchemaBuilder::null()
+            ])),
+            "z.tuple([z.number(), z.null()])"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::enum_values(vec!["a", "it's"])),
+            "z.enum(['a', 'it\\'s'])"
+        );
+        assert_eq!(
+            expr(
+                SchemaBuilder::object()
+                    .additional_properties(Some(SchemaBuilder::float64()))
+                    .build()
+            ),
+            "z.record(z.number())"
+        );
+        assert_eq!(
+            expr(Schema::new(SchemaKind::Bytes {
+                min_length: None,
+                max_length: Some(16),
+            })),
+            "z.instanceof(Uint8Array).refine((bytes) => bytes.length <= 16)"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::bool().with_default(Value::Bool(true))),
+            "z.boolean().default(true)"
+        );
+        assert_eq!(
+            expr(SchemaBuilder::function(
+                vec![SchemaBuilder::int64()],
+                SchemaBuilder::void()
+            )),
+            "z.function().args(z.number().int()).returns(z.void())"
+        );
+
+        let shape = SchemaBuilder::union(vec![
+            SchemaBuilder::object()
+                .field("kind", SchemaBuilder::literal("circle"))
+                .field("radius", SchemaBuilder::float64())
+                .build(),
+            SchemaBuilder::object()
+                .field("kind", SchemaBuilder::literal("square"))
+                .field("side", SchemaBuilder::float64())
+                .build(),
+        ]);
+        assert!(expr(shape).starts_with("z.discriminatedUnion('kind', [z.object({"));
+        assert_eq!(
+            expr(SchemaBuilder::union(vec![
+                SchemaBuilder::string().build(),
+                SchemaBuilder::int64()
+            ])),
+            "z.union([z.string(), z.number().int()])"
+        );
+    }
+
+    #[test]
+    fn test_generate_recursive() {
+        let gen = ZodGenerator::new();
+        let node = SchemaBuilder::recursive("Node", |this| {
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int64())
+                .field("children", SchemaBuilder::array(this).build())
+                .build()
+        });
+
+        let output = gen.generate("Node", &node).unwrap();
+        assert_eq!(
+            output,
+            "export type Node = { value: number; children: Array<Node> };\nexport const Node: z.ZodType<Node, z.ZodTypeDef, unknown> = z.lazy(() => z.object({\n  value: z.number().int(),\n  children: z.array(Node),\n}).strict());\n"
+        );
+
+        // Nested, it becomes a definition of its own.
+        let tree = SchemaBuilder::object().field("root", node).build();
+        let output = gen.generate("Tree", &tree).unwrap();
+        assert!(output.contains("export const Node: z.ZodType<Node, z.ZodTypeDef, unknown>"));
+        assert!(output.contains("export const Tree = z.object({\n  root: Node,\n}).strict();"));
+    }
+
+    #[test]
+    fn test_generate_module_references() {
+        let gen = ZodGenerator::new();
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .build(),
+        );
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .field("address", SchemaBuilder::r#ref("Address"))
+                .optional_field("pet", SchemaBuilder::r#ref("Pet"))
+                .build(),
+        );
+        registry.register(
+            "Pet",
+            SchemaBuilder::object()
+                .field("owner", SchemaBuilder::r#ref("Person"))
+                .build(),
+        );
+
+        let output = gen.generate_module(&registry).unwrap();
+        assert!(output.contains("import { z } from 'zod';"));
+        assert!(output.contains("export type Address = z.infer<typeof Address>;"));
+        // `Person` and `Pet` refer to each other: both spell out their types,
+        // and the first refers to the second lazily.
+        assert!(output.contains(
+            "export type Person = { address: Address; pet?: Pet };\nexport const Person: z.ZodType<Person, z.ZodTypeDef, unknown> = z.object({\n  address: Address,\n  pet: z.lazy(() => Pet).optional(),\n}).strict();"
+        ));
+        assert!(output.contains(
+            "export const Pet: z.ZodType<Pet, z.ZodTypeDef, unknown> = z.object({\n  owner: Person,\n}).strict();"
+        ));
+    }
+}
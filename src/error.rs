@@ -29,6 +29,11 @@ pub enum Error {
     /// Schema not found in registry.
     #[error("Schema not found: {0}")]
     SchemaNotFound(String),
+
+    /// A schema cannot be rendered in the target language (codegen feature).
+    #[cfg(feature = "codegen")]
+    #[error("Code generation error: {0}")]
+    Codegen(String),
 }
 
 /// Parse errors for value construction.
@@ -322,4 +327,340 @@ pub enum RegistryError {
     /// Circular reference detected.
     #[error("Circular reference detected: {0}")]
     CircularRef(String),
+
+    /// Reference could not be parsed.
+    #[error("Invalid reference {reference}: {reason}")]
+    InvalidRef {
+        /// The reference as written.
+        reference: String,
+        /// Why it could not be parsed.
+        reason: String,
+    },
+
+    /// The URI part of a reference names no registered schema.
+    #[error("Reference {reference}: no schema registered for {uri}")]
+    DocumentNotFound {
+        /// The reference as written.
+        reference: String,
+        /// The URI resolved against the base URI.
+        uri: String,
+    },
+
+    /// A JSON Pointer fragment does not lead to a schema.
+    #[error("Reference {reference}: pointer {pointer} has no segment {segment:?}")]
+    PointerNotFound {
+        /// The reference as written.
+        reference: String,
+        /// The JSON Pointer fragment.
+        pointer: String,
+        /// The first segment that could not be followed.
+        segment: String,
+    },
+
+    /// A plain-name fragment matches no anchor.
+    #[error("Reference {reference}: anchor {anchor:?} not found")]
+    AnchorNotFound {
+        /// The reference as written.
+        reference: String,
+        /// The anchor name.
+        anchor: String,
+    },
+}
+
+/// Errors from loading schema documents with
+/// [`SchemaRegistry::load_dir`](crate::SchemaRegistry::load_dir) and friends.
+#[derive(Debug, Error)]
+pub enum LoadError {
+    /// A file or directory could not be read or written.
+    #[error("{}: {source}", path.display())]
+    Io {
+        /// The file or directory.
+        path: std::path::PathBuf,
+        /// The underlying IO error.
+        source: std::io::Error,
+    },
+
+    /// A document is not a valid schema.
+    #[error("{}:{line}:{column}: {message}", path.display())]
+    Parse {
+        /// The file being parsed.
+        path: std::path::PathBuf,
+        /// Line of the error (1-based).
+        line: usize,
+        /// Column of the error (1-based).
+        column: usize,
+        /// Parser message.
+        message: String,
+    },
+
+    /// The file extension is not a supported schema format.
+    #[error("{}: unsupported schema format", path.display())]
+    UnsupportedFormat {
+        /// The file.
+        path: std::path::PathBuf,
+    },
+
+    /// Two documents register under the same `$id` or name.
+    #[error("{}: duplicate schema id {id:?} (first defined in {})", second.display(), first.display())]
+    DuplicateId {
+        /// The id or name.
+        id: String,
+        /// The file that registered it first.
+        first: std::path::PathBuf,
+        /// The file that was rejected.
+        second: std::path::PathBuf,
+    },
+
+    /// A `$ref` does not resolve after all documents are loaded.
+    #[error("{}: {location}: {source}", path.display())]
+    DanglingRef {
+        /// The file containing the reference.
+        path: std::path::PathBuf,
+        /// Location of the reference (e.g., `Person#/properties/address`).
+        location: String,
+        /// Why the reference does not resolve.
+        source: RegistryError,
+    },
+
+    /// Several problems, in file order.
+    #[error("{} error(s) loading schemas:{}", .0.len(), .0.iter().map(|e| format!("\n  {}", e)).collect::<String>())]
+    Multiple(Vec<LoadError>),
+}
+
+impl LoadError {
+    /// The individual problems: the inner errors of
+    /// [`Multiple`](Self::Multiple), or `self` otherwise.
+    pub fn errors(&self) -> &[LoadError] {
+        match self {
+            LoadError::Multiple(errors) => errors,
+            other => std::slice::from_ref(other),
+        }
+    }
+
+    /// Combines collected problems into one error, if there are any.
+    pub(crate) fn collect(mut errors: Vec<LoadError>) -> Result<(), LoadError> {
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(LoadError::Multiple(errors)),
+        }
+    }
+}
+
+/// Errors from registering schema revisions in a
+/// [`VersionedRegistry`](crate::registry::VersionedRegistry).
+#[derive(Debug, Error)]
+pub enum VersionError {
+    /// The name or version cannot be used in a reference.
+    #[error("Invalid version {name}@{version}: {reason}")]
+    InvalidVersion {
+        /// The schema name.
+        name: String,
+        /// The version as given.
+        version: String,
+        /// Why it was rejected.
+        reason: String,
+    },
+
+    /// The version is already registered with different content.
+    #[error("{name}@{version} is already registered with content {existing}, not {attempted}")]
+    Immutable {
+        /// The schema name.
+        name: String,
+        /// The version.
+        version: String,
+        /// Content hash of the registered revision.
+        existing: String,
+        /// Content hash of the rejected schema.
+        attempted: String,
+    },
+
+    /// The content hash of the new version already addresses a revision
+    /// with different content.
+    #[error("{name}@{hash} already addresses different content than {name}@{version}")]
+    HashCollision {
+        /// The schema name.
+        name: String,
+        /// The rejected version.
+        version: String,
+        /// The shared content hash.
+        hash: String,
+    },
+
+    /// The new version violates the registry's compatibility policy.
+    #[error("{name}@{version} is not {policy:?} compatible with {name}@{against}:{}", issues.iter().map(|i| format!("\n  {}", i)).collect::<String>())]
+    Incompatible {
+        /// The schema name.
+        name: String,
+        /// The rejected version.
+        version: String,
+        /// The neighbouring version it was checked against.
+        against: String,
+        /// The policy that was violated.
+        policy: crate::registry::CompatibilityPolicy,
+        /// Why the versions are incompatible.
+        issues: Vec<crate::registry::Incompatibility>,
+    },
+}
+
+/// Errors from migrating values with a [`Migrator`](crate::migrate::Migrator).
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    /// No chain of migrations leads between the versions.
+    #[error("No migration path from {from} to {to}")]
+    NoPath {
+        /// The source version.
+        from: String,
+        /// The target version.
+        to: String,
+    },
+
+    /// A step failed to apply.
+    #[error("Migration {from} -> {to}, step {step}: {message}")]
+    Step {
+        /// Version the migration starts from.
+        from: String,
+        /// Version the migration leads to.
+        to: String,
+        /// Index of the failing step within the migration.
+        step: usize,
+        /// What went wrong.
+        message: String,
+    },
+
+    /// The target version is not registered.
+    #[error("Schema not found: {0}")]
+    SchemaNotFound(String),
+
+    /// The migrated value does not match the target schema.
+    #[error("Migrated value is not a valid {reference}: {source}")]
+    Invalid {
+        /// The `Name@version` reference of the target schema.
+        reference: String,
+        /// The validation failure.
+        source: ValidationError,
+    },
+}
+
+/// Errors from schema linting.
+#[derive(Debug, Error)]
+pub enum LintError {
+    /// Schema has one or more error-level diagnostics.
+    #[error("Schema is invalid: {} diagnostic(s)", .0.len())]
+    Invalid(Vec<crate::lint::Diagnostic>),
+}
+
+/// Errors from importing schemas written by other tools.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    /// The input is not valid JSON.
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The input is not valid in its language.
+    #[error("Syntax error at line {line}: {message}")]
+    Syntax {
+        /// 1-based line of the error.
+        line: usize,
+        /// What is wrong.
+        message: String,
+    },
+
+    /// A construct has no `SchemaKind` equivalent.
+    #[error("Unsupported schema at {path}: {message}")]
+    Unsupported {
+        /// Location of the construct: a JSON Pointer such as
+        /// `#/properties/id`, or a qualified name such as `Person.id`.
+        path: String,
+        /// What is unsupported.
+        message: String,
+    },
+}
+
+/// Errors from Avro schema conversion and binary encoding.
+#[derive(Debug, Error)]
+pub enum AvroError {
+    /// The input is not valid JSON.
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Avro schema JSON is malformed.
+    #[error("Invalid Avro schema at {path}: {message}")]
+    InvalidSchema {
+        /// JSON Pointer of the malformed construct, such as `#/fields/0`.
+        path: String,
+        /// What is wrong.
+        message: String,
+    },
+
+    /// A schema has no Avro equivalent.
+    #[error("Unsupported schema at {path}: {message}")]
+    Unsupported {
+        /// JSON Pointer of the construct, such as `#/properties/id`.
+        path: String,
+        /// What is unsupported.
+        message: String,
+    },
+
+    /// A value does not match the schema it is encoded with.
+    #[error("Cannot encode value at {path}: {message}")]
+    Encode {
+        /// JSON Pointer of the value, such as `#/items/0`.
+        path: String,
+        /// What is wrong.
+        message: String,
+    },
+
+    /// Binary data is truncated or was not written with the schema.
+    #[error("Cannot decode Avro data: {0}")]
+    Decode(String),
+
+    /// A single-object encoded message was written with another schema.
+    #[error("Schema fingerprint mismatch: expected {expected:016x}, found {found:016x}")]
+    FingerprintMismatch {
+        /// Fingerprint of the reading schema.
+        expected: u64,
+        /// Fingerprint in the message.
+        found: u64,
+    },
+}
+
+/// Errors from mapping schemas and values to SQL tables.
+#[derive(Debug, Error)]
+pub enum SqlError {
+    /// A schema cannot be stored in a table.
+    #[error("Unsupported schema at {path}: {message}")]
+    Unsupported {
+        /// JSON Pointer of the construct, such as `#/properties/id`.
+        path: String,
+        /// What is unsupported.
+        message: String,
+    },
+
+    /// A value cannot be bound to the table's columns.
+    #[error("Cannot encode value at {path}: {message}")]
+    Encode {
+        /// JSON Pointer of the value, such as `#/email`.
+        path: String,
+        /// What is wrong.
+        message: String,
+    },
+
+    /// A row does not hold a value of the table.
+    #[error("Cannot decode row at {path}: {message}")]
+    Decode {
+        /// JSON Pointer of the column's property, such as `#/email`.
+        path: String,
+        /// What is wrong.
+        message: String,
+    },
+
+    /// A row does not have a value per column.
+    #[error("Row has {found} values, expected {expected}")]
+    RowLength {
+        /// Number of columns.
+        expected: usize,
+        /// Number of values in the row.
+        found: usize,
+    },
 }
@@ -0,0 +1,259 @@
+//! Content-addressed schema fingerprints.
+//!
+//! A [`Fingerprint`] identifies a schema by its canonical form, so it can key
+//! caches of compiled validators or tag stored records with the exact schema
+//! they were written under. The canonical form is the schema's JSON with:
+//!
+//! - object keys sorted, so property declaration order does not matter
+//! - `required` and `Enum` values sorted and deduplicated, as they are sets
+//! - tuple items and union variants kept in order, as their order matters
+//! - annotations (`title`, `description`, `default`, `examples`,
+//!   `readOnly`, `writeOnly`, `deprecated`) removed unless
+//!   requested with [`FingerprintOptions::annotations`]
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::fingerprint::{fingerprint, fingerprint_with_options, FingerprintOptions};
+//! use typebox::SchemaBuilder;
+//!
+//! let a = SchemaBuilder::object()
+//!     .field("id", SchemaBuilder::int64())
+//!     .field("name", SchemaBuilder::string().build())
+//!     .build();
+//! let b = SchemaBuilder::object()
+//!     .field("name", SchemaBuilder::string().build())
+//!     .field("id", SchemaBuilder::int64())
+//!     .build()
+//!     .with_description("A person");
+//!
+//! assert_eq!(fingerprint(&a), fingerprint(&b));
+//!
+//! let exact = FingerprintOptions::default().annotations(true);
+//! assert_ne!(
+//!     fingerprint_with_options(&a, exact),
+//!     fingerprint_with_options(&b, exact)
+//! );
+//! ```
+
+use crate::schema::{Schema, SchemaKind};
+use crate::value::hash::Fnv1aHasher;
+use crate::visit::{walk_schema_mut, SchemaPath, SchemaVisitorMut};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// What a fingerprint covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FingerprintOptions {
+    annotations: bool,
+}
+
+impl FingerprintOptions {
+    /// Whether annotations such as `description` and `default` contribute
+    /// to the fingerprint (default: `false`).
+    pub fn annotations(mut self, include: bool) -> Self {
+        self.annotations = include;
+        self
+    }
+}
+
+/// Fingerprint of a schema's canonical form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    /// 64-bit FNV-1a hash, for in-memory cache keys.
+    pub fast: u64,
+    /// SHA-256 digest, for persisted, collision-resistant identifiers.
+    pub sha256: [u8; 32],
+}
+
+impl Fingerprint {
+    /// Computes the fingerprint of canonical bytes.
+    fn of(canonical: &[u8]) -> Self {
+        let mut hasher = Fnv1aHasher::new();
+        hasher.write_bytes(canonical);
+        Self {
+            fast: hasher.finish(),
+            sha256: Sha256::digest(canonical).into(),
+        }
+    }
+
+    /// The SHA-256 digest as lowercase hex.
+    pub fn sha256_hex(&self) -> String {
+        self.sha256.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    /// Formats as `sha256:<hex>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sha256:{}", self.sha256_hex())
+    }
+}
+
+/// Fingerprints `schema`, excluding annotations.
+pub fn fingerprint(schema: &Schema) -> Fingerprint {
+    fingerprint_with_options(schema, FingerprintOptions::default())
+}
+
+/// Fingerprints `schema` with the given options.
+pub fn fingerprint_with_options(schema: &Schema, options: FingerprintOptions) -> Fingerprint {
+    Fingerprint::of(canonical_json(schema, options).as_bytes())
+}
+
+/// Fingerprints `root` together with the named schemas it depends on. The
+/// names are part of the fingerprint, as `$ref`s address them; their order
+/// is not. Without dependencies this equals the fingerprint of `root`.
+pub(crate) fn fingerprint_with_dependencies<'a>(
+    root: &Schema,
+    dependencies: impl IntoIterator<Item = (&'a str, &'a Schema)>,
+    options: FingerprintOptions,
+) -> Fingerprint {
+    let mut entries: Vec<(&str, String)> = dependencies
+        .into_iter()
+        .map(|(name, schema)| (name, canonical_json(schema, options)))
+        .collect();
+    if entries.is_empty() {
+        return fingerprint_with_options(root, options);
+    }
+    entries.sort();
+    let mut canonical = format!("[{},{{", canonical_json(root, options));
+    for (i, (name, json)) in entries.iter().enumerate() {
+        if i > 0 {
+            canonical.push(',');
+        }
+        canonical.push_str(&serde_json::Value::from(*name).to_string());
+        canonical.push(':');
+        canonical.push_str(json);
+    }
+    canonical.push_str("}]");
+    Fingerprint::of(canonical.as_bytes())
+}
+
+/// The canonical JSON text a fingerprint is computed from.
+pub fn canonical_json(schema: &Schema, options: FingerprintOptions) -> String {
+    let mut schema = schema.clone();
+    schema.accept_mut(&mut Canonicalizer { options });
+    let json = serde_json::to_value(&schema).unwrap_or(serde_json::Value::Null);
+    let mut out = String::new();
+    write_sorted(&json, &mut out);
+    out
+}
+
+struct Canonicalizer {
+    options: FingerprintOptions,
+}
+
+impl SchemaVisitorMut for Canonicalizer {
+    fn visit_schema_mut(&mut self, schema: &mut Schema, path: &mut SchemaPath) {
+        if !self.options.annotations {
+            schema.title = None;
+            schema.description = None;
+            schema.default = None;
+            schema.examples = None;
+            schema.read_only = None;
+            schema.write_only = None;
+            schema.deprecated = None;
+        }
+        match &mut schema.kind {
+            SchemaKind::Object { required, .. } => {
+                required.sort();
+                required.dedup();
+            }
+            SchemaKind::Enum { values } => {
+                values.sort();
+                values.dedup();
+            }
+            _ => {}
+        }
+        walk_schema_mut(self, schema, path);
+    }
+}
+
+/// Writes JSON with object keys sorted, independent of whether
+/// `serde_json` preserves insertion order.
+fn write_sorted(json: &serde_json::Value, out: &mut String) {
+    match json {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_sorted(value, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_sorted(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+
+    #[test]
+    fn test_sets_are_order_insensitive() {
+        let a = SchemaBuilder::object()
+            .field("a", SchemaBuilder::enum_values(vec!["x", "y"]))
+            .field("b", SchemaBuilder::int32())
+            .build();
+        let b = SchemaBuilder::object()
+            .field("b", SchemaBuilder::int32())
+            .field("a", SchemaBuilder::enum_values(vec!["y", "x", "y"]))
+            .build();
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+        assert_eq!(
+            canonical_json(&a, FingerprintOptions::default()),
+            r#"{"kind":"object","properties":{"a":{"kind":"enum","values":["x","y"]},"b":{"kind":"int32"}},"required":["a","b"]}"#
+        );
+    }
+
+    #[test]
+    fn test_sequences_are_order_sensitive() {
+        let a = SchemaBuilder::tuple(vec![SchemaBuilder::int32(), SchemaBuilder::bool()]);
+        let b = SchemaBuilder::tuple(vec![SchemaBuilder::bool(), SchemaBuilder::int32()]);
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+    }
+
+    #[test]
+    fn test_annotations() {
+        let plain = SchemaBuilder::string().build();
+        let described = SchemaBuilder::string()
+            .build()
+            .with_description("A name")
+            .with_title("Name");
+        assert_eq!(fingerprint(&plain), fingerprint(&described));
+
+        let exact = FingerprintOptions::default().annotations(true);
+        assert_ne!(
+            fingerprint_with_options(&plain, exact),
+            fingerprint_with_options(&described, exact)
+        );
+        assert_eq!(fingerprint_with_options(&plain, exact), fingerprint(&plain));
+    }
+
+    #[test]
+    fn test_digest_is_stable() {
+        let fp = fingerprint(&SchemaBuilder::string().build());
+        // SHA-256 of `{"kind":"string"}`.
+        assert_eq!(
+            fp.to_string(),
+            "sha256:9bddc1fdfb55932e41cf34e4a0bf062564cf848d3e0070d86dcd3a998fdce008"
+        );
+    }
+}
@@ -0,0 +1,718 @@
+//! Importing schemas authored with other tools.
+//!
+//! [`from_proto`] reads Protocol Buffers definitions into a registry; see
+//! its documentation for the mapping.
+//!
+//! [`from_typebox`] reads the JSON a TypeBox schema serializes to
+//! (`JSON.stringify(schema)`), so schemas owned by a TypeScript codebase can
+//! be consumed in Rust. Besides standard JSON Schema keywords it maps
+//! TypeBox's own conventions:
+//!
+//! | TypeBox JSON | `SchemaKind` |
+//! |---|---|
+//! | `{"type": "Uint8Array"}` | `Bytes` |
+//! | `{"type": "Date"}` | `String` with the `date-time` format |
+//! | `{"type": "bigint"}` | `Int64` |
+//! | `{"type": "undefined"}`, `{"type": "void"}` | `Undefined`, `Void` |
+//! | `{"type": "Function"}`, `{"type": "Constructor"}` | `Function` |
+//! | `{"type": "RegExp"}` | `String` with its `pattern` |
+//! | `{"const": ...}` | `Literal` |
+//! | `anyOf` of string literals | `Enum` |
+//! | `{"not": {}}`, `{}` | `Never`, `Any` |
+//! | `$id` referenced from within | `Recursive` |
+//!
+//! The `instanceOf`/`typeOf` markers of older TypeBox releases and their
+//! `$defs/self` form of recursive schemas are read too. Integer ranges that
+//! span exactly a fixed-width type (as `TypeBoxGenerator` emits them) import
+//! as that type; exclusive float bounds import as inclusive ones.
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::import::from_typebox_str;
+//! use typebox::{check, Value};
+//!
+//! let schema = from_typebox_str(r#"{
+//!     "type": "object",
+//!     "properties": {
+//!         "id": {"type": "integer", "minimum": 0},
+//!         "status": {"anyOf": [{"const": "active", "type": "string"}, {"const": "banned", "type": "string"}]}
+//!     },
+//!     "required": ["id", "status"]
+//! }"#).unwrap();
+//!
+//! let user = Value::object()
+//!     .field("id", Value::int64(7))
+//!     .field("status", Value::string("active"))
+//!     .build();
+//! assert!(check(&schema, &user));
+//! ```
+
+use crate::error::ImportError;
+use crate::schema::{LiteralValue, Schema, SchemaKind, StringFormat};
+use crate::value::Value;
+use crate::visit::{walk_schema_mut, SchemaPath, SchemaVisitorMut};
+use indexmap::IndexMap;
+use serde_json::{Map, Value as Json};
+
+mod proto;
+
+pub use proto::{from_proto, from_proto_with_numbers, FieldNumbers};
+
+/// Converts serialized TypeBox JSON into a [`Schema`].
+///
+/// # Errors
+///
+/// Returns [`ImportError::Unsupported`] with the JSON Pointer of the first
+/// construct that has no `SchemaKind` equivalent, such as `symbol` or
+/// `Promise` types.
+pub fn from_typebox(json: &Json) -> Result<Schema, ImportError> {
+    convert(json, "#")
+}
+
+/// Parses `text` and converts it as [`from_typebox`] does.
+pub fn from_typebox_str(text: &str) -> Result<Schema, ImportError> {
+    from_typebox(&serde_json::from_str(text)?)
+}
+
+fn convert(json: &Json, path: &str) -> Result<Schema, ImportError> {
+    let object = match json {
+        Json::Bool(true) => return Ok(Schema::new(SchemaKind::Any)),
+        Json::Bool(false) => return Ok(Schema::new(SchemaKind::Never)),
+        Json::Object(object) => object,
+        _ => return Err(unsupported(path, "expected a schema object")),
+    };
+    let id = object.get("$id").and_then(Json::as_str);
+
+    // Older releases wrap a recursive body as `{$id, $ref: "<id>#/$defs/self",
+    // $defs: {self}}` and refer to it by that `$ref`.
+    let legacy = id.zip(object.get("$ref").and_then(Json::as_str));
+    if let Some((id, reference)) = legacy.filter(|(id, r)| *r == format!("{}#/$defs/self", id)) {
+        let body = object
+            .get("$defs")
+            .and_then(|defs| defs.get("self"))
+            .ok_or_else(|| unsupported(path, "recursive schema without $defs/self"))?;
+        let mut body = convert(body, &format!("{}/$defs/self", path))?;
+        rewrite_refs(&mut body, reference, id);
+        let schema = Schema::new(SchemaKind::Recursive {
+            schema: Box::new(body),
+        });
+        return annotate(schema.with_id(id), object, path);
+    }
+
+    let mut schema = Schema::new(kind(object, path)?);
+    if let Some(id) = id {
+        // A reference to the schema's own `$id` from within makes it recursive.
+        if rewrite_refs(&mut schema, id, id) {
+            schema = Schema::new(SchemaKind::Recursive {
+                schema: Box::new(schema),
+            });
+        }
+        schema.id = Some(id.to_string());
+    }
+    annotate(schema, object, path)
+}
+
+fn kind(object: &Map<String, Json>, path: &str) -> Result<SchemaKind, ImportError> {
+    if let Some(value) = object.get("const") {
+        return Ok(SchemaKind::Literal {
+            value: literal(value, &format!("{}/const", path))?,
+        });
+    }
+    if let Some(values) = object.get("enum") {
+        let Json::Array(values) = values else {
+            return Err(unsupported(path, "enum must be an array"));
+        };
+        let any_of = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let value = literal(value, &format!("{}/enum/{}", path, i))?;
+                Ok(Schema::new(SchemaKind::Literal { value }))
+            })
+            .collect::<Result<Vec<_>, ImportError>>()?;
+        return Ok(union(any_of));
+    }
+    if let Some(reference) = object.get("$ref").and_then(Json::as_str) {
+        return Ok(SchemaKind::Ref {
+            reference: reference.to_string(),
+        });
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(variants) = object.get(keyword) {
+            return Ok(union(list(variants, &format!("{}/{}", path, keyword))?));
+        }
+    }
+    if let Some(members) = object.get("allOf") {
+        return Ok(SchemaKind::Intersect {
+            all_of: list(members, &format!("{}/allOf", path))?,
+        });
+    }
+    if let Some(not) = object.get("not") {
+        return match not {
+            Json::Object(inner) if inner.is_empty() => Ok(SchemaKind::Never),
+            _ => Err(unsupported(path, "`not` other than `{}` (Never)")),
+        };
+    }
+
+    // Older releases mark JavaScript types with `instanceOf`/`typeOf`.
+    let marker = object
+        .get("instanceOf")
+        .or_else(|| object.get("typeOf"))
+        .and_then(Json::as_str);
+    let ty = match (marker, object.get("type")) {
+        (Some(marker), _) => marker,
+        (None, Some(Json::String(ty))) => ty.as_str(),
+        (None, Some(Json::Array(types))) => {
+            // `"type": ["string", "null"]`: one variant per type.
+            let variants = types
+                .iter()
+                .map(|ty| {
+                    let mut single = object.clone();
+                    single.insert("type".to_string(), ty.clone());
+                    kind(&single, path).map(Schema::new)
+                })
+                .collect::<Result<Vec<_>, ImportError>>()?;
+            return Ok(SchemaKind::Union { any_of: variants });
+        }
+        (None, Some(_)) => return Err(unsupported(path, "type must be a string or array")),
+        (None, None) => return Ok(SchemaKind::Any),
+    };
+
+    let kind = match ty {
+        "null" => SchemaKind::Null,
+        "boolean" => SchemaKind::Bool,
+        "integer" => integer(object),
+        "bigint" | "BigInt" => SchemaKind::Int64 {
+            minimum: None,
+            maximum: None,
+        },
+        "number" => SchemaKind::Float64 {
+            minimum: float(object, "minimum").or(float(object, "exclusiveMinimum")),
+            maximum: float(object, "maximum").or(float(object, "exclusiveMaximum")),
+        },
+        "string" if object.get("contentEncoding").and_then(Json::as_str) == Some("base64") => {
+            SchemaKind::Bytes {
+                min_length: None,
+                max_length: None,
+            }
+        }
+        "string" => SchemaKind::String {
+            format: object.get("format").and_then(Json::as_str).map(format),
+            pattern: object
+                .get("pattern")
+                .and_then(Json::as_str)
+                .map(str::to_string),
+            min_length: size(object, "minLength"),
+            max_length: size(object, "maxLength"),
+        },
+        "RegExp" => SchemaKind::String {
+            format: None,
+            pattern: object
+                .get("source")
+                .and_then(Json::as_str)
+                .map(str::to_string),
+            min_length: size(object, "minLength"),
+            max_length: size(object, "maxLength"),
+        },
+        "Date" => SchemaKind::String {
+            format: Some(StringFormat::DateTime),
+            pattern: None,
+            min_length: None,
+            max_length: None,
+        },
+        "Uint8Array" => SchemaKind::Bytes {
+            min_length: size(object, "minByteLength"),
+            max_length: size(object, "maxByteLength"),
+        },
+        "undefined" | "Undefined" => SchemaKind::Undefined,
+        "void" | "Void" => SchemaKind::Void,
+        "array" => array(object, path)?,
+        "object" => self::object(object, path)?,
+        "Function" | "Constructor" => SchemaKind::Function {
+            parameters: match object.get("parameters") {
+                Some(parameters) => list(parameters, &format!("{}/parameters", path))?,
+                None => Vec::new(),
+            },
+            returns: Box::new(match object.get("returns") {
+                Some(returns) => convert(returns, &format!("{}/returns", path))?,
+                None => Schema::new(SchemaKind::Unknown),
+            }),
+        },
+        other => return Err(unsupported(path, &format!("type `{}`", other))),
+    };
+    Ok(kind)
+}
+
+/// A union, or an `Enum` when every variant is a plain string literal.
+fn union(any_of: Vec<Schema>) -> SchemaKind {
+    let values: Option<Vec<String>> = any_of
+        .iter()
+        .map(|variant| match variant {
+            Schema {
+                kind:
+                    SchemaKind::Literal {
+                        value: LiteralValue::String(s),
+                    },
+                id: None,
+                title: None,
+                description: None,
+                ..
+            } => Some(s.clone()),
+            _ => None,
+        })
+        .collect();
+    match values {
+        Some(values) if !values.is_empty() => SchemaKind::Enum { values },
+        _ => SchemaKind::Union { any_of },
+    }
+}
+
+fn integer(object: &Map<String, Json>) -> SchemaKind {
+    let bound = |inclusive: &str, exclusive: &str, step: i128| {
+        object
+            .get(inclusive)
+            .and_then(as_i128)
+            .or_else(|| object.get(exclusive).and_then(as_i128).map(|n| n + step))
+    };
+    let minimum = bound("minimum", "exclusiveMinimum", 1);
+    let maximum = bound("maximum", "exclusiveMaximum", -1);
+
+    // Ranges spanning exactly a fixed-width type are that type.
+    let span = |min: i128, max: i128| minimum == Some(min) && maximum == Some(max);
+    if span(i8::MIN.into(), i8::MAX.into()) {
+        return SchemaKind::Int8 {
+            minimum: None,
+            maximum: None,
+        };
+    }
+    if span(i16::MIN.into(), i16::MAX.into()) {
+        return SchemaKind::Int16 {
+            minimum: None,
+            maximum: None,
+        };
+    }
+    if span(i32::MIN.into(), i32::MAX.into()) {
+        return SchemaKind::Int32 {
+            minimum: None,
+            maximum: None,
+        };
+    }
+    if span(0, u8::MAX.into()) {
+        return SchemaKind::UInt8 {
+            minimum: None,
+            maximum: None,
+        };
+    }
+    if span(0, u16::MAX.into()) {
+        return SchemaKind::UInt16 {
+            minimum: None,
+            maximum: None,
+        };
+    }
+    if span(0, u32::MAX.into()) {
+        return SchemaKind::UInt32 {
+            minimum: None,
+            maximum: None,
+        };
+    }
+    if maximum.is_some_and(|max| max > i64::MAX.into()) {
+        return SchemaKind::UInt64 {
+            minimum: minimum.and_then(|n| u64::try_from(n).ok()),
+            maximum: maximum.and_then(|n| u64::try_from(n).ok()),
+        };
+    }
+    SchemaKind::Int64 {
+        minimum: minimum.and_then(|n| i64::try_from(n).ok()),
+        maximum: maximum.and_then(|n| i64::try_from(n).ok()),
+    }
+}
+
+fn array(object: &Map<String, Json>, path: &str) -> Result<SchemaKind, ImportError> {
+    if let Some(items) = object.get("prefixItems") {
+        return Ok(SchemaKind::Tuple {
+            items: list(items, &format!("{}/prefixItems", path))?,
+        });
+    }
+    match object.get("items") {
+        // Older releases encode tuples as an `items` array.
+        Some(items @ Json::Array(_)) => Ok(SchemaKind::Tuple {
+            items: list(items, &format!("{}/items", path))?,
+        }),
+        items => Ok(SchemaKind::Array {
+            items: Box::new(match items {
+                Some(items) => convert(items, &format!("{}/items", path))?,
+                None => Schema::new(SchemaKind::Any),
+            }),
+            min_items: size(object, "minItems"),
+            max_items: size(object, "maxItems"),
+            unique_items: object.get("uniqueItems").and_then(Json::as_bool),
+        }),
+    }
+}
+
+fn object(object: &Map<String, Json>, path: &str) -> Result<SchemaKind, ImportError> {
+    let mut properties = IndexMap::new();
+    if let Some(props) = object.get("properties") {
+        let Json::Object(props) = props else {
+            return Err(unsupported(path, "properties must be an object"));
+        };
+        for (name, prop) in props {
+            let prop_path = format!("{}/properties/{}", path, escape(name));
+            properties.insert(name.clone(), convert(prop, &prop_path)?);
+        }
+    }
+    let required = match object.get("required") {
+        Some(Json::Array(names)) => names
+            .iter()
+            .filter_map(Json::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    // `Type.Record` puts its value schema under a key pattern.
+    let additional_properties = match (
+        object.get("patternProperties"),
+        object.get("additionalProperties"),
+    ) {
+        (Some(Json::Object(patterns)), _) if patterns.len() <= 1 => match patterns.iter().next() {
+            Some((pattern, values)) => {
+                let values_path = format!("{}/patternProperties/{}", path, escape(pattern));
+                Some(Box::new(convert(values, &values_path)?))
+            }
+            None => Some(Box::new(Schema::new(SchemaKind::Any))),
+        },
+        (Some(_), _) => {
+            return Err(unsupported(
+                path,
+                "patternProperties with more than one pattern",
+            ))
+        }
+        (None, Some(Json::Bool(false))) => None,
+        (None, None | Some(Json::Bool(true))) => Some(Box::new(Schema::new(SchemaKind::Any))),
+        (None, Some(values)) => Some(Box::new(convert(
+            values,
+            &format!("{}/additionalProperties", path),
+        )?)),
+    };
+
+    Ok(SchemaKind::Object {
+        properties,
+        required,
+        additional_properties,
+    })
+}
+
+/// Copies the annotations of `object` onto `schema`.
+fn annotate(
+    mut schema: Schema,
+    object: &Map<String, Json>,
+    path: &str,
+) -> Result<Schema, ImportError> {
+    let text = |key: &str| object.get(key).and_then(Json::as_str).map(str::to_string);
+    schema.schema_version = text("$schema");
+    schema.title = text("title");
+    schema.description = text("description");
+    schema.default = object.get("default").map(|json| value(json, &schema));
+    schema.examples = match object.get("examples") {
+        Some(Json::Array(examples)) => Some(examples.iter().map(|e| value(e, &schema)).collect()),
+        Some(_) => return Err(unsupported(path, "examples must be an array")),
+        None => None,
+    };
+    schema.read_only = object.get("readOnly").and_then(Json::as_bool);
+    schema.write_only = object.get("writeOnly").and_then(Json::as_bool);
+    schema.deprecated = object.get("deprecated").and_then(Json::as_bool);
+    Ok(schema)
+}
+
+fn list(json: &Json, path: &str) -> Result<Vec<Schema>, ImportError> {
+    let Json::Array(items) = json else {
+        return Err(unsupported(path, "expected an array of schemas"));
+    };
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| convert(item, &format!("{}/{}", path, i)))
+        .collect()
+}
+
+fn literal(json: &Json, path: &str) -> Result<LiteralValue, ImportError> {
+    match json {
+        Json::String(s) => Ok(LiteralValue::String(s.clone())),
+        Json::Bool(b) => Ok(LiteralValue::Boolean(*b)),
+        Json::Null => Ok(LiteralValue::Null),
+        Json::Number(n) => match n.as_i64() {
+            Some(n) => Ok(LiteralValue::Number(n)),
+            None => Ok(LiteralValue::Float(n.as_f64().unwrap_or_default())),
+        },
+        _ => Err(unsupported(path, "literal must be a scalar")),
+    }
+}
+
+/// `json` as a value of `schema`, or as its plain JSON shape if it is not
+/// one.
+fn value(json: &Json, schema: &Schema) -> Value {
+    Value::from_json(json.clone(), schema).unwrap_or_else(|_| untyped(json))
+}
+
+fn untyped(json: &Json) -> Value {
+    match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Bool(*b),
+        Json::Number(n) => match n.as_i64() {
+            Some(n) => Value::Int64(n),
+            None => Value::Float64(n.as_f64().unwrap_or_default()),
+        },
+        Json::String(s) => Value::String(s.clone()),
+        Json::Array(items) => Value::Array(items.iter().map(untyped).collect()),
+        Json::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), untyped(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn format(name: &str) -> StringFormat {
+    serde_json::from_value(Json::String(name.to_string()))
+        .unwrap_or_else(|_| StringFormat::Custom(name.to_string()))
+}
+
+fn size(object: &Map<String, Json>, key: &str) -> Option<usize> {
+    object
+        .get(key)
+        .and_then(Json::as_u64)
+        .and_then(|n| usize::try_from(n).ok())
+}
+
+fn float(object: &Map<String, Json>, key: &str) -> Option<f64> {
+    object.get(key).and_then(Json::as_f64)
+}
+
+fn as_i128(json: &Json) -> Option<i128> {
+    json.as_i64()
+        .map(i128::from)
+        .or_else(|| json.as_u64().map(i128::from))
+        .or_else(|| {
+            json.as_f64()
+                .filter(|f| f.fract() == 0.0)
+                .map(|f| f as i128)
+        })
+}
+
+/// Escapes a JSON Pointer token.
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unsupported(path: &str, message: &str) -> ImportError {
+    ImportError::Unsupported {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Replaces `$ref`s to `from` with `to` in `schema`, returning whether there
+/// were any.
+fn rewrite_refs(schema: &mut Schema, from: &str, to: &str) -> bool {
+    struct Rewrite<'a> {
+        from: &'a str,
+        to: &'a str,
+        found: bool,
+    }
+
+    impl SchemaVisitorMut for Rewrite<'_> {
+        fn visit_schema_mut(&mut self, schema: &mut Schema, path: &mut SchemaPath) {
+            if let SchemaKind::Ref { reference } = &mut schema.kind {
+                if reference == self.from {
+                    *reference = self.to.to_string();
+                    self.found = true;
+                }
+            }
+            walk_schema_mut(self, schema, path);
+        }
+    }
+
+    let mut rewrite = Rewrite {
+        from,
+        to,
+        found: false,
+    };
+    schema.accept_mut(&mut rewrite);
+    rewrite.found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use serde_json::json;
+
+    #[test]
+    fn test_import_object() {
+        let schema = from_typebox(&json!({
+            "$id": "Person",
+            "type": "object",
+            "description": "A person.",
+            "properties": {
+                "id": {"type": "integer", "minimum": 0},
+                "email": {"type": "string", "format": "email", "maxLength": 64},
+                "age": {"type": "integer", "minimum": 0, "maximum": 255},
+                "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true},
+                "avatar": {"type": "Uint8Array", "maxByteLength": 1024},
+                "born": {"type": "Date"},
+                "labels": {"type": "object", "patternProperties": {"^(.*)$": {"type": "number"}}}
+            },
+            "required": ["id", "tags"],
+            "additionalProperties": false
+        }))
+        .unwrap();
+
+        let expected = SchemaBuilder::object()
+            .field(
+                "id",
+                Schema::new(SchemaKind::Int64 {
+                    minimum: Some(0),
+                    maximum: None,
+                }),
+            )
+            .optional_field(
+                "email",
+                SchemaBuilder::string()
+                    .format(StringFormat::Email)
+                    .max_length(64)
+                    .build(),
+            )
+            .optional_field("age", SchemaBuilder::uint8())
+            .field(
+                "tags",
+                SchemaBuilder::array(SchemaBuilder::string().build())
+                    .unique_items(true)
+                    .build(),
+            )
+            .optional_field(
+                "avatar",
+                Schema::new(SchemaKind::Bytes {
+                    min_length: None,
+                    max_length: Some(1024),
+                }),
+            )
+            .optional_field(
+                "born",
+                SchemaBuilder::string()
+                    .format(StringFormat::DateTime)
+                    .build(),
+            )
+            .optional_field(
+                "labels",
+                SchemaBuilder::object()
+                    .additional_properties(Some(SchemaBuilder::float64()))
+                    .build(),
+            )
+            .build()
+            .with_id("Person")
+            .with_description("A person.");
+        assert_eq!(schema, expected);
+    }
+
+    #[test]
+    fn test_import_typebox_kinds() {
+        let import = |json: Json| from_typebox(&json).unwrap().kind;
+
+        assert_eq!(
+            import(
+                json!({"anyOf": [{"const": "a", "type": "string"}, {"const": "b", "type": "string"}]})
+            ),
+            SchemaKind::Enum {
+                values: vec!["a".to_string(), "b".to_string()]
+            }
+        );
+        assert_eq!(
+            import(json!({"anyOf": [{"type": "string"}, {"type": "null"}]})),
+            SchemaBuilder::optional(SchemaBuilder::string().build()).kind
+        );
+        assert_eq!(
+            import(json!({"const": 42})),
+            SchemaKind::Literal {
+                value: LiteralValue::Number(42)
+            }
+        );
+        assert_eq!(
+            import(
+                json!({"type": "array", "items": [{"type": "boolean"}, {"type": "null"}], "additionalItems": false})
+            ),
+            SchemaBuilder::tuple(vec![SchemaBuilder::bool(), SchemaBuilder::null()]).kind
+        );
+        assert_eq!(
+            import(
+                json!({"type": "Function", "parameters": [{"type": "string"}], "returns": {"type": "void"}})
+            ),
+            SchemaBuilder::function(vec![SchemaBuilder::string().build()], SchemaBuilder::void())
+                .kind
+        );
+        assert_eq!(import(json!({"type": "undefined"})), SchemaKind::Undefined);
+        assert_eq!(
+            import(json!({"type": "null", "typeOf": "Void"})),
+            SchemaKind::Void
+        );
+        assert_eq!(import(json!({"not": {}})), SchemaKind::Never);
+        assert_eq!(import(json!({})), SchemaKind::Any);
+        assert_eq!(
+            import(json!({"type": "integer", "minimum": -2147483648_i64, "maximum": 2147483647})),
+            SchemaKind::Int32 {
+                minimum: None,
+                maximum: None
+            }
+        );
+
+        let err = from_typebox(&json!({"type": "object", "properties": {"s": {"type": "symbol"}}}))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unsupported schema at #/properties/s: type `symbol`"
+        );
+    }
+
+    #[test]
+    fn test_import_recursive() {
+        let node = json!({
+            "$id": "Node",
+            "type": "object",
+            "properties": {
+                "children": {"type": "array", "items": {"$ref": "Node"}}
+            },
+            "required": ["children"]
+        });
+        let legacy = json!({
+            "$id": "Node",
+            "$ref": "Node#/$defs/self",
+            "$defs": {"self": {
+                "type": "object",
+                "properties": {
+                    "children": {"type": "array", "items": {"$ref": "Node#/$defs/self"}}
+                },
+                "required": ["children"]
+            }}
+        });
+
+        let expected = SchemaBuilder::recursive("Node", |this| {
+            SchemaBuilder::object()
+                .field("children", SchemaBuilder::array(this).build())
+                .additional_properties(Some(Schema::new(SchemaKind::Any)))
+                .build()
+        });
+        assert_eq!(from_typebox(&node).unwrap(), expected);
+        assert_eq!(from_typebox(&legacy).unwrap(), expected);
+
+        let tree = Value::object()
+            .field(
+                "children",
+                Value::Array(vec![Value::object()
+                    .field("children", Value::Array(vec![]))
+                    .build()]),
+            )
+            .build();
+        assert!(crate::validate(&expected, &tree).is_ok());
+    }
+}
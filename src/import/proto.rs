@@ -0,0 +1,1138 @@
+//! Import of Protocol Buffers (`.proto`) definitions.
+
+use crate::error::ImportError;
+use crate::registry::SchemaRegistry;
+use crate::schema::{Schema, SchemaKind, StringFormat};
+use crate::SchemaBuilder;
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, HashSet};
+
+/// Field numbers by message name, then by field or `oneof` member name, as
+/// they appear in a `.proto` file.
+pub type FieldNumbers = BTreeMap<String, BTreeMap<String, u32>>;
+
+/// Parses `.proto` text into a registry holding a definition per message
+/// and enum, so `ProtoGenerator` output and hand-written files can be
+/// validated against and generated from like any other schema.
+///
+/// | `.proto` | `SchemaKind` |
+/// |---|---|
+/// | `message` | `Object` |
+/// | `repeated T` | `Array` |
+/// | `map<K, V>` | `Object` with `additional_properties` |
+/// | `oneof` | `Union` of its members, under the `oneof`'s name |
+/// | `enum` | `Enum` |
+/// | `int32`, `sint64`, `fixed32`, ... | `Int32`, `Int64`, `UInt32`, ... |
+/// | `bytes` | `Bytes` |
+/// | `google.protobuf.Timestamp` | `String` with the `date-time` format |
+/// | `google.protobuf.Value`, `Any` | `Any` |
+/// | `google.protobuf.StringValue`, ... | the scalar or `null` |
+///
+/// Nested messages and enums are named by joining their enclosing names,
+/// so `Person.Address` becomes `PersonAddress`. Properties take the field's
+/// `json_name` if it has one. Singular fields are required unless marked
+/// `optional`; `repeated`, `map` and `oneof` fields are optional, as
+/// protobuf's JSON form omits them when empty. Enum values drop the
+/// `{ENUM}_` prefix and are lowercased, and a zero `{ENUM}_UNSPECIFIED`
+/// value is dropped, undoing what `ProtoGenerator` does to them. Leading
+/// comments become descriptions and `deprecated` options carry over;
+/// services, extensions and other options are skipped. Field numbers are
+/// not part of the schemas; [`from_proto_with_numbers`] returns them too.
+///
+/// # Errors
+///
+/// Returns [`ImportError::Syntax`] for malformed input, and
+/// [`ImportError::Unsupported`] for `group`s and references to types that
+/// are neither declared in `text` nor well-known.
+///
+/// # Examples
+///
+/// ```
+/// use typebox::import::from_proto;
+/// use typebox::{check, Value};
+///
+/// let registry = from_proto(r#"
+///     syntax = "proto3";
+///
+///     message User {
+///       int64 id = 1;
+///       optional string email = 2;
+///       repeated string roles = 3;
+///     }
+/// "#).unwrap();
+///
+/// let user = Value::object()
+///     .field("id", Value::int64(7))
+///     .field("roles", Value::array(vec![Value::string("admin")]))
+///     .build();
+/// assert!(check(registry.get("User").unwrap(), &user));
+/// ```
+pub fn from_proto(text: &str) -> Result<SchemaRegistry, ImportError> {
+    Ok(from_proto_with_numbers(text)?.0)
+}
+
+/// Parses `.proto` text as [`from_proto`] does, and returns the field
+/// numbers of each message as well, by registry name and then field name.
+/// They are in the form `ProtoGenerator::with_field_numbers` takes, so
+/// regenerating an imported file keeps its numbers.
+///
+/// # Errors
+///
+/// As [`from_proto`].
+pub fn from_proto_with_numbers(text: &str) -> Result<(SchemaRegistry, FieldNumbers), ImportError> {
+    let tokens = lex(text)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        package: None,
+        messages: Vec::new(),
+        enums: Vec::new(),
+    };
+    parser.file()?;
+
+    let names: HashSet<String> = parser
+        .messages
+        .iter()
+        .map(|m| m.name.clone())
+        .chain(parser.enums.iter().map(|e| e.name.clone()))
+        .collect();
+    let context = Context {
+        package: parser.package.as_deref(),
+        names: &names,
+    };
+
+    let mut registry = SchemaRegistry::new();
+    let mut numbers = BTreeMap::new();
+    for message in &parser.messages {
+        registry.register(def_name(&message.name), context.message(message)?);
+        numbers.insert(def_name(&message.name), field_numbers(message));
+    }
+    for declaration in &parser.enums {
+        registry.register(def_name(&declaration.name), enum_schema(declaration));
+    }
+    Ok((registry, numbers))
+}
+
+/// The number of each field and `oneof` member of `message`, by name.
+fn field_numbers(message: &MessageDecl) -> BTreeMap<String, u32> {
+    let mut numbers = BTreeMap::new();
+    for item in &message.items {
+        let fields = match item {
+            Item::Field(field) => std::slice::from_ref(field),
+            Item::Oneof { fields, .. } => fields.as_slice(),
+        };
+        for field in fields {
+            numbers.insert(field.name.clone(), field.number);
+        }
+    }
+    numbers
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Symbol(char),
+}
+
+struct Lexed {
+    token: Token,
+    line: usize,
+    /// The comment block directly above the token.
+    comments: Vec<String>,
+}
+
+fn lex(text: &str) -> Result<Vec<Lexed>, ImportError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<Lexed> = Vec::new();
+    let mut comments: Vec<String> = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            // A blank line detaches the comments above it.
+            if i > 0
+                && chars[..i]
+                    .iter()
+                    .rev()
+                    .take_while(|c| **c != '\n')
+                    .all(|c| c.is_whitespace())
+            {
+                comments.clear();
+            }
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            let end = chars[i..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(chars.len(), |p| i + p);
+            let comment: String = chars[i + 2..end].iter().collect();
+            // Comments after a token on its line describe that token.
+            if tokens.last().is_none_or(|t| t.line != line) {
+                comments.push(strip_comment(&comment));
+            }
+            i = end;
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let start_line = line;
+            let mut j = i + 2;
+            while j < chars.len() && !(chars[j] == '*' && chars.get(j + 1) == Some(&'/')) {
+                if chars[j] == '\n' {
+                    line += 1;
+                }
+                j += 1;
+            }
+            if j >= chars.len() {
+                return Err(syntax(start_line, "unterminated comment"));
+            }
+            let comment: String = chars[i + 2..j].iter().collect();
+            if tokens.last().is_none_or(|t| t.line != start_line) {
+                comments.extend(
+                    comment
+                        .lines()
+                        .map(|l| strip_comment(l.trim_start().trim_start_matches('*'))),
+                );
+            }
+            i = j + 2;
+        } else if c.is_alphabetic()
+            || c == '_'
+            || (c == '.' && chars.get(i + 1).is_some_and(|c| c.is_alphabetic()))
+        {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            tokens.push(Lexed {
+                token: Token::Ident(chars[start..i].iter().collect()),
+                line,
+                comments: std::mem::take(&mut comments),
+            });
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric()
+                    || chars[i] == '.'
+                    || ((chars[i] == '-' || chars[i] == '+') && matches!(chars[i - 1], 'e' | 'E')))
+            {
+                i += 1;
+            }
+            tokens.push(Lexed {
+                token: Token::Number(chars[start..i].iter().collect()),
+                line,
+                comments: std::mem::take(&mut comments),
+            });
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            let mut j = i + 1;
+            loop {
+                match chars.get(j) {
+                    None | Some('\n') => return Err(syntax(line, "unterminated string")),
+                    Some(q) if *q == c => break,
+                    Some('\\') => {
+                        j += 1;
+                        match chars.get(j) {
+                            Some('n') => value.push('\n'),
+                            Some('r') => value.push('\r'),
+                            Some('t') => value.push('\t'),
+                            Some(other) => value.push(*other),
+                            None => return Err(syntax(line, "unterminated string")),
+                        }
+                    }
+                    Some(other) => value.push(*other),
+                }
+                j += 1;
+            }
+            tokens.push(Lexed {
+                token: Token::Str(value),
+                line,
+                comments: std::mem::take(&mut comments),
+            });
+            i = j + 1;
+        } else if "{}[]<>()=;,:-+".contains(c) {
+            tokens.push(Lexed {
+                token: Token::Symbol(c),
+                line,
+                comments: std::mem::take(&mut comments),
+            });
+            i += 1;
+        } else {
+            return Err(syntax(line, &format!("unexpected character `{}`", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+fn strip_comment(comment: &str) -> String {
+    comment
+        .strip_prefix(' ')
+        .unwrap_or(comment)
+        .trim_end()
+        .to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Label {
+    None,
+    Optional,
+    Required,
+    Repeated,
+}
+
+enum FieldType {
+    Named(String),
+    Map(String),
+}
+
+struct FieldDecl {
+    label: Label,
+    field_type: FieldType,
+    name: String,
+    number: u32,
+    json_name: Option<String>,
+    deprecated: bool,
+    doc: Option<String>,
+}
+
+enum Item {
+    Field(FieldDecl),
+    Oneof {
+        name: String,
+        doc: Option<String>,
+        fields: Vec<FieldDecl>,
+    },
+}
+
+struct MessageDecl {
+    /// Dotted name within the file, such as `Person.Address`.
+    name: String,
+    doc: Option<String>,
+    deprecated: bool,
+    items: Vec<Item>,
+}
+
+struct EnumDecl {
+    name: String,
+    doc: Option<String>,
+    deprecated: bool,
+    values: Vec<(String, i64)>,
+}
+
+struct Parser {
+    tokens: Vec<Lexed>,
+    pos: usize,
+    package: Option<String>,
+    messages: Vec<MessageDecl>,
+    enums: Vec<EnumDecl>,
+}
+
+impl Parser {
+    fn file(&mut self) -> Result<(), ImportError> {
+        while self.pos < self.tokens.len() {
+            let keyword = self.ident()?;
+            match keyword.as_str() {
+                "syntax" | "edition" => {
+                    self.symbol('=')?;
+                    self.string()?;
+                    self.symbol(';')?;
+                }
+                "package" => {
+                    self.package = Some(self.ident()?);
+                    self.symbol(';')?;
+                }
+                "import" => {
+                    if self.peek_ident("public") || self.peek_ident("weak") {
+                        self.pos += 1;
+                    }
+                    self.string()?;
+                    self.symbol(';')?;
+                }
+                "option" => self.skip_statement()?,
+                "message" => self.message("")?,
+                "enum" => self.enumeration("")?,
+                "service" | "extend" => self.skip_block()?,
+                _ => return Err(self.error_before(&format!("unexpected `{}`", keyword))),
+            }
+            while self.eat(';') {}
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, scope: &str) -> Result<(), ImportError> {
+        let doc = self.doc(1);
+        let name = format!("{}{}", scope, self.ident()?);
+        self.symbol('{')?;
+        let mut message = MessageDecl {
+            name: name.clone(),
+            doc,
+            deprecated: false,
+            items: Vec::new(),
+        };
+        let nested = format!("{}.", name);
+        while !self.eat('}') {
+            if self.eat(';') {
+                continue;
+            }
+            let keyword = self.peek_word()?;
+            match keyword.as_str() {
+                "message" => {
+                    self.pos += 1;
+                    self.message(&nested)?;
+                }
+                "enum" => {
+                    self.pos += 1;
+                    self.enumeration(&nested)?;
+                }
+                "oneof" => {
+                    let doc = self.doc(0);
+                    self.pos += 1;
+                    let name = self.ident()?;
+                    self.symbol('{')?;
+                    let mut fields = Vec::new();
+                    while !self.eat('}') {
+                        if self.eat(';') {
+                            continue;
+                        }
+                        if self.peek_ident("option") {
+                            self.skip_statement()?;
+                            continue;
+                        }
+                        fields.push(self.field(&name)?);
+                    }
+                    message.items.push(Item::Oneof { name, doc, fields });
+                }
+                "option" => {
+                    self.pos += 1;
+                    let option = self.ident()?;
+                    self.symbol('=')?;
+                    let value = self.option_value()?;
+                    self.symbol(';')?;
+                    if option == "deprecated" && value == "true" {
+                        message.deprecated = true;
+                    }
+                }
+                "reserved" | "extensions" => self.skip_statement()?,
+                "extend" => {
+                    self.pos += 1;
+                    self.skip_block()?;
+                }
+                _ => {
+                    let field = self.field(&name)?;
+                    message.items.push(Item::Field(field));
+                }
+            }
+        }
+        self.messages.push(message);
+        Ok(())
+    }
+
+    fn field(&mut self, message: &str) -> Result<FieldDecl, ImportError> {
+        let doc = self.doc(0);
+        let label = match self.peek_word()?.as_str() {
+            "optional" => Label::Optional,
+            "required" => Label::Required,
+            "repeated" => Label::Repeated,
+            _ => Label::None,
+        };
+        if label != Label::None {
+            self.pos += 1;
+        }
+        let type_name = self.ident()?;
+        let field_type = if type_name == "map" && self.eat('<') {
+            self.ident()?;
+            self.symbol(',')?;
+            let value = self.ident()?;
+            self.symbol('>')?;
+            FieldType::Map(value)
+        } else if type_name == "group" {
+            let name = self.ident()?;
+            return Err(ImportError::Unsupported {
+                path: format!("{}.{}", message, name),
+                message: "groups are not supported".to_string(),
+            });
+        } else {
+            FieldType::Named(type_name)
+        };
+        let name = self.ident()?;
+        self.symbol('=')?;
+        let number = self.number()?;
+        let mut field = FieldDecl {
+            label,
+            field_type,
+            name,
+            number,
+            json_name: None,
+            deprecated: false,
+            doc,
+        };
+        if self.eat('[') {
+            loop {
+                let option = self.option_name()?;
+                self.symbol('=')?;
+                let value = self.option_value()?;
+                match option.as_str() {
+                    "json_name" => field.json_name = Some(value),
+                    "deprecated" => field.deprecated = value == "true",
+                    _ => {}
+                }
+                if !self.eat(',') {
+                    break;
+                }
+            }
+            self.symbol(']')?;
+        }
+        self.symbol(';')?;
+        Ok(field)
+    }
+
+    fn enumeration(&mut self, scope: &str) -> Result<(), ImportError> {
+        let doc = self.doc(1);
+        let name = format!("{}{}", scope, self.ident()?);
+        self.symbol('{')?;
+        let mut declaration = EnumDecl {
+            name,
+            doc,
+            deprecated: false,
+            values: Vec::new(),
+        };
+        while !self.eat('}') {
+            if self.eat(';') {
+                continue;
+            }
+            let value = self.ident()?;
+            match value.as_str() {
+                "option" => {
+                    let option = self.ident()?;
+                    self.symbol('=')?;
+                    let value = self.option_value()?;
+                    self.symbol(';')?;
+                    if option == "deprecated" && value == "true" {
+                        declaration.deprecated = true;
+                    }
+                }
+                "reserved" => {
+                    self.pos -= 1;
+                    self.skip_statement()?;
+                }
+                _ => {
+                    self.symbol('=')?;
+                    let number = self.integer()?;
+                    if self.eat('[') {
+                        self.skip_until(']')?;
+                    }
+                    self.symbol(';')?;
+                    declaration.values.push((value, number));
+                }
+            }
+        }
+        self.enums.push(declaration);
+        Ok(())
+    }
+
+    /// The description from the comments above the token `offset` tokens
+    /// back, which is where a declaration's keyword is.
+    fn doc(&self, offset: usize) -> Option<String> {
+        let token = self.tokens.get(self.pos.checked_sub(offset)?)?;
+        let doc = token.comments.join("\n");
+        let doc = doc.trim();
+        (!doc.is_empty()).then(|| doc.to_string())
+    }
+
+    fn next(&mut self) -> Result<&Lexed, ImportError> {
+        let line = self.tokens.last().map_or(1, |t| t.line);
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| syntax(line, "unexpected end of input"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn ident(&mut self) -> Result<String, ImportError> {
+        let lexed = self.next()?;
+        match &lexed.token {
+            Token::Ident(name) => Ok(name.clone()),
+            other => Err(syntax(
+                lexed.line,
+                &format!("expected a name, found {}", describe(other)),
+            )),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ImportError> {
+        let lexed = self.next()?;
+        match &lexed.token {
+            Token::Str(value) => Ok(value.clone()),
+            other => Err(syntax(
+                lexed.line,
+                &format!("expected a string, found {}", describe(other)),
+            )),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, ImportError> {
+        let negative = self.eat('-');
+        let lexed = self.next()?;
+        let parsed = match &lexed.token {
+            Token::Number(text) => {
+                match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                    Some(hex) => i64::from_str_radix(hex, 16).ok(),
+                    None if text.len() > 1 && text.starts_with('0') => {
+                        i64::from_str_radix(&text[1..], 8).ok()
+                    }
+                    None => text.parse().ok(),
+                }
+            }
+            _ => None,
+        };
+        let line = lexed.line;
+        parsed
+            .map(|n| if negative { -n } else { n })
+            .ok_or_else(|| syntax(line, "expected an integer"))
+    }
+
+    fn number(&mut self) -> Result<u32, ImportError> {
+        let line = self.tokens.get(self.pos).map_or(0, |t| t.line);
+        let number = self.integer()?;
+        u32::try_from(number)
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| syntax(line, &format!("{} is not a valid field number", number)))
+    }
+
+    fn symbol(&mut self, symbol: char) -> Result<(), ImportError> {
+        let lexed = self.next()?;
+        match &lexed.token {
+            Token::Symbol(c) if *c == symbol => Ok(()),
+            other => Err(syntax(
+                lexed.line,
+                &format!("expected `{}`, found {}", symbol, describe(other)),
+            )),
+        }
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        let matched = matches!(
+            self.tokens.get(self.pos),
+            Some(Lexed { token: Token::Symbol(c), .. }) if *c == symbol
+        );
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn peek_ident(&self, name: &str) -> bool {
+        matches!(
+            self.tokens.get(self.pos),
+            Some(Lexed { token: Token::Ident(ident), .. }) if ident == name
+        )
+    }
+
+    /// The next token if it is a name, without consuming it.
+    fn peek_word(&self) -> Result<String, ImportError> {
+        match self.tokens.get(self.pos) {
+            Some(Lexed {
+                token: Token::Ident(name),
+                ..
+            }) => Ok(name.clone()),
+            Some(lexed) => Err(syntax(
+                lexed.line,
+                &format!("expected a declaration, found {}", describe(&lexed.token)),
+            )),
+            None => Err(self.error_before("unexpected end of input")),
+        }
+    }
+
+    /// An option name: a name, or a parenthesized extension name followed
+    /// by an optional field path.
+    fn option_name(&mut self) -> Result<String, ImportError> {
+        if self.eat('(') {
+            let name = self.ident()?;
+            self.symbol(')')?;
+            let mut name = format!("({})", name);
+            if let Some(Lexed {
+                token: Token::Ident(path),
+                ..
+            }) = self.tokens.get(self.pos)
+            {
+                if path.starts_with('.') {
+                    name.push_str(path);
+                    self.pos += 1;
+                }
+            }
+            Ok(name)
+        } else {
+            self.ident()
+        }
+    }
+
+    /// An option value as text; message-valued options are skipped.
+    fn option_value(&mut self) -> Result<String, ImportError> {
+        if self.eat('{') {
+            self.skip_until('}')?;
+            return Ok(String::new());
+        }
+        let sign = if self.eat('-') { "-" } else { "" };
+        let lexed = self.next()?;
+        match &lexed.token {
+            Token::Ident(text) | Token::Number(text) | Token::Str(text) => {
+                Ok(format!("{}{}", sign, text))
+            }
+            Token::Symbol(c) => Err(syntax(lexed.line, &format!("unexpected `{}`", c))),
+        }
+    }
+
+    /// Skips to the `;` ending the current statement.
+    fn skip_statement(&mut self) -> Result<(), ImportError> {
+        self.skip_until(';')
+    }
+
+    /// Skips past the next `close` outside nested braces and brackets.
+    fn skip_until(&mut self, close: char) -> Result<(), ImportError> {
+        let mut depth = 0usize;
+        loop {
+            match &self.next()?.token {
+                Token::Symbol(c) if *c == close && depth == 0 => return Ok(()),
+                Token::Symbol('{' | '[') => depth += 1,
+                Token::Symbol('}' | ']') => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips a declaration with a braced body, such as a `service`.
+    fn skip_block(&mut self) -> Result<(), ImportError> {
+        while !self.eat('{') {
+            self.next()?;
+        }
+        self.skip_until('}')
+    }
+
+    fn error_before(&self, message: &str) -> ImportError {
+        let line = self
+            .tokens
+            .get(self.pos.saturating_sub(1))
+            .map_or(1, |t| t.line);
+        syntax(line, message)
+    }
+}
+
+/// Name resolution and conversion of the parsed declarations.
+struct Context<'a> {
+    package: Option<&'a str>,
+    /// Dotted names of the declared messages and enums.
+    names: &'a HashSet<String>,
+}
+
+impl Context<'_> {
+    fn message(&self, message: &MessageDecl) -> Result<Schema, ImportError> {
+        let mut properties = IndexMap::new();
+        let mut required = Vec::new();
+        for item in &message.items {
+            match item {
+                Item::Field(field) => {
+                    let schema = self.field(message, field)?;
+                    let prop = field
+                        .json_name
+                        .clone()
+                        .unwrap_or_else(|| field.name.clone());
+                    let singular = matches!(field.field_type, FieldType::Named(_))
+                        && field.label != Label::Repeated;
+                    if singular && field.label != Label::Optional {
+                        required.push(prop.clone());
+                    }
+                    properties.insert(prop, schema);
+                }
+                Item::Oneof { name, doc, fields } => {
+                    let any_of = fields
+                        .iter()
+                        .map(|field| self.field(message, field))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let mut schema = SchemaBuilder::union(any_of);
+                    schema.description = doc.clone();
+                    properties.insert(name.clone(), schema);
+                }
+            }
+        }
+
+        let mut schema = Schema::new(SchemaKind::Object {
+            properties,
+            required,
+            additional_properties: None,
+        });
+        schema.description = message.doc.clone();
+        if message.deprecated {
+            schema.deprecated = Some(true);
+        }
+        Ok(schema)
+    }
+
+    fn field(&self, message: &MessageDecl, field: &FieldDecl) -> Result<Schema, ImportError> {
+        let path = format!("{}.{}", message.name, field.name);
+        let mut schema = match &field.field_type {
+            FieldType::Named(name) => {
+                let item = self.type_schema(name, &message.name, &path)?;
+                if field.label == Label::Repeated {
+                    SchemaBuilder::array(item).build()
+                } else {
+                    item
+                }
+            }
+            FieldType::Map(value) => SchemaBuilder::object()
+                .additional_properties(Some(self.type_schema(value, &message.name, &path)?))
+                .build(),
+        };
+        if field.doc.is_some() {
+            schema.description = field.doc.clone();
+        }
+        if field.deprecated {
+            schema.deprecated = Some(true);
+        }
+        Ok(schema)
+    }
+
+    fn type_schema(&self, name: &str, scope: &str, path: &str) -> Result<Schema, ImportError> {
+        if let Some(schema) = scalar(name) {
+            return Ok(schema);
+        }
+        let absolute = name.strip_prefix('.');
+        let name = absolute.unwrap_or(name);
+        if let Some(schema) = well_known(name.strip_prefix("google.protobuf.").unwrap_or("")) {
+            return Ok(schema);
+        }
+        let unqualified = self
+            .package
+            .and_then(|package| name.strip_prefix(package)?.strip_prefix('.'));
+
+        // Like protoc, search from the innermost enclosing scope outwards.
+        let mut candidates: Vec<String> = Vec::new();
+        if absolute.is_none() {
+            let mut scope = Some(scope);
+            while let Some(current) = scope {
+                candidates.push(format!("{}.{}", current, name));
+                scope = current.rsplit_once('.').map(|(outer, _)| outer);
+            }
+        }
+        candidates.push(name.to_string());
+        candidates.extend(unqualified.map(String::from));
+
+        match candidates.iter().find(|c| self.names.contains(*c)) {
+            Some(found) => Ok(SchemaBuilder::r#ref(&def_name(found))),
+            None => Err(ImportError::Unsupported {
+                path: path.to_string(),
+                message: format!("unknown type `{}`", name),
+            }),
+        }
+    }
+}
+
+fn enum_schema(declaration: &EnumDecl) -> Schema {
+    let short = declaration
+        .name
+        .rsplit('.')
+        .next()
+        .unwrap_or(&declaration.name);
+    let prefix = format!("{}_", screaming_snake(short));
+    let values: Vec<String> = declaration
+        .values
+        .iter()
+        .filter(|(name, number)| !(*number == 0 && name.ends_with("_UNSPECIFIED")))
+        .map(|(name, _)| name.strip_prefix(&prefix).unwrap_or(name).to_lowercase())
+        .collect();
+    let mut schema = Schema::new(SchemaKind::Enum { values });
+    schema.description = declaration.doc.clone();
+    if declaration.deprecated {
+        schema.deprecated = Some(true);
+    }
+    schema
+}
+
+fn scalar(name: &str) -> Option<Schema> {
+    Some(match name {
+        "double" => SchemaBuilder::float64(),
+        "float" => SchemaBuilder::float32(),
+        "int32" | "sint32" | "sfixed32" => SchemaBuilder::int32(),
+        "int64" | "sint64" | "sfixed64" => SchemaBuilder::int64(),
+        "uint32" | "fixed32" => SchemaBuilder::uint32(),
+        "uint64" | "fixed64" => SchemaBuilder::uint64(),
+        "bool" => SchemaBuilder::bool(),
+        "string" => SchemaBuilder::string().build(),
+        "bytes" => SchemaBuilder::bytes(),
+        _ => return None,
+    })
+}
+
+/// The schema of a `google.protobuf` type, by its JSON form.
+fn well_known(name: &str) -> Option<Schema> {
+    let nullable = |schema: Schema| Some(SchemaBuilder::optional(schema));
+    match name {
+        "Timestamp" => Some(
+            SchemaBuilder::string()
+                .format(StringFormat::DateTime)
+                .build(),
+        ),
+        "Duration" | "FieldMask" => Some(SchemaBuilder::string().build()),
+        "Value" | "Any" => Some(SchemaBuilder::any()),
+        "Struct" => Some(
+            SchemaBuilder::object()
+                .additional_properties(Some(SchemaBuilder::any()))
+                .build(),
+        ),
+        "ListValue" => Some(SchemaBuilder::array(SchemaBuilder::any()).build()),
+        "NullValue" => Some(SchemaBuilder::null()),
+        "Empty" => Some(SchemaBuilder::object().build()),
+        "DoubleValue" => nullable(SchemaBuilder::float64()),
+        "FloatValue" => nullable(SchemaBuilder::float32()),
+        "Int64Value" => nullable(SchemaBuilder::int64()),
+        "UInt64Value" => nullable(SchemaBuilder::uint64()),
+        "Int32Value" => nullable(SchemaBuilder::int32()),
+        "UInt32Value" => nullable(SchemaBuilder::uint32()),
+        "BoolValue" => nullable(SchemaBuilder::bool()),
+        "StringValue" => nullable(SchemaBuilder::string().build()),
+        "BytesValue" => nullable(SchemaBuilder::bytes()),
+        _ => None,
+    }
+}
+
+/// The registry name of a dotted declaration name: `Person.Address`
+/// becomes `PersonAddress`.
+fn def_name(name: &str) -> String {
+    name.replace('.', "")
+}
+
+/// `STATUS_CODE` for `StatusCode`.
+fn screaming_snake(name: &str) -> String {
+    let mut out = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) {
+            out.push('_');
+        }
+        out.extend(c.to_uppercase());
+        previous = Some(c);
+    }
+    out
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Ident(name) => format!("`{}`", name),
+        Token::Number(number) => format!("`{}`", number),
+        Token::Str(value) => format!("{:?}", value),
+        Token::Symbol(c) => format!("`{}`", c),
+    }
+}
+
+fn syntax(line: usize, message: &str) -> ImportError {
+    ImportError::Syntax {
+        line,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check_with_registry;
+    use crate::value::Value;
+
+    const PROTO: &str = r#"
+        syntax = "proto3";
+
+        package shop.v1;
+
+        import "google/protobuf/timestamp.proto";
+
+        option go_package = "example.com/shop";
+
+        // An order.
+        // Placed by a customer.
+        message Order {
+          // Order line.
+          message Line {
+            string sku = 1;
+            uint32 quantity = 2;
+          }
+
+          enum Status {
+            STATUS_UNSPECIFIED = 0;
+            STATUS_OPEN = 1;
+            STATUS_SHIPPED = 2 [deprecated = true];
+          }
+
+          int64 id = 1;
+          optional string note = 2; // not a description
+          repeated Line lines = 3;
+          map<string, string> labels = 4;
+          Status status = 5;
+          google.protobuf.Timestamp created_at = 6 [json_name = "createdAt"];
+          oneof payment {
+            Card card = 7;
+            string voucher = 8;
+          }
+          reserved 9, 10 to 12;
+          reserved "legacy";
+          .shop.v1.Order.Line first = 13 [deprecated = true];
+        }
+
+        message Card {
+          option deprecated = true;
+          string number = 1;
+        }
+
+        service Orders {
+          rpc Get (Order) returns (Order) { option idempotency_level = NO_SIDE_EFFECTS; }
+        }
+    "#;
+
+    #[test]
+    fn test_from_proto() {
+        let (registry, numbers) = from_proto_with_numbers(PROTO).unwrap();
+        assert_eq!(registry.len(), 4);
+
+        let order = registry.get("Order").unwrap();
+        assert_eq!(
+            order.description.as_deref(),
+            Some("An order.\nPlaced by a customer.")
+        );
+        let SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } = &order.kind
+        else {
+            panic!("expected an object");
+        };
+        let names: Vec<&str> = properties.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            [
+                "id",
+                "note",
+                "lines",
+                "labels",
+                "status",
+                "createdAt",
+                "payment",
+                "first"
+            ]
+        );
+        assert_eq!(required, &["id", "status", "createdAt", "first"]);
+        assert_eq!(numbers["Order"]["note"], 2);
+        assert_eq!(properties["note"].description, None);
+        assert_eq!(properties["first"].deprecated, Some(true));
+        assert_eq!(
+            properties["first"].kind,
+            SchemaBuilder::r#ref("OrderLine").kind
+        );
+        let SchemaKind::Union { any_of } = &properties["payment"].kind else {
+            panic!("expected a union");
+        };
+        assert_eq!(any_of[0].kind, SchemaBuilder::r#ref("Card").kind);
+        assert_eq!(numbers["Order"]["voucher"], 8);
+        assert_eq!(numbers["OrderLine"]["quantity"], 2);
+
+        assert_eq!(
+            registry.get("OrderStatus").unwrap().kind,
+            SchemaBuilder::enum_values(vec!["open", "shipped"]).kind
+        );
+        assert_eq!(
+            registry.get("OrderLine").unwrap().description.as_deref(),
+            Some("Order line.")
+        );
+        assert_eq!(registry.get("Card").unwrap().deprecated, Some(true));
+
+        let value = Value::object()
+            .field("id", Value::int64(1))
+            .field("status", Value::string("open"))
+            .field("createdAt", Value::string("2024-05-01T12:00:00Z"))
+            .field(
+                "first",
+                Value::object()
+                    .field("sku", Value::string("A-1"))
+                    .field("quantity", Value::int64(2))
+                    .build(),
+            )
+            .field("payment", Value::string("SPRING"))
+            .build();
+        assert!(check_with_registry(order, &value, Some(&registry)));
+    }
+
+    #[cfg(feature = "codegen")]
+    #[test]
+    fn test_from_proto_round_trips_generated_code() {
+        use crate::ProtoGenerator;
+
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Status",
+            SchemaBuilder::enum_values(vec!["active", "closed"]),
+        );
+        registry.register(
+            "Account",
+            SchemaBuilder::object()
+                .field("account_id", SchemaBuilder::uint64())
+                .field("status", SchemaBuilder::r#ref("Status"))
+                .optional_field("balance", SchemaBuilder::float64())
+                .field(
+                    "owners",
+                    SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+                )
+                .build(),
+        );
+        let numbers = BTreeMap::from([(
+            "Account".to_string(),
+            BTreeMap::from([("status".to_string(), 7)]),
+        )]);
+        let generated = ProtoGenerator::new()
+            .with_field_numbers(numbers)
+            .generate_module(&registry)
+            .unwrap();
+        assert!(generated.contains("  Status status = 7;\n"));
+        let (imported, numbers) = from_proto_with_numbers(&generated).unwrap();
+        assert_eq!(
+            ProtoGenerator::new()
+                .with_field_numbers(numbers)
+                .generate_module(&imported)
+                .unwrap(),
+            generated
+        );
+
+        let account = Value::object()
+            .field("account_id", Value::int64(9))
+            .field("status", Value::string("closed"))
+            .field("owners", Value::array(vec![]))
+            .build();
+        assert!(check_with_registry(
+            imported.get("Account").unwrap(),
+            &account,
+            Some(&imported)
+        ));
+    }
+
+    #[test]
+    fn test_from_proto_errors() {
+        assert_eq!(
+            from_proto("message A {\n  int32 x = ;\n}")
+                .err()
+                .map(|e| e.to_string()),
+            Some("Syntax error at line 2: expected an integer".to_string())
+        );
+        assert_eq!(
+            from_proto("message A { Missing x = 1; }")
+                .err()
+                .map(|e| e.to_string()),
+            Some("Unsupported schema at A.x: unknown type `Missing`".to_string())
+        );
+    }
+}
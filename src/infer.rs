@@ -0,0 +1,573 @@
+//! Schema inference from sample values.
+//!
+//! Builds a [`Schema`] that accepts every sample: integers get the narrowest
+//! fitting width, strings get a format or enum when every sample agrees,
+//! object fields missing from some samples become optional, and mixed types
+//! become unions.
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::infer_json;
+//! use serde_json::json;
+//!
+//! let samples = vec![
+//!     json!({"id": 1, "email": "alice@example.com", "tags": ["a"]}),
+//!     json!({"id": 300, "email": "bob@example.com", "tags": [], "nick": "bobby"}),
+//! ];
+//!
+//! let schema = infer_json(&samples);
+//! assert_eq!(
+//!     schema.to_string(),
+//!     "{email: string, id: uint16, tags: Array<string>, nick?: string}"
+//! );
+//! ```
+
+use crate::schema::{Schema, SchemaKind, StringFormat};
+use crate::value::{value_to_untyped, Value};
+use indexmap::IndexMap;
+use std::collections::BTreeSet;
+
+/// Tunable thresholds for schema inference.
+#[derive(Debug, Clone)]
+pub struct InferOptions {
+    /// Pick the narrowest integer width instead of always `Int64`.
+    pub narrow_integers: bool,
+    /// Prefer unsigned widths when no sample is negative.
+    pub prefer_unsigned: bool,
+    /// Detect string formats (uuid, date-time, date, email).
+    pub detect_formats: bool,
+    /// Turn low-cardinality strings into `Enum` schemas.
+    pub detect_enums: bool,
+    /// Maximum number of distinct strings for an enum.
+    pub enum_max_values: usize,
+    /// Minimum number of string samples before an enum is considered.
+    pub enum_min_samples: usize,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        Self {
+            narrow_integers: true,
+            prefer_unsigned: true,
+            detect_formats: true,
+            detect_enums: true,
+            enum_max_values: 8,
+            enum_min_samples: 10,
+        }
+    }
+}
+
+/// Infers a schema from sample values using default options.
+///
+/// Returns `Unknown` when there are no samples.
+pub fn infer(samples: &[Value]) -> Schema {
+    infer_with_options(samples, &InferOptions::default())
+}
+
+/// Infers a schema from sample values.
+pub fn infer_with_options(samples: &[Value], options: &InferOptions) -> Schema {
+    let mut stats = Stats::default();
+    for sample in samples {
+        stats.add(sample, options);
+    }
+    stats.to_schema(options)
+}
+
+/// Infers a schema from JSON samples using default options.
+pub fn infer_json(samples: &[serde_json::Value]) -> Schema {
+    infer_json_with_options(samples, &InferOptions::default())
+}
+
+/// Infers a schema from JSON samples.
+pub fn infer_json_with_options(samples: &[serde_json::Value], options: &InferOptions) -> Schema {
+    let values: Vec<Value> = samples.iter().cloned().map(value_to_untyped).collect();
+    infer_with_options(&values, options)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum TypedArray {
+    Float32,
+    Float64,
+    Int32,
+    Int64,
+    UInt8,
+}
+
+#[derive(Default)]
+struct Stats {
+    samples: usize,
+    nulls: usize,
+    bools: usize,
+    ints: Option<(i64, i64)>,
+    floats: usize,
+    strings: Option<StringStats>,
+    bytes: usize,
+    arrays: Option<Box<Stats>>,
+    array_count: usize,
+    typed_arrays: BTreeSet<TypedArray>,
+    objects: Option<ObjectStats>,
+}
+
+struct StringStats {
+    count: usize,
+    distinct: BTreeSet<String>,
+    overflowed: bool,
+    formats: Vec<StringFormat>,
+}
+
+struct ObjectStats {
+    count: usize,
+    fields: IndexMap<String, (usize, Stats)>,
+}
+
+impl Stats {
+    fn add(&mut self, value: &Value, options: &InferOptions) {
+        self.samples += 1;
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Bool(_) => self.bools += 1,
+            Value::Int64(n) => {
+                self.ints = Some(match self.ints {
+                    Some((min, max)) => (min.min(*n), max.max(*n)),
+                    None => (*n, *n),
+                })
+            }
+            Value::Float64(_) => self.floats += 1,
+            Value::String(s) => self.add_string(s, options),
+            Value::Bytes(_) => self.bytes += 1,
+            Value::Array(items) => {
+                self.array_count += 1;
+                let item_stats = self.arrays.get_or_insert_with(Default::default);
+                for item in items {
+                    item_stats.add(item, options);
+                }
+            }
+            Value::Object(map) => {
+                let objects = self.objects.get_or_insert_with(|| ObjectStats {
+                    count: 0,
+                    fields: IndexMap::new(),
+                });
+                objects.count += 1;
+                for (key, val) in map {
+                    let (present, field) = objects.fields.entry(key.clone()).or_default();
+                    *present += 1;
+                    field.add(val, options);
+                }
+            }
+            Value::Float32Array(_) => {
+                self.typed_arrays.insert(TypedArray::Float32);
+            }
+            Value::Float64Array(_) => {
+                self.typed_arrays.insert(TypedArray::Float64);
+            }
+            Value::Int32Array(_) => {
+                self.typed_arrays.insert(TypedArray::Int32);
+            }
+            Value::Int64Array(_) => {
+                self.typed_arrays.insert(TypedArray::Int64);
+            }
+            Value::UInt8Array(_) => {
+                self.typed_arrays.insert(TypedArray::UInt8);
+            }
+        }
+    }
+
+    fn add_string(&mut self, s: &str, options: &InferOptions) {
+        let strings = self.strings.get_or_insert_with(|| StringStats {
+            count: 0,
+            distinct: BTreeSet::new(),
+            overflowed: false,
+            formats: if options.detect_formats {
+                vec![
+                    StringFormat::Uuid,
+                    StringFormat::DateTime,
+                    StringFormat::Date,
+                    StringFormat::Email,
+                ]
+            } else {
+                vec![]
+            },
+        });
+        strings.count += 1;
+        strings.formats.retain(|format| matches_format(format, s));
+        if !strings.overflowed {
+            strings.distinct.insert(s.to_string());
+            if strings.distinct.len() > options.enum_max_values {
+                strings.overflowed = true;
+                strings.distinct.clear();
+            }
+        }
+    }
+
+    fn to_schema(&self, options: &InferOptions) -> Schema {
+        if self.samples == 0 {
+            return Schema::new(SchemaKind::Unknown);
+        }
+
+        let mut variants = Vec::new();
+
+        if self.bools > 0 {
+            variants.push(Schema::new(SchemaKind::Bool));
+        }
+        match (self.ints, self.floats) {
+            (_, floats) if floats > 0 => variants.push(Schema::new(SchemaKind::Float64 {
+                minimum: None,
+                maximum: None,
+            })),
+            (Some((min, max)), _) => variants.push(integer_schema(min, max, options)),
+            _ => {}
+        }
+        if let Some(strings) = &self.strings {
+            variants.push(strings.to_schema(options));
+        }
+        if self.bytes > 0 {
+            variants.push(Schema::new(SchemaKind::Bytes {
+                min_length: None,
+                max_length: None,
+            }));
+        }
+        if let Some(items) = &self.arrays {
+            variants.push(array_schema(items.to_schema(options)));
+        }
+        for typed in &self.typed_arrays {
+            let items = match typed {
+                TypedArray::Float32 => SchemaKind::Float32 {
+                    minimum: None,
+                    maximum: None,
+                },
+                TypedArray::Float64 => SchemaKind::Float64 {
+                    minimum: None,
+                    maximum: None,
+                },
+                TypedArray::Int32 => SchemaKind::Int32 {
+                    minimum: None,
+                    maximum: None,
+                },
+                TypedArray::Int64 => SchemaKind::Int64 {
+                    minimum: None,
+                    maximum: None,
+                },
+                TypedArray::UInt8 => SchemaKind::UInt8 {
+                    minimum: None,
+                    maximum: None,
+                },
+            };
+            variants.push(array_schema(Schema::new(items)));
+        }
+        if let Some(objects) = &self.objects {
+            variants.push(objects.to_schema(options));
+        }
+        if self.nulls > 0 {
+            variants.push(Schema::new(SchemaKind::Null));
+        }
+
+        if variants.len() == 1 {
+            variants.remove(0)
+        } else {
+            Schema::new(SchemaKind::Union { any_of: variants })
+        }
+    }
+}
+
+impl StringStats {
+    fn to_schema(&self, options: &InferOptions) -> Schema {
+        if let Some(format) = self.formats.first() {
+            return Schema::new(SchemaKind::String {
+                format: Some(format.clone()),
+                pattern: None,
+                min_length: None,
+                max_length: None,
+            });
+        }
+        if options.detect_enums
+            && !self.overflowed
+            && self.count >= options.enum_min_samples
+            && self.distinct.len() < self.count
+        {
+            return Schema::new(SchemaKind::Enum {
+                values: self.distinct.iter().cloned().collect(),
+            });
+        }
+        Schema::new(SchemaKind::String {
+            format: None,
+            pattern: None,
+            min_length: None,
+            max_length: None,
+        })
+    }
+}
+
+impl ObjectStats {
+    fn to_schema(&self, options: &InferOptions) -> Schema {
+        let mut properties = IndexMap::new();
+        let mut required = Vec::new();
+        for (name, (present, stats)) in &self.fields {
+            properties.insert(name.clone(), stats.to_schema(options));
+            if *present == self.count {
+                required.push(name.clone());
+            }
+        }
+        Schema::new(SchemaKind::Object {
+            properties,
+            required,
+            additional_properties: None,
+        })
+    }
+}
+
+fn array_schema(items: Schema) -> Schema {
+    Schema::new(SchemaKind::Array {
+        items: Box::new(items),
+        min_items: None,
+        max_items: None,
+        unique_items: None,
+    })
+}
+
+fn integer_schema(min: i64, max: i64, options: &InferOptions) -> Schema {
+    let fits = |lo: i64, hi: i64| min >= lo && max <= hi;
+    let kind = if !options.narrow_integers {
+        SchemaKind::Int64 {
+            minimum: None,
+            maximum: None,
+        }
+    } else if options.prefer_unsigned && min >= 0 {
+        if fits(0, u8::MAX as i64) {
+            SchemaKind::UInt8 {
+                minimum: None,
+                maximum: None,
+            }
+        } else if fits(0, u16::MAX as i64) {
+            SchemaKind::UInt16 {
+                minimum: None,
+                maximum: None,
+            }
+        } else if fits(0, u32::MAX as i64) {
+            SchemaKind::UInt32 {
+                minimum: None,
+                maximum: None,
+            }
+        } else {
+            SchemaKind::UInt64 {
+                minimum: None,
+                maximum: None,
+            }
+        }
+    } else if fits(i8::MIN as i64, i8::MAX as i64) {
+        SchemaKind::Int8 {
+            minimum: None,
+            maximum: None,
+        }
+    } else if fits(i16::MIN as i64, i16::MAX as i64) {
+        SchemaKind::Int16 {
+            minimum: None,
+            maximum: None,
+        }
+    } else if fits(i32::MIN as i64, i32::MAX as i64) {
+        SchemaKind::Int32 {
+            minimum: None,
+            maximum: None,
+        }
+    } else {
+        SchemaKind::Int64 {
+            minimum: None,
+            maximum: None,
+        }
+    };
+    Schema::new(kind)
+}
+
+fn matches_format(format: &StringFormat, s: &str) -> bool {
+    match format {
+        StringFormat::Uuid => is_uuid(s),
+        StringFormat::DateTime => is_date_time(s),
+        StringFormat::Date => is_date(s),
+        StringFormat::Email => is_email(s),
+        _ => false,
+    }
+}
+
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_date(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    matches!(parts.as_slice(), [y, m, d] if y.len() == 4 && m.len() == 2 && d.len() == 2
+        && is_digits(y) && is_digits(m) && is_digits(d))
+}
+
+fn is_time(s: &str) -> bool {
+    let (hms, _fraction) = s.split_once('.').unwrap_or((s, ""));
+    let parts: Vec<&str> = hms.split(':').collect();
+    matches!(parts.as_slice(), [h, m, sec] if h.len() == 2 && m.len() == 2 && sec.len() == 2
+        && is_digits(h) && is_digits(m) && is_digits(sec))
+}
+
+fn is_date_time(s: &str) -> bool {
+    let Some((date, rest)) = s.split_once(['T', 't', ' ']) else {
+        return false;
+    };
+    if !is_date(date) {
+        return false;
+    }
+    let time = if let Some(time) = rest.strip_suffix(['Z', 'z']) {
+        time
+    } else if let Some(pos) = rest.rfind(['+', '-']) {
+        let offset = &rest[pos + 1..];
+        if !(offset.len() == 5 && offset.as_bytes()[2] == b':') {
+            return false;
+        }
+        &rest[..pos]
+    } else {
+        return false;
+    };
+    is_time(time)
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !s.chars().any(char::is_whitespace)
+        && domain.split('.').count() >= 2
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::check;
+    use serde_json::json;
+
+    #[test]
+    fn test_narrowest_integer() {
+        assert_eq!(infer(&[Value::Int64(1), Value::Int64(200)]).kind(), "UInt8");
+        assert_eq!(
+            infer(&[Value::Int64(-1), Value::Int64(200)]).kind(),
+            "Int16"
+        );
+        assert_eq!(infer(&[Value::Int64(70_000)]).kind(), "UInt32");
+
+        let options = InferOptions {
+            narrow_integers: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            infer_with_options(&[Value::Int64(1)], &options).kind(),
+            "Int64"
+        );
+    }
+
+    #[test]
+    fn test_mixed_numbers_widen_to_float() {
+        let schema = infer(&[Value::Int64(1), Value::Float64(1.5)]);
+        assert_eq!(schema.kind(), "Float64");
+    }
+
+    #[test]
+    fn test_formats() {
+        let schema = infer_json(&[
+            json!("550e8400-e29b-41d4-a716-446655440000"),
+            json!("6ba7b810-9dad-11d1-80b4-00c04fd430c8"),
+        ]);
+        assert!(matches!(
+            schema.kind,
+            SchemaKind::String {
+                format: Some(StringFormat::Uuid),
+                ..
+            }
+        ));
+
+        let schema = infer_json(&[
+            json!("2024-01-15T10:30:00Z"),
+            json!("2024-01-15 10:30:00.5+02:00"),
+        ]);
+        assert!(matches!(
+            schema.kind,
+            SchemaKind::String {
+                format: Some(StringFormat::DateTime),
+                ..
+            }
+        ));
+
+        let schema = infer_json(&[json!("a@example.com"), json!("not an email")]);
+        assert!(matches!(
+            schema.kind,
+            SchemaKind::String { format: None, .. }
+        ));
+    }
+
+    #[test]
+    fn test_enum_detection() {
+        let samples: Vec<_> = ["open", "closed", "open", "pending", "open"]
+            .iter()
+            .map(|s| json!(s))
+            .collect();
+        let options = InferOptions {
+            enum_min_samples: 5,
+            ..Default::default()
+        };
+
+        let schema = infer_json_with_options(&samples, &options);
+        assert_eq!(schema.to_string(), "\"closed\" | \"open\" | \"pending\"");
+
+        let schema = infer_json(&samples);
+        assert_eq!(schema.kind(), "String");
+    }
+
+    #[test]
+    fn test_optional_and_nullable_fields() {
+        let samples = vec![
+            json!({"id": 1, "name": null}),
+            json!({"id": 2, "name": "x", "extra": true}),
+        ];
+
+        let schema = infer_json(&samples);
+        assert_eq!(
+            schema.to_string(),
+            "{id: uint8, name: string | null, extra?: boolean}"
+        );
+    }
+
+    #[test]
+    fn test_union_of_mixed_types() {
+        let schema = infer_json(&[json!(1), json!("a"), json!([true])]);
+        assert_eq!(schema.to_string(), "uint8 | string | Array<boolean>");
+    }
+
+    #[test]
+    fn test_typed_arrays() {
+        let schema = infer(&[Value::Float32Array(vec![1.0]), Value::Float32Array(vec![])]);
+        assert_eq!(schema.to_string(), "Array<float32>");
+    }
+
+    #[test]
+    fn test_samples_validate() {
+        let samples = vec![
+            json!({"users": [{"id": 1, "tags": ["a", "b"]}, {"id": -5}]}),
+            json!({"users": [], "cursor": "abc"}),
+        ];
+
+        let schema = infer_json(&samples);
+        for sample in samples {
+            assert!(check(&schema, &value_to_untyped(sample)));
+        }
+    }
+
+    #[test]
+    fn test_no_samples() {
+        assert_eq!(infer(&[]).kind(), "Unknown");
+    }
+}
@@ -30,43 +30,73 @@
 //!
 //! # Feature Flags
 //!
-//! - `codegen` - Generate Rust/TypeScript code from schemas
+//! - `codegen` - Generate Rust, TypeScript, TypeBox, Zod, Pydantic, Go, Protocol Buffers and GraphQL code from schemas
 //! - `fake` - Generate random test data (requires `fake` and `rand` crates)
 //! - `pattern` - Regex pattern validation for strings
+//! - `derive` - `#[derive(TypeBox)]` for generating schemas from Rust types
+//! - `yaml` - Load YAML schema documents with `SchemaRegistry::load_dir`
 
+pub mod avro;
 pub mod builder;
 pub mod error;
+pub mod fingerprint;
 pub mod format;
+pub mod import;
+pub mod infer;
 pub mod layout;
+pub mod lint;
+pub mod migrate;
+pub mod reflect;
 pub mod registry;
 pub mod schema;
+pub mod sql;
 pub mod validate;
 pub mod value;
+pub mod visit;
 
 #[cfg(feature = "codegen")]
 #[doc(hidden)]
 pub mod codegen;
 
+#[doc(hidden)]
+pub mod __private;
+
 pub use builder::SchemaBuilder;
-pub use error::{CastError, CleanError, CreateError, Error, PatchError};
+pub use error::{
+    AvroError, CastError, CleanError, CreateError, Error, ImportError, LintError, LoadError,
+    PatchError, SqlError, VersionError,
+};
+pub use fingerprint::{fingerprint, fingerprint_with_options, Fingerprint, FingerprintOptions};
 pub use format::{FormatRegistry, FormatValidator};
+pub use infer::{infer, infer_json, infer_json_with_options, infer_with_options, InferOptions};
 pub use layout::Layout;
-pub use registry::SchemaRegistry;
+pub use lint::{lint, lint_with_registry, verify, Diagnostic, Severity};
+pub use migrate::{Migration, Migrator};
+pub use reflect::TypeBoxSchema;
+pub use registry::{Bundle, DependencyGraph, SchemaRegistry, VersionedRegistry};
 pub use schema::{LiteralValue, Schema, SchemaKind, StringFormat};
 pub use validate::{validate, validate_with_format, validate_with_registry};
 pub use value::Value;
 pub use value::{
-    cast, check, check_with_errors, clean, clone, create, delta, diff_summary, equal, patch, Delta,
-    Edit,
+    cast, cast_with_registry, check, check_with_errors, check_with_registry, clean,
+    clean_with_registry, clone, create, create_with_registry, delta, diff_summary, equal, patch,
+    Delta, Edit,
 };
+pub use visit::{SchemaFold, SchemaPath, SchemaVisitor, SchemaVisitorMut};
 
 #[cfg(feature = "fake")]
 pub use error::FakeError;
 #[cfg(feature = "fake")]
-pub use value::{fake, fake_with_context, FakeContext};
+pub use value::{fake, fake_with_context, fake_with_registry, FakeContext};
 
 #[cfg(feature = "codegen")]
-pub use codegen::{RustGenerator, TypeScriptGenerator};
+pub use codegen::{
+    FieldNumbers, GoGenerator, GraphQLGenerator, ProtoGenerator, PydanticGenerator, RustGenerator,
+    TypeBoxGenerator, TypeScriptGenerator, ZodGenerator,
+};
+
+#[cfg(feature = "derive")]
+pub use typebox_derive::TypeBox;
 
 /// Crate version string.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,651 @@
+//! Schema well-formedness checks.
+//!
+//! Detects schemas that can be constructed but are contradictory or
+//! unresolvable, such as `minimum > maximum`, `required` naming a missing
+//! property, or a `default` that fails its own schema. Run [`verify`] at
+//! startup to fail fast instead of at validation time.
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::{lint, SchemaBuilder, Severity};
+//!
+//! let schema = SchemaBuilder::string().min_length(10).max_length(5).build();
+//!
+//! let diagnostics = lint(&schema);
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(diagnostics[0].severity, Severity::Error);
+//! assert_eq!(diagnostics[0].path, "#");
+//! ```
+
+use crate::error::LintError;
+use crate::registry::{resolve_uri, SchemaRegistry};
+use crate::schema::{Schema, SchemaKind};
+use crate::validate::validate_full;
+use crate::value::scope::Binding;
+use crate::visit::{walk_schema, SchemaPath, SchemaVisitor};
+use std::collections::HashSet;
+use std::fmt::Display;
+
+/// Severity of a lint diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Suspicious but usable schema.
+    Warning,
+    /// Schema that cannot behave as intended.
+    Error,
+}
+
+/// A single problem found in a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Severity of the problem.
+    pub severity: Severity,
+    /// Short rule identifier (e.g., `"range"`, `"unresolved-ref"`).
+    pub rule: &'static str,
+    /// Schema path where the problem was found (e.g., `#/properties/age`).
+    pub path: String,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Returns true if this diagnostic is an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}[{}] at {}: {}",
+            level, self.rule, self.path, self.message
+        )
+    }
+}
+
+/// Lints a standalone schema.
+///
+/// References that are not bound by an enclosing `Recursive` schema are
+/// reported as warnings since they cannot be checked without a registry.
+pub fn lint(schema: &Schema) -> Vec<Diagnostic> {
+    lint_with_registry(schema, None)
+}
+
+/// Lints a schema, resolving references against a registry.
+///
+/// With a registry, references to unregistered names are errors.
+pub fn lint_with_registry(schema: &Schema, registry: Option<&SchemaRegistry>) -> Vec<Diagnostic> {
+    Linter::new(registry, "").run(schema)
+}
+
+/// Lints a schema and fails if any error-level diagnostic is found.
+///
+/// Returns the remaining warnings on success.
+///
+/// # Errors
+///
+/// Returns [`LintError::Invalid`] with every diagnostic if at least one is an error.
+pub fn verify(
+    schema: &Schema,
+    registry: Option<&SchemaRegistry>,
+) -> Result<Vec<Diagnostic>, LintError> {
+    into_result(lint_with_registry(schema, registry))
+}
+
+impl SchemaRegistry {
+    /// Lints every registered schema against this registry.
+    ///
+    /// Diagnostic paths are prefixed with the registered name
+    /// (e.g., `Person#/properties/age`). Schemas are visited in name order.
+    pub fn lint(&self) -> Vec<Diagnostic> {
+        let mut names: Vec<&String> = self.schemas().map(|(name, _)| name).collect();
+        names.sort();
+
+        let mut diagnostics = Vec::new();
+        for name in names {
+            if let Some(schema) = self.get(name) {
+                let mut linter = Linter::new(Some(self), name);
+                linter.bases.extend(self.document_base(name));
+                diagnostics.extend(linter.run(schema));
+            }
+        }
+        diagnostics
+    }
+
+    /// Lints every registered schema and fails if any error is found.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LintError::Invalid`] with every diagnostic if at least one is an error.
+    pub fn verify(&self) -> Result<Vec<Diagnostic>, LintError> {
+        into_result(self.lint())
+    }
+}
+
+fn into_result(diagnostics: Vec<Diagnostic>) -> Result<Vec<Diagnostic>, LintError> {
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        Err(LintError::Invalid(diagnostics))
+    } else {
+        Ok(diagnostics)
+    }
+}
+
+struct Linter<'a> {
+    registry: Option<&'a SchemaRegistry>,
+    prefix: String,
+    /// Enclosing `Recursive` schemas with `$id`s, outermost first, and the
+    /// base URI in effect where each was reached.
+    scope: Vec<(Schema, Option<String>)>,
+    /// Base URIs for relative references, innermost last.
+    bases: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(registry: Option<&'a SchemaRegistry>, prefix: &str) -> Self {
+        Self {
+            registry,
+            prefix: prefix.to_string(),
+            scope: Vec::new(),
+            bases: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn run(mut self, schema: &Schema) -> Vec<Diagnostic> {
+        schema.accept(&mut self);
+        self.diagnostics
+    }
+}
+
+impl SchemaVisitor for Linter<'_> {
+    fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+        let location = format!("{}{}", self.prefix, path);
+        self.check_metadata(schema, &location);
+        self.check_kind(schema, &location);
+
+        let bound = match (&schema.kind, &schema.id) {
+            (SchemaKind::Recursive { .. }, Some(_)) => {
+                self.scope
+                    .push((schema.clone(), self.bases.last().cloned()));
+                true
+            }
+            _ => false,
+        };
+        let based = match &schema.id {
+            Some(id) if !id.starts_with('#') => {
+                let base = match self.bases.last() {
+                    Some(base) => resolve_uri(base, id),
+                    None => id.clone(),
+                };
+                self.bases.push(base);
+                true
+            }
+            _ => false,
+        };
+        walk_schema(self, schema, path);
+        if based {
+            self.bases.pop();
+        }
+        if bound {
+            self.scope.pop();
+        }
+    }
+}
+
+impl Linter<'_> {
+    fn report(&mut self, severity: Severity, rule: &'static str, path: &str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            rule,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn check_range<T: PartialOrd + Display>(
+        &mut self,
+        path: &str,
+        (min_name, min): (&str, Option<T>),
+        (max_name, max): (&str, Option<T>),
+    ) {
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                self.report(
+                    Severity::Error,
+                    "range",
+                    path,
+                    format!("{} {} is greater than {} {}", min_name, min, max_name, max),
+                );
+            }
+        }
+    }
+
+    fn check_kind(&mut self, schema: &Schema, path: &str) {
+        match &schema.kind {
+            SchemaKind::Int8 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::Int16 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::Int32 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::Int64 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::UInt8 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::UInt16 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::UInt32 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::UInt64 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::Float32 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+            SchemaKind::Float64 { minimum, maximum } => {
+                self.check_range(path, ("minimum", *minimum), ("maximum", *maximum))
+            }
+
+            SchemaKind::String {
+                pattern,
+                min_length,
+                max_length,
+                ..
+            } => {
+                self.check_range(
+                    path,
+                    ("min_length", *min_length),
+                    ("max_length", *max_length),
+                );
+                if let Some(pattern) = pattern {
+                    self.check_pattern(path, pattern);
+                }
+            }
+
+            SchemaKind::Bytes {
+                min_length,
+                max_length,
+            } => self.check_range(
+                path,
+                ("min_length", *min_length),
+                ("max_length", *max_length),
+            ),
+
+            SchemaKind::Array {
+                min_items,
+                max_items,
+                ..
+            } => self.check_range(path, ("min_items", *min_items), ("max_items", *max_items)),
+
+            SchemaKind::Object {
+                properties,
+                required,
+                ..
+            } => {
+                let mut seen = HashSet::new();
+                for name in required {
+                    if !properties.contains_key(name) {
+                        self.report(
+                            Severity::Error,
+                            "required",
+                            path,
+                            format!("required property '{}' is not defined in properties", name),
+                        );
+                    }
+                    if !seen.insert(name) {
+                        self.report(
+                            Severity::Warning,
+                            "required",
+                            path,
+                            format!("required property '{}' is listed more than once", name),
+                        );
+                    }
+                }
+            }
+
+            SchemaKind::Union { any_of } if any_of.is_empty() => self.report(
+                Severity::Warning,
+                "empty-union",
+                path,
+                "union has no variants and matches nothing".to_string(),
+            ),
+
+            SchemaKind::Enum { values } => {
+                if values.is_empty() {
+                    self.report(
+                        Severity::Warning,
+                        "empty-enum",
+                        path,
+                        "enum has no values and matches nothing".to_string(),
+                    );
+                }
+                let mut seen = HashSet::new();
+                for value in values {
+                    if !seen.insert(value) {
+                        self.report(
+                            Severity::Warning,
+                            "duplicate-enum",
+                            path,
+                            format!("enum value '{}' is listed more than once", value),
+                        );
+                    }
+                }
+            }
+
+            SchemaKind::Ref { reference } => self.check_ref(path, reference),
+
+            SchemaKind::Recursive { .. } if schema.id.is_none() => self.report(
+                Severity::Error,
+                "recursive-id",
+                path,
+                "recursive schema has no $id to reference itself by".to_string(),
+            ),
+
+            _ => {}
+        }
+    }
+
+    fn check_metadata(&mut self, schema: &Schema, path: &str) {
+        if schema.read_only == Some(true) && schema.write_only == Some(true) {
+            self.report(
+                Severity::Warning,
+                "read-write",
+                path,
+                "schema is marked both read_only and write_only".to_string(),
+            );
+        }
+
+        if schema.default.is_none() && schema.examples.is_none() {
+            return;
+        }
+        let registry = self.registry;
+        let base = self.bases.last().map(String::as_str);
+        // Values are checked with the enclosing `Recursive` schemas bound.
+        let (default, examples) = bound(&self.scope, None, |bindings| {
+            // A dangling ref would make every default fail, which is already reported.
+            if let SchemaKind::Ref { reference } = &schema.kind {
+                if !resolves(registry, bindings, reference, base) {
+                    return (None, Vec::new());
+                }
+            }
+            let validate = |value| validate_full(schema, value, registry, None, base, bindings);
+            let default = schema.default.as_ref().and_then(|v| validate(v).err());
+            let examples: Vec<_> = schema
+                .examples
+                .iter()
+                .flatten()
+                .enumerate()
+                .filter_map(|(i, example)| validate(example).err().map(|e| (i, e)))
+                .collect();
+            (default, examples)
+        });
+
+        if let Some(e) = default {
+            self.report(
+                Severity::Error,
+                "default",
+                path,
+                format!("default value does not match schema: {}", e),
+            );
+        }
+        for (i, e) in examples {
+            self.report(
+                Severity::Warning,
+                "example",
+                path,
+                format!("example {} does not match schema: {}", i, e),
+            );
+        }
+    }
+
+    fn check_ref(&mut self, path: &str, reference: &str) {
+        let base = self.bases.last().map(String::as_str);
+        let bound_here = bound(&self.scope, None, |bindings| {
+            Binding::find(bindings, reference, base).is_some()
+        });
+        if bound_here {
+            return;
+        }
+        match self.registry {
+            Some(registry) => {
+                if let Err(e) = registry.resolve_ref(reference, base) {
+                    self.report(
+                        Severity::Error,
+                        "unresolved-ref",
+                        path,
+                        format!("reference '{}' does not resolve: {}", reference, e),
+                    );
+                }
+            }
+            None => self.report(
+                Severity::Warning,
+                "unresolved-ref",
+                path,
+                format!(
+                    "reference '{}' cannot be checked without a registry",
+                    reference
+                ),
+            ),
+        }
+    }
+
+    #[cfg(feature = "pattern")]
+    fn check_pattern(&mut self, path: &str, pattern: &str) {
+        if let Err(e) = regex::Regex::new(pattern) {
+            self.report(
+                Severity::Error,
+                "pattern",
+                path,
+                format!("invalid regex pattern '{}': {}", pattern, e),
+            );
+        }
+    }
+
+    #[cfg(not(feature = "pattern"))]
+    fn check_pattern(&mut self, _path: &str, _pattern: &str) {}
+}
+
+/// Calls `f` with the `Recursive` schemas of `scope` bound over `parent`.
+fn bound<R>(
+    scope: &[(Schema, Option<String>)],
+    parent: Option<&Binding<'_>>,
+    f: impl FnOnce(Option<&Binding<'_>>) -> R,
+) -> R {
+    match scope.split_first() {
+        None => f(parent),
+        Some(((schema, base), rest)) => {
+            let binding = Binding::new(schema, base.as_deref(), parent);
+            bound(rest, binding.as_ref().or(parent), f)
+        }
+    }
+}
+
+/// Whether `reference` resolves, through `bindings` or the registry.
+fn resolves(
+    registry: Option<&SchemaRegistry>,
+    bindings: Option<&Binding<'_>>,
+    reference: &str,
+    base: Option<&str>,
+) -> bool {
+    Binding::find(bindings, reference, base).is_some()
+        || registry.is_some_and(|r| r.resolve_ref(reference, base).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::value::Value;
+
+    #[test]
+    fn test_valid_schema_has_no_diagnostics() {
+        let schema = SchemaBuilder::object()
+            .field("id", SchemaBuilder::int64())
+            .optional_field("name", SchemaBuilder::string().max_length(10).build())
+            .build();
+
+        assert!(lint(&schema).is_empty());
+    }
+
+    #[test]
+    fn test_numeric_range() {
+        let schema = Schema::new(SchemaKind::Int32 {
+            minimum: Some(10),
+            maximum: Some(5),
+        });
+
+        let diagnostics = lint(&schema);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "range");
+        assert!(diagnostics[0].is_error());
+    }
+
+    #[test]
+    fn test_required_not_in_properties() {
+        let schema = Schema::new(SchemaKind::Object {
+            properties: Default::default(),
+            required: vec!["missing".to_string()],
+            additional_properties: None,
+        });
+
+        let diagnostics = lint(&schema);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "required");
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let schema = SchemaBuilder::object()
+            .field(
+                "tags",
+                SchemaBuilder::array(SchemaBuilder::string().min_length(3).max_length(1).build())
+                    .build(),
+            )
+            .build();
+
+        let diagnostics = lint(&schema);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "#/properties/tags/items");
+    }
+
+    #[test]
+    fn test_invalid_default() {
+        let schema = SchemaBuilder::int64().with_default(Value::string("nope"));
+
+        let diagnostics = lint(&schema);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "default");
+    }
+
+    #[test]
+    fn test_refs_with_and_without_registry() {
+        let schema = SchemaBuilder::array(SchemaBuilder::r#ref("Missing")).build();
+
+        let diagnostics = lint(&schema);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+        let registry = SchemaRegistry::new();
+        let diagnostics = lint_with_registry(&schema, Some(&registry));
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].path, "#/items");
+    }
+
+    #[test]
+    fn test_recursive_self_ref_is_bound() {
+        let schema = SchemaBuilder::recursive("Tree", |this| {
+            SchemaBuilder::object()
+                .field("children", SchemaBuilder::array(this).build())
+                .build()
+        });
+
+        assert!(lint(&schema).is_empty());
+    }
+
+    #[test]
+    fn test_recursive_default_is_bound() {
+        let leaf = Value::object()
+            .field("children", Value::array(Vec::new()))
+            .build();
+        let schema = SchemaBuilder::recursive("Node", |this| {
+            SchemaBuilder::object()
+                .field(
+                    "children",
+                    SchemaBuilder::array(this)
+                        .build()
+                        .with_default(Value::array(vec![leaf])),
+                )
+                .build()
+        });
+
+        assert!(lint(&schema).is_empty());
+        let registry = SchemaRegistry::new();
+        assert!(lint_with_registry(&schema, Some(&registry)).is_empty());
+    }
+
+    #[test]
+    fn test_default_checked_through_uri_ref() {
+        let mut registry = SchemaRegistry::new();
+        registry.register_with_uri(
+            "Positive",
+            Schema::new(SchemaKind::Int64 {
+                minimum: Some(1),
+                maximum: None,
+            }),
+            "urn:example:positive",
+        );
+        let schema = Schema::new(SchemaKind::Ref {
+            reference: "urn:example:positive".to_string(),
+        })
+        .with_default(Value::int64(-5));
+
+        let diagnostics = lint_with_registry(&schema, Some(&registry));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "default");
+    }
+
+    #[cfg(feature = "pattern")]
+    #[test]
+    fn test_invalid_pattern() {
+        let schema = SchemaBuilder::string().pattern("([a-z]").build();
+
+        let diagnostics = lint(&schema);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "pattern");
+    }
+
+    #[test]
+    fn test_registry_verify() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .field("address", SchemaBuilder::r#ref("Address"))
+                .build(),
+        );
+
+        match registry.verify() {
+            Err(LintError::Invalid(diagnostics)) => {
+                assert_eq!(diagnostics[0].path, "Person#/properties/address");
+            }
+            other => panic!("Expected lint failure, got {:?}", other),
+        }
+
+        registry.register("Address", SchemaBuilder::string().build());
+        assert!(registry.verify().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,703 @@
+//! Value migrations between schema versions.
+//!
+//! A [`Migration`] is a list of declarative [`Step`]s that upgrade a value
+//! from one version of a schema to the next. A [`Migrator`] chains the
+//! migrations of one schema name, finds the route between any two
+//! versions (running reversible migrations backwards for downgrades), and
+//! validates the result against the target revision in a
+//! [`VersionedRegistry`].
+//!
+//! Paths in steps are JSON Pointers, applied with
+//! [`get_pointer`], [`set_pointer`] and [`delete_pointer`]. Steps whose
+//! source path is absent (e.g. an optional field that is not set) are
+//! skipped.
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::migrate::{Migration, Migrator};
+//! use typebox::registry::VersionedRegistry;
+//! use typebox::{SchemaBuilder, Value};
+//!
+//! let mut registry = VersionedRegistry::new();
+//! registry.register("Person", "1", SchemaBuilder::object()
+//!     .field("name", SchemaBuilder::string().build())
+//!     .build()).unwrap();
+//! registry.register("Person", "2", SchemaBuilder::object()
+//!     .field("full_name", SchemaBuilder::string().build())
+//!     .field("active", SchemaBuilder::bool())
+//!     .build()).unwrap();
+//!
+//! let migrator = Migrator::new("Person").migration(
+//!     Migration::new("1", "2")
+//!         .rename("/name", "full_name")
+//!         .default("/active", Value::bool(true)),
+//! );
+//!
+//! let v1 = Value::object().field("name", Value::string("Alice")).build();
+//! let v2 = migrator.migrate(&registry, &v1, "1", "2").unwrap();
+//! assert_eq!(v2, Value::object()
+//!     .field("full_name", Value::string("Alice"))
+//!     .field("active", Value::bool(true))
+//!     .build());
+//!
+//! // Renames and defaults can be undone.
+//! assert_eq!(migrator.migrate(&registry, &v2, "2", "1").unwrap(), v1);
+//! ```
+
+use crate::error::MigrationError;
+use crate::registry::VersionedRegistry;
+use crate::schema::Schema;
+use crate::value::{cast, delete_pointer, get_pointer, get_pointer_mut, set_pointer, Value};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+/// A custom transformation of a whole value.
+pub type Transform = Arc<dyn Fn(Value) -> Result<Value, String> + Send + Sync>;
+
+/// One change applied to a value.
+#[derive(Clone)]
+pub enum Step {
+    /// Renames the object field at `path` to `to`, keeping its position.
+    Rename {
+        /// Pointer to the field.
+        path: String,
+        /// The new field name.
+        to: String,
+    },
+    /// Moves the value at `from` to `to`, creating intermediate objects
+    /// and removing those left empty, so moves into and out of nested
+    /// objects undo each other.
+    Move {
+        /// Source pointer.
+        from: String,
+        /// Target pointer.
+        to: String,
+    },
+    /// Sets `path` to `value` when it is absent. Reversed by
+    /// [`RemoveDefault`](Step::RemoveDefault), which keeps values that
+    /// differ from `value`.
+    Default {
+        /// Pointer to the field.
+        path: String,
+        /// The value to set.
+        value: Value,
+    },
+    /// Removes the value at `path` if it equals `value`, undoing
+    /// [`Default`](Step::Default). Reversed by setting it again.
+    RemoveDefault {
+        /// Pointer to the field.
+        path: String,
+        /// The value to remove.
+        value: Value,
+    },
+    /// Removes the value at `path`. Not reversible.
+    Remove {
+        /// Pointer to the value.
+        path: String,
+    },
+    /// Converts the value at `path` (`""` for the whole value) with
+    /// [`cast`](crate::cast). Reversible when `back` is given.
+    Cast {
+        /// Pointer to the value.
+        path: String,
+        /// Schema to cast to.
+        schema: Box<Schema>,
+        /// Schema to cast back to when downgrading.
+        back: Option<Box<Schema>>,
+    },
+    /// Transforms the whole value. Reversible when `backward` is given.
+    Custom {
+        /// The upgrade.
+        forward: Transform,
+        /// The downgrade.
+        backward: Option<Transform>,
+    },
+}
+
+impl Step {
+    /// Whether the step can be undone with [`inverse`](Self::inverse).
+    pub fn is_reversible(&self) -> bool {
+        match self {
+            Step::Rename { .. }
+            | Step::Move { .. }
+            | Step::Default { .. }
+            | Step::RemoveDefault { .. } => true,
+            Step::Remove { .. } => false,
+            Step::Cast { back, .. } => back.is_some(),
+            Step::Custom { backward, .. } => backward.is_some(),
+        }
+    }
+
+    /// The step that undoes this one, if any.
+    pub fn inverse(&self) -> Option<Step> {
+        Some(match self {
+            Step::Rename { path, to } => {
+                let (parent, name) = split_pointer(path);
+                Step::Rename {
+                    path: format!("{}/{}", parent, escape(to)),
+                    to: name,
+                }
+            }
+            Step::Move { from, to } => Step::Move {
+                from: to.clone(),
+                to: from.clone(),
+            },
+            Step::Default { path, value } => Step::RemoveDefault {
+                path: path.clone(),
+                value: value.clone(),
+            },
+            Step::RemoveDefault { path, value } => Step::Default {
+                path: path.clone(),
+                value: value.clone(),
+            },
+            Step::Remove { .. } => return None,
+            Step::Cast { path, schema, back } => Step::Cast {
+                path: path.clone(),
+                schema: back.clone()?,
+                back: Some(schema.clone()),
+            },
+            Step::Custom { forward, backward } => Step::Custom {
+                forward: backward.clone()?,
+                backward: Some(forward.clone()),
+            },
+        })
+    }
+
+    /// Applies the step to `value` in place.
+    pub fn apply(&self, value: &mut Value) -> Result<(), String> {
+        match self {
+            Step::Rename { path, to } => {
+                let (parent, name) = split_pointer(path);
+                let fields = match get_pointer_mut(value, &parent) {
+                    Some(Value::Object(fields)) => fields,
+                    _ => return Ok(()),
+                };
+                if let Some((index, _, field)) = fields.shift_remove_full(&name) {
+                    if fields.contains_key(to) {
+                        return Err(format!("cannot rename {}: {:?} already exists", path, to));
+                    }
+                    fields.shift_insert(index, to.clone(), field);
+                }
+                Ok(())
+            }
+            Step::Move { from, to } => {
+                if let Some(moved) = get_pointer(value, from).cloned() {
+                    delete_pointer(value, from).map_err(|e| e.to_string())?;
+                    let mut parent = split_pointer(from).0;
+                    while !parent.is_empty()
+                        && matches!(get_pointer(value, &parent), Some(Value::Object(fields)) if fields.is_empty())
+                    {
+                        delete_pointer(value, &parent).map_err(|e| e.to_string())?;
+                        parent = split_pointer(&parent).0;
+                    }
+                    set_pointer(value, to, moved).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+            Step::Default {
+                path,
+                value: default,
+            } => {
+                if get_pointer(value, path).is_none() {
+                    set_pointer(value, path, default.clone()).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+            Step::RemoveDefault {
+                path,
+                value: default,
+            } => {
+                if get_pointer(value, path) == Some(default) {
+                    delete_pointer(value, path).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+            Step::Remove { path } => {
+                if get_pointer(value, path).is_some() {
+                    delete_pointer(value, path).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+            Step::Cast { path, schema, .. } => {
+                if let Some(target) = get_pointer_mut(value, path) {
+                    *target = cast(schema, target).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+            Step::Custom { forward, .. } => {
+                *value = forward(std::mem::replace(value, Value::Null))?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Rename { path, to } => write!(f, "Rename({} -> {:?})", path, to),
+            Step::Move { from, to } => write!(f, "Move({} -> {})", from, to),
+            Step::Default { path, value } => write!(f, "Default({} = {:?})", path, value),
+            Step::RemoveDefault { path, value } => {
+                write!(f, "RemoveDefault({} = {:?})", path, value)
+            }
+            Step::Remove { path } => write!(f, "Remove({})", path),
+            Step::Cast { path, schema, back } => {
+                write!(f, "Cast({} as {}", path, schema)?;
+                if let Some(back) = back {
+                    write!(f, ", back as {}", back)?;
+                }
+                write!(f, ")")
+            }
+            Step::Custom { backward, .. } => write!(
+                f,
+                "Custom({})",
+                if backward.is_some() {
+                    "reversible"
+                } else {
+                    "irreversible"
+                }
+            ),
+        }
+    }
+}
+
+/// The steps that upgrade a value from one version to the next.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    /// The version the migration starts from.
+    pub from: String,
+    /// The version the migration leads to.
+    pub to: String,
+    /// The steps, applied in order.
+    pub steps: Vec<Step>,
+}
+
+impl Migration {
+    /// Creates an empty migration from `from` to `to`.
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Adds a step.
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Renames the field at `path` to `to`.
+    pub fn rename(self, path: &str, to: &str) -> Self {
+        self.step(Step::Rename {
+            path: path.to_string(),
+            to: to.to_string(),
+        })
+    }
+
+    /// Moves the value at `from` to `to`.
+    pub fn move_path(self, from: &str, to: &str) -> Self {
+        self.step(Step::Move {
+            from: from.to_string(),
+            to: to.to_string(),
+        })
+    }
+
+    /// Sets `path` to `value` when absent.
+    ///
+    /// Downgrading removes the value at `path` only while it still equals
+    /// `value`, so values set before the upgrade or changed since survive.
+    /// A value that was already equal to `value` before the upgrade is
+    /// removed as well, as the two cannot be told apart.
+    pub fn default(self, path: &str, value: Value) -> Self {
+        self.step(Step::Default {
+            path: path.to_string(),
+            value,
+        })
+    }
+
+    /// Removes the value at `path`.
+    pub fn remove(self, path: &str) -> Self {
+        self.step(Step::Remove {
+            path: path.to_string(),
+        })
+    }
+
+    /// Casts the value at `path` to `schema`.
+    pub fn cast(self, path: &str, schema: Schema) -> Self {
+        self.step(Step::Cast {
+            path: path.to_string(),
+            schema: Box::new(schema),
+            back: None,
+        })
+    }
+
+    /// Casts the value at `path` to `schema`, and back to `back` when
+    /// downgrading.
+    pub fn cast_reversible(self, path: &str, schema: Schema, back: Schema) -> Self {
+        self.step(Step::Cast {
+            path: path.to_string(),
+            schema: Box::new(schema),
+            back: Some(Box::new(back)),
+        })
+    }
+
+    /// Transforms the whole value with `forward`.
+    pub fn map<F>(self, forward: F) -> Self
+    where
+        F: Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.step(Step::Custom {
+            forward: Arc::new(forward),
+            backward: None,
+        })
+    }
+
+    /// Transforms the whole value with `forward`, and with `backward` when
+    /// downgrading.
+    pub fn map_reversible<F, B>(self, forward: F, backward: B) -> Self
+    where
+        F: Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+        B: Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.step(Step::Custom {
+            forward: Arc::new(forward),
+            backward: Some(Arc::new(backward)),
+        })
+    }
+
+    /// Whether every step is reversible.
+    pub fn is_reversible(&self) -> bool {
+        self.steps.iter().all(Step::is_reversible)
+    }
+
+    /// The migration from `to` back to `from`, if every step is reversible.
+    pub fn inverse(&self) -> Option<Migration> {
+        Some(Migration {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            steps: self
+                .steps
+                .iter()
+                .rev()
+                .map(Step::inverse)
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// Applies the steps to a copy of `value`.
+    pub fn apply(&self, value: &Value) -> Result<Value, MigrationError> {
+        let mut value = value.clone();
+        for (index, step) in self.steps.iter().enumerate() {
+            step.apply(&mut value)
+                .map_err(|message| MigrationError::Step {
+                    from: self.from.clone(),
+                    to: self.to.clone(),
+                    step: index,
+                    message,
+                })?;
+        }
+        Ok(value)
+    }
+}
+
+/// The migrations of one schema name, chained between versions.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    name: String,
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    /// Creates a migrator for the schema registered as `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Adds a migration.
+    pub fn migration(mut self, migration: Migration) -> Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    /// The migrations to apply, in order, to get from `from` to `to`: the
+    /// shortest chain, running reversible migrations backwards where
+    /// needed.
+    pub fn plan(&self, from: &str, to: &str) -> Result<Vec<Migration>, MigrationError> {
+        let mut edges: HashMap<&str, Vec<Migration>> = HashMap::new();
+        for migration in &self.migrations {
+            edges
+                .entry(migration.from.as_str())
+                .or_default()
+                .push(migration.clone());
+            if let Some(inverse) = migration.inverse() {
+                edges
+                    .entry(migration.to.as_str())
+                    .or_default()
+                    .push(inverse);
+            }
+        }
+
+        // Breadth-first, so the shortest chain wins; ties go to the
+        // migration declared first.
+        let mut reached: HashMap<String, Option<Migration>> = HashMap::new();
+        reached.insert(from.to_string(), None);
+        let mut queue = VecDeque::from([from.to_string()]);
+        while let Some(version) = queue.pop_front() {
+            if version == to {
+                break;
+            }
+            for migration in edges.get(version.as_str()).into_iter().flatten() {
+                if !reached.contains_key(&migration.to) {
+                    reached.insert(migration.to.clone(), Some(migration.clone()));
+                    queue.push_back(migration.to.clone());
+                }
+            }
+        }
+
+        let mut plan = Vec::new();
+        let mut version = to.to_string();
+        loop {
+            match reached.get(&version) {
+                Some(Some(migration)) => {
+                    version = migration.from.clone();
+                    plan.push(migration.clone());
+                }
+                Some(None) => break,
+                None => {
+                    return Err(MigrationError::NoPath {
+                        from: from.to_string(),
+                        to: to.to_string(),
+                    })
+                }
+            }
+        }
+        plan.reverse();
+        Ok(plan)
+    }
+
+    /// Migrates `value` from version `from` to version `to` without
+    /// validating the result.
+    pub fn migrate_unchecked(
+        &self,
+        value: &Value,
+        from: &str,
+        to: &str,
+    ) -> Result<Value, MigrationError> {
+        self.plan(from, to)?
+            .iter()
+            .try_fold(value.clone(), |value, migration| migration.apply(&value))
+    }
+
+    /// Migrates `value` from version `from` to version `to`, then validates
+    /// it against `Name@to` in `registry`.
+    pub fn migrate(
+        &self,
+        registry: &VersionedRegistry,
+        value: &Value,
+        from: &str,
+        to: &str,
+    ) -> Result<Value, MigrationError> {
+        let reference = format!("{}@{}", self.name, to);
+        if registry.get(&reference).is_none() {
+            return Err(MigrationError::SchemaNotFound(reference));
+        }
+        let migrated = self.migrate_unchecked(value, from, to)?;
+        registry
+            .validate(&reference, &migrated)
+            .map_err(|source| MigrationError::Invalid { reference, source })?;
+        Ok(migrated)
+    }
+}
+
+/// Splits a pointer into its parent pointer and unescaped last token.
+fn split_pointer(pointer: &str) -> (String, String) {
+    match pointer.rfind('/') {
+        Some(index) => (
+            pointer[..index].to_string(),
+            pointer[index + 1..].replace("~1", "/").replace("~0", "~"),
+        ),
+        None => (String::new(), pointer.to_string()),
+    }
+}
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+
+    fn registry() -> VersionedRegistry {
+        let mut registry = VersionedRegistry::new();
+        registry
+            .register(
+                "Person",
+                "1",
+                SchemaBuilder::object()
+                    .field("name", SchemaBuilder::string().build())
+                    .field("age", SchemaBuilder::string().build())
+                    .field("zip", SchemaBuilder::string().build())
+                    .build(),
+            )
+            .unwrap();
+        registry
+            .register(
+                "Person",
+                "2",
+                SchemaBuilder::object()
+                    .field("full_name", SchemaBuilder::string().build())
+                    .field("age", SchemaBuilder::int64())
+                    .field(
+                        "address",
+                        SchemaBuilder::object()
+                            .field("zip", SchemaBuilder::string().build())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .unwrap();
+        registry
+            .register(
+                "Person",
+                "3",
+                SchemaBuilder::object()
+                    .field("full_name", SchemaBuilder::string().build())
+                    .field("age", SchemaBuilder::int64())
+                    .field(
+                        "address",
+                        SchemaBuilder::object()
+                            .field("zip", SchemaBuilder::string().build())
+                            .build(),
+                    )
+                    .field(
+                        "tags",
+                        SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+                    )
+                    .build(),
+            )
+            .unwrap();
+        registry
+    }
+
+    fn migrator() -> Migrator {
+        Migrator::new("Person")
+            .migration(
+                Migration::new("1", "2")
+                    .rename("/name", "full_name")
+                    .cast_reversible(
+                        "/age",
+                        SchemaBuilder::int64(),
+                        SchemaBuilder::string().build(),
+                    )
+                    .move_path("/zip", "/address/zip"),
+            )
+            .migration(Migration::new("2", "3").default("/tags", Value::array(vec![])))
+    }
+
+    fn v1() -> Value {
+        Value::object()
+            .field("name", Value::string("Alice"))
+            .field("age", Value::string("42"))
+            .field("zip", Value::string("12345"))
+            .build()
+    }
+
+    #[test]
+    fn test_upgrade_chain() {
+        let v3 = migrator().migrate(&registry(), &v1(), "1", "3").unwrap();
+        assert_eq!(
+            v3,
+            Value::object()
+                .field("full_name", Value::string("Alice"))
+                .field("age", Value::int64(42))
+                .field(
+                    "address",
+                    Value::object().field("zip", Value::string("12345")).build()
+                )
+                .field("tags", Value::array(vec![]))
+                .build()
+        );
+        assert_eq!(
+            migrator().migrate(&registry(), &v3, "3", "1").unwrap(),
+            v1()
+        );
+    }
+
+    #[test]
+    fn test_irreversible_and_invalid() {
+        let migrator = Migrator::new("Person").migration(
+            Migration::new("1", "2")
+                .rename("/name", "full_name")
+                .remove("/zip")
+                .map(Ok),
+        );
+        assert!(migrator.plan("1", "2").is_ok());
+        assert!(matches!(
+            migrator.plan("2", "1"),
+            Err(MigrationError::NoPath { .. })
+        ));
+
+        // Age stays a string and the address is missing.
+        let err = migrator.migrate(&registry(), &v1(), "1", "2").unwrap_err();
+        assert!(matches!(err, MigrationError::Invalid { .. }));
+        assert!(matches!(
+            migrator.migrate(&registry(), &v1(), "1", "9"),
+            Err(MigrationError::SchemaNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_round_trip_keeps_existing_values() {
+        let migration = Migration::new("2", "3").default("/tags", Value::array(vec![]));
+        let inverse = migration.inverse().unwrap();
+
+        let tagged = Value::object()
+            .field("tags", Value::array(vec![Value::string("admin")]))
+            .build();
+        let upgraded = migration.apply(&tagged).unwrap();
+        assert_eq!(upgraded, tagged);
+        assert_eq!(inverse.apply(&upgraded).unwrap(), tagged);
+
+        let untagged = Value::object().build();
+        let upgraded = migration.apply(&untagged).unwrap();
+        assert_eq!(
+            upgraded,
+            Value::object().field("tags", Value::array(vec![])).build()
+        );
+        assert_eq!(inverse.apply(&upgraded).unwrap(), untagged);
+
+        // Tags set after the upgrade are not the injected default.
+        let edited = Value::object()
+            .field("tags", Value::array(vec![Value::string("new")]))
+            .build();
+        assert_eq!(inverse.apply(&edited).unwrap(), edited);
+    }
+
+    #[test]
+    fn test_step_errors() {
+        let migration = Migration::new("1", "2")
+            .map_reversible(Ok, |_| Err("no way back".to_string()))
+            .rename("/name", "zip");
+        let err = migration.apply(&v1()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Migration 1 -> 2, step 1: cannot rename /name: \"zip\" already exists"
+        );
+        let inverse = migration.inverse().unwrap();
+        assert_eq!(
+            format!("{:?}", inverse.steps[0]),
+            "Rename(/zip -> \"name\")"
+        );
+        assert!(inverse.apply(&Value::object().build()).is_err());
+    }
+}
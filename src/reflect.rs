@@ -0,0 +1,400 @@
+//! Registering Rust types as schemas.
+//!
+//! [`TypeBoxSchema`] lets a type graph populate a [`SchemaRegistry`]: named
+//! types register a `Named` definition and return a `Ref` to it, while std
+//! types return their schema inline. Because a named type is registered
+//! before its fields are visited, recursive types (trees, linked lists)
+//! resolve to a `Ref` instead of recursing forever.
+//!
+//! Implement it by hand or with `#[derive(TypeBox)]` (feature `derive`).
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::{SchemaBuilder, SchemaRegistry, TypeBoxSchema, Schema, Value};
+//!
+//! struct Node {
+//!     value: i32,
+//!     next: Option<Box<Node>>,
+//! }
+//!
+//! impl TypeBoxSchema for Node {
+//!     fn register(registry: &mut SchemaRegistry) -> Schema {
+//!         typebox::reflect::register_named::<Node, _>(registry, "Node", |registry| {
+//!             SchemaBuilder::object()
+//!                 .field("value", i32::register(registry))
+//!                 .optional_field("next", <Option<Box<Node>>>::register(registry))
+//!                 .build()
+//!         })
+//!     }
+//! }
+//!
+//! let mut registry = SchemaRegistry::new();
+//! let schema = registry.register_type::<Node>();
+//! assert!(registry.contains("Node"));
+//!
+//! let list = Value::object()
+//!     .field("value", Value::int64(1))
+//!     .field("next", Value::object().field("value", Value::int64(2)).build())
+//!     .build();
+//! assert!(typebox::validate_with_registry(&schema, &list, Some(&registry)).is_ok());
+//! ```
+
+use crate::builder::SchemaBuilder;
+use crate::registry::SchemaRegistry;
+use crate::schema::{Schema, SchemaKind};
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A Rust type with a schema that can be registered into a [`SchemaRegistry`].
+pub trait TypeBoxSchema {
+    /// Registers this type and its dependencies, returning the schema to use
+    /// where the type is referenced: a `Ref` for named types, or an inline
+    /// schema otherwise.
+    fn register(registry: &mut SchemaRegistry) -> Schema;
+
+    /// Schema for a sequence of `Self`, with `len` set for fixed-size arrays.
+    ///
+    /// Overridden by `u8` so that `Vec<u8>` and `[u8; N]` become `Bytes`.
+    #[doc(hidden)]
+    fn register_sequence(registry: &mut SchemaRegistry, len: Option<usize>) -> Schema
+    where
+        Self: Sized,
+    {
+        let items = Self::register(registry);
+        match len {
+            Some(len) => SchemaBuilder::array(items)
+                .min_items(len)
+                .max_items(len)
+                .build(),
+            None => SchemaBuilder::array(items).build(),
+        }
+    }
+}
+
+/// Registers `T` as a named definition built by `build`, returning a `Ref`
+/// to it.
+///
+/// A placeholder is registered before `build` runs, so recursive references
+/// back to `name` return a `Ref` rather than recursing. If `T` already
+/// registered `name`, `build` is not called.
+///
+/// # Panics
+///
+/// Panics if `name` is already registered by another type, or by a schema
+/// that was not registered through `register_named`, such as two structs
+/// of the same name in different modules. Give one of them another name
+/// (`#[typebox(name = "...")]` when derived).
+pub fn register_named<T, F>(registry: &mut SchemaRegistry, name: &str, build: F) -> Schema
+where
+    T: ?Sized,
+    F: FnOnce(&mut SchemaRegistry) -> Schema,
+{
+    let type_name = std::any::type_name::<T>();
+    match registry.type_of(name) {
+        Some(owner) if owner == type_name => {}
+        owner if owner.is_some() || registry.contains(name) => panic!(
+            "schema name `{}` registered for `{}` is already taken by {}",
+            name,
+            type_name,
+            owner.map_or_else(
+                || "another schema".to_string(),
+                |owner| format!("`{}`", owner)
+            )
+        ),
+        _ => {
+            registry.register_type_named(name, SchemaBuilder::unknown(), type_name);
+            let schema = build(registry);
+            registry.register_type_named(name, SchemaBuilder::named(name, schema), type_name);
+        }
+    }
+    SchemaBuilder::r#ref(name)
+}
+
+impl SchemaRegistry {
+    /// Registers `T` and its dependencies, returning the schema for `T`.
+    pub fn register_type<T: TypeBoxSchema + ?Sized>(&mut self) -> Schema {
+        T::register(self)
+    }
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty => $schema:expr),* $(,)?) => {
+        $(
+            impl TypeBoxSchema for $ty {
+                fn register(_: &mut SchemaRegistry) -> Schema {
+                    $schema
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive! {
+    bool => SchemaBuilder::bool(),
+    i8 => SchemaBuilder::int8(),
+    i16 => SchemaBuilder::int16(),
+    i32 => SchemaBuilder::int32(),
+    i64 => SchemaBuilder::int64(),
+    isize => SchemaBuilder::int64(),
+    u16 => SchemaBuilder::uint16(),
+    u32 => SchemaBuilder::uint32(),
+    u64 => SchemaBuilder::uint64(),
+    usize => SchemaBuilder::uint64(),
+    f32 => SchemaBuilder::float32(),
+    f64 => SchemaBuilder::float64(),
+    char => SchemaBuilder::string().min_length(1).max_length(1).build(),
+    String => SchemaBuilder::string().build(),
+    str => SchemaBuilder::string().build(),
+    () => SchemaBuilder::null(),
+    serde_json::Value => SchemaBuilder::any(),
+}
+
+impl TypeBoxSchema for u8 {
+    fn register(_: &mut SchemaRegistry) -> Schema {
+        SchemaBuilder::uint8()
+    }
+
+    fn register_sequence(_: &mut SchemaRegistry, len: Option<usize>) -> Schema {
+        Schema::new(SchemaKind::Bytes {
+            min_length: len,
+            max_length: len,
+        })
+    }
+}
+
+impl<T: TypeBoxSchema> TypeBoxSchema for Vec<T> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        T::register_sequence(registry, None)
+    }
+}
+
+impl<T: TypeBoxSchema> TypeBoxSchema for VecDeque<T> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        T::register_sequence(registry, None)
+    }
+}
+
+impl<T: TypeBoxSchema> TypeBoxSchema for [T] {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        T::register_sequence(registry, None)
+    }
+}
+
+impl<T: TypeBoxSchema, const N: usize> TypeBoxSchema for [T; N] {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        T::register_sequence(registry, Some(N))
+    }
+}
+
+impl<T: TypeBoxSchema, S> TypeBoxSchema for HashSet<T, S> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        SchemaBuilder::array(T::register(registry))
+            .unique_items(true)
+            .build()
+    }
+}
+
+impl<T: TypeBoxSchema> TypeBoxSchema for BTreeSet<T> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        SchemaBuilder::array(T::register(registry))
+            .unique_items(true)
+            .build()
+    }
+}
+
+impl<T: TypeBoxSchema> TypeBoxSchema for Option<T> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        SchemaBuilder::optional(T::register(registry))
+    }
+}
+
+fn map_schema<V: TypeBoxSchema>(registry: &mut SchemaRegistry) -> Schema {
+    SchemaBuilder::object()
+        .additional_properties(Some(V::register(registry)))
+        .build()
+}
+
+impl<K, V: TypeBoxSchema, S> TypeBoxSchema for HashMap<K, V, S> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        map_schema::<V>(registry)
+    }
+}
+
+impl<K, V: TypeBoxSchema> TypeBoxSchema for BTreeMap<K, V> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        map_schema::<V>(registry)
+    }
+}
+
+impl<K, V: TypeBoxSchema, S> TypeBoxSchema for IndexMap<K, V, S> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        map_schema::<V>(registry)
+    }
+}
+
+macro_rules! impl_pointer {
+    ($($ty:ty),*) => {
+        $(
+            impl<T: TypeBoxSchema + ?Sized> TypeBoxSchema for $ty {
+                fn register(registry: &mut SchemaRegistry) -> Schema {
+                    T::register(registry)
+                }
+            }
+        )*
+    };
+}
+
+impl_pointer!(Box<T>, Rc<T>, Arc<T>, &T, &mut T);
+
+impl<T: TypeBoxSchema + ToOwned + ?Sized> TypeBoxSchema for Cow<'_, T> {
+    fn register(registry: &mut SchemaRegistry) -> Schema {
+        T::register(registry)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: TypeBoxSchema),+> TypeBoxSchema for ($($name,)+) {
+            fn register(registry: &mut SchemaRegistry) -> Schema {
+                SchemaBuilder::tuple(vec![$($name::register(registry)),+])
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A B);
+impl_tuple!(A B C);
+impl_tuple!(A B C D);
+impl_tuple!(A B C D E);
+impl_tuple!(A B C D E F);
+impl_tuple!(A B C D E F G);
+impl_tuple!(A B C D E F G H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::validate_with_registry;
+    use crate::value::Value;
+
+    #[test]
+    fn test_primitives_and_containers() {
+        let mut registry = SchemaRegistry::new();
+        assert!(matches!(
+            u16::register(&mut registry).kind,
+            SchemaKind::UInt16 { .. }
+        ));
+        assert!(matches!(
+            <Vec<u8>>::register(&mut registry).kind,
+            SchemaKind::Bytes { .. }
+        ));
+        assert!(matches!(
+            <[u8; 16]>::register(&mut registry).kind,
+            SchemaKind::Bytes {
+                min_length: Some(16),
+                max_length: Some(16)
+            }
+        ));
+        assert!(matches!(
+            <[f32; 3]>::register(&mut registry).kind,
+            SchemaKind::Array {
+                min_items: Some(3),
+                max_items: Some(3),
+                ..
+            }
+        ));
+        assert!(matches!(
+            <HashMap<String, Vec<i64>>>::register(&mut registry).kind,
+            SchemaKind::Object {
+                additional_properties: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            <(bool, String, Box<f64>)>::register(&mut registry).kind,
+            SchemaKind::Tuple { ref items } if items.len() == 3
+        ));
+        assert!(matches!(
+            <Option<&str>>::register(&mut registry).kind,
+            SchemaKind::Union { ref any_of } if any_of.len() == 2
+        ));
+        assert!(registry.is_empty());
+    }
+
+    struct Tree;
+
+    impl TypeBoxSchema for Tree {
+        fn register(registry: &mut SchemaRegistry) -> Schema {
+            register_named::<Tree, _>(registry, "Tree", |registry| {
+                SchemaBuilder::object()
+                    .field("label", String::register(registry))
+                    .field("children", <Vec<Tree>>::register(registry))
+                    .build()
+            })
+        }
+    }
+
+    #[test]
+    fn test_recursive_type() {
+        let mut registry = SchemaRegistry::new();
+        let schema = registry.register_type::<Tree>();
+        assert!(matches!(schema.kind, SchemaKind::Ref { .. }));
+        assert_eq!(registry.len(), 1);
+        assert!(matches!(
+            registry.get("Tree").unwrap().kind,
+            SchemaKind::Named { .. }
+        ));
+
+        let leaf = Value::object()
+            .field("label", Value::string("leaf"))
+            .field("children", Value::array(vec![]))
+            .build();
+        let root = Value::object()
+            .field("label", Value::string("root"))
+            .field("children", Value::array(vec![leaf]))
+            .build();
+        assert!(validate_with_registry(&schema, &root, Some(&registry)).is_ok());
+
+        let bad = Value::object()
+            .field("label", Value::string("root"))
+            .field("children", Value::array(vec![Value::int64(1)]))
+            .build();
+        assert!(validate_with_registry(&schema, &bad, Some(&registry)).is_err());
+    }
+
+    mod other {
+        use super::*;
+
+        pub struct Tree;
+
+        impl TypeBoxSchema for Tree {
+            fn register(registry: &mut SchemaRegistry) -> Schema {
+                register_named::<Tree, _>(registry, "Tree", |_| SchemaBuilder::string().build())
+            }
+        }
+    }
+
+    #[test]
+    fn test_name_collision() {
+        let mut registry = SchemaRegistry::new();
+        registry.register_type::<Tree>();
+        registry.register_type::<Tree>();
+        assert_eq!(registry.len(), 1);
+
+        let result = std::panic::catch_unwind(move || {
+            registry.register_type::<other::Tree>();
+        });
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("schema name `Tree`"), "{}", message);
+        assert!(message.contains("other::Tree"), "{}", message);
+
+        let mut registry = SchemaRegistry::new();
+        registry.register("Tree", SchemaBuilder::string().build());
+        assert!(std::panic::catch_unwind(move || registry.register_type::<Tree>()).is_err());
+    }
+}
@@ -0,0 +1,566 @@
+//! Bundling and dereferencing registered schemas into portable documents.
+
+use super::load::file_stem_for;
+use super::{resolve_uri, SchemaRegistry};
+use crate::error::RegistryError;
+use crate::schema::{Schema, SchemaKind};
+use crate::visit::{walk_schema_mut, SchemaPath, SchemaVisitorMut};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A self-contained schema document: a root schema plus the definitions it
+/// references, with every `$ref` rewritten to `#/definitions/<name>`.
+///
+/// Serializes as the root schema with an extra `definitions` member.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    /// The root schema.
+    #[serde(flatten)]
+    pub root: Schema,
+    /// Definitions referenced from the root, in discovery order.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub definitions: IndexMap<String, Schema>,
+}
+
+impl Bundle {
+    /// Builds a registry holding the bundle's definitions, against which
+    /// the root (and each definition) validates.
+    pub fn to_registry(&self) -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        for (name, schema) in &self.definitions {
+            registry.register(name.clone(), schema.clone());
+        }
+        registry
+    }
+}
+
+impl SchemaRegistry {
+    /// Collects every schema transitively referenced from `root` into a
+    /// single [`Bundle`].
+    ///
+    /// `root` is a registered name or any reference form accepted by
+    /// [`resolve_ref`](Self::resolve_ref). Each distinct target becomes one
+    /// definition, named after its `Named` name or registered name, and
+    /// references to it are rewritten to `#/definitions/<name>`. References
+    /// bound by an enclosing `Recursive` schema are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first reference that does not resolve.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use typebox::{SchemaBuilder, SchemaRegistry, Value, validate_with_registry};
+    ///
+    /// let mut registry = SchemaRegistry::new();
+    /// registry.register(
+    ///     "Order",
+    ///     SchemaBuilder::object()
+    ///         .field("customer", SchemaBuilder::r#ref("Customer"))
+    ///         .named("Order"),
+    /// );
+    /// registry.register(
+    ///     "Customer",
+    ///     SchemaBuilder::object()
+    ///         .field("name", SchemaBuilder::string().build())
+    ///         .named("Customer"),
+    /// );
+    ///
+    /// let bundle = registry.bundle("Order").unwrap();
+    /// assert_eq!(bundle.definitions.keys().collect::<Vec<_>>(), ["Customer"]);
+    ///
+    /// let json = serde_json::to_value(&bundle).unwrap();
+    /// assert!(json["definitions"]["Customer"].is_object());
+    ///
+    /// let order = Value::object()
+    ///     .field("customer", Value::object().field("name", Value::string("Ada")).build())
+    ///     .build();
+    /// let standalone = bundle.to_registry();
+    /// assert!(validate_with_registry(&bundle.root, &order, Some(&standalone)).is_ok());
+    /// ```
+    pub fn bundle(&self, root: &str) -> Result<Bundle, RegistryError> {
+        let (schema, base) = self.resolve_ref_in(root, None)?;
+        let mut bundler = Bundler {
+            registry: self,
+            roots: self.root_names(),
+            names: HashMap::new(),
+            used: HashSet::new(),
+            definitions: IndexMap::new(),
+            bases: base.into_iter().collect(),
+            scope: Vec::new(),
+            error: None,
+        };
+        let mut root = schema.clone();
+        bundler.visit_schema_mut(&mut root, &mut SchemaPath::new());
+        match bundler.error {
+            Some(e) => Err(e),
+            None => Ok(Bundle {
+                root,
+                definitions: bundler.definitions,
+            }),
+        }
+    }
+
+    /// Inlines every `$ref` reachable from `root`, producing a schema that
+    /// validates without a registry.
+    ///
+    /// A reference back to a schema that is still being inlined (a cycle)
+    /// cannot be expanded; that schema is instead wrapped in a `Recursive`
+    /// schema whose `$id` the back-reference targets, as built by
+    /// [`SchemaBuilder::recursive`](crate::SchemaBuilder::recursive).
+    ///
+    /// # Errors
+    ///
+    /// Returns the first reference that does not resolve.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use typebox::{SchemaBuilder, SchemaKind, SchemaRegistry, Value, validate};
+    ///
+    /// let mut registry = SchemaRegistry::new();
+    /// registry.register(
+    ///     "Node",
+    ///     SchemaBuilder::object()
+    ///         .field("value", SchemaBuilder::int64())
+    ///         .optional_field("next", SchemaBuilder::r#ref("Node"))
+    ///         .named("Node"),
+    /// );
+    ///
+    /// let node = registry.dereference("Node").unwrap();
+    /// assert!(matches!(node.kind, SchemaKind::Recursive { .. }));
+    ///
+    /// let list = Value::object()
+    ///     .field("value", Value::int64(1))
+    ///     .field("next", Value::object().field("value", Value::int64(2)).build())
+    ///     .build();
+    /// assert!(validate(&node, &list).is_ok());
+    /// ```
+    pub fn dereference(&self, root: &str) -> Result<Schema, RegistryError> {
+        let (schema, base) = self.resolve_ref_in(root, None)?;
+        let mut dereferencer = Dereferencer {
+            registry: self,
+            roots: self.root_names(),
+            stack: Vec::new(),
+            bases: Vec::new(),
+            scope: Vec::new(),
+            error: None,
+        };
+        let root = dereferencer.inline(schema, base, root);
+        match dereferencer.error {
+            Some(e) => Err(e),
+            None => Ok(root),
+        }
+    }
+
+    /// Registered names keyed by the address of their schema.
+    fn root_names(&self) -> HashMap<*const Schema, &str> {
+        self.schemas
+            .iter()
+            .map(|(name, schema)| (schema as *const Schema, name.as_str()))
+            .collect()
+    }
+}
+
+/// Name for a definition: its `Named` name, else its registered name (the
+/// last path segment if that is a URI), else the last token of the
+/// reference.
+fn definition_name(target: &Schema, registered: Option<&str>, reference: &str) -> String {
+    if let SchemaKind::Named { name, .. } = &target.kind {
+        return name.clone();
+    }
+    if let Some(name) = registered {
+        return file_stem_for(name, None);
+    }
+    let token = reference
+        .rsplit(['/', '#'])
+        .find(|token| !token.is_empty())
+        .unwrap_or("");
+    file_stem_for(token, None)
+}
+
+/// Base URI handling shared by the bundler and dereferencer: pushes the
+/// scope introduced by `schema`, returning what was pushed.
+fn enter(schema: &Schema, bases: &mut Vec<String>, scope: &mut Vec<String>) -> (bool, bool) {
+    let bound = match (&schema.kind, &schema.id) {
+        (SchemaKind::Recursive { .. }, Some(id)) => {
+            scope.push(id.clone());
+            true
+        }
+        _ => false,
+    };
+    let based = match &schema.id {
+        Some(id) if !id.starts_with('#') => {
+            let base = match bases.last() {
+                Some(base) => resolve_uri(base, id),
+                None => id.clone(),
+            };
+            bases.push(base);
+            true
+        }
+        _ => false,
+    };
+    (bound, based)
+}
+
+fn leave((bound, based): (bool, bool), bases: &mut Vec<String>, scope: &mut Vec<String>) {
+    if based {
+        bases.pop();
+    }
+    if bound {
+        scope.pop();
+    }
+}
+
+struct Bundler<'a> {
+    registry: &'a SchemaRegistry,
+    roots: HashMap<*const Schema, &'a str>,
+    /// Definition names of targets already collected.
+    names: HashMap<*const Schema, String>,
+    used: HashSet<String>,
+    definitions: IndexMap<String, Schema>,
+    bases: Vec<String>,
+    scope: Vec<String>,
+    error: Option<RegistryError>,
+}
+
+impl Bundler<'_> {
+    /// Collects the target of `reference`, returning its definition name.
+    fn collect(&mut self, reference: &str) -> Result<String, RegistryError> {
+        let registry = self.registry;
+        let (target, base) =
+            registry.resolve_ref_in(reference, self.bases.last().map(String::as_str))?;
+        let key = target as *const Schema;
+        if let Some(name) = self.names.get(&key) {
+            return Ok(name.clone());
+        }
+
+        let preferred = definition_name(target, self.roots.get(&key).copied(), reference);
+        let mut name = preferred.clone();
+        let mut n = 2;
+        while !self.used.insert(name.clone()) {
+            name = format!("{}{}", preferred, n);
+            n += 1;
+        }
+        self.names.insert(key, name.clone());
+        // Reserve the slot so definitions appear in discovery order.
+        self.definitions
+            .insert(name.clone(), Schema::new(SchemaKind::Unknown));
+
+        let mut definition = target.clone();
+        let bases = std::mem::replace(&mut self.bases, base.into_iter().collect());
+        let scope = std::mem::take(&mut self.scope);
+        self.visit_schema_mut(&mut definition, &mut SchemaPath::new());
+        self.bases = bases;
+        self.scope = scope;
+
+        self.definitions.insert(name.clone(), definition);
+        Ok(name)
+    }
+}
+
+impl SchemaVisitorMut for Bundler<'_> {
+    fn visit_schema_mut(&mut self, schema: &mut Schema, path: &mut SchemaPath) {
+        let entered = enter(schema, &mut self.bases, &mut self.scope);
+        if let SchemaKind::Ref { reference } = &mut schema.kind {
+            if !self.scope.iter().any(|id| id == reference) {
+                match self.collect(reference) {
+                    Ok(name) => *reference = format!("#/definitions/{}", name),
+                    Err(e) => {
+                        self.error.get_or_insert(e);
+                    }
+                }
+            }
+        }
+        walk_schema_mut(self, schema, path);
+        leave(entered, &mut self.bases, &mut self.scope);
+    }
+}
+
+/// A schema being inlined, for detecting cycles.
+struct Frame {
+    target: *const Schema,
+    id: String,
+    recursive: bool,
+}
+
+struct Dereferencer<'a> {
+    registry: &'a SchemaRegistry,
+    roots: HashMap<*const Schema, &'a str>,
+    stack: Vec<Frame>,
+    bases: Vec<String>,
+    scope: Vec<String>,
+    error: Option<RegistryError>,
+}
+
+impl Dereferencer<'_> {
+    /// Returns a copy of `target` with its references inlined, wrapped in
+    /// `Recursive` if it refers back to itself.
+    fn inline(&mut self, target: &Schema, base: Option<String>, reference: &str) -> Schema {
+        let preferred = definition_name(
+            target,
+            self.roots.get(&(target as *const Schema)).copied(),
+            reference,
+        );
+        let mut id = preferred.clone();
+        let mut n = 2;
+        while self.stack.iter().any(|frame| frame.id == id) {
+            id = format!("{}{}", preferred, n);
+            n += 1;
+        }
+        self.stack.push(Frame {
+            target,
+            id,
+            recursive: false,
+        });
+
+        let mut schema = target.clone();
+        let bases = std::mem::replace(&mut self.bases, base.into_iter().collect());
+        let scope = std::mem::take(&mut self.scope);
+        self.visit_schema_mut(&mut schema, &mut SchemaPath::new());
+        self.bases = bases;
+        self.scope = scope;
+
+        let frame = self.stack.pop().expect("frame pushed above");
+        if frame.recursive {
+            Schema::new(SchemaKind::Recursive {
+                schema: Box::new(schema),
+            })
+            .with_id(frame.id)
+        } else {
+            schema
+        }
+    }
+
+    fn expand(&mut self, reference: &str) -> Result<Schema, RegistryError> {
+        let registry = self.registry;
+        let (target, base) =
+            registry.resolve_ref_in(reference, self.bases.last().map(String::as_str))?;
+        let key = target as *const Schema;
+        if let Some(frame) = self.stack.iter_mut().find(|frame| frame.target == key) {
+            frame.recursive = true;
+            return Ok(Schema::new(SchemaKind::Ref {
+                reference: frame.id.clone(),
+            }));
+        }
+        Ok(self.inline(target, base, reference))
+    }
+}
+
+impl SchemaVisitorMut for Dereferencer<'_> {
+    fn visit_schema_mut(&mut self, schema: &mut Schema, path: &mut SchemaPath) {
+        let entered = enter(schema, &mut self.bases, &mut self.scope);
+        if let SchemaKind::Ref { reference } = &schema.kind {
+            if !self.scope.iter().any(|id| id == reference) {
+                match self.expand(&reference.clone()) {
+                    Ok(expanded) => *schema = with_annotations(expanded, schema),
+                    Err(e) => {
+                        self.error.get_or_insert(e);
+                    }
+                }
+                // The expansion has already been processed.
+                leave(entered, &mut self.bases, &mut self.scope);
+                return;
+            }
+        }
+        walk_schema_mut(self, schema, path);
+        leave(entered, &mut self.bases, &mut self.scope);
+    }
+}
+
+/// Carries annotations written next to a `$ref` over to its expansion.
+fn with_annotations(mut expanded: Schema, reference: &Schema) -> Schema {
+    if reference.title.is_some() {
+        expanded.title = reference.title.clone();
+    }
+    if reference.description.is_some() {
+        expanded.description = reference.description.clone();
+    }
+    if reference.default.is_some() {
+        expanded.default = reference.default.clone();
+    }
+    if reference.examples.is_some() {
+        expanded.examples = reference.examples.clone();
+    }
+    if reference.read_only.is_some() {
+        expanded.read_only = reference.read_only;
+    }
+    if reference.write_only.is_some() {
+        expanded.write_only = reference.write_only;
+    }
+    if reference.deprecated.is_some() {
+        expanded.deprecated = reference.deprecated;
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::validate::{validate, validate_with_registry};
+    use crate::value::Value;
+
+    fn shop() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Order",
+            SchemaBuilder::object()
+                .field("billing", SchemaBuilder::r#ref("Address"))
+                .field("shipping", SchemaBuilder::r#ref("Address"))
+                .field(
+                    "items",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Item")).build(),
+                )
+                .named("Order"),
+        );
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .named("Address"),
+        );
+        registry.register(
+            "Item",
+            SchemaBuilder::object()
+                .field("sku", SchemaBuilder::string().build())
+                .optional_field(
+                    "parts",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Item")).build(),
+                )
+                .named("Item"),
+        );
+        registry.register("Unused", SchemaBuilder::string().build());
+        registry
+    }
+
+    fn order() -> Value {
+        let address = Value::object()
+            .field("street", Value::string("Main"))
+            .build();
+        let part = Value::object().field("sku", Value::string("p1")).build();
+        let item = Value::object()
+            .field("sku", Value::string("i1"))
+            .field("parts", Value::array(vec![part]))
+            .build();
+        Value::object()
+            .field("billing", address.clone())
+            .field("shipping", address)
+            .field("items", Value::array(vec![item]))
+            .build()
+    }
+
+    #[test]
+    fn test_bundle_collects_transitive_refs() {
+        let registry = shop();
+        let bundle = registry.bundle("Order").unwrap();
+        assert_eq!(
+            bundle.definitions.keys().collect::<Vec<_>>(),
+            ["Address", "Item"]
+        );
+        assert!(
+            validate_with_registry(&bundle.root, &order(), Some(&bundle.to_registry())).is_ok()
+        );
+
+        let json = serde_json::to_value(&bundle).unwrap();
+        assert_eq!(
+            json["schema"]["properties"]["billing"]["$ref"],
+            "#/definitions/Address"
+        );
+        let parsed: Bundle = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, bundle);
+    }
+
+    #[test]
+    fn test_bundle_rewrites_uri_refs() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "https://example.com/person.json",
+            SchemaBuilder::object()
+                .field(
+                    "home",
+                    Schema::new(SchemaKind::Ref {
+                        reference: "address.json".to_string(),
+                    }),
+                )
+                .field(
+                    "street",
+                    Schema::new(SchemaKind::Ref {
+                        reference: "address.json#/properties/street".to_string(),
+                    }),
+                )
+                .build()
+                .with_id("https://example.com/person.json"),
+        );
+        registry.register(
+            "https://example.com/address.json",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .build()
+                .with_id("https://example.com/address.json"),
+        );
+
+        let bundle = registry.bundle("https://example.com/person.json").unwrap();
+        assert_eq!(
+            bundle.definitions.keys().collect::<Vec<_>>(),
+            ["address", "street"]
+        );
+        assert!(registry.bundle("Missing").is_err());
+    }
+
+    #[test]
+    fn test_dereference_inlines_and_preserves_cycles() {
+        let registry = shop();
+        let order_schema = registry.dereference("Order").unwrap();
+        assert!(validate(&order_schema, &order()).is_ok());
+
+        let mut refs = Vec::new();
+        order_schema.accept(&mut RefCollector(&mut refs));
+        assert_eq!(refs, ["Item"]);
+
+        let bad = Value::object()
+            .field("billing", Value::object().build())
+            .build();
+        assert!(validate(&order_schema, &bad).is_err());
+    }
+
+    #[test]
+    fn test_dereference_mutual_recursion() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "A",
+            SchemaBuilder::object()
+                .optional_field("b", SchemaBuilder::r#ref("B"))
+                .named("A"),
+        );
+        registry.register(
+            "B",
+            SchemaBuilder::object()
+                .optional_field("a", SchemaBuilder::r#ref("A"))
+                .named("B"),
+        );
+
+        let a = registry.dereference("A").unwrap();
+        assert!(matches!(&a.kind, SchemaKind::Recursive { .. }) && a.id.as_deref() == Some("A"));
+        let value = Value::object()
+            .field(
+                "b",
+                Value::object().field("a", Value::object().build()).build(),
+            )
+            .build();
+        assert!(validate(&a, &value).is_ok());
+    }
+
+    struct RefCollector<'a>(&'a mut Vec<String>);
+
+    impl crate::visit::SchemaVisitor for RefCollector<'_> {
+        fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+            if let SchemaKind::Ref { reference } = &schema.kind {
+                self.0.push(reference.clone());
+            }
+            crate::visit::walk_schema(self, schema, path);
+        }
+    }
+}
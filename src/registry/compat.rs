@@ -0,0 +1,653 @@
+//! Structural compatibility between two versions of a schema.
+
+use super::SchemaRegistry;
+use crate::schema::{LiteralValue, Schema, SchemaKind};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Which values a new version of a schema must keep accepting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompatibilityPolicy {
+    /// No check.
+    #[default]
+    None,
+    /// The new version accepts every value valid under the previous one, so
+    /// consumers can upgrade first.
+    Backward,
+    /// The previous version accepts every value valid under the new one, so
+    /// producers can upgrade first.
+    Forward,
+    /// Both backward and forward.
+    Full,
+}
+
+/// One reason a reader schema does not accept every value of a writer schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    /// JSON Pointer to the offending location in the writer schema.
+    pub path: String,
+    /// What is incompatible.
+    pub message: String,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+/// Lists why `reader` may reject values that are valid under `writer`; an
+/// empty result means every `writer` value is also a `reader` value.
+///
+/// The check is structural and conservative: it may report differences
+/// that no concrete value exercises (e.g. a narrowed `pattern`), but never
+/// misses a change in type, a new required property, a removed property of
+/// a closed object, a narrowed bound or a removed enum member. `$ref`s are
+/// resolved through `registry`.
+///
+/// # Examples
+///
+/// ```
+/// use typebox::SchemaBuilder;
+/// use typebox::registry::compatibility_issues;
+///
+/// let v1 = SchemaBuilder::object()
+///     .field("name", SchemaBuilder::string().build())
+///     .build();
+/// let v2 = SchemaBuilder::object()
+///     .field("name", SchemaBuilder::string().build())
+///     .optional_field("email", SchemaBuilder::string().build())
+///     .build();
+///
+/// // v2 reads everything v1 wrote...
+/// assert!(compatibility_issues(&v2, &v1, None).is_empty());
+/// // ...but v1 rejects v2 values carrying an email.
+/// let issues = compatibility_issues(&v1, &v2, None);
+/// assert_eq!(issues[0].path, "/properties/email");
+/// ```
+pub fn compatibility_issues(
+    reader: &Schema,
+    writer: &Schema,
+    registry: Option<&SchemaRegistry>,
+) -> Vec<Incompatibility> {
+    let mut checker = Checker {
+        registry,
+        active: HashSet::new(),
+        issues: Vec::new(),
+    };
+    checker.check(reader, writer, &mut String::new());
+    checker.issues
+}
+
+struct Checker<'a> {
+    registry: Option<&'a SchemaRegistry>,
+    /// The (reader, writer) pairs being compared along the current path.
+    active: HashSet<(*const Schema, *const Schema)>,
+    issues: Vec<Incompatibility>,
+}
+
+impl<'a> Checker<'a> {
+    fn report(&mut self, path: &str, message: String) {
+        self.issues.push(Incompatibility {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    /// Looks through `Named` wrappers and resolves `Ref`s.
+    fn unwrap<'s>(&self, mut schema: &'s Schema) -> &'s Schema
+    where
+        'a: 's,
+    {
+        loop {
+            match &schema.kind {
+                SchemaKind::Named { schema: inner, .. }
+                | SchemaKind::Recursive { schema: inner } => schema = inner,
+                SchemaKind::Ref { .. } => match self.registry.map(|r| r.resolve(schema)) {
+                    Some(Ok(resolved)) if !std::ptr::eq(resolved, schema) => schema = resolved,
+                    _ => return schema,
+                },
+                _ => return schema,
+            }
+        }
+    }
+
+    /// Whether `reader` accepts every value of `writer`, without reporting.
+    fn accepts(&mut self, reader: &Schema, writer: &Schema) -> bool {
+        let len = self.issues.len();
+        self.check(reader, writer, &mut String::new());
+        let accepted = self.issues.len() == len;
+        self.issues.truncate(len);
+        accepted
+    }
+
+    fn check(&mut self, reader: &Schema, writer: &Schema, path: &mut String) {
+        let reader = self.unwrap(reader);
+        let writer = self.unwrap(writer);
+        let pair = (reader as *const Schema, writer as *const Schema);
+        if !self.active.insert(pair) {
+            // Already being compared further up: assume compatible.
+            return;
+        }
+        self.compare(reader, writer, path);
+        self.active.remove(&pair);
+    }
+
+    fn compare(&mut self, reader: &Schema, writer: &Schema, path: &mut String) {
+        match (&reader.kind, &writer.kind) {
+            (SchemaKind::Any | SchemaKind::Unknown, _) => {}
+            (_, SchemaKind::Never) => {}
+
+            // Every writer variant must be accepted.
+            (_, SchemaKind::Union { any_of }) => {
+                for (i, variant) in any_of.iter().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("/any_of/{}", i));
+                    self.check(reader, variant, path);
+                    path.truncate(len);
+                }
+            }
+            // Some reader variant must accept the writer.
+            (SchemaKind::Union { any_of }, _) => {
+                if !any_of.iter().any(|variant| self.accepts(variant, writer)) {
+                    self.report(
+                        path,
+                        format!("{} is not accepted by any variant", writer.kind()),
+                    );
+                }
+            }
+            (SchemaKind::Intersect { all_of }, _) => {
+                for member in all_of {
+                    self.check(member, writer, path);
+                }
+            }
+
+            (SchemaKind::Null | SchemaKind::Void | SchemaKind::Undefined, SchemaKind::Null)
+            | (SchemaKind::Bool, SchemaKind::Bool)
+            | (SchemaKind::Function { .. }, SchemaKind::Function { .. }) => {}
+
+            (reader_kind, writer_kind)
+                if int_range(reader_kind).is_some() && int_range(writer_kind).is_some() =>
+            {
+                let (reader_min, reader_max) = int_range(reader_kind).unwrap_or_default();
+                let (writer_min, writer_max) = int_range(writer_kind).unwrap_or_default();
+                if writer_min < reader_min || writer_max > reader_max {
+                    self.report(
+                        path,
+                        format!(
+                            "range {}..={} narrowed to {}..={}",
+                            writer_min, writer_max, reader_min, reader_max
+                        ),
+                    );
+                }
+            }
+            (reader_kind, writer_kind)
+                if float_range(reader_kind).is_some() && float_range(writer_kind).is_some() =>
+            {
+                let (reader_min, reader_max) = float_range(reader_kind).unwrap_or_default();
+                let (writer_min, writer_max) = float_range(writer_kind).unwrap_or_default();
+                if writer_min < reader_min || writer_max > reader_max {
+                    self.report(
+                        path,
+                        format!(
+                            "range {}..={} narrowed to {}..={}",
+                            writer_min, writer_max, reader_min, reader_max
+                        ),
+                    );
+                }
+            }
+
+            (
+                SchemaKind::String {
+                    format,
+                    pattern,
+                    min_length,
+                    max_length,
+                },
+                SchemaKind::String {
+                    format: writer_format,
+                    pattern: writer_pattern,
+                    min_length: writer_min,
+                    max_length: writer_max,
+                },
+            ) => {
+                self.check_length(
+                    path,
+                    "length",
+                    *min_length,
+                    *max_length,
+                    *writer_min,
+                    *writer_max,
+                );
+                if format.is_some() && format != writer_format {
+                    self.report(path, format!("format {:?} added or changed", format));
+                }
+                if pattern.is_some() && pattern != writer_pattern {
+                    self.report(path, format!("pattern {:?} added or changed", pattern));
+                }
+            }
+            (SchemaKind::String { .. }, SchemaKind::Enum { values }) => {
+                for value in values {
+                    if !string_accepted(reader, value) {
+                        self.report(path, format!("string {:?} is rejected", value));
+                    }
+                }
+            }
+            (
+                SchemaKind::String { .. },
+                SchemaKind::Literal {
+                    value: LiteralValue::String(s),
+                },
+            ) => {
+                if !string_accepted(reader, s) {
+                    self.report(path, format!("string {:?} is rejected", s));
+                }
+            }
+
+            (
+                SchemaKind::Bytes {
+                    min_length,
+                    max_length,
+                },
+                SchemaKind::Bytes {
+                    min_length: writer_min,
+                    max_length: writer_max,
+                },
+            ) => self.check_length(
+                path,
+                "length",
+                *min_length,
+                *max_length,
+                *writer_min,
+                *writer_max,
+            ),
+
+            (
+                SchemaKind::Array {
+                    items,
+                    min_items,
+                    max_items,
+                    unique_items,
+                    ..
+                },
+                SchemaKind::Array {
+                    items: writer_items,
+                    min_items: writer_min,
+                    max_items: writer_max,
+                    unique_items: writer_unique,
+                    ..
+                },
+            ) => {
+                self.check_length(
+                    path,
+                    "item count",
+                    *min_items,
+                    *max_items,
+                    *writer_min,
+                    *writer_max,
+                );
+                if *unique_items == Some(true) && *writer_unique != Some(true) {
+                    self.report(path, "items must now be unique".to_string());
+                }
+                let len = path.len();
+                path.push_str("/items");
+                self.check(items, writer_items, path);
+                path.truncate(len);
+            }
+
+            (
+                SchemaKind::Tuple { items },
+                SchemaKind::Tuple {
+                    items: writer_items,
+                },
+            ) => {
+                if items.len() != writer_items.len() {
+                    self.report(
+                        path,
+                        format!(
+                            "tuple length changed from {} to {}",
+                            writer_items.len(),
+                            items.len()
+                        ),
+                    );
+                    return;
+                }
+                for (i, (item, writer_item)) in items.iter().zip(writer_items).enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("/items/{}", i));
+                    self.check(item, writer_item, path);
+                    path.truncate(len);
+                }
+            }
+
+            (
+                SchemaKind::Object {
+                    properties,
+                    required,
+                    additional_properties,
+                },
+                SchemaKind::Object {
+                    properties: writer_properties,
+                    required: writer_required,
+                    additional_properties: writer_additional,
+                },
+            ) => {
+                for name in required {
+                    if !writer_required.contains(name) {
+                        self.report(
+                            &format!("{}/properties/{}", path, escape(name)),
+                            "property is now required".to_string(),
+                        );
+                    }
+                }
+                for (name, writer_property) in writer_properties {
+                    let len = path.len();
+                    path.push_str(&format!("/properties/{}", escape(name)));
+                    match properties.get(name).or(additional_properties.as_deref()) {
+                        Some(property) => self.check(property, writer_property, path),
+                        None => self.report(path, "property is no longer allowed".to_string()),
+                    }
+                    path.truncate(len);
+                }
+                if let Some(writer_additional) = writer_additional {
+                    let len = path.len();
+                    path.push_str("/additional_properties");
+                    for (name, property) in properties {
+                        if !writer_properties.contains_key(name) {
+                            self.check(property, writer_additional, path);
+                        }
+                    }
+                    match additional_properties {
+                        Some(additional) => self.check(additional, writer_additional, path),
+                        None => self.report(
+                            path,
+                            "additional properties are no longer allowed".to_string(),
+                        ),
+                    }
+                    path.truncate(len);
+                }
+            }
+
+            (
+                SchemaKind::Enum { values },
+                SchemaKind::Enum {
+                    values: writer_values,
+                },
+            ) => {
+                let removed: Vec<&String> = writer_values
+                    .iter()
+                    .filter(|v| !values.contains(v))
+                    .collect();
+                if !removed.is_empty() {
+                    self.report(path, format!("enum values removed: {:?}", removed));
+                }
+            }
+            (
+                SchemaKind::Enum { values },
+                SchemaKind::Literal {
+                    value: LiteralValue::String(s),
+                },
+            ) => {
+                if !values.contains(s) {
+                    self.report(path, format!("enum no longer contains {:?}", s));
+                }
+            }
+            (
+                SchemaKind::Literal { value },
+                SchemaKind::Literal {
+                    value: writer_value,
+                },
+            ) => {
+                if value != writer_value {
+                    self.report(
+                        path,
+                        format!("literal changed from {:?} to {:?}", writer_value, value),
+                    );
+                }
+            }
+            (reader_kind, SchemaKind::Literal { value }) if literal_fits(reader_kind, value) => {}
+
+            // Unresolvable, e.g. a `Recursive` self-reference.
+            (
+                SchemaKind::Ref { reference },
+                SchemaKind::Ref {
+                    reference: writer_reference,
+                },
+            ) if reference == writer_reference => {}
+
+            _ => self.report(
+                path,
+                format!("type changed from {} to {}", writer.kind(), reader.kind()),
+            ),
+        }
+    }
+
+    fn check_length(
+        &mut self,
+        path: &str,
+        what: &str,
+        min: Option<usize>,
+        max: Option<usize>,
+        writer_min: Option<usize>,
+        writer_max: Option<usize>,
+    ) {
+        let (min, writer_min) = (min.unwrap_or(0), writer_min.unwrap_or(0));
+        let (max, writer_max) = (max.unwrap_or(usize::MAX), writer_max.unwrap_or(usize::MAX));
+        if writer_min < min {
+            self.report(path, format!("minimum {} raised to {}", what, min));
+        }
+        if writer_max > max {
+            self.report(path, format!("maximum {} lowered to {}", what, max));
+        }
+    }
+}
+
+/// Effective range of an integer kind, including its bounds.
+fn int_range(kind: &SchemaKind) -> Option<(i128, i128)> {
+    macro_rules! range {
+        ($ty:ty, $min:expr, $max:expr) => {
+            Some((
+                $min.map(i128::from).unwrap_or(<$ty>::MIN as i128),
+                $max.map(i128::from).unwrap_or(<$ty>::MAX as i128),
+            ))
+        };
+    }
+    match kind {
+        SchemaKind::Int8 { minimum, maximum } => range!(i8, *minimum, *maximum),
+        SchemaKind::Int16 { minimum, maximum } => range!(i16, *minimum, *maximum),
+        SchemaKind::Int32 { minimum, maximum } => range!(i32, *minimum, *maximum),
+        SchemaKind::Int64 { minimum, maximum } => range!(i64, *minimum, *maximum),
+        SchemaKind::UInt8 { minimum, maximum } => range!(u8, *minimum, *maximum),
+        SchemaKind::UInt16 { minimum, maximum } => range!(u16, *minimum, *maximum),
+        SchemaKind::UInt32 { minimum, maximum } => range!(u32, *minimum, *maximum),
+        SchemaKind::UInt64 { minimum, maximum } => range!(u64, *minimum, *maximum),
+        _ => None,
+    }
+}
+
+/// Effective range of a float kind, including its bounds.
+fn float_range(kind: &SchemaKind) -> Option<(f64, f64)> {
+    match kind {
+        SchemaKind::Float32 { minimum, maximum } => Some((
+            minimum.map(f64::from).unwrap_or(f32::MIN as f64),
+            maximum.map(f64::from).unwrap_or(f32::MAX as f64),
+        )),
+        SchemaKind::Float64 { minimum, maximum } => {
+            Some((minimum.unwrap_or(f64::MIN), maximum.unwrap_or(f64::MAX)))
+        }
+        _ => None,
+    }
+}
+
+fn string_accepted(reader: &Schema, s: &str) -> bool {
+    match &reader.kind {
+        SchemaKind::String {
+            min_length,
+            max_length,
+            format,
+            pattern,
+        } => {
+            let len = s.chars().count();
+            format.is_none()
+                && pattern.is_none()
+                && min_length.is_none_or(|min| len >= min)
+                && max_length.is_none_or(|max| len <= max)
+        }
+        _ => false,
+    }
+}
+
+/// Whether a literal is accepted by a non-literal reader kind.
+fn literal_fits(kind: &SchemaKind, value: &LiteralValue) -> bool {
+    match (kind, value) {
+        (SchemaKind::Null, LiteralValue::Null) => true,
+        (SchemaKind::Bool, LiteralValue::Boolean(_)) => true,
+        (kind, LiteralValue::Number(n)) => {
+            int_range(kind).is_some_and(|(min, max)| (min..=max).contains(&(*n as i128)))
+        }
+        (kind, LiteralValue::Float(f)) => {
+            float_range(kind).is_some_and(|(min, max)| *f >= min && *f <= max)
+        }
+        _ => false,
+    }
+}
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+
+    fn person_v1() -> Schema {
+        SchemaBuilder::object()
+            .field("name", SchemaBuilder::string().build())
+            .field("age", SchemaBuilder::int32())
+            .field(
+                "status",
+                Schema::new(SchemaKind::Enum {
+                    values: vec!["active".to_string(), "banned".to_string()],
+                }),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_widening_is_backward_compatible() {
+        let v2 = SchemaBuilder::object()
+            .field("name", SchemaBuilder::string().build())
+            .field("age", SchemaBuilder::int64())
+            .field(
+                "status",
+                Schema::new(SchemaKind::Enum {
+                    values: vec![
+                        "active".to_string(),
+                        "banned".to_string(),
+                        "pending".to_string(),
+                    ],
+                }),
+            )
+            .optional_field("email", SchemaBuilder::string().build())
+            .build();
+        assert_eq!(compatibility_issues(&v2, &person_v1(), None), []);
+
+        let forward: Vec<String> = compatibility_issues(&person_v1(), &v2, None)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            forward,
+            [
+                "/properties/age: range -9223372036854775808..=9223372036854775807 narrowed to -2147483648..=2147483647",
+                "/properties/status: enum values removed: [\"pending\"]",
+                "/properties/email: property is no longer allowed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let v2 = SchemaBuilder::object()
+            .field("name", SchemaBuilder::string().min_length(1).build())
+            .field("age", SchemaBuilder::string().build())
+            .field(
+                "status",
+                Schema::new(SchemaKind::Enum {
+                    values: vec!["active".to_string(), "banned".to_string()],
+                }),
+            )
+            .field("email", SchemaBuilder::string().build())
+            .build();
+        let issues: Vec<String> = compatibility_issues(&v2, &person_v1(), None)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "/properties/email: property is now required",
+                "/properties/name: minimum length raised to 1",
+                "/properties/age: type changed from Int32 to String",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unions_refs_and_recursion() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Node",
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int32())
+                .optional_field("next", SchemaBuilder::r#ref("Node"))
+                .build(),
+        );
+        registry.register(
+            "Node2",
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int64())
+                .optional_field(
+                    "next",
+                    SchemaBuilder::optional(SchemaBuilder::r#ref("Node2")),
+                )
+                .build(),
+        );
+        let v1 = SchemaBuilder::r#ref("Node");
+        let v2 = SchemaBuilder::r#ref("Node2");
+        assert!(compatibility_issues(&v2, &v1, Some(&registry)).is_empty());
+        assert!(!compatibility_issues(&v1, &v2, Some(&registry)).is_empty());
+
+        let nullable = SchemaBuilder::optional(SchemaBuilder::string().build());
+        let plain = SchemaBuilder::string().build();
+        assert!(compatibility_issues(&nullable, &plain, None).is_empty());
+        assert_eq!(
+            compatibility_issues(&plain, &nullable, None)[0].path,
+            "/any_of/1"
+        );
+    }
+
+    #[test]
+    fn test_repeated_refs_checked_each_time() {
+        let mut registry = SchemaRegistry::new();
+        registry.register("Old", SchemaBuilder::int64());
+        registry.register("New", SchemaBuilder::int32());
+        let pair = |name: &str| {
+            SchemaBuilder::object()
+                .field("a", SchemaBuilder::r#ref(name))
+                .field("b", SchemaBuilder::r#ref(name))
+                .build()
+        };
+        let paths: Vec<_> = compatibility_issues(&pair("New"), &pair("Old"), Some(&registry))
+            .into_iter()
+            .map(|issue| issue.path)
+            .collect();
+        assert_eq!(paths, ["/properties/a", "/properties/b"]);
+    }
+}
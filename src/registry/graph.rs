@@ -0,0 +1,528 @@
+//! Dependency graph between registered schemas.
+
+use super::{resolve_uri, SchemaRegistry};
+use crate::schema::{Schema, SchemaKind};
+use crate::visit::{walk_schema, SchemaPath, SchemaVisitor};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Dependencies between the schemas of a [`SchemaRegistry`].
+///
+/// There is an edge from `A` to `B` when `A` contains a `$ref` resolving
+/// into `B`, or embeds a `Named` schema called `B` that is also registered.
+/// References bound by an enclosing `Recursive` schema and references that
+/// do not resolve contribute no edge. Every query returns names in a
+/// deterministic order.
+///
+/// # Examples
+///
+/// ```
+/// use typebox::{SchemaBuilder, SchemaRegistry};
+///
+/// let mut registry = SchemaRegistry::new();
+/// registry.register(
+///     "Order",
+///     SchemaBuilder::object()
+///         .field("customer", SchemaBuilder::r#ref("Customer"))
+///         .named("Order"),
+/// );
+/// registry.register(
+///     "Customer",
+///     SchemaBuilder::object()
+///         .field("address", SchemaBuilder::r#ref("Address"))
+///         .named("Customer"),
+/// );
+/// registry.register("Address", SchemaBuilder::string().build());
+///
+/// let graph = registry.dependency_graph();
+/// assert_eq!(graph.topological_order(), ["Address", "Customer", "Order"]);
+/// assert_eq!(graph.transitive_dependents("Address"), ["Customer", "Order"]);
+/// assert_eq!(graph.unreferenced(), ["Order"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Direct dependencies of each registered name.
+    edges: BTreeMap<String, BTreeSet<String>>,
+    /// Direct dependents of each registered name.
+    reverse: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SchemaRegistry {
+    /// Builds the dependency graph of the registered schemas.
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let mut owners = HashMap::new();
+        for (name, schema) in &self.schemas {
+            schema.accept(&mut OwnerIndexer {
+                name,
+                owners: &mut owners,
+            });
+        }
+
+        let mut graph = DependencyGraph::default();
+        for (name, schema) in &self.schemas {
+            let mut collector = EdgeCollector {
+                registry: self,
+                owners: &owners,
+                bases: self.document_base(name).into_iter().collect(),
+                scope: Vec::new(),
+                edges: BTreeSet::new(),
+            };
+            schema.accept(&mut collector);
+            graph.reverse.entry(name.clone()).or_default();
+            for dependency in &collector.edges {
+                graph
+                    .reverse
+                    .entry(dependency.clone())
+                    .or_default()
+                    .insert(name.clone());
+            }
+            graph.edges.insert(name.clone(), collector.edges);
+        }
+        graph
+    }
+}
+
+impl DependencyGraph {
+    /// All registered names, sorted.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.edges.keys().map(String::as_str)
+    }
+
+    /// Every edge as `(dependent, dependency)`, sorted.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.edges
+            .iter()
+            .flat_map(|(from, to)| to.iter().map(move |to| (from.as_str(), to.as_str())))
+    }
+
+    /// Names `name` refers to directly, sorted.
+    pub fn dependencies(&self, name: &str) -> Vec<&str> {
+        self.edges
+            .get(name)
+            .map(|deps| deps.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Names referring to `name` directly, sorted.
+    pub fn dependents(&self, name: &str) -> Vec<&str> {
+        self.reverse
+            .get(name)
+            .map(|deps| deps.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Names that depend on `name` directly or indirectly, i.e. everything
+    /// affected by a change to it, sorted. `name` itself is only included
+    /// if it is recursive.
+    pub fn transitive_dependents(&self, name: &str) -> Vec<&str> {
+        reachable(&self.reverse, [name]).into_iter().collect()
+    }
+
+    /// Names that `name` depends on directly or indirectly, sorted.
+    pub fn transitive_dependencies(&self, name: &str) -> Vec<&str> {
+        reachable(&self.edges, [name]).into_iter().collect()
+    }
+
+    /// Names no other schema refers to, sorted: the entry points, or
+    /// definitions that are never used.
+    pub fn unreferenced(&self) -> Vec<&str> {
+        self.reverse
+            .iter()
+            .filter(|(name, dependents)| dependents.iter().all(|d| d == *name))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Names not reachable from any of `roots`, sorted: definitions that
+    /// can be dropped when only `roots` are needed.
+    pub fn unreachable<'a>(&self, roots: impl IntoIterator<Item = &'a str>) -> Vec<&str> {
+        let roots: Vec<&str> = roots.into_iter().collect();
+        let mut used = reachable(&self.edges, roots.iter().copied());
+        used.extend(
+            roots
+                .iter()
+                .filter_map(|root| self.edges.get_key_value(*root))
+                .map(|(k, _)| k.as_str()),
+        );
+        self.nodes().filter(|name| !used.contains(name)).collect()
+    }
+
+    /// Strongly connected components, dependencies first. Each component
+    /// is sorted; a component with more than one name, or a single name
+    /// referring to itself, is a group of mutually recursive schemas.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<&str>> {
+        let components = tarjan(&self.edges);
+
+        // Component of each name, then Kahn's algorithm over the condensed
+        // graph, taking the ready component with the smallest name first.
+        let mut component_of = HashMap::new();
+        for (index, component) in components.iter().enumerate() {
+            for name in component {
+                component_of.insert(*name, index);
+            }
+        }
+        let mut pending = vec![0usize; components.len()];
+        let mut dependents = vec![BTreeSet::new(); components.len()];
+        for (from, to) in self.edges() {
+            let (from, to) = (component_of[from], component_of[to]);
+            if from != to && dependents[to].insert(from) {
+                pending[from] += 1;
+            }
+        }
+
+        let mut ready: BTreeSet<(&str, usize)> = pending
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count == 0)
+            .map(|(index, _)| (components[index][0], index))
+            .collect();
+        let mut order = Vec::with_capacity(components.len());
+        while let Some(next) = ready.pop_first() {
+            let index = next.1;
+            for &dependent in &dependents[index] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    ready.insert((components[dependent][0], dependent));
+                }
+            }
+            order.push(components[index].clone());
+        }
+        order
+    }
+
+    /// Groups of mutually recursive schemas (including self-recursive
+    /// ones), dependencies first.
+    pub fn recursive_groups(&self) -> Vec<Vec<&str>> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.edges[component[0]].contains(component[0])
+            })
+            .collect()
+    }
+
+    /// Whether `name` takes part in a reference cycle.
+    pub fn is_recursive(&self, name: &str) -> bool {
+        reachable(&self.edges, [name]).contains(name)
+    }
+
+    /// All names with dependencies before their dependents. Names within
+    /// a recursive group are sorted; otherwise ties are broken by name, so
+    /// the order is stable across runs.
+    pub fn topological_order(&self) -> Vec<&str> {
+        self.strongly_connected_components()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Renders the graph in Graphviz DOT format, with an edge from each
+    /// schema to each of its dependencies.
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph schemas {\n");
+        for name in self.nodes() {
+            output.push_str(&format!("    {:?};\n", name));
+        }
+        for (from, to) in self.edges() {
+            output.push_str(&format!("    {:?} -> {:?};\n", from, to));
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    /// Renders the graph as a Mermaid flowchart, with an edge from each
+    /// schema to each of its dependencies.
+    pub fn to_mermaid(&self) -> String {
+        let ids: HashMap<&str, usize> = self
+            .nodes()
+            .enumerate()
+            .map(|(index, name)| (name, index))
+            .collect();
+        let mut output = String::from("graph TD\n");
+        for name in self.nodes() {
+            let label = name.replace('"', "#quot;");
+            output.push_str(&format!("    n{}[\"{}\"]\n", ids[name], label));
+        }
+        for (from, to) in self.edges() {
+            output.push_str(&format!("    n{} --> n{}\n", ids[from], ids[to]));
+        }
+        output
+    }
+}
+
+/// Names reachable from `starts` through at least one edge.
+fn reachable<'a: 'b, 'b>(
+    edges: &'a BTreeMap<String, BTreeSet<String>>,
+    starts: impl IntoIterator<Item = &'b str>,
+) -> BTreeSet<&'a str> {
+    let mut seen = BTreeSet::new();
+    let mut stack: Vec<&'b str> = starts.into_iter().collect();
+    while let Some(name) = stack.pop() {
+        for next in edges.get(name).into_iter().flatten() {
+            if seen.insert(next.as_str()) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Tarjan's algorithm; each component is sorted.
+fn tarjan(edges: &BTreeMap<String, BTreeSet<String>>) -> Vec<Vec<&str>> {
+    struct State<'a> {
+        edges: &'a BTreeMap<String, BTreeSet<String>>,
+        index: HashMap<&'a str, usize>,
+        low: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        components: Vec<Vec<&'a str>>,
+    }
+
+    fn connect<'a>(state: &mut State<'a>, name: &'a str) {
+        let index = state.index.len();
+        state.index.insert(name, index);
+        state.low.insert(name, index);
+        state.stack.push(name);
+        state.on_stack.insert(name);
+
+        for next in state.edges.get(name).into_iter().flatten() {
+            let next = next.as_str();
+            if !state.index.contains_key(next) {
+                connect(state, next);
+                let low = state.low[name].min(state.low[next]);
+                state.low.insert(name, low);
+            } else if state.on_stack.contains(next) {
+                let low = state.low[name].min(state.index[next]);
+                state.low.insert(name, low);
+            }
+        }
+
+        if state.low[name] == state.index[name] {
+            let mut component = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                component.push(member);
+                if member == name {
+                    break;
+                }
+            }
+            component.sort_unstable();
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        edges,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        components: Vec::new(),
+    };
+    for name in edges.keys() {
+        if !state.index.contains_key(name.as_str()) {
+            connect(&mut state, name);
+        }
+    }
+    state.components
+}
+
+/// Maps every subschema to the registered name containing it.
+struct OwnerIndexer<'a, 'b> {
+    name: &'a str,
+    owners: &'b mut HashMap<*const Schema, &'a str>,
+}
+
+impl SchemaVisitor for OwnerIndexer<'_, '_> {
+    fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+        self.owners.insert(schema as *const Schema, self.name);
+        walk_schema(self, schema, path);
+    }
+}
+
+/// Collects the registered names one schema depends on.
+struct EdgeCollector<'a> {
+    registry: &'a SchemaRegistry,
+    owners: &'a HashMap<*const Schema, &'a str>,
+    bases: Vec<String>,
+    scope: Vec<String>,
+    edges: BTreeSet<String>,
+}
+
+impl SchemaVisitor for EdgeCollector<'_> {
+    fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+        let bound = match (&schema.kind, &schema.id) {
+            (SchemaKind::Recursive { .. }, Some(id)) => {
+                self.scope.push(id.clone());
+                true
+            }
+            _ => false,
+        };
+        let based = match &schema.id {
+            Some(id) if !id.starts_with('#') => {
+                let base = match self.bases.last() {
+                    Some(base) => resolve_uri(base, id),
+                    None => id.clone(),
+                };
+                self.bases.push(base);
+                true
+            }
+            _ => false,
+        };
+
+        match &schema.kind {
+            SchemaKind::Ref { reference } if !self.scope.iter().any(|id| id == reference) => {
+                let base = self.bases.last().map(String::as_str);
+                if let Ok(target) = self.registry.resolve_ref(reference, base) {
+                    if let Some(owner) = self.owners.get(&(target as *const Schema)) {
+                        self.edges.insert(owner.to_string());
+                    }
+                }
+            }
+            SchemaKind::Named { name, .. } if !path.is_root() && self.registry.contains(name) => {
+                self.edges.insert(name.clone());
+            }
+            _ => {}
+        }
+
+        walk_schema(self, schema, path);
+        if based {
+            self.bases.pop();
+        }
+        if bound {
+            self.scope.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+
+    fn graph() -> DependencyGraph {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Order",
+            SchemaBuilder::object()
+                .field("customer", SchemaBuilder::r#ref("Customer"))
+                .field(
+                    "lines",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Line")).build(),
+                )
+                .named("Order"),
+        );
+        registry.register(
+            "Customer",
+            SchemaBuilder::object()
+                .field("address", SchemaBuilder::r#ref("Address"))
+                .field("street", SchemaBuilder::r#ref("Address/properties/street"))
+                .named("Customer"),
+        );
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .named("Address"),
+        );
+        // Line and Product refer to each other.
+        registry.register(
+            "Line",
+            SchemaBuilder::object()
+                .field("product", SchemaBuilder::r#ref("Product"))
+                .named("Line"),
+        );
+        registry.register(
+            "Product",
+            SchemaBuilder::object()
+                .optional_field(
+                    "bundle",
+                    SchemaBuilder::array(SchemaBuilder::r#ref("Line")).build(),
+                )
+                .field(
+                    "maker",
+                    SchemaBuilder::object()
+                        .field("name", SchemaBuilder::string().build())
+                        .named("Address"),
+                )
+                .named("Product"),
+        );
+        registry.register(
+            "Tree",
+            SchemaBuilder::recursive("Tree", |this| {
+                SchemaBuilder::object()
+                    .field("children", SchemaBuilder::array(this).build())
+                    .build()
+            }),
+        );
+        registry.register(
+            "Unused",
+            SchemaBuilder::object()
+                .field("missing", SchemaBuilder::r#ref("Nowhere"))
+                .build(),
+        );
+        registry.dependency_graph()
+    }
+
+    #[test]
+    fn test_edges_and_reverse_dependencies() {
+        let graph = graph();
+        assert_eq!(graph.dependencies("Customer"), ["Address"]);
+        assert_eq!(graph.dependencies("Product"), ["Address", "Line"]);
+        assert!(graph.dependencies("Tree").is_empty());
+        assert!(graph.dependencies("Unused").is_empty());
+        assert_eq!(graph.dependents("Address"), ["Customer", "Product"]);
+        assert_eq!(
+            graph.transitive_dependents("Address"),
+            ["Customer", "Line", "Order", "Product"]
+        );
+        assert_eq!(
+            graph.transitive_dependencies("Order"),
+            ["Address", "Customer", "Line", "Product"]
+        );
+    }
+
+    #[test]
+    fn test_topological_order_and_components() {
+        let graph = graph();
+        assert_eq!(
+            graph.topological_order(),
+            ["Address", "Customer", "Line", "Product", "Order", "Tree", "Unused"]
+        );
+        assert_eq!(graph.recursive_groups(), [vec!["Line", "Product"]]);
+        assert!(graph.is_recursive("Line"));
+        assert!(!graph.is_recursive("Order"));
+    }
+
+    #[test]
+    fn test_unused_definitions() {
+        let graph = graph();
+        assert_eq!(graph.unreferenced(), ["Order", "Tree", "Unused"]);
+        assert_eq!(graph.unreachable(["Order"]), ["Tree", "Unused"]);
+        assert_eq!(
+            graph.unreachable(["Customer"]),
+            ["Line", "Order", "Product", "Tree", "Unused"]
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "A",
+            SchemaBuilder::object()
+                .field("b", SchemaBuilder::r#ref("B"))
+                .build(),
+        );
+        registry.register("B", SchemaBuilder::string().build());
+        let graph = registry.dependency_graph();
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph schemas {\n    \"A\";\n    \"B\";\n    \"A\" -> \"B\";\n}\n"
+        );
+        assert_eq!(
+            graph.to_mermaid(),
+            "graph TD\n    n0[\"A\"]\n    n1[\"B\"]\n    n0 --> n1\n"
+        );
+    }
+}
@@ -0,0 +1,657 @@
+//! Loading and saving registries as directories of schema documents.
+
+use super::{resolve_uri, schema_id, strip_fragment, SchemaRegistry};
+use crate::error::{LoadError, RegistryError};
+use crate::schema::{Schema, SchemaKind};
+use crate::visit::{walk_schema, SchemaPath, SchemaVisitor};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Document formats recognized by file extension.
+enum Format {
+    Json,
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl Format {
+    fn of(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+}
+
+impl SchemaRegistry {
+    /// Loads every schema document under `dir`, recursing into
+    /// subdirectories, and returns the registered names in load order.
+    ///
+    /// Files are read in sorted path order. `.json` files are always loaded;
+    /// `.yaml`/`.yml` files are loaded with the `yaml` feature. Other files
+    /// are ignored. Each document is registered as by
+    /// [`load_file`](Self::load_file), and once all are loaded every `$ref`
+    /// they contain must resolve, so documents may reference each other by
+    /// relative path (`"address.json"`) or `$id` in any order.
+    ///
+    /// # Errors
+    ///
+    /// Every problem found (unreadable files, parse errors with line and
+    /// column, duplicate ids, dangling references) is reported together,
+    /// as [`LoadError::Multiple`] when there is more than one. Documents
+    /// that parsed are registered even when an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use typebox::{SchemaRegistry, Value, validate_with_registry, SchemaBuilder};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// std::fs::write(
+    ///     dir.path().join("person.json"),
+    ///     r#"{"kind": "object", "properties": {"address": {"kind": "ref", "$ref": "address.json"}}, "required": ["address"]}"#,
+    /// ).unwrap();
+    /// std::fs::write(
+    ///     dir.path().join("address.json"),
+    ///     r#"{"kind": "object", "properties": {"street": {"kind": "string"}}, "required": ["street"]}"#,
+    /// ).unwrap();
+    ///
+    /// let mut registry = SchemaRegistry::new();
+    /// let names = registry.load_dir(dir.path()).unwrap();
+    /// assert_eq!(names, ["address", "person"]);
+    ///
+    /// let value = Value::object()
+    ///     .field("address", Value::object().field("street", Value::string("Main")).build())
+    ///     .build();
+    /// let person = SchemaBuilder::r#ref("person");
+    /// assert!(validate_with_registry(&person, &value, Some(&registry)).is_ok());
+    /// ```
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<Vec<String>, LoadError> {
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+        collect_files(dir.as_ref(), &mut files, &mut errors);
+        files.sort();
+
+        let mut loaded = Vec::new();
+        for path in files {
+            match self.load_file(&path) {
+                Ok(name) => loaded.push((name, path)),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        for (name, path) in &loaded {
+            errors.extend(self.dangling_refs(name, path));
+        }
+
+        LoadError::collect(errors)?;
+        Ok(loaded.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// Loads one schema document and returns the name it was registered
+    /// under: its `$id` if it has one, otherwise the file stem.
+    ///
+    /// The document is registered with its `file://` URI as retrieval URI,
+    /// so relative `$id`s and `$ref`s in it resolve against its location.
+    /// References are not checked here since their targets may not be
+    /// loaded yet; use [`load_dir`](Self::load_dir) or
+    /// [`lint`](Self::lint) for that.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read, has an unsupported extension or
+    /// does not parse as a schema, or if its name is already registered
+    /// from a different file.
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<String, LoadError> {
+        let path = path.as_ref();
+        let format = Format::of(path).ok_or_else(|| LoadError::UnsupportedFormat {
+            path: path.to_path_buf(),
+        })?;
+        let io_error = |source| LoadError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let text = fs::read_to_string(path).map_err(io_error)?;
+        let schema = parse(&text, format, path)?;
+
+        let name = match schema_id(&schema) {
+            Some(id) if !id.starts_with('#') => id.to_string(),
+            _ => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        let uri = file_uri(&fs::canonicalize(path).map_err(io_error)?);
+        if let Some(existing) = self.uri_of(&name) {
+            if existing != uri {
+                return Err(LoadError::DuplicateId {
+                    id: name,
+                    first: uri_path(existing),
+                    second: path.to_path_buf(),
+                });
+            }
+        }
+
+        self.register_with_uri(name.clone(), schema, uri);
+        Ok(name)
+    }
+
+    /// Writes every registered schema to `dir` as pretty-printed JSON, one
+    /// file per schema, and returns the written paths in name order.
+    ///
+    /// Schemas loaded from files keep their path relative to the directory
+    /// containing all of them (with a `.json` extension), creating
+    /// subdirectories as needed, so relative references between them still
+    /// resolve when the directory is loaded again. Other schemas are
+    /// written to `<name>.json` in `dir`, using the last path segment when
+    /// the name is a URI. Output is deterministic: the same registry always
+    /// produces the same files byte for byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LoadError::Io`] if a directory or a file cannot be written.
+    pub fn save_dir(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, LoadError> {
+        let dir = dir.as_ref();
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| LoadError::Io { path, source }
+        };
+        fs::create_dir_all(dir).map_err(io_error(dir))?;
+
+        let mut names: Vec<&String> = self.schemas.keys().collect();
+        names.sort();
+
+        let base = self.file_base();
+        let mut used = HashSet::new();
+        let mut written = Vec::new();
+        for name in names {
+            let relative = self
+                .uri_of(name)
+                .filter(|uri| uri.starts_with("file://"))
+                .zip(base.as_deref())
+                .and_then(|(uri, base)| {
+                    let path = uri_path(uri);
+                    path.strip_prefix(base).ok().map(Path::to_path_buf)
+                })
+                .map(|path| path.with_extension("json"))
+                .unwrap_or_else(|| {
+                    PathBuf::from(format!("{}.json", file_stem_for(name, self.uri_of(name))))
+                });
+
+            let stem = relative
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let mut relative_path = relative.clone();
+            let mut n = 2;
+            while !used.insert(relative_path.clone()) {
+                relative_path = relative.with_file_name(format!("{}-{}.json", stem, n));
+                n += 1;
+            }
+
+            let path = dir.join(relative_path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(io_error(parent))?;
+            }
+            let mut text = serde_json::to_string_pretty(&self.schemas[name])
+                .expect("schemas always serialize");
+            text.push('\n');
+            fs::write(&path, text).map_err(io_error(&path))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// The deepest directory containing every schema loaded from a file.
+    fn file_base(&self) -> Option<PathBuf> {
+        let mut base: Option<PathBuf> = None;
+        for uri in self.uris.values().filter(|uri| uri.starts_with("file://")) {
+            let Some(parent) = uri_path(uri).parent().map(Path::to_path_buf) else {
+                continue;
+            };
+            base = Some(match base {
+                None => parent,
+                Some(base) => base
+                    .components()
+                    .zip(parent.components())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect(),
+            });
+        }
+        base
+    }
+
+    /// Checks that every `$ref` in the schema registered as `name` resolves.
+    fn dangling_refs(&self, name: &str, path: &Path) -> Vec<LoadError> {
+        let Some(schema) = self.get(name) else {
+            return Vec::new();
+        };
+        let mut checker = RefChecker {
+            registry: self,
+            name,
+            bases: self.document_base(name).into_iter().collect(),
+            scope: Vec::new(),
+            dangling: Vec::new(),
+        };
+        checker.visit_schema(schema, &mut SchemaPath::new());
+        checker
+            .dangling
+            .into_iter()
+            .map(|(location, source)| LoadError::DanglingRef {
+                path: path.to_path_buf(),
+                location,
+                source,
+            })
+            .collect()
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, errors: &mut Vec<LoadError>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(source) => {
+            errors.push(LoadError::Io {
+                path: dir.to_path_buf(),
+                source,
+            });
+            return;
+        }
+    };
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(source) => {
+                errors.push(LoadError::Io {
+                    path: dir.to_path_buf(),
+                    source,
+                });
+                continue;
+            }
+        };
+        if path.is_dir() {
+            collect_files(&path, files, errors);
+        } else if Format::of(&path).is_some() {
+            files.push(path);
+        }
+    }
+}
+
+fn parse(text: &str, format: Format, path: &Path) -> Result<Schema, LoadError> {
+    match format {
+        Format::Json => serde_json::from_str(text).map_err(|e| LoadError::Parse {
+            path: path.to_path_buf(),
+            line: e.line(),
+            column: e.column(),
+            message: e.to_string(),
+        }),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => serde_yaml::from_str(text).map_err(|e| {
+            let (line, column) = e
+                .location()
+                .map(|location| (location.line(), location.column()))
+                .unwrap_or((0, 0));
+            LoadError::Parse {
+                path: path.to_path_buf(),
+                line,
+                column,
+                message: e.to_string(),
+            }
+        }),
+    }
+}
+
+/// `file://` URI for an absolute path.
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
+fn uri_path(uri: &str) -> PathBuf {
+    PathBuf::from(uri.strip_prefix("file://").unwrap_or(uri))
+}
+
+/// File stem used by [`SchemaRegistry::save_dir`] for a registered schema.
+pub(super) fn file_stem_for(name: &str, uri: Option<&str>) -> String {
+    let source = uri.unwrap_or(name);
+    let segment = strip_fragment(source)
+        .rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .unwrap_or(name);
+    let stem = segment
+        .strip_suffix(".json")
+        .or_else(|| segment.strip_suffix(".yaml"))
+        .or_else(|| segment.strip_suffix(".yml"))
+        .unwrap_or(segment);
+    let stem: String = stem
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+    if stem.is_empty() {
+        "schema".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Collects references that do not resolve, tracking base URIs and
+/// `Recursive` bindings like validation does.
+struct RefChecker<'a> {
+    registry: &'a SchemaRegistry,
+    name: &'a str,
+    bases: Vec<String>,
+    scope: Vec<String>,
+    dangling: Vec<(String, RegistryError)>,
+}
+
+impl SchemaVisitor for RefChecker<'_> {
+    fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+        let bound = match (&schema.kind, &schema.id) {
+            (SchemaKind::Recursive { .. }, Some(id)) => {
+                self.scope.push(id.clone());
+                true
+            }
+            _ => false,
+        };
+        let based = match &schema.id {
+            Some(id) if !id.starts_with('#') => {
+                let base = match self.bases.last() {
+                    Some(base) => resolve_uri(base, id),
+                    None => id.clone(),
+                };
+                self.bases.push(base);
+                true
+            }
+            _ => false,
+        };
+
+        if let SchemaKind::Ref { reference } = &schema.kind {
+            let bound_here = self.scope.iter().any(|id| id == reference);
+            if !bound_here {
+                let base = self.bases.last().map(String::as_str);
+                if let Err(e) = self.registry.resolve_ref(reference, base) {
+                    self.dangling.push((format!("{}{}", self.name, path), e));
+                }
+            }
+        }
+
+        walk_schema(self, schema, path);
+        if based {
+            self.bases.pop();
+        }
+        if bound {
+            self.scope.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::validate::validate_with_registry;
+    use crate::value::Value;
+
+    fn write(dir: &Path, name: &str, text: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn test_load_dir_relative_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "person.json",
+            r#"{
+                "kind": "object",
+                "properties": {
+                    "name": {"kind": "string"},
+                    "address": {"kind": "ref", "$ref": "common/address.json"}
+                },
+                "required": ["name", "address"]
+            }"#,
+        );
+        write(
+            dir.path(),
+            "common/address.json",
+            r#"{
+                "$id": "https://example.com/address.json",
+                "kind": "object",
+                "properties": {"zip": {"kind": "ref", "$ref": "zip.json"}},
+                "required": ["zip"]
+            }"#,
+        );
+        write(
+            dir.path(),
+            "zip.json",
+            r#"{"$id": "https://example.com/zip.json", "kind": "string", "pattern": "^[0-9]{5}$"}"#,
+        );
+        write(dir.path(), "notes.txt", "not a schema");
+
+        let mut registry = SchemaRegistry::new();
+        let names = registry.load_dir(dir.path()).unwrap();
+        assert_eq!(
+            names,
+            [
+                "https://example.com/address.json",
+                "person",
+                "https://example.com/zip.json"
+            ]
+        );
+
+        let valid = Value::object()
+            .field("name", Value::string("Ada"))
+            .field(
+                "address",
+                Value::object().field("zip", Value::string("12345")).build(),
+            )
+            .build();
+        let person = SchemaBuilder::r#ref("person");
+        assert!(validate_with_registry(&person, &valid, Some(&registry)).is_ok());
+        assert!(registry.lint().is_empty());
+    }
+
+    #[test]
+    fn test_load_dir_aggregates_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "a.json",
+            r#"{"$id": "Shared", "kind": "string"}"#,
+        );
+        write(
+            dir.path(),
+            "b.json",
+            r#"{"$id": "Shared", "kind": "int64"}"#,
+        );
+        write(
+            dir.path(),
+            "c.json",
+            "{\n  \"kind\": \"object\",\n  oops\n}",
+        );
+        write(
+            dir.path(),
+            "d.json",
+            r#"{"kind": "array", "items": {"kind": "ref", "$ref": "missing.json"}}"#,
+        );
+
+        let mut registry = SchemaRegistry::new();
+        let error = registry.load_dir(dir.path()).unwrap_err();
+        let errors = error.errors();
+        assert_eq!(errors.len(), 3, "{}", error);
+        assert!(matches!(&errors[0], LoadError::DuplicateId { id, .. } if id == "Shared"));
+        assert!(matches!(&errors[1], LoadError::Parse { line: 3, .. }));
+        assert!(matches!(
+            &errors[2],
+            LoadError::DanglingRef { location, source: RegistryError::DocumentNotFound { .. }, .. }
+                if location == "d#/items"
+        ));
+
+        // Documents that parsed are still registered.
+        assert!(registry.contains("Shared"));
+        assert!(registry.contains("d"));
+        assert!(matches!(
+            registry.get("Shared").unwrap().kind,
+            SchemaKind::String { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_file_unsupported() {
+        let mut registry = SchemaRegistry::new();
+        assert!(matches!(
+            registry.load_file("schema.toml"),
+            Err(LoadError::UnsupportedFormat { .. })
+        ));
+        assert!(matches!(
+            registry.load_file("does-not-exist.json"),
+            Err(LoadError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_save_dir_round_trip() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .field("address", SchemaBuilder::r#ref("Address"))
+                .named("Person"),
+        );
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .named("Address"),
+        );
+        registry.register(
+            "https://example.com/schemas/tag.json",
+            SchemaBuilder::string()
+                .build()
+                .with_id("https://example.com/schemas/tag.json"),
+        );
+
+        let first = tempfile::tempdir().unwrap();
+        let written = registry.save_dir(first.path()).unwrap();
+        let file_names: Vec<_> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(file_names, ["Address.json", "Person.json", "tag.json"]);
+
+        let mut loaded = SchemaRegistry::new();
+        loaded.load_dir(first.path()).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert!(loaded.contains("https://example.com/schemas/tag.json"));
+
+        let second = tempfile::tempdir().unwrap();
+        loaded.save_dir(second.path()).unwrap();
+        for name in &file_names {
+            assert_eq!(
+                fs::read_to_string(first.path().join(name)).unwrap(),
+                fs::read_to_string(second.path().join(name)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_save_dir_keeps_subdirectories() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "order.json",
+            r#"{
+                "kind": "object",
+                "properties": {
+                    "customer": {"kind": "ref", "$ref": "people/customer.json"}
+                },
+                "required": ["customer"]
+            }"#,
+        );
+        write(
+            dir.path(),
+            "people/customer.json",
+            r#"{
+                "kind": "object",
+                "properties": {"address": {"kind": "ref", "$ref": "../common/address.json"}},
+                "required": ["address"]
+            }"#,
+        );
+        write(
+            dir.path(),
+            "common/address.json",
+            r#"{"kind": "object", "properties": {"city": {"kind": "string"}}, "required": ["city"]}"#,
+        );
+
+        let mut registry = SchemaRegistry::new();
+        registry.load_dir(dir.path()).unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let written = registry.save_dir(out.path()).unwrap();
+        let mut relative: Vec<_> = written
+            .iter()
+            .map(|path| path.strip_prefix(out.path()).unwrap().to_path_buf())
+            .collect();
+        relative.sort();
+        assert_eq!(
+            relative,
+            [
+                Path::new("common/address.json"),
+                Path::new("order.json"),
+                Path::new("people/customer.json")
+            ]
+        );
+
+        let mut reloaded = SchemaRegistry::new();
+        reloaded.load_dir(out.path()).unwrap();
+        let order = Value::object()
+            .field(
+                "customer",
+                Value::object()
+                    .field(
+                        "address",
+                        Value::object().field("city", Value::string("Oslo")).build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let schema = SchemaBuilder::r#ref("order");
+        assert!(validate_with_registry(&schema, &order, Some(&reloaded)).is_ok());
+        assert!(reloaded.lint().is_empty());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_load_yaml() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "point.yaml",
+            "kind: object\nproperties:\n  x:\n    kind: float64\n  y:\n    kind: float64\nrequired: [x, y]\n",
+        );
+        write(dir.path(), "broken.yml", "kind: object\nproperties: [\n");
+
+        let mut registry = SchemaRegistry::new();
+        let error = registry.load_dir(dir.path()).unwrap_err();
+        assert!(matches!(
+            error.errors(),
+            [LoadError::Parse { line, .. }] if *line > 0
+        ));
+        assert!(matches!(
+            registry.get("point").unwrap().kind,
+            SchemaKind::Object { .. }
+        ));
+    }
+}
@@ -0,0 +1,962 @@
+//! Schema registry for `$ref` resolution and named schema lookup.
+//!
+//! The registry stores named schemas and resolves `$ref` pointers during
+//! validation. This enables:
+//! - Schema reuse via references
+//! - Recursive type definitions
+//! - Cross-schema validation
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::{SchemaBuilder, SchemaRegistry, Value, validate_with_registry};
+//!
+//! let person_schema = SchemaBuilder::object()
+//!     .field("name", SchemaBuilder::string().build())
+//!     .field("age", SchemaBuilder::int64())
+//!     .named("Person");
+//!
+//! let mut registry = SchemaRegistry::new();
+//! registry.register("Person", person_schema);
+//!
+//! let ref_schema = SchemaBuilder::r#ref("Person");
+//! let value = Value::object()
+//!     .field("name", Value::string("Alice"))
+//!     .field("age", Value::int64(30))
+//!     .build();
+//!
+//! assert!(validate_with_registry(&ref_schema, &value, Some(&registry)).is_ok());
+//! ```
+//!
+//! # Reference forms
+//!
+//! References are resolved per RFC 3986 and RFC 6901 (JSON Pointer):
+//!
+//! - `#/definitions/Person` or `#/$defs/Person` - a registered name
+//! - `Person` - a registered name or `$id`
+//! - `https://example.com/person.json` - a schema registered with that `$id`,
+//!   or a relative form such as `address.json` resolved against a base URI
+//! - `Person#/properties/address` - a pointer into a registered schema,
+//!   walking `properties`, `items`, `any_of`, `all_of` and friends
+//! - `person.json#street` - an anchor, declared as a nested `$id: "#street"`
+//!
+//! ```
+//! use typebox::{SchemaBuilder, SchemaRegistry, SchemaKind};
+//!
+//! let mut registry = SchemaRegistry::new();
+//! registry.register(
+//!     "Person",
+//!     SchemaBuilder::object()
+//!         .field("address", SchemaBuilder::r#ref("Address"))
+//!         .build()
+//!         .with_id("https://example.com/schemas/person.json"),
+//! );
+//! registry.register(
+//!     "Address",
+//!     SchemaBuilder::object()
+//!         .field("street", SchemaBuilder::string().build())
+//!         .build()
+//!         .with_id("https://example.com/schemas/address.json"),
+//! );
+//!
+//! let street = registry
+//!     .resolve_ref(
+//!         "address.json#/properties/street",
+//!         Some("https://example.com/schemas/person.json"),
+//!     )
+//!     .unwrap();
+//! assert!(matches!(street.kind, SchemaKind::String { .. }));
+//! ```
+mod bundle;
+mod compat;
+mod graph;
+mod load;
+mod versioned;
+
+pub use bundle::Bundle;
+pub use compat::{compatibility_issues, CompatibilityPolicy, Incompatibility};
+pub use graph::DependencyGraph;
+pub use versioned::{content_hash, Revision, VersionedRegistry};
+
+use crate::error::RegistryError;
+use crate::fingerprint::{fingerprint_with_dependencies, Fingerprint, FingerprintOptions};
+use crate::schema::{Schema, SchemaKind};
+use crate::visit::{walk_schema, SchemaPath, SchemaVisitor};
+use std::collections::{HashMap, HashSet};
+
+/// Location of an identified schema: registered name and pointer within it.
+type Location = (String, String);
+
+/// Registry for storing and resolving named schemas.
+#[derive(Clone)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, Schema>,
+    /// Absolute `$id` URIs (without fragment) of registered schemas and
+    /// their embedded subschemas.
+    ids: HashMap<String, Location>,
+    /// Anchors keyed by `<document>#<anchor>`, where the document is the
+    /// enclosing base URI or, lacking one, the registered name.
+    anchors: HashMap<String, Location>,
+    /// Retrieval URIs of schemas loaded from a location, used as the base
+    /// URI when the schema has no absolute `$id` of its own.
+    uris: HashMap<String, String>,
+    /// Rust types that registered a name through
+    /// [`register_named`](crate::reflect::register_named).
+    types: HashMap<String, &'static str>,
+}
+
+impl SchemaRegistry {
+    /// Creates a new empty registry.
+    pub fn new() -> Self {
+        Self {
+            schemas: HashMap::new(),
+            ids: HashMap::new(),
+            anchors: HashMap::new(),
+            uris: HashMap::new(),
+            types: HashMap::new(),
+        }
+    }
+
+    /// Registers a schema with the given name.
+    ///
+    /// Any `$id` within the schema, including on nested subschemas, is
+    /// indexed so references can target it by URI or anchor.
+    pub fn register(&mut self, name: impl Into<String>, schema: Schema) {
+        let name = name.into();
+        self.uris.remove(&name);
+        self.types.remove(&name);
+        self.index(&name, &schema, None);
+        self.schemas.insert(name, schema);
+    }
+
+    /// Registers a schema that was retrieved from `uri` (e.g. a `file://`
+    /// URI), so that relative `$id`s and `$ref`s within it resolve against
+    /// that location (RFC 3986 §5.1.3).
+    pub fn register_with_uri(
+        &mut self,
+        name: impl Into<String>,
+        schema: Schema,
+        uri: impl Into<String>,
+    ) {
+        let name = name.into();
+        let uri = strip_fragment(&uri.into()).to_string();
+        self.index(&name, &schema, Some(&uri));
+        self.ids
+            .entry(uri.clone())
+            .or_insert_with(|| (name.clone(), String::new()));
+        self.uris.insert(name.clone(), uri);
+        self.schemas.insert(name, schema);
+    }
+
+    /// Re-indexes the `$id`s and anchors owned by `name`.
+    fn index(&mut self, name: &str, schema: &Schema, uri: Option<&str>) {
+        self.ids.retain(|_, (owner, _)| owner != name);
+        self.anchors.retain(|_, (owner, _)| owner != name);
+
+        let mut indexer = IdIndexer {
+            name,
+            bases: uri.map(str::to_string).into_iter().collect(),
+            ids: &mut self.ids,
+            anchors: &mut self.anchors,
+        };
+        indexer.visit_schema(schema, &mut SchemaPath::new());
+    }
+
+    /// Registers `schema` as the definition of the Rust type `type_name`.
+    pub(crate) fn register_type_named(
+        &mut self,
+        name: &str,
+        schema: Schema,
+        type_name: &'static str,
+    ) {
+        self.register(name, schema);
+        self.types.insert(name.to_string(), type_name);
+    }
+
+    /// The Rust type that registered `name`, if any.
+    pub(crate) fn type_of(&self, name: &str) -> Option<&'static str> {
+        self.types.get(name).copied()
+    }
+
+    /// Returns the URI a schema was registered from with
+    /// [`register_with_uri`](Self::register_with_uri).
+    pub fn uri_of(&self, name: &str) -> Option<&str> {
+        self.uris.get(name).map(String::as_str)
+    }
+
+    /// Gets a schema by name.
+    pub fn get(&self, name: &str) -> Option<&Schema> {
+        self.schemas.get(name)
+    }
+
+    /// Checks if a schema exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.schemas.contains_key(name)
+    }
+
+    /// Iterates over all registered schemas.
+    pub fn schemas(&self) -> impl Iterator<Item = (&String, &Schema)> {
+        self.schemas.iter()
+    }
+
+    /// Returns the number of registered schemas.
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    /// Returns true if no schemas are registered.
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /// Fingerprints the schema registered as `name` together with every
+    /// registered schema it depends on, so the fingerprint changes whenever
+    /// any schema reachable through its `$ref`s does. Annotations are
+    /// excluded; see [`fingerprint_with_options`](Self::fingerprint_with_options).
+    pub fn fingerprint(&self, name: &str) -> Option<Fingerprint> {
+        self.fingerprint_with_options(name, FingerprintOptions::default())
+    }
+
+    /// Like [`fingerprint`](Self::fingerprint), with the given options.
+    pub fn fingerprint_with_options(
+        &self,
+        name: &str,
+        options: FingerprintOptions,
+    ) -> Option<Fingerprint> {
+        let schema = self.get(name)?;
+        let graph = self.dependency_graph();
+        let dependencies = graph
+            .transitive_dependencies(name)
+            .into_iter()
+            .filter(|dependency| *dependency != name)
+            .filter_map(|dependency| Some((dependency, self.get(dependency)?)));
+        Some(fingerprint_with_dependencies(schema, dependencies, options))
+    }
+
+    /// Returns the registered name of the schema with the given `$id`.
+    pub fn name_for_id(&self, id: &str) -> Option<&str> {
+        let (name, pointer) = self.ids.get(strip_fragment(id))?;
+        pointer.is_empty().then_some(name.as_str())
+    }
+
+    /// Resolves a schema, following `$ref` pointers.
+    pub fn resolve<'a>(&'a self, schema: &'a Schema) -> Result<&'a Schema, RegistryError> {
+        self.resolve_in(schema, None).map(|(schema, _)| schema)
+    }
+
+    /// Resolves a reference string against an optional base URI, following
+    /// any further `$ref`s the target contains.
+    pub fn resolve_ref(
+        &self,
+        reference: &str,
+        base: Option<&str>,
+    ) -> Result<&Schema, RegistryError> {
+        self.resolve_ref_in(reference, base)
+            .map(|(schema, _)| schema)
+    }
+
+    /// Like [`resolve_ref`](Self::resolve_ref), also returning the base URI
+    /// in effect at the resolved schema.
+    pub(crate) fn resolve_ref_in(
+        &self,
+        reference: &str,
+        base: Option<&str>,
+    ) -> Result<(&Schema, Option<String>), RegistryError> {
+        let mut visited = HashSet::new();
+        let (schema, base) = self.lookup(reference, base, &mut visited)?;
+        self.follow(schema, base, &mut visited)
+    }
+
+    /// Like [`resolve`](Self::resolve), also returning the base URI in
+    /// effect at the resolved schema.
+    pub(crate) fn resolve_in<'a>(
+        &'a self,
+        schema: &'a Schema,
+        base: Option<&str>,
+    ) -> Result<(&'a Schema, Option<String>), RegistryError> {
+        self.follow(schema, base.map(str::to_string), &mut HashSet::new())
+    }
+
+    fn follow<'a>(
+        &'a self,
+        mut schema: &'a Schema,
+        mut base: Option<String>,
+        visited: &mut HashSet<String>,
+    ) -> Result<(&'a Schema, Option<String>), RegistryError> {
+        while let SchemaKind::Ref { reference } = &schema.kind {
+            (schema, base) = self.lookup(reference, base.as_deref(), visited)?;
+        }
+        Ok((schema, base))
+    }
+
+    /// Resolves one reference without following a `$ref` at the target.
+    fn lookup(
+        &self,
+        reference: &str,
+        base: Option<&str>,
+        visited: &mut HashSet<String>,
+    ) -> Result<(&Schema, Option<String>), RegistryError> {
+        let (uri, fragment) = match reference.split_once('#') {
+            Some((uri, fragment)) => (uri, Some(fragment)),
+            None => (reference, None),
+        };
+        let fragment =
+            percent_decode(fragment.unwrap_or("")).ok_or_else(|| RegistryError::InvalidRef {
+                reference: reference.to_string(),
+                reason: "malformed percent-encoding".to_string(),
+            })?;
+
+        // Fragments under `definitions`/`$defs` address the registry itself.
+        if let Some(rest) = fragment
+            .strip_prefix("/definitions/")
+            .or_else(|| fragment.strip_prefix("/$defs/"))
+        {
+            let (name, pointer) = match rest.find('/') {
+                Some(i) => (&rest[..i], &rest[i..]),
+                None => (rest, ""),
+            };
+            let name = unescape_token(name);
+            if !visited.insert(format!("{}#{}", name, pointer)) {
+                return Err(RegistryError::CircularRef(name));
+            }
+            let schema = self
+                .schemas
+                .get(&name)
+                .ok_or_else(|| RegistryError::SchemaNotFound(reference.to_string()))?;
+            let target = walk_pointer(schema, pointer, reference)?;
+            return Ok((target, self.base_of(&name, schema)));
+        }
+
+        // Identify the document the fragment applies to.
+        let (document, document_base) = if uri.is_empty() {
+            match base {
+                Some(base) => {
+                    let base = strip_fragment(base);
+                    match self.locate(base, base) {
+                        Some((schema, _)) => (Some(schema), Some(base.to_string())),
+                        None => (None, Some(base.to_string())),
+                    }
+                }
+                None => (None, None),
+            }
+        } else {
+            let absolute = match base {
+                Some(base) => resolve_uri(base, uri),
+                None => remove_dot_segments(uri),
+            };
+            match self.locate(&absolute, uri) {
+                Some((schema, found_base)) => (Some(schema), found_base),
+                None => {
+                    return Err(if base.is_none() && is_plain_name(uri) {
+                        RegistryError::SchemaNotFound(reference.to_string())
+                    } else {
+                        RegistryError::DocumentNotFound {
+                            reference: reference.to_string(),
+                            uri: absolute,
+                        }
+                    })
+                }
+            }
+        };
+
+        let key = format!("{}#{}", document_base.as_deref().unwrap_or(uri), fragment);
+        if !visited.insert(key) {
+            return Err(RegistryError::CircularRef(reference.to_string()));
+        }
+
+        if fragment.is_empty() || fragment.starts_with('/') {
+            let document = document.ok_or_else(|| RegistryError::InvalidRef {
+                reference: reference.to_string(),
+                reason: "no base URI to apply the fragment to".to_string(),
+            })?;
+            let target = walk_pointer(document, &fragment, reference)?;
+            return Ok((target, document_base));
+        }
+
+        // Plain-name fragment: an anchor.
+        let location = match &document_base {
+            Some(document) => self.anchors.get(&format!("{}#{}", document, fragment)),
+            None => {
+                let suffix = format!("#{}", fragment);
+                let mut matches = self
+                    .anchors
+                    .iter()
+                    .filter(|(key, _)| key.ends_with(&suffix));
+                match (matches.next(), matches.next()) {
+                    (Some((_, location)), None) => Some(location),
+                    (Some(_), Some(_)) => {
+                        return Err(RegistryError::InvalidRef {
+                            reference: reference.to_string(),
+                            reason: format!(
+                                "anchor {:?} is ambiguous without a base URI",
+                                fragment
+                            ),
+                        })
+                    }
+                    _ => None,
+                }
+            }
+        };
+        let (name, pointer) = location.ok_or_else(|| RegistryError::AnchorNotFound {
+            reference: reference.to_string(),
+            anchor: fragment.to_string(),
+        })?;
+        let schema = &self.schemas[name];
+        let target = walk_pointer(schema, pointer, reference)?;
+        Ok((target, document_base))
+    }
+
+    /// Finds the schema identified by `absolute`, falling back to the
+    /// reference as written, then to a registered name.
+    fn locate(&self, absolute: &str, written: &str) -> Option<(&Schema, Option<String>)> {
+        for candidate in [absolute, written] {
+            if let Some((name, pointer)) = self.ids.get(candidate) {
+                let schema = walk_pointer(&self.schemas[name], pointer, candidate).ok()?;
+                return Some((schema, Some(candidate.to_string())));
+            }
+        }
+        for candidate in [absolute, written] {
+            if let Some(schema) = self.schemas.get(candidate) {
+                return Some((schema, self.base_of(candidate, schema)));
+            }
+        }
+        None
+    }
+
+    /// Base URI of a registered schema: its own `$id` (resolved against its
+    /// retrieval URI), else its retrieval URI, else its name.
+    fn base_of(&self, name: &str, schema: &Schema) -> Option<String> {
+        let uri = self.uris.get(name);
+        match (schema_id(schema), uri) {
+            (Some(id), Some(uri)) if !id.starts_with('#') => {
+                Some(strip_fragment(&resolve_uri(uri, id)).to_string())
+            }
+            (Some(id), None) if !id.starts_with('#') => Some(strip_fragment(id).to_string()),
+            (_, Some(uri)) => Some(uri.clone()),
+            _ => Some(name.to_string()),
+        }
+    }
+
+    /// Base URI in effect at the root of the registered schema `name`.
+    pub(crate) fn document_base(&self, name: &str) -> Option<String> {
+        let schema = self.schemas.get(name)?;
+        self.base_of(name, schema)
+    }
+}
+
+/// Returns the `$id` of a schema, looking through a `Named` wrapper.
+fn schema_id(schema: &Schema) -> Option<&str> {
+    match (&schema.id, &schema.kind) {
+        (Some(id), _) => Some(id),
+        (None, SchemaKind::Named { schema, .. }) => schema.id.as_deref(),
+        _ => None,
+    }
+}
+
+/// Indexes `$id`s and anchors of a schema being registered.
+struct IdIndexer<'a> {
+    name: &'a str,
+    bases: Vec<String>,
+    ids: &'a mut HashMap<String, Location>,
+    anchors: &'a mut HashMap<String, Location>,
+}
+
+impl SchemaVisitor for IdIndexer<'_> {
+    fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+        let mut pushed = false;
+        if let Some(id) = &schema.id {
+            let location = (self.name.to_string(), path.to_pointer());
+            if let Some(anchor) = id.strip_prefix('#') {
+                let document = self.bases.last().map(String::as_str).unwrap_or(self.name);
+                self.anchors
+                    .insert(format!("{}#{}", document, anchor), location);
+            } else {
+                let absolute = match self.bases.last() {
+                    Some(base) => resolve_uri(base, id),
+                    None => remove_dot_segments(id),
+                };
+                let absolute = strip_fragment(&absolute).to_string();
+                self.ids.entry(absolute.clone()).or_insert(location);
+                self.bases.push(absolute);
+                pushed = true;
+            }
+        }
+        walk_schema(self, schema, path);
+        if pushed {
+            self.bases.pop();
+        }
+    }
+}
+
+/// Follows a JSON Pointer through a schema tree.
+///
+/// Segments use the serialized field names (`properties`, `items`,
+/// `any_of`, `all_of`, `additional_properties`, `parameters`, `returns`,
+/// `schema`); the JSON Schema spellings (`anyOf`, `prefixItems`, ...) are
+/// accepted too. `Named` and `Recursive` wrappers are looked through.
+pub(crate) fn walk_pointer<'a>(
+    schema: &'a Schema,
+    pointer: &str,
+    reference: &str,
+) -> Result<&'a Schema, RegistryError> {
+    if pointer.is_empty() {
+        return Ok(schema);
+    }
+    let not_found = |segment: &str| RegistryError::PointerNotFound {
+        reference: reference.to_string(),
+        pointer: pointer.to_string(),
+        segment: segment.to_string(),
+    };
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(RegistryError::InvalidRef {
+            reference: reference.to_string(),
+            reason: format!("pointer {:?} must start with '/'", pointer),
+        });
+    };
+
+    let tokens: Vec<String> = rest.split('/').map(unescape_token).collect();
+    let mut current = schema;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i].as_str();
+        let index = |i: usize| -> Result<usize, RegistryError> {
+            let raw = tokens.get(i).ok_or_else(|| not_found(token))?;
+            raw.parse().map_err(|_| not_found(raw))
+        };
+
+        let next = match (&current.kind, token) {
+            (SchemaKind::Named { schema, .. } | SchemaKind::Recursive { schema }, "schema") => {
+                Some(&**schema)
+            }
+            (SchemaKind::Named { schema, .. } | SchemaKind::Recursive { schema }, _) => {
+                current = schema;
+                continue;
+            }
+            (SchemaKind::Array { items, .. }, "items") => Some(&**items),
+            (SchemaKind::Tuple { items }, "items" | "prefixItems") => {
+                i += 1;
+                items.get(index(i)?)
+            }
+            (SchemaKind::Object { properties, .. }, "properties") => {
+                i += 1;
+                let name = tokens.get(i).ok_or_else(|| not_found(token))?;
+                properties.get(name.as_str())
+            }
+            (
+                SchemaKind::Object {
+                    additional_properties,
+                    ..
+                },
+                "additional_properties" | "additionalProperties",
+            ) => additional_properties.as_deref(),
+            (SchemaKind::Union { any_of }, "any_of" | "anyOf") => {
+                i += 1;
+                any_of.get(index(i)?)
+            }
+            (SchemaKind::Intersect { all_of }, "all_of" | "allOf") => {
+                i += 1;
+                all_of.get(index(i)?)
+            }
+            (SchemaKind::Function { parameters, .. }, "parameters") => {
+                i += 1;
+                parameters.get(index(i)?)
+            }
+            (SchemaKind::Function { returns, .. }, "returns") => Some(&**returns),
+            _ => None,
+        };
+        current = next.ok_or_else(|| not_found(&tokens[i]))?;
+        i += 1;
+    }
+    Ok(current)
+}
+
+/// The registry name and remaining JSON Pointer addressed by a
+/// `#/definitions/...` or `#/$defs/...` reference.
+pub(crate) fn definition_ref(reference: &str) -> Option<(String, String)> {
+    let fragment = percent_decode(reference.split_once('#')?.1)?;
+    let rest = fragment
+        .strip_prefix("/definitions/")
+        .or_else(|| fragment.strip_prefix("/$defs/"))?;
+    let (name, pointer) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    Some((unescape_token(name), pointer.to_string()))
+}
+
+fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn strip_fragment(uri: &str) -> &str {
+    uri.split_once('#').map_or(uri, |(uri, _)| uri)
+}
+
+/// True for references like `Person` that are registry names rather than URIs.
+fn is_plain_name(uri: &str) -> bool {
+    !uri.contains([':', '/', '.'])
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    if !input.contains('%') {
+        return Some(input.to_string());
+    }
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Splits a URI into scheme (with `:`), authority (with `//`) and the rest.
+fn split_uri(uri: &str) -> (&str, &str, &str) {
+    let scheme_end = uri
+        .find(':')
+        .filter(|&i| {
+            i > 0
+                && uri[..i]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                && uri.as_bytes()[0].is_ascii_alphabetic()
+        })
+        .map_or(0, |i| i + 1);
+    let (scheme, rest) = uri.split_at(scheme_end);
+    if let Some(after) = rest.strip_prefix("//") {
+        let end = after.find(['/', '?', '#']).map_or(rest.len(), |i| i + 2);
+        let (authority, rest) = rest.split_at(end);
+        (scheme, authority, rest)
+    } else {
+        (scheme, "", rest)
+    }
+}
+
+/// Resolves `reference` against `base` per RFC 3986 section 5.2.
+pub(crate) fn resolve_uri(base: &str, reference: &str) -> String {
+    let (r_scheme, r_authority, r_rest) = split_uri(reference);
+    if !r_scheme.is_empty() {
+        return format!("{}{}{}", r_scheme, r_authority, remove_dot_segments(r_rest));
+    }
+    let base = strip_fragment(base);
+    let (b_scheme, b_authority, b_rest) = split_uri(base);
+    if !r_authority.is_empty() {
+        return format!("{}{}{}", b_scheme, r_authority, remove_dot_segments(r_rest));
+    }
+    if reference.is_empty() {
+        return base.to_string();
+    }
+    let b_path = b_rest.split(['?']).next().unwrap_or("");
+    let rest = if reference.starts_with('/') {
+        remove_dot_segments(reference)
+    } else if reference.starts_with('?') {
+        format!("{}{}", b_path, reference)
+    } else {
+        let merged = match b_path.rfind('/') {
+            Some(i) => format!("{}{}", &b_path[..=i], reference),
+            None if !b_authority.is_empty() => format!("/{}", reference),
+            None => reference.to_string(),
+        };
+        remove_dot_segments(&merged)
+    };
+    format!("{}{}{}", b_scheme, b_authority, rest)
+}
+
+/// Removes `.` and `..` path segments per RFC 3986 section 5.2.4.
+fn remove_dot_segments(path: &str) -> String {
+    let (path, suffix) = match path.find(['?', '#']) {
+        Some(i) => path.split_at(i),
+        None => (path, ""),
+    };
+    if !path.contains("./") && !path.ends_with("/.") && !path.ends_with("/..") {
+        return format!("{}{}", path, suffix);
+    }
+    let absolute = path.starts_with('/');
+    let mut output: Vec<&str> = Vec::new();
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let last = segments.len() - 1;
+    let mut trailing_slash = false;
+    for (i, segment) in segments.iter().enumerate() {
+        match *segment {
+            "." => trailing_slash = i == last,
+            ".." => {
+                output.pop();
+                trailing_slash = i == last;
+            }
+            _ => {
+                output.push(segment);
+                trailing_slash = false;
+            }
+        }
+    }
+    let mut result = output.join("/");
+    if absolute {
+        result.insert(0, '/');
+    }
+    if trailing_slash {
+        result.push('/');
+    }
+    format!("{}{}", result, suffix)
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+    use crate::schema::SchemaKind;
+
+    #[test]
+    fn test_register_and_get() {
+        let mut registry = SchemaRegistry::new();
+        let schema = SchemaBuilder::int64();
+        registry.register("Age", schema.clone());
+
+        assert!(registry.contains("Age"));
+        assert!(registry.get("Age").is_some());
+        assert!(registry.get("Unknown").is_none());
+    }
+
+    #[test]
+    fn test_resolve_ref() {
+        let mut registry = SchemaRegistry::new();
+        let person = SchemaBuilder::object()
+            .field("name", SchemaBuilder::string().build())
+            .named("Person");
+        registry.register("Person", person);
+
+        let ref_schema = SchemaBuilder::r#ref("Person");
+        let resolved = registry.resolve(&ref_schema).unwrap();
+
+        assert!(matches!(resolved.kind, SchemaKind::Named { .. }));
+    }
+
+    #[test]
+    fn test_resolve_not_found() {
+        let registry = SchemaRegistry::new();
+        let ref_schema = SchemaBuilder::r#ref("Unknown");
+
+        assert!(matches!(
+            registry.resolve(&ref_schema),
+            Err(RegistryError::SchemaNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_circular() {
+        let mut registry = SchemaRegistry::new();
+
+        let a = SchemaBuilder::r#ref("B");
+        let b = SchemaBuilder::r#ref("A");
+
+        registry.register("A", a);
+        registry.register("B", b);
+
+        let ref_a = SchemaBuilder::r#ref("A");
+        assert!(matches!(
+            registry.resolve(&ref_a),
+            Err(RegistryError::CircularRef(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_non_ref() {
+        let registry = SchemaRegistry::new();
+        let schema = SchemaBuilder::int64();
+
+        let resolved = registry.resolve(&schema).unwrap();
+        assert!(matches!(resolved.kind, SchemaKind::Int64 { .. }));
+    }
+
+    fn person_registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        let mut street = SchemaBuilder::string().build();
+        street.id = Some("#street".to_string());
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", street)
+                .build()
+                .with_id("https://example.com/schemas/address.json"),
+        );
+        registry.register(
+            "Person",
+            SchemaBuilder::object()
+                .field(
+                    "address",
+                    Schema::new(SchemaKind::Ref {
+                        reference: "address.json".to_string(),
+                    }),
+                )
+                .field(
+                    "tags",
+                    SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+                )
+                .field(
+                    "contact",
+                    SchemaBuilder::union(vec![
+                        SchemaBuilder::string().build(),
+                        SchemaBuilder::int64(),
+                    ]),
+                )
+                .build()
+                .with_id("https://example.com/schemas/person.json"),
+        );
+        registry
+    }
+
+    #[test]
+    fn test_resolve_defs_and_pointer() {
+        let registry = person_registry();
+        let defs = registry.resolve_ref("#/$defs/Person", None).unwrap();
+        assert!(matches!(defs.kind, SchemaKind::Object { .. }));
+
+        let tags = registry
+            .resolve_ref("#/definitions/Person/properties/tags/items", None)
+            .unwrap();
+        assert!(matches!(tags.kind, SchemaKind::String { .. }));
+
+        let contact = registry
+            .resolve_ref("Person#/properties/contact/any_of/1", None)
+            .unwrap();
+        assert!(matches!(contact.kind, SchemaKind::Int64 { .. }));
+
+        let camel = registry
+            .resolve_ref("Person#/properties/contact/anyOf/0", None)
+            .unwrap();
+        assert!(matches!(camel.kind, SchemaKind::String { .. }));
+    }
+
+    #[test]
+    fn test_resolve_uri_and_base() {
+        let registry = person_registry();
+        let person = registry
+            .resolve_ref("https://example.com/schemas/person.json", None)
+            .unwrap();
+        assert!(matches!(person.kind, SchemaKind::Object { .. }));
+
+        // The relative `address.json` ref is followed from Person's base.
+        let address = registry
+            .resolve_ref(
+                "#/properties/address",
+                Some("https://example.com/schemas/person.json"),
+            )
+            .unwrap();
+        assert_eq!(
+            address.id.as_deref(),
+            Some("https://example.com/schemas/address.json")
+        );
+        assert_eq!(
+            registry.name_for_id("https://example.com/schemas/address.json"),
+            Some("Address")
+        );
+    }
+
+    #[test]
+    fn test_resolve_anchor() {
+        let registry = person_registry();
+        let street = registry
+            .resolve_ref("https://example.com/schemas/address.json#street", None)
+            .unwrap();
+        assert!(matches!(street.kind, SchemaKind::String { .. }));
+
+        let street = registry.resolve_ref("#street", None).unwrap();
+        assert!(matches!(street.kind, SchemaKind::String { .. }));
+
+        assert!(matches!(
+            registry.resolve_ref("address.json#city", Some("https://example.com/schemas/")),
+            Err(RegistryError::AnchorNotFound { anchor, .. }) if anchor == "city"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let registry = person_registry();
+        assert!(matches!(
+            registry.resolve_ref("Person#/properties/missing", None),
+            Err(RegistryError::PointerNotFound { segment, .. }) if segment == "missing"
+        ));
+        assert!(matches!(
+            registry.resolve_ref("Person#/properties/tags/any_of/0", None),
+            Err(RegistryError::PointerNotFound { segment, .. }) if segment == "any_of"
+        ));
+        assert!(matches!(
+            registry.resolve_ref("other.json", Some("https://example.com/schemas/person.json")),
+            Err(RegistryError::DocumentNotFound { uri, .. })
+                if uri == "https://example.com/schemas/other.json"
+        ));
+        assert!(matches!(
+            registry.resolve_ref("#/properties/tags", None),
+            Err(RegistryError::InvalidRef { .. })
+        ));
+    }
+
+    #[test]
+    fn test_reregister_replaces_ids() {
+        let mut registry = person_registry();
+        registry.register("Address", SchemaBuilder::string().build());
+        assert!(registry
+            .name_for_id("https://example.com/schemas/address.json")
+            .is_none());
+    }
+
+    #[test]
+    fn test_resolve_uri_rfc3986() {
+        let base = "http://a/b/c/d;p?q";
+        for (reference, expected) in [
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("g;x=1/../y", "http://a/b/c/y"),
+            ("urn:example:x", "urn:example:x"),
+        ] {
+            assert_eq!(resolve_uri(base, reference), expected, "{}", reference);
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_covers_dependencies() {
+        let mut registry = person_registry();
+        let before = registry.fingerprint("Person").unwrap();
+        assert_eq!(
+            registry.fingerprint("Address"),
+            Some(crate::fingerprint::fingerprint(
+                registry.get("Address").unwrap()
+            ))
+        );
+
+        let address = registry.get("Address").unwrap().clone();
+        registry.register("Address", address.with_description("Postal"));
+        assert_eq!(registry.fingerprint("Person"), Some(before));
+
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("city", SchemaBuilder::string().build())
+                .build()
+                .with_id("https://example.com/schemas/address.json"),
+        );
+        assert_ne!(registry.fingerprint("Person"), Some(before));
+        assert_eq!(registry.fingerprint("Missing"), None);
+    }
+}
@@ -0,0 +1,533 @@
+//! Multiple immutable revisions per schema name.
+//!
+//! A [`VersionedRegistry`] keeps every registered revision of a schema so
+//! historical payloads can still be validated. Each revision is addressable
+//! in `$ref`s by:
+//!
+//! - `Name@version` - a specific version, e.g. `Person@2` or `Person@1.4.0`
+//! - `Name@<hash>` - the [`content_hash`] of the revision
+//! - `Name@latest` or `Name` - the highest version
+//!
+//! Versions are ordered segment by segment on `.`, numerically where both
+//! segments are numbers, so `1.10` is newer than `1.9`.
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::registry::{CompatibilityPolicy, VersionedRegistry};
+//! use typebox::{SchemaBuilder, Value};
+//!
+//! let mut registry = VersionedRegistry::with_policy(CompatibilityPolicy::Backward);
+//! registry
+//!     .register("Person", "1", SchemaBuilder::object()
+//!         .field("name", SchemaBuilder::string().build())
+//!         .build())
+//!     .unwrap();
+//! registry
+//!     .register("Person", "2", SchemaBuilder::object()
+//!         .field("name", SchemaBuilder::string().build())
+//!         .optional_field("email", SchemaBuilder::string().build())
+//!         .build())
+//!     .unwrap();
+//!
+//! let old = Value::object().field("name", Value::string("Alice")).build();
+//! assert!(registry.validate("Person@1", &old).is_ok());
+//! assert!(registry.validate("Person", &old).is_ok());
+//! assert_eq!(registry.latest("Person").unwrap().version, "2");
+//!
+//! // Making email required would reject version 1 payloads.
+//! let breaking = SchemaBuilder::object()
+//!     .field("name", SchemaBuilder::string().build())
+//!     .field("email", SchemaBuilder::string().build())
+//!     .build();
+//! assert!(registry.register("Person", "3", breaking).is_err());
+//! ```
+
+use super::compat::{compatibility_issues, CompatibilityPolicy};
+use super::SchemaRegistry;
+use crate::error::{ValidationError, VersionError};
+use crate::fingerprint::{canonical_json, fingerprint_with_options, FingerprintOptions};
+use crate::schema::{Schema, SchemaKind};
+use crate::validate::validate_with_registry;
+use crate::value::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Version label that always addresses the newest revision.
+const LATEST: &str = "latest";
+
+/// Length of a [`content_hash`] in hex digits.
+const HASH_DIGITS: usize = 32;
+
+/// One registered revision of a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    /// The schema name.
+    pub name: String,
+    /// The version label.
+    pub version: String,
+    /// The [`content_hash`] of the schema.
+    pub hash: String,
+}
+
+impl Revision {
+    /// The `Name@version` reference to this revision.
+    pub fn reference(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// Registry holding several immutable revisions per schema name.
+///
+/// Revisions are stored in a [`SchemaRegistry`] under every name they are
+/// addressable by, so `$ref`s between revisions resolve with the usual
+/// validation and value functions via [`registry`](Self::registry).
+#[derive(Clone, Default)]
+pub struct VersionedRegistry {
+    registry: SchemaRegistry,
+    /// Revisions per name, oldest first.
+    revisions: BTreeMap<String, Vec<Revision>>,
+    policy: CompatibilityPolicy,
+}
+
+impl VersionedRegistry {
+    /// Creates an empty registry without a compatibility policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty registry that checks each new version against its
+    /// neighbouring versions under `policy`.
+    pub fn with_policy(policy: CompatibilityPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// The compatibility policy enforced on registration.
+    pub fn policy(&self) -> CompatibilityPolicy {
+        self.policy
+    }
+
+    /// Registers `schema` as `version` of `name`.
+    ///
+    /// Registering identical content under an existing version is a no-op;
+    /// different content is rejected, as revisions are immutable. Content is
+    /// compared by canonical form, so a schema whose [`content_hash`] already
+    /// addresses different content is rejected as well. With a compatibility
+    /// policy, the schema is first checked against the nearest lower version
+    /// as its predecessor and, when backfilling, against the next higher
+    /// version as its successor.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        version: impl Into<String>,
+        schema: Schema,
+    ) -> Result<Revision, VersionError> {
+        let name = name.into();
+        let version = version.into();
+        check_label(&name, &version)?;
+
+        let hash = content_hash(&schema);
+        let content = canonical(&schema);
+        let holds = |reference: &str| {
+            self.registry
+                .get(reference)
+                .is_some_and(|stored| canonical(stored) == content)
+        };
+        let revisions = self.revisions.get(&name).map(Vec::as_slice).unwrap_or(&[]);
+        if let Some(existing) = revisions.iter().find(|r| r.version == version) {
+            if holds(&existing.reference()) {
+                return Ok(existing.clone());
+            }
+            return Err(VersionError::Immutable {
+                name,
+                version,
+                existing: existing.hash.clone(),
+                attempted: hash,
+            });
+        }
+        let by_hash = format!("{}@{}", name, hash);
+        if self.registry.contains(&by_hash) && !holds(&by_hash) {
+            return Err(VersionError::HashCollision {
+                name,
+                version,
+                hash,
+            });
+        }
+
+        let position = revisions
+            .iter()
+            .position(|r| compare_versions(&r.version, &version) == Ordering::Greater)
+            .unwrap_or(revisions.len());
+        if let Some(previous) = position.checked_sub(1).map(|i| &revisions[i]) {
+            self.check_policy(&version, &schema, previous, false)?;
+        }
+        if let Some(next) = revisions.get(position) {
+            self.check_policy(&version, &schema, next, true)?;
+        }
+
+        let revision = Revision {
+            name: name.clone(),
+            version: version.clone(),
+            hash: hash.clone(),
+        };
+        let revisions = self.revisions.entry(name.clone()).or_default();
+        revisions.insert(position, revision.clone());
+        let is_latest = position == revisions.len() - 1;
+
+        if !self.registry.contains(&by_hash) {
+            self.registry.register(by_hash, schema.clone());
+        }
+        if is_latest {
+            self.registry
+                .register(format!("{}@{}", name, LATEST), schema.clone());
+            self.registry.register(name.clone(), schema.clone());
+        }
+        self.registry.register(revision.reference(), schema);
+        Ok(revision)
+    }
+
+    /// Checks `schema`, being registered as `version`, against the existing
+    /// revision `other`, which is newer than it if `other_is_newer`.
+    fn check_policy(
+        &self,
+        version: &str,
+        schema: &Schema,
+        other: &Revision,
+        other_is_newer: bool,
+    ) -> Result<(), VersionError> {
+        let other_schema = match self.registry.get(&other.reference()) {
+            Some(other_schema) => other_schema,
+            None => return Ok(()),
+        };
+        let (older, newer) = if other_is_newer {
+            (schema, other_schema)
+        } else {
+            (other_schema, schema)
+        };
+        let registry = Some(&self.registry);
+        let mut issues = Vec::new();
+        if matches!(
+            self.policy,
+            CompatibilityPolicy::Backward | CompatibilityPolicy::Full
+        ) {
+            issues.extend(compatibility_issues(newer, older, registry));
+        }
+        if matches!(
+            self.policy,
+            CompatibilityPolicy::Forward | CompatibilityPolicy::Full
+        ) {
+            issues.extend(compatibility_issues(older, newer, registry));
+        }
+        if issues.is_empty() {
+            return Ok(());
+        }
+        Err(VersionError::Incompatible {
+            name: other.name.clone(),
+            version: version.to_string(),
+            against: other.version.clone(),
+            policy: self.policy,
+            issues,
+        })
+    }
+
+    /// Gets a schema by `Name`, `Name@version`, `Name@latest` or
+    /// `Name@<hash>`.
+    pub fn get(&self, reference: &str) -> Option<&Schema> {
+        self.registry.get(reference)
+    }
+
+    /// Gets the revision a reference of the forms accepted by
+    /// [`get`](Self::get) addresses.
+    pub fn revision(&self, reference: &str) -> Option<&Revision> {
+        let (name, label) = reference.split_once('@').unwrap_or((reference, LATEST));
+        let revisions = self.revisions.get(name)?;
+        if label == LATEST {
+            return revisions.last();
+        }
+        revisions
+            .iter()
+            .find(|r| r.version == label)
+            .or_else(|| revisions.iter().find(|r| r.hash == label))
+    }
+
+    /// The newest revision of `name`.
+    pub fn latest(&self, name: &str) -> Option<&Revision> {
+        self.revisions.get(name)?.last()
+    }
+
+    /// All revisions of `name`, oldest first.
+    pub fn versions(&self, name: &str) -> &[Revision] {
+        self.revisions.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Names with at least one revision, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.revisions.keys().map(String::as_str)
+    }
+
+    /// The underlying registry, for use with
+    /// [`validate_with_registry`](crate::validate_with_registry) and the
+    /// other registry-aware functions.
+    pub fn registry(&self) -> &SchemaRegistry {
+        &self.registry
+    }
+
+    /// Validates `value` against the schema `reference` addresses.
+    pub fn validate(&self, reference: &str, value: &Value) -> Result<(), ValidationError> {
+        let schema = Schema::new(SchemaKind::Ref {
+            reference: reference.to_string(),
+        });
+        validate_with_registry(&schema, value, Some(&self.registry))
+    }
+}
+
+/// Content hash of a schema: the first 128 bits of the SHA-256
+/// [fingerprint](crate::fingerprint) of its canonical form including
+/// annotations, as 32 hex digits.
+pub fn content_hash(schema: &Schema) -> String {
+    let mut hash = fingerprint_with_options(schema, content_options()).sha256_hex();
+    hash.truncate(HASH_DIGITS);
+    hash
+}
+
+/// The canonical form revisions are hashed over and compared by.
+fn canonical(schema: &Schema) -> String {
+    canonical_json(schema, content_options())
+}
+
+fn content_options() -> FingerprintOptions {
+    FingerprintOptions::default().annotations(true)
+}
+
+fn is_content_hash(label: &str) -> bool {
+    label.len() == HASH_DIGITS
+        && label
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn check_label(name: &str, version: &str) -> Result<(), VersionError> {
+    let reason = if name.is_empty() || name.contains(['@', '#', '/']) {
+        Some("name must be non-empty and not contain '@', '#' or '/'")
+    } else if version.is_empty() || version.contains(['@', '#', '/']) {
+        Some("version must be non-empty and not contain '@', '#' or '/'")
+    } else if version == LATEST {
+        Some("\"latest\" is reserved")
+    } else if is_content_hash(version) {
+        Some("32 hex digits are reserved for content hashes")
+    } else {
+        None
+    };
+    match reason {
+        Some(reason) => Err(VersionError::InvalidVersion {
+            name: name.to_string(),
+            version: version.to_string(),
+            reason: reason.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Orders versions by `.`-separated segments, comparing numerically where
+/// both segments are numbers.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    _ => x.cmp(y),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+
+    fn person(fields: &[&str]) -> Schema {
+        fields
+            .iter()
+            .fold(SchemaBuilder::object(), |object, field| {
+                object.field(field, SchemaBuilder::string().build())
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_versions_and_lookup() {
+        let mut registry = VersionedRegistry::new();
+        let v2 = registry
+            .register("Person", "1.10", person(&["name", "email"]))
+            .unwrap();
+        registry
+            .register("Person", "1.9", person(&["name"]))
+            .unwrap();
+
+        assert_eq!(registry.latest("Person"), Some(&v2));
+        let versions: Vec<&str> = registry
+            .versions("Person")
+            .iter()
+            .map(|r| r.version.as_str())
+            .collect();
+        assert_eq!(versions, ["1.9", "1.10"]);
+
+        assert_eq!(registry.get("Person"), Some(&person(&["name", "email"])));
+        assert_eq!(registry.get("Person@latest"), registry.get("Person"));
+        assert_eq!(registry.get("Person@1.9"), Some(&person(&["name"])));
+        assert_eq!(
+            registry.get(&format!("Person@{}", v2.hash)),
+            registry.get("Person@1.10")
+        );
+        assert_eq!(registry.revision(&format!("Person@{}", v2.hash)), Some(&v2));
+
+        let old = Value::object()
+            .field("name", Value::string("Alice"))
+            .build();
+        assert!(registry.validate("Person@1.9", &old).is_ok());
+        assert!(registry.validate("#/definitions/Person@1.9", &old).is_ok());
+        assert!(registry.validate("Person", &old).is_err());
+    }
+
+    #[test]
+    fn test_revisions_are_immutable() {
+        let mut registry = VersionedRegistry::new();
+        let first = registry.register("Person", "1", person(&["name"])).unwrap();
+        assert_eq!(
+            registry.register("Person", "1", person(&["name"])).unwrap(),
+            first
+        );
+        let err = registry
+            .register("Person", "1", person(&["name", "email"]))
+            .unwrap_err();
+        assert!(matches!(err, VersionError::Immutable { .. }));
+        assert_eq!(registry.get("Person@1"), Some(&person(&["name"])));
+
+        for version in ["latest", "a@b", "", "0123456789abcdef0123456789abcdef"] {
+            assert!(matches!(
+                registry.register("Person", version, person(&[])),
+                Err(VersionError::InvalidVersion { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_content_hash_collision_is_rejected() {
+        let mut registry = VersionedRegistry::new();
+        let first = registry.register("Person", "1", person(&["name"])).unwrap();
+        assert_eq!(first.hash.len(), 32);
+        assert!(first.hash.bytes().all(|b| b.is_ascii_hexdigit()));
+
+        // Same content under another version shares the hash.
+        let relabelled = registry.register("Person", "2", person(&["name"])).unwrap();
+        assert_eq!(relabelled.hash, first.hash);
+
+        // Simulate different content hashing to the stored hash.
+        let colliding = person(&["name", "email"]);
+        let hash = content_hash(&colliding);
+        registry
+            .registry
+            .register(format!("Person@{}", hash), person(&["email"]));
+        let err = registry.register("Person", "3", colliding).unwrap_err();
+        assert!(matches!(err, VersionError::HashCollision { version, .. } if version == "3"));
+        assert_eq!(registry.versions("Person").len(), 2);
+    }
+
+    #[test]
+    fn test_refs_between_versions() {
+        let mut registry = VersionedRegistry::new();
+        registry
+            .register(
+                "Address",
+                "1",
+                SchemaBuilder::object()
+                    .field("street", SchemaBuilder::string().build())
+                    .build(),
+            )
+            .unwrap();
+        registry
+            .register(
+                "Person",
+                "1",
+                SchemaBuilder::object()
+                    .field("address", SchemaBuilder::r#ref("Address@1"))
+                    .build(),
+            )
+            .unwrap();
+        registry
+            .register("Address", "2", SchemaBuilder::object().build())
+            .unwrap();
+
+        let value = Value::object()
+            .field(
+                "address",
+                Value::object()
+                    .field("street", Value::string("Main"))
+                    .build(),
+            )
+            .build();
+        assert!(registry.validate("Person@1", &value).is_ok());
+    }
+
+    #[test]
+    fn test_compatibility_policy() {
+        let mut registry = VersionedRegistry::with_policy(CompatibilityPolicy::Full);
+        registry.register("Person", "1", person(&["name"])).unwrap();
+        let err = registry
+            .register("Person", "2", person(&["name", "email"]))
+            .unwrap_err();
+        match err {
+            VersionError::Incompatible {
+                against, issues, ..
+            } => {
+                assert_eq!(against, "1");
+                assert_eq!(issues[0].path, "/properties/email");
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert_eq!(registry.versions("Person").len(), 1);
+
+        let relabelled = person(&["name"]).with_description("A person");
+        assert!(registry.register("Person", "2", relabelled).is_ok());
+    }
+
+    #[test]
+    fn test_backfill_checks_neighbours() {
+        let mut registry = VersionedRegistry::with_policy(CompatibilityPolicy::Backward);
+        registry.register("Person", "1", person(&[])).unwrap();
+        registry.register("Person", "3", person(&[])).unwrap();
+
+        // 2 must read everything 1 wrote, and 3 everything 2 writes.
+        let err = registry
+            .register("Person", "2", person(&["name"]))
+            .unwrap_err();
+        assert!(matches!(err, VersionError::Incompatible { against, .. } if against == "1"));
+
+        let mut registry = VersionedRegistry::with_policy(CompatibilityPolicy::Backward);
+        registry.register("Person", "1", person(&["name"])).unwrap();
+        registry.register("Person", "3", person(&["name"])).unwrap();
+        let nullable = SchemaBuilder::object()
+            .field(
+                "name",
+                SchemaBuilder::optional(SchemaBuilder::string().build()),
+            )
+            .build();
+        let err = registry.register("Person", "2", nullable).unwrap_err();
+        assert!(matches!(err, VersionError::Incompatible { against, .. } if against == "3"));
+        assert_eq!(registry.versions("Person").len(), 2);
+    }
+}
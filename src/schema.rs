@@ -310,6 +310,24 @@ pub enum StringFormat {
     Custom(String),
 }
 
+impl StringFormat {
+    /// The JSON Schema `format` name, e.g. `date-time`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            StringFormat::Email => "email",
+            StringFormat::Uuid => "uuid",
+            StringFormat::Uri => "uri",
+            StringFormat::DateTime => "date-time",
+            StringFormat::Date => "date",
+            StringFormat::Time => "time",
+            StringFormat::Hostname => "hostname",
+            StringFormat::Ipv4 => "ipv4",
+            StringFormat::Ipv6 => "ipv6",
+            StringFormat::Custom(name) => name,
+        }
+    }
+}
+
 /// Literal value types.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -0,0 +1,1082 @@
+//! SQL tables for object schemas.
+//!
+//! A [`Table`] maps the properties of an object schema to columns.
+//! [`SqlGenerator`] writes `CREATE TABLE` statements for SQLite or
+//! PostgreSQL, and a table turns [`Value`]s into parameters ordered like its
+//! columns ([`Table::to_params`]) and rows back into values
+//! ([`Table::from_row`]).
+//!
+//! | `SchemaKind` | SQLite | PostgreSQL |
+//! |---|---|---|
+//! | `Bool` | `INTEGER` | `BOOLEAN` |
+//! | `Int8`, `Int16`, `UInt8` | `INTEGER` | `SMALLINT` |
+//! | `Int32`, `UInt16` | `INTEGER` | `INTEGER` |
+//! | `Int64`, `UInt32`, `UInt64` | `INTEGER` | `BIGINT` |
+//! | `Float32`, `Float64` | `REAL` | `REAL`, `DOUBLE PRECISION` |
+//! | `String`, `Enum`, unions of string literals | `TEXT` | `TEXT` |
+//! | `Bytes` | `BLOB` | `BYTEA` |
+//! | `Literal` | the type of its value | the type of its value |
+//! | anything else, such as objects and arrays | `TEXT` holding JSON | `JSONB` |
+//!
+//! Columns are `NOT NULL` when their property is required and does not
+//! admit `null`. `CHECK` constraints hold `minimum` and `maximum`, the
+//! range of integer types narrower than their column, string and byte
+//! lengths, `Enum` values and literals. Patterns and formats are not
+//! checked.
+//!
+//! # Examples
+//!
+//! ```
+//! use typebox::sql::{Dialect, SqlGenerator, SqlValue, Table};
+//! use typebox::{SchemaBuilder, Value};
+//!
+//! let user = SchemaBuilder::object()
+//!     .field("id", SchemaBuilder::int64())
+//!     .optional_field("email", SchemaBuilder::string().max_length(255).build())
+//!     .build();
+//! assert_eq!(
+//!     SqlGenerator::new(Dialect::Postgres).generate("users", &user).unwrap(),
+//!     "CREATE TABLE \"users\" (\n  \"id\" BIGINT NOT NULL,\n  \"email\" TEXT CHECK (char_length(\"email\") <= 255)\n);"
+//! );
+//!
+//! let table = Table::from_schema("users", &user, None).unwrap();
+//! assert_eq!(
+//!     table.insert_sql(Dialect::Sqlite),
+//!     "INSERT INTO \"users\" (\"id\", \"email\") VALUES (?, ?)"
+//! );
+//! let value = Value::object().field("id", Value::int64(1)).build();
+//! let params = table.to_params(&value).unwrap();
+//! assert_eq!(params, vec![SqlValue::Integer(1), SqlValue::Null]);
+//! assert_eq!(table.from_row(&params).unwrap(), value);
+//! ```
+
+use crate::error::SqlError;
+use crate::registry::SchemaRegistry;
+use crate::schema::{LiteralValue, Schema, SchemaKind};
+use crate::value::{check_with_registry, value_to_untyped, Value};
+use indexmap::IndexMap;
+use serde_json::Value as Json;
+use std::collections::HashSet;
+
+/// The SQL dialect to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// SQLite.
+    Sqlite,
+    /// PostgreSQL.
+    Postgres,
+}
+
+/// A value bound to or read from a column.
+///
+/// Booleans stay [`SqlValue::Bool`] for both dialects; SQLite drivers store
+/// them as `0` and `1`, which [`Table::from_row`] also accepts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    /// `NULL`.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Integer(i64),
+    /// A floating point number.
+    Real(f64),
+    /// Text, including JSON columns.
+    Text(String),
+    /// Binary data.
+    Blob(Vec<u8>),
+}
+
+impl SqlValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            SqlValue::Null => "NULL",
+            SqlValue::Bool(_) => "boolean",
+            SqlValue::Integer(_) => "integer",
+            SqlValue::Real(_) => "real",
+            SqlValue::Text(_) => "text",
+            SqlValue::Blob(_) => "blob",
+        }
+    }
+}
+
+/// Writes `CREATE TABLE` statements.
+pub struct SqlGenerator {
+    dialect: Dialect,
+}
+
+impl SqlGenerator {
+    pub fn new(dialect: Dialect) -> Self {
+        Self { dialect }
+    }
+
+    /// Generates the table `name` for the object `schema`.
+    ///
+    /// `$ref`s are not resolved, so referenced schemas become JSON columns.
+    pub fn generate(&self, name: &str, schema: &Schema) -> Result<String, SqlError> {
+        Ok(Table::from_schema(name, schema, None)?.create_table_sql(self.dialect))
+    }
+
+    /// Generates a table for every object schema in `registry`, named after
+    /// its definition. Other definitions are skipped.
+    pub fn generate_module(&self, registry: &SchemaRegistry) -> Result<String, SqlError> {
+        let mut output = String::new();
+        output.push_str("-- Code generated by typebox-rs. DO NOT EDIT.\n");
+
+        for name in registry.dependency_graph().topological_order() {
+            let Some(schema) = registry.get(name) else {
+                continue;
+            };
+            if properties(schema, Some(registry), &mut HashSet::new()).is_none() {
+                continue;
+            }
+            let table = Table::from_schema(name, schema, Some(registry))?;
+            output.push('\n');
+            output.push_str(&table.create_table_sql(self.dialect));
+            output.push('\n');
+        }
+        Ok(output)
+    }
+}
+
+/// The columns of an object schema.
+pub struct Table<'r> {
+    name: String,
+    columns: Vec<Column>,
+    registry: Option<&'r SchemaRegistry>,
+}
+
+struct Column {
+    name: String,
+    schema: Schema,
+    column_type: ColumnType,
+    required: bool,
+    nullable: bool,
+}
+
+impl Column {
+    fn not_null(&self) -> bool {
+        self.required && !self.nullable
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Boolean,
+    SmallInt,
+    Integer,
+    BigInt,
+    Real,
+    Double,
+    Text,
+    Blob,
+    Json,
+}
+
+impl ColumnType {
+    fn sql(self, dialect: Dialect) -> &'static str {
+        match (self, dialect) {
+            (ColumnType::Boolean, Dialect::Postgres) => "BOOLEAN",
+            (ColumnType::SmallInt, Dialect::Postgres) => "SMALLINT",
+            (ColumnType::BigInt, Dialect::Postgres) => "BIGINT",
+            (ColumnType::Double, Dialect::Postgres) => "DOUBLE PRECISION",
+            (ColumnType::Blob, Dialect::Postgres) => "BYTEA",
+            (ColumnType::Json, Dialect::Postgres) => "JSONB",
+            (
+                ColumnType::Boolean
+                | ColumnType::SmallInt
+                | ColumnType::Integer
+                | ColumnType::BigInt,
+                _,
+            ) => "INTEGER",
+            (ColumnType::Real | ColumnType::Double, _) => "REAL",
+            (ColumnType::Text | ColumnType::Json, _) => "TEXT",
+            (ColumnType::Blob, _) => "BLOB",
+        }
+    }
+
+    /// The range of integers the column holds.
+    fn range(self, dialect: Dialect) -> (i128, i128) {
+        match (self, dialect) {
+            (ColumnType::SmallInt, Dialect::Postgres) => (i16::MIN.into(), i16::MAX.into()),
+            (ColumnType::Integer, Dialect::Postgres) => (i32::MIN.into(), i32::MAX.into()),
+            _ => (i64::MIN.into(), i64::MAX.into()),
+        }
+    }
+}
+
+impl<'r> Table<'r> {
+    /// Maps the properties of `schema` to columns, resolving `$ref`s
+    /// through `registry`.
+    ///
+    /// `schema` is an object, an intersection of objects (the first schema
+    /// of a property wins), or a reference to one. A property that is a
+    /// union with `null` gets the column of its other variant.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqlError::Unsupported`] when `schema` is not an object
+    /// with properties, or a property can hold no value, such as `Null`,
+    /// `Never` and functions.
+    pub fn from_schema(
+        name: &str,
+        schema: &Schema,
+        registry: Option<&'r SchemaRegistry>,
+    ) -> Result<Self, SqlError> {
+        let properties = properties(schema, registry, &mut HashSet::new())
+            .filter(|properties| !properties.is_empty())
+            .ok_or_else(|| SqlError::Unsupported {
+                path: "#".to_string(),
+                message: format!(
+                    "{} has no properties to map to columns",
+                    schema.kind.kind_name()
+                ),
+            })?;
+
+        let columns = properties
+            .into_iter()
+            .map(|(prop, prop_schema, required)| {
+                let (schema, nullable) = match &prop_schema.kind {
+                    SchemaKind::Union { any_of } => match optional_inner(any_of) {
+                        Some(inner) => (inner.clone(), true),
+                        None => (prop_schema.clone(), false),
+                    },
+                    _ => (prop_schema.clone(), false),
+                };
+                let column_type =
+                    column_type(&schema, registry).ok_or_else(|| SqlError::Unsupported {
+                        path: format!("#/properties/{}", prop),
+                        message: format!("{} cannot be stored", schema.kind.kind_name()),
+                    })?;
+                Ok(Column {
+                    name: prop,
+                    schema,
+                    column_type,
+                    required,
+                    nullable,
+                })
+            })
+            .collect::<Result<_, SqlError>>()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            columns,
+            registry,
+        })
+    }
+
+    /// The table name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The column names, in order.
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect()
+    }
+
+    /// The `CREATE TABLE` statement for the table.
+    pub fn create_table_sql(&self, dialect: Dialect) -> String {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let mut line = format!(
+                    "  {} {}",
+                    quote_ident(&column.name),
+                    column.column_type.sql(dialect)
+                );
+                if column.not_null() {
+                    line.push_str(" NOT NULL");
+                }
+                let checks = checks(column, dialect, self.registry);
+                if !checks.is_empty() {
+                    line.push_str(&format!(" CHECK ({})", checks.join(" AND ")));
+                }
+                line
+            })
+            .collect();
+        format!(
+            "CREATE TABLE {} (\n{}\n);",
+            quote_ident(&self.name),
+            columns.join(",\n")
+        )
+    }
+
+    /// An `INSERT` statement taking the parameters of
+    /// [`to_params`](Self::to_params). On PostgreSQL, the JSON text of JSON
+    /// columns is cast to `JSONB`.
+    pub fn insert_sql(&self, dialect: Dialect) -> String {
+        let placeholders: Vec<String> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match dialect {
+                Dialect::Sqlite => "?".to_string(),
+                Dialect::Postgres if column.column_type == ColumnType::Json => {
+                    format!("${}::text::jsonb", i + 1)
+                }
+                Dialect::Postgres => format!("${}", i + 1),
+            })
+            .collect();
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| quote_ident(&column.name))
+            .collect();
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote_ident(&self.name),
+            columns.join(", "),
+            placeholders.join(", ")
+        )
+    }
+
+    /// A `SELECT` statement returning rows for [`from_row`](Self::from_row).
+    /// On PostgreSQL, JSON columns are selected as text.
+    pub fn select_sql(&self, dialect: Dialect) -> String {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| match dialect {
+                Dialect::Postgres if column.column_type == ColumnType::Json => {
+                    format!("{}::text", quote_ident(&column.name))
+                }
+                _ => quote_ident(&column.name),
+            })
+            .collect();
+        format!(
+            "SELECT {} FROM {}",
+            columns.join(", "),
+            quote_ident(&self.name)
+        )
+    }
+
+    /// The parameters for the columns of `value`, an object, in column
+    /// order. Absent properties are `NULL`, and JSON columns hold the
+    /// property as JSON text.
+    ///
+    /// Values are not validated against the schema beyond their types; use
+    /// [`check`](crate::check()) first, or rely on the table's constraints.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqlError::Encode`] when `value` is not an object, a
+    /// `NOT NULL` column is absent or `null`, or a property has the wrong
+    /// type for its column.
+    pub fn to_params(&self, value: &Value) -> Result<Vec<SqlValue>, SqlError> {
+        let Value::Object(fields) = value else {
+            return Err(SqlError::Encode {
+                path: "#".to_string(),
+                message: format!("expected Object, found {}", value.kind()),
+            });
+        };
+
+        self.columns
+            .iter()
+            .map(|column| {
+                let path = format!("#/{}", column.name);
+                let field = match fields.get(&column.name) {
+                    None | Some(Value::Null) if column.not_null() => {
+                        return Err(SqlError::Encode {
+                            path,
+                            message: "NOT NULL column has no value".to_string(),
+                        });
+                    }
+                    None | Some(Value::Null) => return Ok(SqlValue::Null),
+                    Some(field) => field,
+                };
+                let param = match (column.column_type, field) {
+                    (ColumnType::Boolean, Value::Bool(b)) => SqlValue::Bool(*b),
+                    (
+                        ColumnType::SmallInt | ColumnType::Integer | ColumnType::BigInt,
+                        Value::Int64(n),
+                    ) => SqlValue::Integer(*n),
+                    (ColumnType::Real | ColumnType::Double, Value::Float64(f)) => {
+                        SqlValue::Real(*f)
+                    }
+                    (ColumnType::Real | ColumnType::Double, Value::Int64(n)) => {
+                        SqlValue::Real(*n as f64)
+                    }
+                    (ColumnType::Text, Value::String(s)) => SqlValue::Text(s.clone()),
+                    (ColumnType::Blob, Value::Bytes(b) | Value::UInt8Array(b)) => {
+                        SqlValue::Blob(b.clone())
+                    }
+                    (ColumnType::Json, field) => SqlValue::Text(field.to_json().to_string()),
+                    (column_type, field) => {
+                        return Err(SqlError::Encode {
+                            path,
+                            message: format!(
+                                "{:?} column cannot hold {}",
+                                column_type,
+                                field.kind()
+                            ),
+                        });
+                    }
+                };
+                Ok(param)
+            })
+            .collect()
+    }
+
+    /// The object stored in `row`, whose values are in column order.
+    ///
+    /// `NULL`s of properties that are not required are left out of the
+    /// object. JSON columns are parsed and read back as their schema, with
+    /// `Bytes` decoded from base64.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqlError::RowLength`] when `row` does not have a value per
+    /// column, and [`SqlError::Decode`] when a value has the wrong type for
+    /// its column or a JSON column does not hold JSON.
+    pub fn from_row(&self, row: &[SqlValue]) -> Result<Value, SqlError> {
+        if row.len() != self.columns.len() {
+            return Err(SqlError::RowLength {
+                expected: self.columns.len(),
+                found: row.len(),
+            });
+        }
+
+        let mut fields = IndexMap::new();
+        for (column, cell) in self.columns.iter().zip(row) {
+            let path = format!("#/{}", column.name);
+            let field = match (column.column_type, cell) {
+                (_, SqlValue::Null) if column.not_null() => {
+                    return Err(SqlError::Decode {
+                        path,
+                        message: "NOT NULL column is NULL".to_string(),
+                    });
+                }
+                (_, SqlValue::Null) if !column.required => continue,
+                (_, SqlValue::Null) => Value::Null,
+                (ColumnType::Boolean, SqlValue::Bool(b)) => Value::Bool(*b),
+                (ColumnType::Boolean, SqlValue::Integer(n)) => Value::Bool(*n != 0),
+                (
+                    ColumnType::SmallInt | ColumnType::Integer | ColumnType::BigInt,
+                    SqlValue::Integer(n),
+                ) => Value::Int64(*n),
+                (ColumnType::Real | ColumnType::Double, SqlValue::Real(f)) => Value::Float64(*f),
+                (ColumnType::Real | ColumnType::Double, SqlValue::Integer(n)) => {
+                    Value::Float64(*n as f64)
+                }
+                (ColumnType::Text, SqlValue::Text(s)) => Value::String(s.clone()),
+                (ColumnType::Blob, SqlValue::Blob(b)) => Value::Bytes(b.clone()),
+                (ColumnType::Json, SqlValue::Text(text)) => {
+                    let json: Json = serde_json::from_str(text).map_err(|e| SqlError::Decode {
+                        path: path.clone(),
+                        message: e.to_string(),
+                    })?;
+                    decode_json(json, &column.schema, self.registry)
+                }
+                (column_type, cell) => {
+                    return Err(SqlError::Decode {
+                        path,
+                        message: format!("{:?} column holds {}", column_type, cell.kind()),
+                    });
+                }
+            };
+            fields.insert(column.name.clone(), field);
+        }
+        Ok(Value::Object(fields))
+    }
+}
+
+/// The properties of an object, an intersection of objects, or the object
+/// a reference resolves to, with whether each is required.
+fn properties(
+    schema: &Schema,
+    registry: Option<&SchemaRegistry>,
+    visited: &mut HashSet<String>,
+) -> Option<Vec<(String, Schema, bool)>> {
+    match &schema.kind {
+        SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } => Some(
+            properties
+                .iter()
+                .map(|(name, prop)| (name.clone(), prop.clone(), required.contains(name)))
+                .collect(),
+        ),
+        SchemaKind::Intersect { all_of } => {
+            let mut merged: Vec<(String, Schema, bool)> = Vec::new();
+            for part in all_of {
+                for (name, prop, required) in properties(part, registry, visited)? {
+                    match merged.iter_mut().find(|(existing, ..)| *existing == name) {
+                        Some(entry) => entry.2 |= required,
+                        None => merged.push((name, prop, required)),
+                    }
+                }
+            }
+            Some(merged)
+        }
+        SchemaKind::Named { schema, .. } => properties(schema, registry, visited),
+        SchemaKind::Ref { reference } if visited.insert(reference.clone()) => {
+            let target = registry?.resolve_ref(reference, None).ok()?;
+            properties(target, registry, visited)
+        }
+        _ => None,
+    }
+}
+
+/// The variant of a two-variant union with `null`.
+fn optional_inner(any_of: &[Schema]) -> Option<&Schema> {
+    match any_of {
+        [a, b] if matches!(b.kind, SchemaKind::Null) => Some(a),
+        [a, b] if matches!(a.kind, SchemaKind::Null) => Some(b),
+        _ => None,
+    }
+}
+
+/// The column type of `schema`, or `None` if it holds no value.
+fn column_type(schema: &Schema, registry: Option<&SchemaRegistry>) -> Option<ColumnType> {
+    let column_type = match &schema.kind {
+        SchemaKind::Bool => ColumnType::Boolean,
+        SchemaKind::Int8 { .. } | SchemaKind::Int16 { .. } | SchemaKind::UInt8 { .. } => {
+            ColumnType::SmallInt
+        }
+        SchemaKind::Int32 { .. } | SchemaKind::UInt16 { .. } => ColumnType::Integer,
+        SchemaKind::Int64 { .. } | SchemaKind::UInt32 { .. } | SchemaKind::UInt64 { .. } => {
+            ColumnType::BigInt
+        }
+        SchemaKind::Float32 { .. } => ColumnType::Real,
+        SchemaKind::Float64 { .. } => ColumnType::Double,
+        SchemaKind::String { .. } | SchemaKind::Enum { .. } => ColumnType::Text,
+        SchemaKind::Bytes { .. } => ColumnType::Blob,
+        SchemaKind::Literal { value } => match value {
+            LiteralValue::String(_) => ColumnType::Text,
+            LiteralValue::Number(_) => ColumnType::BigInt,
+            LiteralValue::Float(_) => ColumnType::Double,
+            LiteralValue::Boolean(_) => ColumnType::Boolean,
+            LiteralValue::Null => return None,
+        },
+        SchemaKind::Union { any_of } if string_literals(any_of).is_some() => ColumnType::Text,
+        SchemaKind::Named { schema, .. } => return column_type(schema, registry),
+        SchemaKind::Ref { reference } => {
+            return match registry.and_then(|r| r.resolve_ref(reference, None).ok()) {
+                Some(target) if !matches!(target.kind, SchemaKind::Ref { .. }) => {
+                    column_type(target, registry)
+                }
+                _ => Some(ColumnType::Json),
+            };
+        }
+        SchemaKind::Null
+        | SchemaKind::Void
+        | SchemaKind::Undefined
+        | SchemaKind::Never
+        | SchemaKind::Function { .. } => return None,
+        _ => ColumnType::Json,
+    };
+    Some(column_type)
+}
+
+/// The values of a union of string literals.
+fn string_literals(any_of: &[Schema]) -> Option<Vec<&str>> {
+    any_of
+        .iter()
+        .map(|variant| match &variant.kind {
+            SchemaKind::Literal {
+                value: LiteralValue::String(s),
+            } => Some(s.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The `CHECK` conditions of a column.
+fn checks(column: &Column, dialect: Dialect, registry: Option<&SchemaRegistry>) -> Vec<String> {
+    let name = quote_ident(&column.name);
+    let mut checks = Vec::new();
+    let mut schema = &column.schema;
+    while let Some(target) = match &schema.kind {
+        SchemaKind::Named { schema, .. } => Some(schema.as_ref()),
+        SchemaKind::Ref { reference } => registry.and_then(|r| r.resolve_ref(reference, None).ok()),
+        _ => None,
+    } {
+        schema = target;
+    }
+
+    let length = match dialect {
+        Dialect::Sqlite => "length",
+        Dialect::Postgres => "char_length",
+    };
+    match &schema.kind {
+        SchemaKind::Bool if dialect == Dialect::Sqlite => {
+            checks.push(format!("{} IN (0, 1)", name));
+        }
+        SchemaKind::Int8 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            i8::MIN.into(),
+            i8::MAX.into(),
+        ),
+        SchemaKind::Int16 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            i16::MIN.into(),
+            i16::MAX.into(),
+        ),
+        SchemaKind::Int32 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            i32::MIN.into(),
+            i32::MAX.into(),
+        ),
+        SchemaKind::Int64 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            i64::MIN.into(),
+            i64::MAX.into(),
+        ),
+        SchemaKind::UInt8 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            0,
+            u8::MAX.into(),
+        ),
+        SchemaKind::UInt16 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            0,
+            u16::MAX.into(),
+        ),
+        SchemaKind::UInt32 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            0,
+            u32::MAX.into(),
+        ),
+        SchemaKind::UInt64 { minimum, maximum } => int_checks(
+            &mut checks,
+            &name,
+            column.column_type,
+            dialect,
+            minimum.map(i128::from),
+            maximum.map(i128::from),
+            0,
+            u64::MAX.into(),
+        ),
+        SchemaKind::Float32 { minimum, maximum } => {
+            float_checks(
+                &mut checks,
+                &name,
+                minimum.map(f64::from),
+                maximum.map(f64::from),
+            );
+        }
+        SchemaKind::Float64 { minimum, maximum } => {
+            float_checks(&mut checks, &name, *minimum, *maximum);
+        }
+        SchemaKind::String {
+            min_length,
+            max_length,
+            ..
+        } => length_checks(&mut checks, &name, length, *min_length, *max_length),
+        SchemaKind::Bytes {
+            min_length,
+            max_length,
+        } => {
+            let length = match dialect {
+                Dialect::Sqlite => "length",
+                Dialect::Postgres => "octet_length",
+            };
+            length_checks(&mut checks, &name, length, *min_length, *max_length);
+        }
+        SchemaKind::Enum { values } => {
+            checks.push(in_list(&name, values.iter().map(String::as_str)));
+        }
+        SchemaKind::Union { any_of } => {
+            if let Some(values) = string_literals(any_of) {
+                checks.push(in_list(&name, values.into_iter()));
+            }
+        }
+        SchemaKind::Literal { value } => {
+            let literal = match value {
+                LiteralValue::String(s) => quote_literal(s),
+                LiteralValue::Number(n) => n.to_string(),
+                LiteralValue::Float(f) => f.to_string(),
+                LiteralValue::Boolean(b) => match dialect {
+                    Dialect::Sqlite => u8::from(*b).to_string(),
+                    Dialect::Postgres => b.to_string().to_uppercase(),
+                },
+                LiteralValue::Null => "NULL".to_string(),
+            };
+            checks.push(format!("{} = {}", name, literal));
+        }
+        _ if column.column_type == ColumnType::Json && dialect == Dialect::Sqlite => {
+            checks.push(format!("json_valid({})", name));
+        }
+        _ => {}
+    }
+    checks
+}
+
+/// Bounds an integer column by `minimum` and `maximum`, or by the range of
+/// its type where the column is wider.
+#[allow(clippy::too_many_arguments)]
+fn int_checks(
+    checks: &mut Vec<String>,
+    name: &str,
+    column_type: ColumnType,
+    dialect: Dialect,
+    minimum: Option<i128>,
+    maximum: Option<i128>,
+    type_min: i128,
+    type_max: i128,
+) {
+    let (column_min, column_max) = column_type.range(dialect);
+    let minimum = minimum.or((type_min > column_min).then_some(type_min));
+    let maximum = maximum.or((type_max < column_max).then_some(type_max));
+    if let Some(minimum) = minimum {
+        checks.push(format!("{} >= {}", name, minimum));
+    }
+    if let Some(maximum) = maximum.filter(|maximum| *maximum <= column_max) {
+        checks.push(format!("{} <= {}", name, maximum));
+    }
+}
+
+fn float_checks(checks: &mut Vec<String>, name: &str, minimum: Option<f64>, maximum: Option<f64>) {
+    if let Some(minimum) = minimum.filter(|f| f.is_finite()) {
+        checks.push(format!("{} >= {}", name, minimum));
+    }
+    if let Some(maximum) = maximum.filter(|f| f.is_finite()) {
+        checks.push(format!("{} <= {}", name, maximum));
+    }
+}
+
+fn length_checks(
+    checks: &mut Vec<String>,
+    name: &str,
+    length: &str,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+) {
+    if let Some(min_length) = min_length.filter(|n| *n > 0) {
+        checks.push(format!("{}({}) >= {}", length, name, min_length));
+    }
+    if let Some(max_length) = max_length {
+        checks.push(format!("{}({}) <= {}", length, name, max_length));
+    }
+}
+
+fn in_list<'a>(name: &str, values: impl Iterator<Item = &'a str>) -> String {
+    let values: Vec<String> = values.map(quote_literal).collect();
+    format!("{} IN ({})", name, values.join(", "))
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Reads the JSON of a JSON column as `schema`: numbers of float schemas
+/// become floats, base64 strings of `Bytes` schemas bytes, and union
+/// variants are tried in order. JSON the schema does not describe is read
+/// as is.
+fn decode_json(json: Json, schema: &Schema, registry: Option<&SchemaRegistry>) -> Value {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    match (&schema.kind, json) {
+        (SchemaKind::Float32 { .. } | SchemaKind::Float64 { .. }, Json::Number(n)) => {
+            n.as_f64().map(Value::Float64).unwrap_or(Value::Null)
+        }
+        (SchemaKind::Bytes { .. }, Json::String(s)) => match STANDARD.decode(&s) {
+            Ok(bytes) => Value::Bytes(bytes),
+            Err(_) => Value::String(s),
+        },
+        (SchemaKind::Array { items, .. }, Json::Array(elements)) => Value::Array(
+            elements
+                .into_iter()
+                .map(|element| decode_json(element, items, registry))
+                .collect(),
+        ),
+        (SchemaKind::Tuple { items }, Json::Array(elements)) => Value::Array(
+            elements
+                .into_iter()
+                .enumerate()
+                .map(|(i, element)| match items.get(i) {
+                    Some(item) => decode_json(element, item, registry),
+                    None => value_to_untyped(element),
+                })
+                .collect(),
+        ),
+        (
+            SchemaKind::Object {
+                additional_properties,
+                ..
+            },
+            Json::Object(map),
+        ) => {
+            let props = properties(schema, registry, &mut HashSet::new()).unwrap_or_default();
+            Value::Object(
+                map.into_iter()
+                    .map(|(key, field)| {
+                        let field_schema = props
+                            .iter()
+                            .find(|(name, ..)| *name == key)
+                            .map(|(_, prop, _)| prop)
+                            .or(additional_properties.as_deref());
+                        let field = match field_schema {
+                            Some(field_schema) => decode_json(field, field_schema, registry),
+                            None => value_to_untyped(field),
+                        };
+                        (key, field)
+                    })
+                    .collect(),
+            )
+        }
+        (SchemaKind::Intersect { .. }, Json::Object(map)) => {
+            let props = properties(schema, registry, &mut HashSet::new()).unwrap_or_default();
+            Value::Object(
+                map.into_iter()
+                    .map(|(key, field)| {
+                        let field = match props.iter().find(|(name, ..)| *name == key) {
+                            Some((_, prop, _)) => decode_json(field, prop, registry),
+                            None => value_to_untyped(field),
+                        };
+                        (key, field)
+                    })
+                    .collect(),
+            )
+        }
+        (SchemaKind::Union { any_of }, json) => any_of
+            .iter()
+            .map(|variant| (variant, decode_json(json.clone(), variant, registry)))
+            .find(|(variant, value)| check_with_registry(variant, value, registry))
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| value_to_untyped(json)),
+        (SchemaKind::Named { schema, .. } | SchemaKind::Recursive { schema }, json) => {
+            decode_json(json, schema, registry)
+        }
+        (SchemaKind::Ref { reference }, json) => {
+            match registry.and_then(|r| r.resolve_ref(reference, None).ok()) {
+                Some(target) if !matches!(target.kind, SchemaKind::Ref { .. }) => {
+                    decode_json(json, target, registry)
+                }
+                _ => value_to_untyped(json),
+            }
+        }
+        (_, json) => value_to_untyped(json),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SchemaBuilder;
+
+    fn user() -> Schema {
+        SchemaBuilder::object()
+            .field(
+                "id",
+                Schema::new(SchemaKind::UInt32 {
+                    minimum: Some(1),
+                    maximum: None,
+                }),
+            )
+            .field(
+                "name",
+                SchemaBuilder::string().min_length(1).max_length(64).build(),
+            )
+            .field("role", SchemaBuilder::enum_values(vec!["admin", "o'brien"]))
+            .field("active", SchemaBuilder::bool())
+            .field("age", SchemaBuilder::optional(SchemaBuilder::uint8()))
+            .optional_field("score", SchemaBuilder::float64())
+            .optional_field("avatar", SchemaBuilder::bytes())
+            .field(
+                "tags",
+                SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+            )
+            .optional_field(
+                "address",
+                SchemaBuilder::object()
+                    .field("city", SchemaBuilder::string().build())
+                    .build(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_create_table() {
+        assert_eq!(
+            SqlGenerator::new(Dialect::Postgres)
+                .generate("users", &user())
+                .unwrap(),
+            r#"CREATE TABLE "users" (
+  "id" BIGINT NOT NULL CHECK ("id" >= 1 AND "id" <= 4294967295),
+  "name" TEXT NOT NULL CHECK (char_length("name") >= 1 AND char_length("name") <= 64),
+  "role" TEXT NOT NULL CHECK ("role" IN ('admin', 'o''brien')),
+  "active" BOOLEAN NOT NULL,
+  "age" SMALLINT CHECK ("age" >= 0 AND "age" <= 255),
+  "score" DOUBLE PRECISION,
+  "avatar" BYTEA,
+  "tags" JSONB NOT NULL,
+  "address" JSONB
+);"#
+        );
+        assert_eq!(
+            SqlGenerator::new(Dialect::Sqlite)
+                .generate("users", &user())
+                .unwrap(),
+            r#"CREATE TABLE "users" (
+  "id" INTEGER NOT NULL CHECK ("id" >= 1 AND "id" <= 4294967295),
+  "name" TEXT NOT NULL CHECK (length("name") >= 1 AND length("name") <= 64),
+  "role" TEXT NOT NULL CHECK ("role" IN ('admin', 'o''brien')),
+  "active" INTEGER NOT NULL CHECK ("active" IN (0, 1)),
+  "age" INTEGER CHECK ("age" >= 0 AND "age" <= 255),
+  "score" REAL,
+  "avatar" BLOB,
+  "tags" TEXT NOT NULL CHECK (json_valid("tags")),
+  "address" TEXT CHECK (json_valid("address"))
+);"#
+        );
+
+        let err = SqlGenerator::new(Dialect::Sqlite)
+            .generate(
+                "names",
+                &SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+            )
+            .err()
+            .map(|e| e.to_string());
+        assert_eq!(
+            err.as_deref(),
+            Some("Unsupported schema at #: Array has no properties to map to columns")
+        );
+    }
+
+    #[test]
+    fn test_generate_module_resolves_refs() {
+        let mut registry = SchemaRegistry::new();
+        registry.register("Status", SchemaBuilder::enum_values(vec!["open", "closed"]));
+        registry.register(
+            "Point",
+            SchemaBuilder::object()
+                .field("x", SchemaBuilder::int16())
+                .build(),
+        );
+        registry.register(
+            "Ticket",
+            SchemaBuilder::object()
+                .field("status", SchemaBuilder::r#ref("Status"))
+                .field("at", SchemaBuilder::r#ref("Point"))
+                .build(),
+        );
+
+        let output = SqlGenerator::new(Dialect::Postgres)
+            .generate_module(&registry)
+            .unwrap();
+        assert!(output.starts_with("-- Code generated by typebox-rs. DO NOT EDIT.\n\n"));
+        assert!(output.contains("CREATE TABLE \"Point\" (\n  \"x\" SMALLINT NOT NULL\n);\n"));
+        assert!(output.contains(
+            "CREATE TABLE \"Ticket\" (\n  \"status\" TEXT NOT NULL CHECK (\"status\" IN ('open', 'closed')),\n  \"at\" JSONB NOT NULL\n);\n"
+        ));
+        assert!(!output.contains("\"Status\""));
+    }
+
+    #[test]
+    fn test_row_round_trip() {
+        let schema = user();
+        let table = Table::from_schema("users", &schema, None).unwrap();
+        assert_eq!(
+            table.insert_sql(Dialect::Postgres),
+            r#"INSERT INTO "users" ("id", "name", "role", "active", "age", "score", "avatar", "tags", "address") VALUES ($1, $2, $3, $4, $5, $6, $7, $8::text::jsonb, $9::text::jsonb)"#
+        );
+        assert_eq!(
+            table.select_sql(Dialect::Postgres),
+            r#"SELECT "id", "name", "role", "active", "age", "score", "avatar", "tags"::text, "address"::text FROM "users""#
+        );
+
+        let value = Value::object()
+            .field("id", Value::int64(7))
+            .field("name", Value::string("Ada"))
+            .field("role", Value::string("admin"))
+            .field("active", Value::Bool(true))
+            .field("age", Value::Null)
+            .field("avatar", Value::bytes(vec![0, 255]))
+            .field("tags", Value::array(vec![Value::string("x")]))
+            .field(
+                "address",
+                Value::object()
+                    .field("city", Value::string("Paris"))
+                    .build(),
+            )
+            .build();
+        let params = table.to_params(&value).unwrap();
+        assert_eq!(
+            params,
+            vec![
+                SqlValue::Integer(7),
+                SqlValue::Text("Ada".to_string()),
+                SqlValue::Text("admin".to_string()),
+                SqlValue::Bool(true),
+                SqlValue::Null,
+                SqlValue::Null,
+                SqlValue::Blob(vec![0, 255]),
+                SqlValue::Text(r#"["x"]"#.to_string()),
+                SqlValue::Text(r#"{"city":"Paris"}"#.to_string()),
+            ]
+        );
+        assert_eq!(table.from_row(&params).unwrap(), value);
+
+        // SQLite returns booleans as integers.
+        let mut row = params.clone();
+        row[3] = SqlValue::Integer(1);
+        assert_eq!(table.from_row(&row).unwrap(), value);
+
+        let missing = Value::object().field("id", Value::int64(7)).build();
+        assert_eq!(
+            table
+                .to_params(&missing)
+                .err()
+                .map(|e| e.to_string())
+                .as_deref(),
+            Some("Cannot encode value at #/name: NOT NULL column has no value")
+        );
+        assert!(matches!(
+            table.from_row(&params[..2]),
+            Err(SqlError::RowLength {
+                expected: 9,
+                found: 2
+            })
+        ));
+        row[0] = SqlValue::Text("7".to_string());
+        assert!(matches!(table.from_row(&row), Err(SqlError::Decode { .. })));
+    }
+}
@@ -5,14 +5,15 @@
 
 use crate::error::ValidationError;
 use crate::format::FormatRegistry;
-use crate::registry::SchemaRegistry;
-use crate::schema::{LiteralValue, Schema, SchemaKind, StringFormat};
+use crate::registry::{resolve_uri, SchemaRegistry};
+use crate::schema::{LiteralValue, Schema, SchemaKind};
+use crate::value::scope::Binding;
 use crate::value::{hash_fnv1a, Value};
 use std::collections::HashSet;
 
 /// Validates a value against a schema.
 pub fn validate(schema: &Schema, value: &Value) -> Result<(), ValidationError> {
-    validate_full(schema, value, None, None)
+    validate_full(schema, value, None, None, None, None)
 }
 
 /// Validates with a schema registry for `$ref` resolution.
@@ -21,7 +22,7 @@ pub fn validate_with_registry(
     value: &Value,
     registry: Option<&SchemaRegistry>,
 ) -> Result<(), ValidationError> {
-    validate_full(schema, value, registry, None)
+    validate_full(schema, value, registry, None, None, None)
 }
 
 /// Validates with format checking via FormatRegistry.
@@ -31,15 +32,31 @@ pub fn validate_with_format(
     registry: Option<&SchemaRegistry>,
     formats: Option<&FormatRegistry>,
 ) -> Result<(), ValidationError> {
-    validate_full(schema, value, registry, formats)
+    validate_full(schema, value, registry, formats, None, None)
 }
 
-fn validate_full(
+/// `base` is the URI relative `$ref`s are resolved against; it changes as
+/// validation enters schemas carrying an `$id`.
+pub(crate) fn validate_full(
     schema: &Schema,
     value: &Value,
     registry: Option<&SchemaRegistry>,
     formats: Option<&FormatRegistry>,
+    base: Option<&str>,
+    bindings: Option<&Binding<'_>>,
 ) -> Result<(), ValidationError> {
+    let scoped;
+    let base = match &schema.id {
+        Some(id) if !id.starts_with('#') => {
+            scoped = match base {
+                Some(base) => resolve_uri(base, id),
+                None => id.clone(),
+            };
+            Some(scoped.as_str())
+        }
+        _ => base,
+    };
+
     match (&schema.kind, value) {
         (SchemaKind::Null, Value::Null) => Ok(()),
 
@@ -181,18 +198,7 @@ fn validate_full(
                 }
             }
             if let Some(fmt) = format {
-                let format_name = match fmt {
-                    StringFormat::Email => "email",
-                    StringFormat::Uuid => "uuid",
-                    StringFormat::Uri => "uri",
-                    StringFormat::DateTime => "date-time",
-                    StringFormat::Date => "date",
-                    StringFormat::Time => "time",
-                    StringFormat::Hostname => "hostname",
-                    StringFormat::Ipv4 => "ipv4",
-                    StringFormat::Ipv6 => "ipv6",
-                    StringFormat::Custom(name) => name.as_str(),
-                };
+                let format_name = fmt.as_str();
                 if let Some(fmt_registry) = formats {
                     if let Some(result) = fmt_registry.validate(format_name, s) {
                         if !result {
@@ -234,18 +240,7 @@ fn validate_full(
                 }
             }
             if let Some(fmt) = format {
-                let format_name = match fmt {
-                    StringFormat::Email => "email",
-                    StringFormat::Uuid => "uuid",
-                    StringFormat::Uri => "uri",
-                    StringFormat::DateTime => "date-time",
-                    StringFormat::Date => "date",
-                    StringFormat::Time => "time",
-                    StringFormat::Hostname => "hostname",
-                    StringFormat::Ipv4 => "ipv4",
-                    StringFormat::Ipv6 => "ipv6",
-                    StringFormat::Custom(name) => name.as_str(),
-                };
+                let format_name = fmt.as_str();
                 if let Some(fmt_registry) = formats {
                     if let Some(result) = fmt_registry.validate(format_name, s) {
                         if !result {
@@ -316,7 +311,7 @@ fn validate_full(
                 }
             }
             for (i, item) in arr.iter().enumerate() {
-                validate_full(items, item, registry, formats)
+                validate_full(items, item, registry, formats, base, bindings)
                     .map_err(|e| e.with_path(i.to_string()))?;
             }
             Ok(())
@@ -369,10 +364,10 @@ fn validate_full(
 
             for (name, val) in map {
                 if let Some(prop_schema) = properties.get(name) {
-                    validate_full(prop_schema, val, registry, formats)
+                    validate_full(prop_schema, val, registry, formats, base, bindings)
                         .map_err(|e| e.with_path(name))?;
                 } else if let Some(ref additional) = additional_properties {
-                    validate_full(additional, val, registry, formats)
+                    validate_full(additional, val, registry, formats, base, bindings)
                         .map_err(|e| e.with_path(name))?;
                 } else {
                     return Err(ValidationError::UnknownField {
@@ -392,7 +387,7 @@ fn validate_full(
                 });
             }
             for (i, (item_schema, item_val)) in items.iter().zip(arr.iter()).enumerate() {
-                validate_full(item_schema, item_val, registry, formats)
+                validate_full(item_schema, item_val, registry, formats, base, bindings)
                     .map_err(|e| e.with_path(i.to_string()))?;
             }
             Ok(())
@@ -400,7 +395,7 @@ fn validate_full(
 
         (SchemaKind::Union { any_of }, value) => {
             for variant in any_of {
-                if validate_full(variant, value, registry, formats).is_ok() {
+                if validate_full(variant, value, registry, formats, base, bindings).is_ok() {
                     return Ok(());
                 }
             }
@@ -425,21 +420,32 @@ fn validate_full(
         }
 
         (SchemaKind::Ref { reference }, value) => {
+            if let Some((target, base)) = Binding::find(bindings, reference, base) {
+                return validate_full(target, value, registry, formats, base, bindings);
+            }
             let registry = registry.ok_or_else(|| ValidationError::TypeMismatch {
                 expected: format!("resolved ref {}", reference),
                 actual: "no registry".to_string(),
             })?;
-            let resolved = registry
-                .resolve(schema)
-                .map_err(|_| ValidationError::TypeMismatch {
-                    expected: format!("resolved ref {}", reference),
-                    actual: "unresolved".to_string(),
-                })?;
-            validate_full(resolved, value, Some(registry), formats)
+            let (resolved, resolved_base) =
+                registry
+                    .resolve_in(schema, base)
+                    .map_err(|_| ValidationError::TypeMismatch {
+                        expected: format!("resolved ref {}", reference),
+                        actual: "unresolved".to_string(),
+                    })?;
+            validate_full(
+                resolved,
+                value,
+                Some(registry),
+                formats,
+                resolved_base.as_deref(),
+                bindings,
+            )
         }
 
         (SchemaKind::Named { schema, .. }, value) => {
-            validate_full(schema, value, registry, formats)
+            validate_full(schema, value, registry, formats, base, bindings)
         }
 
         (SchemaKind::Function { .. }, _) => Ok(()),
@@ -458,16 +464,18 @@ fn validate_full(
         (SchemaKind::Undefined, Value::Null) => Ok(()),
 
         (SchemaKind::Recursive { schema: inner }, value) => {
-            let mut temp_registry = registry.cloned().unwrap_or_default();
-            if let Some(ref id) = schema.id {
-                temp_registry.register(id, (**inner).clone());
+            // `base` already includes this schema's `$id`.
+            match Binding::new(schema, base, bindings) {
+                Some(binding) => {
+                    validate_full(inner, value, registry, formats, base, Some(&binding))
+                }
+                None => validate_full(inner, value, registry, formats, base, bindings),
             }
-            validate_full(inner, value, Some(&temp_registry), formats)
         }
 
         (SchemaKind::Intersect { all_of }, value) => {
             for s in all_of {
-                validate_full(s, value, registry, formats)?;
+                validate_full(s, value, registry, formats, base, bindings)?;
             }
             Ok(())
         }
@@ -559,7 +567,7 @@ mod tests {
             &Value::String("hello".to_string())
         )
         .is_ok());
-        assert!(validate(&SchemaBuilder::float64(), &Value::Float64(3.14)).is_ok());
+        assert!(validate(&SchemaBuilder::float64(), &Value::Float64(2.5)).is_ok());
     }
 
     #[test]
@@ -758,6 +766,56 @@ mod tests {
         assert!(validate_with_registry(&ref_schema, &invalid, Some(&registry)).is_err());
     }
 
+    #[test]
+    fn test_validate_relative_ref_across_documents() {
+        use crate::registry::SchemaRegistry;
+
+        let relative = |reference: &str| {
+            Schema::new(SchemaKind::Ref {
+                reference: reference.to_string(),
+            })
+        };
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "Order",
+            SchemaBuilder::object()
+                .field("customer", relative("people/customer.json"))
+                .build()
+                .with_id("https://example.com/schemas/order.json"),
+        );
+        registry.register(
+            "Customer",
+            SchemaBuilder::object()
+                .field("name", SchemaBuilder::string().build())
+                .optional_field("referrer", relative("customer.json"))
+                .build()
+                .with_id("https://example.com/schemas/people/customer.json"),
+        );
+
+        let order = relative("https://example.com/schemas/order.json");
+        let value = Value::object()
+            .field(
+                "customer",
+                Value::object()
+                    .field("name", Value::string("Ann"))
+                    .field(
+                        "referrer",
+                        Value::object().field("name", Value::string("Bo")).build(),
+                    )
+                    .build(),
+            )
+            .build();
+        assert!(validate_with_registry(&order, &value, Some(&registry)).is_ok());
+
+        let invalid = Value::object()
+            .field(
+                "customer",
+                Value::object().field("name", Value::int64(1)).build(),
+            )
+            .build();
+        assert!(validate_with_registry(&order, &invalid, Some(&registry)).is_err());
+    }
+
     #[test]
     fn test_validate_ref_without_registry() {
         let ref_schema = SchemaBuilder::r#ref("Person");
@@ -835,6 +893,44 @@ mod tests {
         .is_ok());
     }
 
+    #[test]
+    fn test_validate_nested_recursive() {
+        // `Section` refers to both its own `$id` and the enclosing `Doc`.
+        let schema = SchemaBuilder::recursive("Doc", |doc| {
+            let section = SchemaBuilder::recursive("Section", |section| {
+                SchemaBuilder::object()
+                    .field("children", SchemaBuilder::array(section).build())
+                    .field(
+                        "embed",
+                        SchemaBuilder::union(vec![doc, SchemaBuilder::null()]),
+                    )
+                    .build()
+            });
+            SchemaBuilder::object()
+                .field("sections", SchemaBuilder::array(section).build())
+                .build()
+        });
+
+        let leaf = |embed| {
+            Value::object()
+                .field("children", Value::Array(vec![]))
+                .field("embed", embed)
+                .build()
+        };
+        let doc = |sections| {
+            Value::object()
+                .field("sections", Value::Array(sections))
+                .build()
+        };
+        let inner = doc(vec![leaf(Value::Null)]);
+        let value = doc(vec![Value::object()
+            .field("children", Value::Array(vec![leaf(inner)]))
+            .field("embed", Value::Null)
+            .build()]);
+        assert!(validate(&schema, &value).is_ok());
+        assert!(validate(&schema, &doc(vec![leaf(Value::Int64(1))])).is_err());
+    }
+
     #[test]
     fn test_validate_intersect() {
         let node = SchemaBuilder::object()
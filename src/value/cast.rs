@@ -1,6 +1,8 @@
 //! Value coercion to match schemas.
 
+use super::scope::RefScope;
 use crate::error::CastError;
+use crate::registry::SchemaRegistry;
 use crate::schema::{LiteralValue, Schema, SchemaKind};
 use crate::value::Value;
 use indexmap::IndexMap;
@@ -10,6 +12,21 @@ use indexmap::IndexMap;
 /// Performs type conversions (string to int, int to bool, etc.),
 /// clamps numeric values to bounds, and fills in missing object fields.
 pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
+    cast_with_registry(schema, value, None)
+}
+
+/// Coerce a value to conform to a schema, resolving `$ref`s through
+/// `registry`. Missing fields are filled in as by
+/// [`create_with_registry`](super::create::create_with_registry).
+pub fn cast_with_registry(
+    schema: &Schema,
+    value: &Value,
+    registry: Option<&SchemaRegistry>,
+) -> Result<Value, CastError> {
+    cast_in(schema, value, &RefScope::new(registry, schema))
+}
+
+fn cast_in(schema: &Schema, value: &Value, scope: &RefScope<'_>) -> Result<Value, CastError> {
     match (&schema.kind, value) {
         (SchemaKind::Null, Value::Null) => Ok(Value::Null),
         (SchemaKind::Null, _) => Ok(Value::Null),
@@ -191,11 +208,11 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
             let mut result: Vec<Value> = arr
                 .iter()
                 .take(max)
-                .map(|v| cast(items, v))
+                .map(|v| cast_in(items, v, scope))
                 .collect::<Result<Vec<_>, _>>()?;
 
             while result.len() < min {
-                result.push(super::create::create(items).map_err(|e| {
+                result.push(super::create::create_in(items, scope).map_err(|e| {
                     CastError::CannotCast(format!("cannot create default item: {}", e))
                 })?);
             }
@@ -209,10 +226,10 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
             v,
         ) => {
             let min = min_items.unwrap_or(0);
-            let single = cast(items, v)?;
+            let single = cast_in(items, v, scope)?;
             let mut arr = vec![single];
             while arr.len() < min {
-                arr.push(super::create::create(items).map_err(|e| {
+                arr.push(super::create::create_in(items, scope).map_err(|e| {
                     CastError::CannotCast(format!("cannot create default item: {}", e))
                 })?);
             }
@@ -232,11 +249,11 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
             for field_name in required {
                 if let Some(field_schema) = properties.get(field_name) {
                     if let Some(val) = map.get(field_name) {
-                        result.insert(field_name.clone(), cast(field_schema, val)?);
+                        result.insert(field_name.clone(), cast_in(field_schema, val, scope)?);
                     } else {
                         result.insert(
                             field_name.clone(),
-                            super::create::create(field_schema).map_err(|e| {
+                            super::create::create_in(field_schema, scope).map_err(|e| {
                                 CastError::CannotCast(format!("cannot create default field: {}", e))
                             })?,
                         );
@@ -247,7 +264,7 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
             if let Some(additional_schema) = additional_properties {
                 for (key, val) in map {
                     if !properties.contains_key(key) {
-                        result.insert(key.clone(), cast(additional_schema, val)?);
+                        result.insert(key.clone(), cast_in(additional_schema, val, scope)?);
                     }
                 }
             }
@@ -267,7 +284,7 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
                 if let Some(field_schema) = properties.get(field_name) {
                     result.insert(
                         field_name.clone(),
-                        super::create::create(field_schema).map_err(|e| {
+                        super::create::create_in(field_schema, scope).map_err(|e| {
                             CastError::CannotCast(format!("cannot create default field: {}", e))
                         })?,
                     );
@@ -280,9 +297,9 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
             let mut result = Vec::with_capacity(items.len());
             for (i, item_schema) in items.iter().enumerate() {
                 if let Some(val) = arr.get(i) {
-                    result.push(cast(item_schema, val)?);
+                    result.push(cast_in(item_schema, val, scope)?);
                 } else {
-                    result.push(super::create::create(item_schema).map_err(|e| {
+                    result.push(super::create::create_in(item_schema, scope).map_err(|e| {
                         CastError::CannotCast(format!("cannot create tuple element: {}", e))
                     })?);
                 }
@@ -292,7 +309,7 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
         (SchemaKind::Tuple { items }, _) => {
             let mut result = Vec::with_capacity(items.len());
             for item_schema in items {
-                result.push(super::create::create(item_schema).map_err(|e| {
+                result.push(super::create::create_in(item_schema, scope).map_err(|e| {
                     CastError::CannotCast(format!("cannot create tuple element: {}", e))
                 })?);
             }
@@ -301,13 +318,13 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
 
         (SchemaKind::Union { any_of }, value) => {
             for variant in any_of {
-                let casted = cast(variant, value)?;
-                if super::check::check(variant, &casted) {
+                let casted = cast_in(variant, value, scope)?;
+                if super::check::check_in(variant, &casted, scope) {
                     return Ok(casted);
                 }
             }
             if let Some(first) = any_of.first() {
-                return cast(first, value);
+                return cast_in(first, value, scope);
             }
             Err(CastError::CannotCast("empty union".to_string()))
         }
@@ -351,12 +368,12 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
             }
         }
 
-        (SchemaKind::Ref { reference }, _) => Err(CastError::CannotCast(format!(
-            "unresolved ref: {}",
-            reference
-        ))),
+        (SchemaKind::Ref { .. }, value) => {
+            let (target, scope) = scope.resolve(schema).map_err(CastError::CannotCast)?;
+            cast_in(target, value, &scope)
+        }
 
-        (SchemaKind::Named { schema, .. }, value) => cast(schema, value),
+        (SchemaKind::Named { schema, .. }, value) => cast_in(schema, value, scope),
 
         (SchemaKind::Function { .. }, val) => Ok(val.clone()),
         (SchemaKind::Void, _) => Ok(Value::Null),
@@ -364,11 +381,15 @@ pub fn cast(schema: &Schema, value: &Value) -> Result<Value, CastError> {
         (SchemaKind::Any, val) => Ok(val.clone()),
         (SchemaKind::Unknown, val) => Ok(val.clone()),
         (SchemaKind::Undefined, _) => Ok(Value::Null),
-        (SchemaKind::Recursive { schema }, value) => cast(schema, value),
+        (SchemaKind::Recursive { .. }, value) => {
+            let bound = scope.bind(schema);
+            let (inner, scope) = scope.enter_recursive(schema, &bound);
+            cast_in(inner, value, &scope)
+        }
         (SchemaKind::Intersect { all_of }, value) => {
             let mut result = value.clone();
             for s in all_of {
-                result = cast(s, &result)?;
+                result = cast_in(s, &result, scope)?;
             }
             Ok(result)
         }
@@ -693,4 +714,97 @@ mod tests {
             Value::Int64(3)
         );
     }
+
+    #[test]
+    fn test_cast_nested_recursive() {
+        let schema = SchemaBuilder::recursive("Doc", |doc| {
+            let section = SchemaBuilder::recursive("Section", |section| {
+                SchemaBuilder::object()
+                    .field("title", SchemaBuilder::string().build())
+                    .field("children", SchemaBuilder::array(section).build())
+                    .field(
+                        "embed",
+                        SchemaBuilder::union(vec![doc, SchemaBuilder::null()]),
+                    )
+                    .build()
+            });
+            SchemaBuilder::object()
+                .field("sections", SchemaBuilder::array(section).build())
+                .build()
+        });
+        let embedded = Value::object()
+            .field("sections", Value::Array(vec![]))
+            .build();
+        let value = Value::object()
+            .field(
+                "sections",
+                Value::Array(vec![Value::object()
+                    .field("title", Value::Int64(1))
+                    .field("embed", embedded.clone())
+                    .build()]),
+            )
+            .build();
+        let result = cast(&schema, &value).unwrap();
+        assert_eq!(
+            result,
+            Value::object()
+                .field(
+                    "sections",
+                    Value::Array(vec![Value::object()
+                        .field("title", Value::string("1"))
+                        .field("children", Value::Array(vec![]))
+                        .field("embed", embedded)
+                        .build()]),
+                )
+                .build()
+        );
+        assert!(crate::value::check::check(&schema, &result));
+    }
+
+    #[test]
+    fn test_cast_with_registry() {
+        let mut registry = crate::registry::SchemaRegistry::new();
+        registry.register(
+            "Node",
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int64())
+                .field(
+                    "next",
+                    SchemaBuilder::union(vec![SchemaBuilder::r#ref("Node"), SchemaBuilder::null()]),
+                )
+                .named("Node"),
+        );
+        let schema = SchemaBuilder::r#ref("Node");
+        let input = Value::object()
+            .field("value", Value::String("1".to_string()))
+            .field(
+                "next",
+                Value::object().field("value", Value::Float64(2.0)).build(),
+            )
+            .build();
+
+        let cast = cast_with_registry(&schema, &input, Some(&registry)).unwrap();
+        assert_eq!(
+            cast,
+            Value::object()
+                .field("value", Value::Int64(1))
+                .field(
+                    "next",
+                    Value::object()
+                        .field("value", Value::Int64(2))
+                        .field("next", Value::Null)
+                        .build()
+                )
+                .build()
+        );
+        assert!(crate::value::check_with_registry(
+            &schema,
+            &cast,
+            Some(&registry)
+        ));
+        assert!(matches!(
+            cast_with_registry(&schema, &input, None),
+            Err(CastError::CannotCast(_))
+        ));
+    }
 }
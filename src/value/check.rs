@@ -1,6 +1,8 @@
 //! Value validation against schemas.
 
+use super::scope::RefScope;
 use crate::error::ValidationError;
+use crate::registry::SchemaRegistry;
 use crate::schema::Schema;
 use crate::value::Value;
 
@@ -16,6 +18,29 @@ pub fn check_with_errors(schema: &Schema, value: &Value) -> Result<(), Validatio
     crate::validate::validate(schema, value)
 }
 
+/// Check if a value conforms to a schema, resolving `$ref`s through
+/// `registry`.
+pub fn check_with_registry(
+    schema: &Schema,
+    value: &Value,
+    registry: Option<&SchemaRegistry>,
+) -> bool {
+    crate::validate::validate_with_registry(schema, value, registry).is_ok()
+}
+
+/// Checks `value` against a subschema reached while walking `scope`.
+pub(crate) fn check_in(schema: &Schema, value: &Value, scope: &RefScope<'_>) -> bool {
+    crate::validate::validate_full(
+        schema,
+        value,
+        scope.registry,
+        None,
+        scope.base.as_deref(),
+        scope.bindings,
+    )
+    .is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,7 +55,7 @@ mod tests {
             &SchemaBuilder::string().build(),
             &Value::String("hello".to_string())
         ));
-        assert!(check(&SchemaBuilder::float64(), &Value::Float64(3.14)));
+        assert!(check(&SchemaBuilder::float64(), &Value::Float64(2.5)));
     }
 
     #[test]
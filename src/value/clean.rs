@@ -1,6 +1,8 @@
 //! Remove extraneous properties from values.
 
+use super::scope::RefScope;
 use crate::error::CleanError;
+use crate::registry::SchemaRegistry;
 use crate::schema::{Schema, SchemaKind};
 use crate::value::Value;
 use indexmap::IndexMap;
@@ -10,6 +12,20 @@ use indexmap::IndexMap;
 /// Object fields not in the schema are removed unless they match
 /// `additionalProperties`. Nested objects and arrays are cleaned recursively.
 pub fn clean(schema: &Schema, value: &Value) -> Result<Value, CleanError> {
+    clean_with_registry(schema, value, None)
+}
+
+/// Remove properties not defined in the schema, resolving `$ref`s through
+/// `registry`.
+pub fn clean_with_registry(
+    schema: &Schema,
+    value: &Value,
+    registry: Option<&SchemaRegistry>,
+) -> Result<Value, CleanError> {
+    clean_in(schema, value, &RefScope::new(registry, schema))
+}
+
+fn clean_in(schema: &Schema, value: &Value, scope: &RefScope<'_>) -> Result<Value, CleanError> {
     match (&schema.kind, value) {
         (SchemaKind::Null, Value::Null) => Ok(Value::Null),
         (SchemaKind::Bool, Value::Bool(b)) => Ok(Value::Bool(*b)),
@@ -31,7 +47,7 @@ pub fn clean(schema: &Schema, value: &Value) -> Result<Value, CleanError> {
 
         (SchemaKind::Array { items, .. }, Value::Array(arr)) => {
             let cleaned: Result<Vec<Value>, CleanError> =
-                arr.iter().map(|v| clean(items, v)).collect();
+                arr.iter().map(|v| clean_in(items, v, scope)).collect();
             Ok(Value::Array(cleaned?))
         }
 
@@ -47,10 +63,10 @@ pub fn clean(schema: &Schema, value: &Value) -> Result<Value, CleanError> {
 
             for (key, val) in map {
                 if let Some(prop_schema) = properties.get(key) {
-                    result.insert(key.clone(), clean(prop_schema, val)?);
+                    result.insert(key.clone(), clean_in(prop_schema, val, scope)?);
                 } else if let Some(ref additional_schema) = additional_properties {
-                    if super::check::check(additional_schema, val) {
-                        result.insert(key.clone(), clean(additional_schema, val)?);
+                    if super::check::check_in(additional_schema, val, scope) {
+                        result.insert(key.clone(), clean_in(additional_schema, val, scope)?);
                     }
                 }
             }
@@ -62,15 +78,15 @@ pub fn clean(schema: &Schema, value: &Value) -> Result<Value, CleanError> {
             let len = items.len().min(arr.len());
             let mut result = Vec::with_capacity(len);
             for i in 0..len {
-                result.push(clean(&items[i], &arr[i])?);
+                result.push(clean_in(&items[i], &arr[i], scope)?);
             }
             Ok(Value::Array(result))
         }
 
         (SchemaKind::Union { any_of }, value) => {
             for variant in any_of {
-                if super::check::check(variant, value) {
-                    return clean(variant, value);
+                if super::check::check_in(variant, value, scope) {
+                    return clean_in(variant, value, scope);
                 }
             }
             Ok(value.clone())
@@ -80,12 +96,12 @@ pub fn clean(schema: &Schema, value: &Value) -> Result<Value, CleanError> {
 
         (SchemaKind::Enum { .. }, Value::String(s)) => Ok(Value::String(s.clone())),
 
-        (SchemaKind::Ref { reference }, _) => Err(CleanError::CannotClean(format!(
-            "unresolved ref: {}",
-            reference
-        ))),
+        (SchemaKind::Ref { .. }, value) => {
+            let (target, scope) = scope.resolve(schema).map_err(CleanError::CannotClean)?;
+            clean_in(target, value, &scope)
+        }
 
-        (SchemaKind::Named { schema, .. }, value) => clean(schema, value),
+        (SchemaKind::Named { schema, .. }, value) => clean_in(schema, value, scope),
 
         (SchemaKind::Function { .. }, val) => Ok(val.clone()),
         (SchemaKind::Void, val) => Ok(val.clone()),
@@ -93,11 +109,15 @@ pub fn clean(schema: &Schema, value: &Value) -> Result<Value, CleanError> {
         (SchemaKind::Any, val) => Ok(val.clone()),
         (SchemaKind::Unknown, val) => Ok(val.clone()),
         (SchemaKind::Undefined, val) => Ok(val.clone()),
-        (SchemaKind::Recursive { schema }, value) => clean(schema, value),
+        (SchemaKind::Recursive { .. }, value) => {
+            let bound = scope.bind(schema);
+            let (inner, scope) = scope.enter_recursive(schema, &bound);
+            clean_in(inner, value, &scope)
+        }
         (SchemaKind::Intersect { all_of }, value) => {
             let mut result = value.clone();
             for s in all_of {
-                result = clean(s, &result)?;
+                result = clean_in(s, &result, scope)?;
             }
             Ok(result)
         }
@@ -276,4 +296,64 @@ mod tests {
         let cleaned = clean(&schema, &value).unwrap();
         assert_eq!(cleaned, value);
     }
+
+    #[test]
+    fn test_clean_with_registry() {
+        let mut registry = crate::registry::SchemaRegistry::new();
+        registry.register(
+            "Address",
+            SchemaBuilder::object()
+                .field("street", SchemaBuilder::string().build())
+                .build(),
+        );
+        let schema = SchemaBuilder::object()
+            .field("home", SchemaBuilder::r#ref("Address"))
+            .build();
+        let value = Value::object()
+            .field(
+                "home",
+                Value::object()
+                    .field("street", Value::String("Main".to_string()))
+                    .field("extra", Value::Int64(1))
+                    .build(),
+            )
+            .build();
+
+        let cleaned = clean_with_registry(&schema, &value, Some(&registry)).unwrap();
+        assert_eq!(
+            cleaned,
+            Value::object()
+                .field(
+                    "home",
+                    Value::object()
+                        .field("street", Value::String("Main".to_string()))
+                        .build()
+                )
+                .build()
+        );
+        assert!(clean(&schema, &value).is_err());
+
+        let tree = SchemaBuilder::recursive("Tree", |this| {
+            SchemaBuilder::object()
+                .field("children", SchemaBuilder::array(this).build())
+                .build()
+        });
+        let leaf = Value::object()
+            .field("children", Value::Array(vec![]))
+            .field("extra", Value::Null)
+            .build();
+        let root = Value::object()
+            .field("children", Value::Array(vec![leaf]))
+            .build();
+        let cleaned = clean(&tree, &root).unwrap();
+        let expected_leaf = Value::object()
+            .field("children", Value::Array(vec![]))
+            .build();
+        assert_eq!(
+            cleaned,
+            Value::object()
+                .field("children", Value::Array(vec![expected_leaf]))
+                .build()
+        );
+    }
 }
@@ -27,7 +27,7 @@ mod tests {
         let int_val = Value::Int64(42);
         assert_eq!(clone(&int_val), int_val);
 
-        let float_val = Value::Float64(3.14);
+        let float_val = Value::Float64(2.5);
         assert_eq!(clone(&float_val), float_val);
 
         let string_val = Value::String("hello".to_string());
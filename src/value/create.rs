@@ -1,6 +1,8 @@
 //! Default value generation from schemas.
 
+use super::scope::RefScope;
 use crate::error::CreateError;
+use crate::registry::SchemaRegistry;
 use crate::schema::{LiteralValue, Schema, SchemaKind};
 use crate::value::Value;
 use indexmap::IndexMap;
@@ -10,6 +12,24 @@ use indexmap::IndexMap;
 /// Uses minimum bounds for numbers, empty strings, empty arrays,
 /// and required object fields.
 pub fn create(schema: &Schema) -> Result<Value, CreateError> {
+    create_with_registry(schema, None)
+}
+
+/// Create a default value, resolving `$ref`s through `registry`.
+///
+/// Unions prefer variants that do not refer back to a schema already being
+/// created, so recursive types terminate at their first non-recursive
+/// alternative (e.g. `null` for an optional `next` link). A required
+/// back-reference uses its `default` if it has one and fails with
+/// [`CreateError::RecursiveWithoutDefault`] otherwise.
+pub fn create_with_registry(
+    schema: &Schema,
+    registry: Option<&SchemaRegistry>,
+) -> Result<Value, CreateError> {
+    create_in(schema, &RefScope::new(registry, schema))
+}
+
+pub(crate) fn create_in(schema: &Schema, scope: &RefScope<'_>) -> Result<Value, CreateError> {
     match &schema.kind {
         SchemaKind::Null => Ok(Value::Null),
 
@@ -38,7 +58,7 @@ pub fn create(schema: &Schema) -> Result<Value, CreateError> {
             let count = min_items.unwrap_or(0);
             let mut arr = Vec::with_capacity(count);
             for _ in 0..count {
-                arr.push(create(items)?);
+                arr.push(create_in(items, scope)?);
             }
             Ok(Value::Array(arr))
         }
@@ -51,7 +71,7 @@ pub fn create(schema: &Schema) -> Result<Value, CreateError> {
             let mut obj = IndexMap::new();
             for field_name in required {
                 if let Some(field_schema) = properties.get(field_name) {
-                    obj.insert(field_name.clone(), create(field_schema)?);
+                    obj.insert(field_name.clone(), create_in(field_schema, scope)?);
                 }
             }
             Ok(Value::Object(obj))
@@ -60,14 +80,16 @@ pub fn create(schema: &Schema) -> Result<Value, CreateError> {
         SchemaKind::Tuple { items } => {
             let mut arr = Vec::with_capacity(items.len());
             for item_schema in items {
-                arr.push(create(item_schema)?);
+                arr.push(create_in(item_schema, scope)?);
             }
             Ok(Value::Array(arr))
         }
 
         SchemaKind::Union { any_of } => any_of
-            .first()
-            .map(create)
+            .iter()
+            .find(|variant| !scope.is_back_ref(variant))
+            .or(any_of.first())
+            .map(|variant| create_in(variant, scope))
             .unwrap_or_else(|| Ok(Value::Null)),
 
         SchemaKind::Literal { value } => Ok(match value {
@@ -83,12 +105,19 @@ pub fn create(schema: &Schema) -> Result<Value, CreateError> {
             .map(|s| Value::String(s.clone()))
             .ok_or_else(|| CreateError::UnsupportedSchema("empty enum".to_string())),
 
-        SchemaKind::Ref { reference } => Err(CreateError::UnsupportedSchema(format!(
-            "unresolved ref: {}",
-            reference
-        ))),
+        SchemaKind::Ref { reference } => {
+            let recursive = scope.is_back_ref(schema);
+            let (target, scope) = scope
+                .resolve(schema)
+                .map_err(CreateError::UnsupportedSchema)?;
+            match (&schema.default, recursive) {
+                (Some(default), true) => Ok(default.clone()),
+                (None, true) => Err(CreateError::RecursiveWithoutDefault(reference.clone())),
+                (_, false) => create_in(target, &scope),
+            }
+        }
 
-        SchemaKind::Named { schema, .. } => create(schema),
+        SchemaKind::Named { schema, .. } => create_in(schema, scope),
 
         SchemaKind::Function { .. } => Ok(Value::Null),
 
@@ -102,7 +131,11 @@ pub fn create(schema: &Schema) -> Result<Value, CreateError> {
 
         SchemaKind::Undefined => Ok(Value::Null),
 
-        SchemaKind::Recursive { schema } => create(schema),
+        SchemaKind::Recursive { .. } => {
+            let bound = scope.bind(schema);
+            let (inner, scope) = scope.enter_recursive(schema, &bound);
+            create_in(inner, &scope)
+        }
 
         SchemaKind::Intersect { all_of } => {
             let mut result = Value::Object(IndexMap::new());
@@ -115,7 +148,7 @@ pub fn create(schema: &Schema) -> Result<Value, CreateError> {
                 {
                     for field_name in required {
                         if let Some(field_schema) = properties.get(field_name) {
-                            if let Value::Object(obj) = create(field_schema)? {
+                            if let Value::Object(obj) = create_in(field_schema, scope)? {
                                 if let Value::Object(ref mut res_obj) = result {
                                     res_obj.insert(
                                         field_name.clone(),
@@ -285,4 +318,67 @@ mod tests {
     fn test_create_undefined() {
         assert_eq!(create(&SchemaBuilder::undefined()).unwrap(), Value::Null);
     }
+
+    fn linked_list() -> crate::registry::SchemaRegistry {
+        let mut registry = crate::registry::SchemaRegistry::new();
+        registry.register(
+            "Node",
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int64())
+                .field(
+                    "next",
+                    SchemaBuilder::union(vec![SchemaBuilder::r#ref("Node"), SchemaBuilder::null()]),
+                )
+                .named("Node"),
+        );
+        registry
+    }
+
+    #[test]
+    fn test_create_with_registry_recursive() {
+        let registry = linked_list();
+        let value = create_with_registry(&SchemaBuilder::r#ref("Node"), Some(&registry)).unwrap();
+        assert_eq!(
+            value,
+            Value::object()
+                .field("value", Value::Int64(0))
+                .field("next", Value::Null)
+                .build()
+        );
+
+        let schema = SchemaBuilder::recursive("Tree", |this| {
+            SchemaBuilder::object()
+                .field("children", SchemaBuilder::array(this).build())
+                .build()
+        });
+        assert!(create(&schema).is_ok());
+    }
+
+    #[test]
+    fn test_create_with_registry_required_cycle() {
+        let mut registry = crate::registry::SchemaRegistry::new();
+        registry.register(
+            "Loop",
+            SchemaBuilder::object()
+                .field("next", SchemaBuilder::r#ref("Loop"))
+                .build(),
+        );
+        let schema = SchemaBuilder::r#ref("Loop");
+        assert!(matches!(
+            create_with_registry(&schema, Some(&registry)),
+            Err(CreateError::RecursiveWithoutDefault(_))
+        ));
+
+        registry.register(
+            "Loop",
+            SchemaBuilder::object()
+                .field(
+                    "next",
+                    SchemaBuilder::r#ref("Loop").with_default(Value::Null),
+                )
+                .build(),
+        );
+        let value = create_with_registry(&schema, Some(&registry)).unwrap();
+        assert_eq!(value, Value::object().field("next", Value::Null).build());
+    }
 }
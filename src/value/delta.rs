@@ -191,7 +191,7 @@ mod tests {
         assert_eq!(edits.len(), 1);
         assert!(matches!(
             &edits[0],
-            Edit::Update { path, value } if path == "" && *value == Value::Int64(43)
+            Edit::Update { path, value } if path.is_empty() && *value == Value::Int64(43)
         ));
     }
 
@@ -304,7 +304,7 @@ mod tests {
         assert_eq!(edits.len(), 1);
         assert!(matches!(
             &edits[0],
-            Edit::Update { path, .. } if path == ""
+            Edit::Update { path, .. } if path.is_empty()
         ));
     }
 
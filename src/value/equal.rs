@@ -19,7 +19,7 @@ mod tests {
         assert!(!equal(&Value::Bool(true), &Value::Bool(false)));
         assert!(equal(&Value::Int64(42), &Value::Int64(42)));
         assert!(!equal(&Value::Int64(42), &Value::Int64(43)));
-        assert!(equal(&Value::Float64(3.14), &Value::Float64(3.14)));
+        assert!(equal(&Value::Float64(2.5), &Value::Float64(2.5)));
         assert!(equal(
             &Value::String("hello".to_string()),
             &Value::String("hello".to_string())
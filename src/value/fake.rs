@@ -1,4 +1,6 @@
+use super::scope::RefScope;
 use crate::error::FakeError;
+use crate::registry::SchemaRegistry;
 use crate::schema::{LiteralValue, Schema, SchemaKind, StringFormat};
 use crate::value::Value;
 use indexmap::IndexMap;
@@ -44,6 +46,25 @@ pub fn fake(schema: &Schema) -> Result<Value, FakeError> {
 
 #[cfg(feature = "fake")]
 pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, FakeError> {
+    fake_with_registry(schema, None, ctx)
+}
+
+/// Generates a random value, resolving `$ref`s through `registry`.
+///
+/// Recursive references are bounded by `ctx.max_depth`: once it is reached,
+/// unions prefer variants that do not refer back to a schema already being
+/// generated, and objects are left empty.
+#[cfg(feature = "fake")]
+pub fn fake_with_registry(
+    schema: &Schema,
+    registry: Option<&SchemaRegistry>,
+    ctx: &FakeContext,
+) -> Result<Value, FakeError> {
+    fake_in(schema, ctx, &RefScope::new(registry, schema))
+}
+
+#[cfg(feature = "fake")]
+fn fake_in(schema: &Schema, ctx: &FakeContext, scope: &RefScope<'_>) -> Result<Value, FakeError> {
     if ctx.current_depth > ctx.max_depth {
         return Err(FakeError::MaxDepthExceeded);
     }
@@ -157,7 +178,7 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
             let child_ctx = ctx.child();
             let mut arr = Vec::with_capacity(len);
             for _ in 0..len {
-                arr.push(fake_with_context(items, &child_ctx)?);
+                arr.push(fake_in(items, &child_ctx, scope)?);
             }
             Ok(Value::Array(arr))
         }
@@ -178,7 +199,7 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
                 if let Some(field_schema) = properties.get(field_name) {
                     obj.insert(
                         field_name.clone(),
-                        fake_with_context(field_schema, &child_ctx)?,
+                        fake_in(field_schema, &child_ctx, scope)?,
                     );
                 }
             }
@@ -188,7 +209,7 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
                 if should_add {
                     obj.insert(
                         "extra".to_string(),
-                        fake_with_context(additional_schema, &child_ctx)?,
+                        fake_in(additional_schema, &child_ctx, scope)?,
                     );
                 }
             }
@@ -200,7 +221,7 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
             let child_ctx = ctx.child();
             let mut arr = Vec::with_capacity(items.len());
             for item_schema in items {
-                arr.push(fake_with_context(item_schema, &child_ctx)?);
+                arr.push(fake_in(item_schema, &child_ctx, scope)?);
             }
             Ok(Value::Array(arr))
         }
@@ -209,8 +230,13 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
             if any_of.is_empty() {
                 return Ok(Value::Null);
             }
+            if ctx.current_depth >= ctx.max_depth {
+                if let Some(variant) = any_of.iter().find(|v| !scope.is_back_ref(v)) {
+                    return fake_in(variant, ctx, scope);
+                }
+            }
             let idx = rand::rng().random_range(0..any_of.len());
-            fake_with_context(&any_of[idx], ctx)
+            fake_in(&any_of[idx], ctx, scope)
         }
 
         SchemaKind::Literal { value } => Ok(match value {
@@ -229,12 +255,14 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
             Ok(Value::String(values[idx].clone()))
         }
 
-        SchemaKind::Ref { reference } => Err(FakeError::UnsupportedSchema(format!(
-            "unresolved ref: {}",
-            reference
-        ))),
+        SchemaKind::Ref { .. } => {
+            let (target, scope) = scope
+                .resolve(schema)
+                .map_err(FakeError::UnsupportedSchema)?;
+            fake_in(target, ctx, &scope)
+        }
 
-        SchemaKind::Named { schema, .. } => fake_with_context(schema, ctx),
+        SchemaKind::Named { schema, .. } => fake_in(schema, ctx, scope),
 
         SchemaKind::Function { .. } => Ok(Value::Null),
         SchemaKind::Void => Ok(Value::Null),
@@ -242,7 +270,11 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
         SchemaKind::Any => Ok(Value::Null),
         SchemaKind::Unknown => Ok(Value::Null),
         SchemaKind::Undefined => Ok(Value::Null),
-        SchemaKind::Recursive { schema } => fake_with_context(schema, ctx),
+        SchemaKind::Recursive { .. } => {
+            let bound = scope.bind(schema);
+            let (inner, scope) = scope.enter_recursive(schema, &bound);
+            fake_in(inner, ctx, &scope)
+        }
         SchemaKind::Intersect { all_of } => {
             let mut result = IndexMap::new();
             for s in all_of {
@@ -258,7 +290,7 @@ pub fn fake_with_context(schema: &Schema, ctx: &FakeContext) -> Result<Value, Fa
                             if !result.contains_key(field_name) {
                                 result.insert(
                                     field_name.clone(),
-                                    fake_with_context(field_schema, &child_ctx)?,
+                                    fake_in(field_schema, &child_ctx, scope)?,
                                 );
                             }
                         }
@@ -479,4 +511,30 @@ mod tests {
         let result = fake_with_context(&SchemaBuilder::int64(), &ctx);
         assert!(matches!(result, Err(FakeError::MaxDepthExceeded)));
     }
+
+    #[test]
+    fn test_fake_with_registry_recursive() {
+        let mut registry = crate::registry::SchemaRegistry::new();
+        registry.register(
+            "Node",
+            SchemaBuilder::object()
+                .field("value", SchemaBuilder::int64())
+                .field(
+                    "next",
+                    SchemaBuilder::union(vec![SchemaBuilder::r#ref("Node"), SchemaBuilder::null()]),
+                )
+                .named("Node"),
+        );
+        let schema = SchemaBuilder::r#ref("Node");
+        for _ in 0..20 {
+            let value =
+                fake_with_registry(&schema, Some(&registry), &FakeContext::default()).unwrap();
+            assert!(crate::value::check_with_registry(
+                &schema,
+                &value,
+                Some(&registry)
+            ));
+        }
+        assert!(fake(&schema).is_err());
+    }
 }
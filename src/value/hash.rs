@@ -26,12 +26,12 @@ enum TypeMarker {
     UInt8Array = 12,
 }
 
-struct Fnv1aHasher {
+pub(crate) struct Fnv1aHasher {
     state: u64,
 }
 
 impl Fnv1aHasher {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             state: FNV_OFFSET_BASIS,
         }
@@ -70,7 +70,7 @@ impl Fnv1aHasher {
         self.write_u64(bits);
     }
 
-    fn write_bytes(&mut self, bytes: &[u8]) {
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
         for byte in bytes {
             self.write_byte(*byte);
         }
@@ -86,7 +86,7 @@ impl Fnv1aHasher {
         self.write_byte(marker as u8);
     }
 
-    fn finish(self) -> u64 {
+    pub(crate) fn finish(self) -> u64 {
         self.state
     }
 }
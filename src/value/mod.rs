@@ -14,6 +14,7 @@ pub mod hash;
 pub mod mutate;
 pub mod patch;
 pub mod pointer;
+pub(crate) mod scope;
 
 #[cfg(feature = "fake")]
 #[doc(hidden)]
@@ -25,20 +26,21 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
-pub use cast::cast;
-pub use check::{check, check_with_errors};
-pub use clean::clean;
+pub use cast::{cast, cast_with_registry};
+pub use check::{check, check_with_errors, check_with_registry};
+pub use clean::{clean, clean_with_registry};
 pub use clone::clone;
-pub use create::create;
+pub use create::{create, create_with_registry};
 pub use delta::{delta, diff_summary, Delta, Edit};
 pub use equal::equal;
 pub use hash::hash_fnv1a;
 pub use mutate::mutate;
 pub use patch::patch;
 pub use pointer::{delete_pointer, get_pointer, get_pointer_mut, has_pointer, set_pointer};
+pub use scope::MAX_REF_DEPTH;
 
 #[cfg(feature = "fake")]
-pub use fake::{fake, fake_with_context, FakeContext};
+pub use fake::{fake, fake_with_context, fake_with_registry, FakeContext};
 
 /// A dynamically-typed value with schema-aware operations.
 ///
@@ -213,18 +215,31 @@ impl Value {
                 }
             }
             (serde_json::Value::Bool(b), SchemaKind::Bool) => Ok(Value::Bool(b)),
-            (serde_json::Value::Number(n), SchemaKind::Int64 { .. }) => n
+            (
+                serde_json::Value::Number(n),
+                kind @ (SchemaKind::Int8 { .. }
+                | SchemaKind::Int16 { .. }
+                | SchemaKind::Int32 { .. }
+                | SchemaKind::Int64 { .. }
+                | SchemaKind::UInt8 { .. }
+                | SchemaKind::UInt16 { .. }
+                | SchemaKind::UInt32 { .. }
+                | SchemaKind::UInt64 { .. }),
+            ) => n
                 .as_i64()
                 .map(Value::Int64)
                 .ok_or_else(|| ParseError::TypeMismatch {
-                    expected: "Int64".to_string(),
+                    expected: kind.kind_name().to_string(),
                     got: "Number".to_string(),
                 }),
-            (serde_json::Value::Number(n), SchemaKind::Float64 { .. }) => n
+            (
+                serde_json::Value::Number(n),
+                kind @ (SchemaKind::Float32 { .. } | SchemaKind::Float64 { .. }),
+            ) => n
                 .as_f64()
                 .map(Value::Float64)
                 .ok_or_else(|| ParseError::TypeMismatch {
-                    expected: "Float64".to_string(),
+                    expected: kind.kind_name().to_string(),
                     got: "Number".to_string(),
                 }),
             (serde_json::Value::String(s), SchemaKind::String { .. }) => Ok(Value::String(s)),
@@ -315,6 +330,7 @@ impl Value {
             (serde_json::Value::Null, SchemaKind::Void) => Ok(Value::Null),
             (serde_json::Value::Null, SchemaKind::Undefined) => Ok(Value::Null),
             (value, SchemaKind::Any) | (value, SchemaKind::Unknown) => Ok(value_to_untyped(value)),
+            (value, SchemaKind::Named { schema, .. }) => Value::from_json(value, schema),
             (value, schema_kind) => Err(ParseError::TypeMismatch {
                 expected: schema_kind.kind_name().to_string(),
                 got: match value {
@@ -415,7 +431,7 @@ impl Value {
     }
 }
 
-fn value_to_untyped(value: serde_json::Value) -> Value {
+pub(crate) fn value_to_untyped(value: serde_json::Value) -> Value {
     match value {
         serde_json::Value::Null => Value::Null,
         serde_json::Value::Bool(b) => Value::Bool(b),
@@ -537,7 +553,7 @@ mod tests {
             .build();
 
         let value = Value::from_json(json, &schema).unwrap();
-        assert!(matches!(value.as_object(), Some(_)));
+        assert!(value.as_object().is_some());
     }
 
     #[test]
@@ -591,6 +607,19 @@ mod tests {
         assert!(matches!(result, Err(ParseError::LiteralMismatch)));
     }
 
+    #[test]
+    fn test_from_json_sized_numbers_and_named() {
+        let schema = SchemaBuilder::object()
+            .field("port", SchemaBuilder::uint16())
+            .field("ratio", SchemaBuilder::float32())
+            .named("Config");
+        let json = serde_json::json!({"port": 8080, "ratio": 0.5});
+        let value = Value::from_json(json, &schema).unwrap();
+        let object = value.as_object().unwrap();
+        assert_eq!(object.get("port"), Some(&Value::Int64(8080)));
+        assert_eq!(object.get("ratio"), Some(&Value::Float64(0.5)));
+    }
+
     #[test]
     fn test_to_json_typed_arrays() {
         let float32 = Value::Float32Array(vec![1.0, 2.0, 3.0]);
@@ -632,7 +661,7 @@ mod tests {
         assert_eq!(Value::Null.kind(), "Null");
         assert_eq!(Value::Bool(true).kind(), "Bool");
         assert_eq!(Value::Int64(42).kind(), "Int64");
-        assert_eq!(Value::Float64(3.14).kind(), "Float64");
+        assert_eq!(Value::Float64(2.5).kind(), "Float64");
         assert_eq!(Value::String("test".to_string()).kind(), "String");
         assert_eq!(Value::Bytes(vec![]).kind(), "Bytes");
         assert_eq!(Value::Array(vec![]).kind(), "Array");
@@ -650,7 +679,7 @@ mod tests {
         assert_eq!(Value::Int64(42).as_i64(), Some(42));
         assert_eq!(Value::Bool(true).as_i64(), None);
 
-        assert_eq!(Value::Float64(3.14).as_f64(), Some(3.14));
+        assert_eq!(Value::Float64(2.5).as_f64(), Some(2.5));
         assert_eq!(Value::Int64(42).as_f64(), Some(42.0));
         assert_eq!(Value::Bool(true).as_f64(), None);
 
@@ -0,0 +1,186 @@
+//! Reference resolution for the registry-aware value operations.
+
+use crate::registry::{definition_ref, resolve_uri, walk_pointer, SchemaRegistry};
+use crate::schema::{Schema, SchemaKind};
+
+/// Maximum number of nested `$ref` expansions along one path.
+///
+/// Value-driven operations (`cast`, `clean`) only expand a reference when
+/// the value has a matching level of nesting, so this bounds the depth of
+/// recursive values they accept as well as runaway schema recursion.
+pub const MAX_REF_DEPTH: usize = 128;
+
+/// A `Recursive` schema's `$id` bound to its inner schema while the inner
+/// schema is being walked, layered over the bindings of enclosing
+/// `Recursive` schemas. Bindings shadow registry entries of the same name.
+pub(crate) struct Binding<'a> {
+    id: &'a str,
+    schema: &'a Schema,
+    base: Option<String>,
+    parent: Option<&'a Binding<'a>>,
+}
+
+impl<'a> Binding<'a> {
+    /// Binds the `$id` of the `Recursive` schema `schema`, reached with
+    /// `base` in effect, over `parent`.
+    pub(crate) fn new(
+        schema: &'a Schema,
+        base: Option<&str>,
+        parent: Option<&'a Binding<'a>>,
+    ) -> Option<Self> {
+        match (&schema.kind, &schema.id) {
+            (SchemaKind::Recursive { schema: inner }, Some(id)) => Some(Self {
+                id,
+                schema: inner,
+                base: match (base, id.starts_with('#')) {
+                    (_, true) => base.map(str::to_string),
+                    (Some(base), false) => Some(resolve_uri(base, id)),
+                    (None, false) => Some(id.clone()),
+                },
+                parent,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Resolves `reference` against the innermost binding in `bindings` it
+    /// names, directly, once resolved against `base`, or through
+    /// `#/definitions/<id>`, returning the target and the base URI in effect
+    /// there.
+    pub(crate) fn find(
+        mut bindings: Option<&'a Binding<'a>>,
+        reference: &str,
+        base: Option<&str>,
+    ) -> Option<(&'a Schema, Option<&'a str>)> {
+        let definition = definition_ref(reference);
+        let absolute = base.map(|base| resolve_uri(base, reference));
+        while let Some(binding) = bindings {
+            match &definition {
+                Some((name, pointer)) if name == binding.id => {
+                    return walk_pointer(binding.schema, pointer, reference)
+                        .ok()
+                        .map(|target| (target, binding.base.as_deref()));
+                }
+                None if binding.id == reference || absolute.as_deref() == Some(binding.id) => {
+                    return Some((binding.schema, binding.base.as_deref()));
+                }
+                _ => bindings = binding.parent,
+            }
+        }
+        None
+    }
+}
+
+/// Where `$ref`s are resolved while walking a schema: the registry, the
+/// `Recursive` bindings in effect, the base URI, and the reference targets
+/// currently being expanded.
+#[derive(Clone)]
+pub(crate) struct RefScope<'r> {
+    pub(crate) registry: Option<&'r SchemaRegistry>,
+    pub(crate) bindings: Option<&'r Binding<'r>>,
+    pub(crate) base: Option<String>,
+    active: Vec<*const Schema>,
+}
+
+impl<'r> RefScope<'r> {
+    /// Scope for walking `schema` from its root.
+    pub(crate) fn new(registry: Option<&'r SchemaRegistry>, schema: &Schema) -> Self {
+        let base = match &schema.id {
+            Some(id) if !id.starts_with('#') => Some(id.clone()),
+            _ => None,
+        };
+        Self {
+            registry,
+            bindings: None,
+            base,
+            active: Vec::new(),
+        }
+    }
+
+    /// Resolves the `Ref` schema `schema`, returning its target and the
+    /// scope to walk the target in.
+    pub(crate) fn resolve<'s>(
+        &'s self,
+        schema: &'s Schema,
+    ) -> Result<(&'s Schema, RefScope<'s>), String> {
+        let reference = match &schema.kind {
+            SchemaKind::Ref { reference } => reference,
+            _ => return Ok((schema, self.clone())),
+        };
+        if self.active.len() >= MAX_REF_DEPTH {
+            return Err(format!(
+                "ref {}: maximum reference depth {} exceeded",
+                reference, MAX_REF_DEPTH
+            ));
+        }
+        let (target, base) = self.target(schema, reference)?;
+
+        let mut active = self.active.clone();
+        active.push(target as *const Schema);
+        Ok((
+            target,
+            RefScope {
+                registry: self.registry,
+                bindings: self.bindings,
+                base,
+                active,
+            },
+        ))
+    }
+
+    fn target<'s>(
+        &'s self,
+        schema: &'s Schema,
+        reference: &str,
+    ) -> Result<(&'s Schema, Option<String>), String> {
+        if let Some((target, base)) = Binding::find(self.bindings, reference, self.base.as_deref())
+        {
+            return Ok((target, base.map(str::to_string)));
+        }
+        let registry = self
+            .registry
+            .ok_or_else(|| format!("unresolved ref: {} (no registry)", reference))?;
+        registry
+            .resolve_in(schema, self.base.as_deref())
+            .map_err(|e| format!("unresolved ref: {}", e))
+    }
+
+    /// Whether `schema` is a `Ref` back to a target that is already being
+    /// expanded, i.e. expanding it would recurse.
+    pub(crate) fn is_back_ref(&self, schema: &Schema) -> bool {
+        match &schema.kind {
+            SchemaKind::Ref { reference } => self
+                .target(schema, reference)
+                .is_ok_and(|(target, _)| self.active.contains(&(target as *const Schema))),
+            _ => false,
+        }
+    }
+
+    /// For a `Recursive` schema with an `$id`, the binding of the `$id` to
+    /// its inner schema, as validation makes. Keep it alive and pass it to
+    /// [`enter_recursive`](Self::enter_recursive).
+    pub(crate) fn bind<'s>(&'s self, schema: &'s Schema) -> Option<Binding<'s>> {
+        Binding::new(schema, self.base.as_deref(), self.bindings)
+    }
+
+    /// The inner schema of the `Recursive` schema `schema` and the scope to
+    /// walk it in, in which references to its `$id` count as back-references.
+    pub(crate) fn enter_recursive<'s>(
+        &'s self,
+        schema: &'s Schema,
+        bound: &'s Option<Binding<'s>>,
+    ) -> (&'s Schema, RefScope<'s>) {
+        let inner = match &schema.kind {
+            SchemaKind::Recursive { schema: inner } => inner.as_ref(),
+            _ => schema,
+        };
+        let mut scope: RefScope<'s> = self.clone();
+        if let Some(binding) = bound {
+            scope.bindings = Some(binding);
+            scope.base = binding.base.clone();
+            scope.active.push(binding.schema as *const Schema);
+            return (binding.schema, scope);
+        }
+        (inner, scope)
+    }
+}
@@ -0,0 +1,481 @@
+//! Visitor and fold traits for traversing [`Schema`] trees.
+//!
+//! Each trait has one overridable method whose default implementation walks
+//! every child schema: object properties and additional properties, array and
+//! tuple items, union and intersection members, function parameters and return
+//! type, and the inner schema of `Named` and `Recursive`. Override the method,
+//! do your work, then call the matching `walk_*` function to keep descending.
+//!
+//! The current location is tracked as a [`SchemaPath`], which renders as a
+//! JSON Pointer into the serialized schema (e.g., `#/properties/tags/items`).
+//!
+//! # Examples
+//!
+//! Collect every `$ref`:
+//!
+//! ```
+//! use typebox::visit::{walk_schema, SchemaPath, SchemaVisitor};
+//! use typebox::{Schema, SchemaBuilder, SchemaKind};
+//!
+//! struct Refs(Vec<String>);
+//!
+//! impl SchemaVisitor for Refs {
+//!     fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+//!         if let SchemaKind::Ref { reference } = &schema.kind {
+//!             self.0.push(reference.clone());
+//!         }
+//!         walk_schema(self, schema, path);
+//!     }
+//! }
+//!
+//! let schema = SchemaBuilder::object()
+//!     .field("home", SchemaBuilder::r#ref("Address"))
+//!     .field("friends", SchemaBuilder::array(SchemaBuilder::r#ref("Person")).build())
+//!     .build();
+//!
+//! let mut refs = Refs(Vec::new());
+//! schema.accept(&mut refs);
+//! assert_eq!(refs.0, vec!["#/definitions/Address", "#/definitions/Person"]);
+//! ```
+//!
+//! Strip all descriptions:
+//!
+//! ```
+//! use typebox::visit::{walk_schema_mut, SchemaPath, SchemaVisitorMut};
+//! use typebox::{Schema, SchemaBuilder};
+//!
+//! struct StripDescriptions;
+//!
+//! impl SchemaVisitorMut for StripDescriptions {
+//!     fn visit_schema_mut(&mut self, schema: &mut Schema, path: &mut SchemaPath) {
+//!         schema.description = None;
+//!         walk_schema_mut(self, schema, path);
+//!     }
+//! }
+//!
+//! let mut schema = SchemaBuilder::object()
+//!     .field("id", SchemaBuilder::int64().with_description("Identifier"))
+//!     .build()
+//!     .with_description("A record");
+//!
+//! schema.accept_mut(&mut StripDescriptions);
+//! assert_eq!(schema.to_string(), "{id: int64}");
+//! assert!(schema.description.is_none());
+//! ```
+
+use crate::schema::{Schema, SchemaKind};
+
+/// Location of a schema within a schema tree.
+///
+/// Segments follow the serialized field names, so the rendered path is a
+/// valid JSON Pointer into `serde_json::to_value(&root)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SchemaPath {
+    segments: Vec<String>,
+}
+
+impl SchemaPath {
+    /// Creates a path pointing at the root schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a segment.
+    pub fn push(&mut self, segment: impl Into<String>) {
+        self.segments.push(segment.into());
+    }
+
+    /// Removes and returns the last segment.
+    pub fn pop(&mut self) -> Option<String> {
+        self.segments.pop()
+    }
+
+    /// Returns the unescaped path segments.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Returns true if this path points at the root schema.
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns the path as an RFC6901 JSON Pointer without the leading `#`.
+    pub fn to_pointer(&self) -> String {
+        let mut pointer = String::new();
+        for segment in &self.segments {
+            pointer.push('/');
+            pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        }
+        pointer
+    }
+}
+
+impl std::fmt::Display for SchemaPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.to_pointer())
+    }
+}
+
+/// Read-only traversal of a schema tree.
+pub trait SchemaVisitor {
+    /// Visits a schema. The default implementation walks its children.
+    fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+        walk_schema(self, schema, path);
+    }
+}
+
+/// In-place mutable traversal of a schema tree.
+pub trait SchemaVisitorMut {
+    /// Visits a schema. The default implementation walks its children.
+    fn visit_schema_mut(&mut self, schema: &mut Schema, path: &mut SchemaPath) {
+        walk_schema_mut(self, schema, path);
+    }
+}
+
+/// By-value transformation of a schema tree.
+pub trait SchemaFold {
+    /// Folds a schema into a new one. The default implementation folds its children.
+    fn fold_schema(&mut self, schema: Schema, path: &mut SchemaPath) -> Schema {
+        fold_children(self, schema, path)
+    }
+}
+
+/// Visits every direct child of `schema` with `visitor`.
+pub fn walk_schema<V: SchemaVisitor + ?Sized>(
+    visitor: &mut V,
+    schema: &Schema,
+    path: &mut SchemaPath,
+) {
+    let mut visit = |segments: &[&str], child: &Schema| {
+        for segment in segments {
+            path.push(*segment);
+        }
+        visitor.visit_schema(child, path);
+        for _ in segments {
+            path.pop();
+        }
+    };
+
+    match &schema.kind {
+        SchemaKind::Array { items, .. } => visit(&["items"], items),
+        SchemaKind::Object {
+            properties,
+            additional_properties,
+            ..
+        } => {
+            for (name, prop) in properties {
+                visit(&["properties", name], prop);
+            }
+            if let Some(additional) = additional_properties {
+                visit(&["additional_properties"], additional);
+            }
+        }
+        SchemaKind::Tuple { items } => {
+            for (i, item) in items.iter().enumerate() {
+                visit(&["items", &i.to_string()], item);
+            }
+        }
+        SchemaKind::Union { any_of } => {
+            for (i, variant) in any_of.iter().enumerate() {
+                visit(&["any_of", &i.to_string()], variant);
+            }
+        }
+        SchemaKind::Intersect { all_of } => {
+            for (i, member) in all_of.iter().enumerate() {
+                visit(&["all_of", &i.to_string()], member);
+            }
+        }
+        SchemaKind::Function {
+            parameters,
+            returns,
+        } => {
+            for (i, param) in parameters.iter().enumerate() {
+                visit(&["parameters", &i.to_string()], param);
+            }
+            visit(&["returns"], returns);
+        }
+        SchemaKind::Named { schema, .. } | SchemaKind::Recursive { schema } => {
+            visit(&["schema"], schema)
+        }
+        _ => {}
+    }
+}
+
+/// Visits every direct child of `schema` with `visitor`, allowing mutation.
+pub fn walk_schema_mut<V: SchemaVisitorMut + ?Sized>(
+    visitor: &mut V,
+    schema: &mut Schema,
+    path: &mut SchemaPath,
+) {
+    let mut visit = |segments: &[&str], child: &mut Schema| {
+        for segment in segments {
+            path.push(*segment);
+        }
+        visitor.visit_schema_mut(child, path);
+        for _ in segments {
+            path.pop();
+        }
+    };
+
+    match &mut schema.kind {
+        SchemaKind::Array { items, .. } => visit(&["items"], items),
+        SchemaKind::Object {
+            properties,
+            additional_properties,
+            ..
+        } => {
+            for (name, prop) in properties.iter_mut() {
+                visit(&["properties", name], prop);
+            }
+            if let Some(additional) = additional_properties {
+                visit(&["additional_properties"], additional);
+            }
+        }
+        SchemaKind::Tuple { items } => {
+            for (i, item) in items.iter_mut().enumerate() {
+                visit(&["items", &i.to_string()], item);
+            }
+        }
+        SchemaKind::Union { any_of } => {
+            for (i, variant) in any_of.iter_mut().enumerate() {
+                visit(&["any_of", &i.to_string()], variant);
+            }
+        }
+        SchemaKind::Intersect { all_of } => {
+            for (i, member) in all_of.iter_mut().enumerate() {
+                visit(&["all_of", &i.to_string()], member);
+            }
+        }
+        SchemaKind::Function {
+            parameters,
+            returns,
+        } => {
+            for (i, param) in parameters.iter_mut().enumerate() {
+                visit(&["parameters", &i.to_string()], param);
+            }
+            visit(&["returns"], returns);
+        }
+        SchemaKind::Named { schema, .. } | SchemaKind::Recursive { schema } => {
+            visit(&["schema"], schema)
+        }
+        _ => {}
+    }
+}
+
+/// Folds every direct child of `schema` with `folder` and rebuilds it.
+pub fn fold_children<F: SchemaFold + ?Sized>(
+    folder: &mut F,
+    mut schema: Schema,
+    path: &mut SchemaPath,
+) -> Schema {
+    let mut fold = |segments: &[&str], child: Schema| -> Schema {
+        for segment in segments {
+            path.push(*segment);
+        }
+        let folded = folder.fold_schema(child, path);
+        for _ in segments {
+            path.pop();
+        }
+        folded
+    };
+
+    schema.kind = match schema.kind {
+        SchemaKind::Array {
+            items,
+            min_items,
+            max_items,
+            unique_items,
+        } => SchemaKind::Array {
+            items: Box::new(fold(&["items"], *items)),
+            min_items,
+            max_items,
+            unique_items,
+        },
+        SchemaKind::Object {
+            properties,
+            required,
+            additional_properties,
+        } => SchemaKind::Object {
+            properties: properties
+                .into_iter()
+                .map(|(name, prop)| {
+                    let folded = fold(&["properties", &name], prop);
+                    (name, folded)
+                })
+                .collect(),
+            required,
+            additional_properties: additional_properties
+                .map(|additional| Box::new(fold(&["additional_properties"], *additional))),
+        },
+        SchemaKind::Tuple { items } => SchemaKind::Tuple {
+            items: items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| fold(&["items", &i.to_string()], item))
+                .collect(),
+        },
+        SchemaKind::Union { any_of } => SchemaKind::Union {
+            any_of: any_of
+                .into_iter()
+                .enumerate()
+                .map(|(i, variant)| fold(&["any_of", &i.to_string()], variant))
+                .collect(),
+        },
+        SchemaKind::Intersect { all_of } => SchemaKind::Intersect {
+            all_of: all_of
+                .into_iter()
+                .enumerate()
+                .map(|(i, member)| fold(&["all_of", &i.to_string()], member))
+                .collect(),
+        },
+        SchemaKind::Function {
+            parameters,
+            returns,
+        } => SchemaKind::Function {
+            parameters: parameters
+                .into_iter()
+                .enumerate()
+                .map(|(i, param)| fold(&["parameters", &i.to_string()], param))
+                .collect(),
+            returns: Box::new(fold(&["returns"], *returns)),
+        },
+        SchemaKind::Named { name, schema } => SchemaKind::Named {
+            name,
+            schema: Box::new(fold(&["schema"], *schema)),
+        },
+        SchemaKind::Recursive { schema } => SchemaKind::Recursive {
+            schema: Box::new(fold(&["schema"], *schema)),
+        },
+        kind => kind,
+    };
+    schema
+}
+
+impl Schema {
+    /// Runs a visitor over this schema, starting at the root path.
+    pub fn accept<V: SchemaVisitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_schema(self, &mut SchemaPath::new());
+    }
+
+    /// Runs a mutable visitor over this schema, starting at the root path.
+    pub fn accept_mut<V: SchemaVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_schema_mut(self, &mut SchemaPath::new());
+    }
+
+    /// Transforms this schema with a fold, starting at the root path.
+    pub fn fold<F: SchemaFold + ?Sized>(self, folder: &mut F) -> Schema {
+        folder.fold_schema(self, &mut SchemaPath::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SchemaBuilder;
+
+    struct Paths(Vec<String>);
+
+    impl SchemaVisitor for Paths {
+        fn visit_schema(&mut self, schema: &Schema, path: &mut SchemaPath) {
+            self.0.push(path.to_string());
+            walk_schema(self, schema, path);
+        }
+    }
+
+    #[test]
+    fn test_visitor_paths() {
+        let schema = SchemaBuilder::object()
+            .field("a/b", SchemaBuilder::tuple(vec![SchemaBuilder::int8()]))
+            .field(
+                "f",
+                SchemaBuilder::function(vec![SchemaBuilder::bool()], SchemaBuilder::void()),
+            )
+            .named("Thing");
+
+        let mut paths = Paths(Vec::new());
+        schema.accept(&mut paths);
+
+        assert_eq!(
+            paths.0,
+            vec![
+                "#",
+                "#/schema",
+                "#/schema/properties/a~1b",
+                "#/schema/properties/a~1b/items/0",
+                "#/schema/properties/f",
+                "#/schema/properties/f/parameters/0",
+                "#/schema/properties/f/returns",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_is_json_pointer() {
+        let schema = SchemaBuilder::union(vec![
+            SchemaBuilder::null(),
+            SchemaBuilder::array(SchemaBuilder::string().build()).build(),
+        ]);
+
+        let mut paths = Paths(Vec::new());
+        schema.accept(&mut paths);
+
+        let json = serde_json::to_value(&schema).unwrap();
+        let last = paths.0.last().unwrap().trim_start_matches('#');
+        assert_eq!(json.pointer(last).unwrap()["kind"], "string");
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        struct Widen;
+
+        impl SchemaVisitorMut for Widen {
+            fn visit_schema_mut(&mut self, schema: &mut Schema, path: &mut SchemaPath) {
+                if let SchemaKind::Int32 { .. } = schema.kind {
+                    *schema = SchemaBuilder::int64();
+                }
+                walk_schema_mut(self, schema, path);
+            }
+        }
+
+        let mut schema = SchemaBuilder::array(SchemaBuilder::int32()).build();
+        schema.accept_mut(&mut Widen);
+
+        assert_eq!(schema.to_string(), "Array<int64>");
+    }
+
+    #[test]
+    fn test_fold() {
+        struct Optionalize;
+
+        impl SchemaFold for Optionalize {
+            fn fold_schema(&mut self, schema: Schema, path: &mut SchemaPath) -> Schema {
+                let schema = fold_children(self, schema, path);
+                match schema.kind {
+                    SchemaKind::Object {
+                        properties,
+                        additional_properties,
+                        ..
+                    } => Schema::new(SchemaKind::Object {
+                        properties,
+                        required: vec![],
+                        additional_properties,
+                    }),
+                    _ => schema,
+                }
+            }
+        }
+
+        let schema = SchemaBuilder::object()
+            .field(
+                "inner",
+                SchemaBuilder::object()
+                    .field("x", SchemaBuilder::int64())
+                    .build(),
+            )
+            .build();
+
+        let folded = schema.fold(&mut Optionalize);
+        assert_eq!(folded.to_string(), "{inner?: {x?: int64}}");
+    }
+}
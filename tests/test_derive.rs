@@ -0,0 +1,510 @@
+#![cfg(feature = "derive")]
+
+use serde::{Deserialize, Serialize};
+use typebox::{
+    check, validate_with_registry, LiteralValue, Schema, SchemaKind, SchemaRegistry, StringFormat,
+    TypeBox, Value,
+};
+
+fn inner(schema: &Schema) -> &Schema {
+    match &schema.kind {
+        SchemaKind::Named { schema, .. } => schema,
+        _ => schema,
+    }
+}
+
+fn properties(schema: &Schema) -> (Vec<String>, Vec<String>) {
+    match &inner(schema).kind {
+        SchemaKind::Object {
+            properties,
+            required,
+            ..
+        } => (properties.keys().cloned().collect(), required.clone()),
+        other => panic!("expected object, got {:?}", other),
+    }
+}
+
+fn property<'a>(schema: &'a Schema, name: &str) -> &'a Schema {
+    match &inner(schema).kind {
+        SchemaKind::Object { properties, .. } => &properties[name],
+        other => panic!("expected object, got {:?}", other),
+    }
+}
+
+/// A registered user.
+#[allow(dead_code)]
+#[derive(TypeBox, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct User {
+    /// Unique identifier.
+    user_id: u16,
+    #[typebox(format = "email", min_length = 3)]
+    email_address: String,
+    nick_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age: Option<u8>,
+    #[typebox(min_items = 1, max_items = 10)]
+    tags: Vec<String>,
+    #[serde(default)]
+    score: f64,
+    #[serde(skip)]
+    cache: Vec<u8>,
+    #[serde(rename = "type")]
+    kind: i32,
+}
+
+#[test]
+fn test_struct_fields() {
+    let schema = User::schema();
+    assert!(matches!(&schema.kind, SchemaKind::Named { name, .. } if name == "User"));
+    assert_eq!(
+        inner(&schema).description.as_deref(),
+        Some("A registered user.")
+    );
+
+    let (names, required) = properties(&schema);
+    assert_eq!(
+        names,
+        vec![
+            "userId",
+            "emailAddress",
+            "nickName",
+            "age",
+            "tags",
+            "score",
+            "type"
+        ]
+    );
+    assert_eq!(required, vec!["userId", "emailAddress", "tags", "type"]);
+
+    let id = property(&schema, "userId");
+    assert!(matches!(id.kind, SchemaKind::UInt16 { .. }));
+    assert_eq!(id.description.as_deref(), Some("Unique identifier."));
+
+    assert!(matches!(
+        &property(&schema, "emailAddress").kind,
+        SchemaKind::String {
+            format: Some(StringFormat::Email),
+            min_length: Some(3),
+            ..
+        }
+    ));
+    assert!(matches!(
+        &property(&schema, "nickName").kind,
+        SchemaKind::Union { any_of } if any_of.len() == 2
+    ));
+    assert!(matches!(
+        property(&schema, "age").kind,
+        SchemaKind::UInt8 { .. }
+    ));
+    assert!(matches!(
+        property(&schema, "tags").kind,
+        SchemaKind::Array {
+            min_items: Some(1),
+            max_items: Some(10),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_struct_validates_serialized_value() {
+    let user = User {
+        user_id: 7,
+        email_address: "a@example.com".to_string(),
+        nick_name: None,
+        age: None,
+        tags: vec!["admin".to_string()],
+        score: 1.5,
+        cache: Vec::new(),
+        kind: 2,
+    };
+    let json = serde_json::to_value(&user).unwrap();
+    let value = Value::from_json(json, &User::schema()).unwrap();
+    assert!(check(&User::schema(), &value));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct Ranges {
+    #[typebox(min = 1, max = 100)]
+    count: u32,
+    #[typebox(min = -1.5)]
+    ratio: Option<f32>,
+    #[typebox(min_items = 1, unique_items)]
+    ids: Vec<i64>,
+    #[typebox(format = "slug", pattern = "^[a-z-]+$")]
+    slug: String,
+    fixed: [u8; 4],
+    #[typebox(min = 0, max = 1)]
+    weight: f64,
+}
+
+#[test]
+fn test_constraints() {
+    let schema = Ranges::schema();
+    assert!(matches!(
+        property(&schema, "count").kind,
+        SchemaKind::UInt32 {
+            minimum: Some(1),
+            maximum: Some(100)
+        }
+    ));
+    match &property(&schema, "ratio").kind {
+        SchemaKind::Union { any_of } => assert!(matches!(
+            any_of[0].kind,
+            SchemaKind::Float32 {
+                minimum: Some(m),
+                ..
+            } if m == -1.5
+        )),
+        other => panic!("expected union, got {:?}", other),
+    }
+    assert!(matches!(
+        property(&schema, "ids").kind,
+        SchemaKind::Array {
+            min_items: Some(1),
+            unique_items: Some(true),
+            ..
+        }
+    ));
+    assert!(matches!(
+        &property(&schema, "slug").kind,
+        SchemaKind::String {
+            format: Some(StringFormat::Custom(f)),
+            pattern: Some(_),
+            ..
+        } if f == "slug"
+    ));
+    assert!(matches!(
+        property(&schema, "fixed").kind,
+        SchemaKind::Bytes {
+            min_length: Some(4),
+            max_length: Some(4)
+        }
+    ));
+    assert!(matches!(
+        property(&schema, "weight").kind,
+        SchemaKind::Float64 {
+            minimum: Some(min),
+            maximum: Some(max)
+        } if min == 0.0 && max == 1.0
+    ));
+}
+
+#[test]
+fn test_mismatched_constraints_fail_to_compile() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct Address {
+    city: String,
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct Timestamps {
+    created: i64,
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct Customer {
+    address: Address,
+    #[serde(flatten)]
+    timestamps: Timestamps,
+}
+
+#[test]
+fn test_nested_and_flatten() {
+    let schema = Customer::schema();
+    let (names, required) = properties(&schema);
+    assert_eq!(names, vec!["address", "created"]);
+    assert_eq!(required, vec!["address", "created"]);
+    assert!(matches!(
+        &property(&schema, "address").kind,
+        SchemaKind::Named { name, .. } if name == "Address"
+    ));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Active,
+    OnHold,
+    #[serde(rename = "gone")]
+    Deleted,
+}
+
+#[test]
+fn test_unit_enum() {
+    let schema = Status::schema();
+    assert!(matches!(
+        &inner(&schema).kind,
+        SchemaKind::Enum { values } if values == &["active", "on_hold", "gone"]
+    ));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox, Serialize)]
+#[serde(tag = "type")]
+enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+    Empty,
+}
+
+#[test]
+fn test_internally_tagged_enum() {
+    let schema = Shape::schema();
+    let SchemaKind::Union { any_of } = &inner(&schema).kind else {
+        panic!("expected union");
+    };
+    assert_eq!(any_of.len(), 3);
+    let SchemaKind::Object {
+        properties,
+        required,
+        ..
+    } = &any_of[0].kind
+    else {
+        panic!("expected object");
+    };
+    assert_eq!(required, &["type", "radius"]);
+    assert_eq!(
+        properties["type"].kind,
+        SchemaKind::Literal {
+            value: LiteralValue::String("Circle".to_string())
+        }
+    );
+
+    let json = serde_json::to_value(Shape::Square { side: 2.0 }).unwrap();
+    let value = Value::from_json(json, &schema).unwrap();
+    assert!(check(&schema, &value));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+enum Message {
+    Quit,
+    Write(String),
+    Move { x: i32, y: i32 },
+}
+
+#[test]
+fn test_externally_tagged_enum() {
+    let schema = Message::schema();
+    let SchemaKind::Union { any_of } = &inner(&schema).kind else {
+        panic!("expected union");
+    };
+    assert!(matches!(any_of[0].kind, SchemaKind::Literal { .. }));
+    let SchemaKind::Object { properties, .. } = &any_of[1].kind else {
+        panic!("expected object");
+    };
+    assert!(matches!(
+        properties["Write"].kind,
+        SchemaKind::String { .. }
+    ));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+    Num(i64),
+    Unit,
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+#[serde(untagged)]
+enum Either {
+    Text(String),
+    Number(f64),
+}
+
+#[test]
+fn test_adjacent_and_untagged_enums() {
+    let (names, _) = match &inner(&Adjacent::schema()).kind {
+        SchemaKind::Union { any_of } => properties(&any_of[0]),
+        other => panic!("expected union, got {:?}", other),
+    };
+    assert_eq!(names, vec!["t", "c"]);
+
+    let schema = Either::schema();
+    let SchemaKind::Union { any_of } = &inner(&schema).kind else {
+        panic!("expected union");
+    };
+    assert!(matches!(any_of[0].kind, SchemaKind::String { .. }));
+    assert!(matches!(any_of[1].kind, SchemaKind::Float64 { .. }));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+#[typebox(name = "Id")]
+struct UserId(#[typebox(min = 1)] u64);
+
+#[test]
+fn test_newtype_struct() {
+    let schema = UserId::schema();
+    assert!(matches!(&schema.kind, SchemaKind::Named { name, .. } if name == "Id"));
+    assert!(matches!(
+        inner(&schema).kind,
+        SchemaKind::UInt64 {
+            minimum: Some(1),
+            ..
+        }
+    ));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct TreeNode {
+    label: String,
+    children: Vec<TreeNode>,
+    parent: Option<Box<TreeNode>>,
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct Account {
+    owner: Customer,
+    #[serde(flatten)]
+    timestamps: Timestamps,
+    avatar: Vec<u8>,
+}
+
+#[test]
+fn test_register_recursive_type() {
+    let mut registry = SchemaRegistry::new();
+    let schema = registry.register_type::<TreeNode>();
+    assert!(matches!(schema.kind, SchemaKind::Ref { .. }));
+
+    let leaf = Value::object()
+        .field("label", Value::string("leaf"))
+        .field("children", Value::array(vec![]))
+        .field("parent", Value::Null)
+        .build();
+    let root = Value::object()
+        .field("label", Value::string("root"))
+        .field("children", Value::array(vec![leaf]))
+        .field("parent", Value::Null)
+        .build();
+    assert!(validate_with_registry(&schema, &root, Some(&registry)).is_ok());
+}
+
+#[test]
+fn test_register_dependencies() {
+    let mut registry = SchemaRegistry::new();
+    registry.register_type::<Account>();
+
+    let mut names: Vec<&String> = registry.schemas().map(|(name, _)| name).collect();
+    names.sort();
+    assert_eq!(names, vec!["Account", "Address", "Customer", "Timestamps"]);
+
+    let account = registry.get("Account").unwrap();
+    let (fields, required) = properties(account);
+    assert_eq!(fields, vec!["owner", "created", "avatar"]);
+    assert_eq!(required, vec!["owner", "created", "avatar"]);
+    assert!(matches!(
+        property(account, "owner").kind,
+        SchemaKind::Ref { .. }
+    ));
+    assert!(matches!(
+        property(account, "avatar").kind,
+        SchemaKind::Bytes { .. }
+    ));
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+#[serde(tag = "op")]
+enum Expr {
+    Num { value: i64 },
+    Add { left: Box<Expr>, right: Box<Self> },
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct Folder {
+    name: String,
+    files: Vec<File>,
+}
+
+#[allow(dead_code)]
+#[derive(TypeBox)]
+struct File {
+    name: String,
+    parent: Option<Box<Folder>>,
+}
+
+#[test]
+fn test_recursive_schema() {
+    let schema = TreeNode::schema();
+    assert!(matches!(&inner(&schema).kind, SchemaKind::Recursive { .. }));
+    assert_eq!(inner(&schema).id.as_deref(), Some("TreeNode"));
+
+    let leaf = Value::object()
+        .field("label", Value::string("leaf"))
+        .field("children", Value::array(vec![]))
+        .build();
+    let root = Value::object()
+        .field("label", Value::string("root"))
+        .field("children", Value::array(vec![leaf.clone()]))
+        .field("parent", leaf)
+        .build();
+    assert!(check(&schema, &root));
+    let bad = Value::object()
+        .field("label", Value::string("root"))
+        .field("children", Value::array(vec![Value::int64(1)]))
+        .build();
+    assert!(!check(&schema, &bad));
+
+    let schema = Expr::schema();
+    let num = |n| {
+        Value::object()
+            .field("op", Value::string("Num"))
+            .field("value", Value::int64(n))
+            .build()
+    };
+    let sum = Value::object()
+        .field("op", Value::string("Add"))
+        .field("left", num(1))
+        .field("right", num(2))
+        .build();
+    assert!(check(&schema, &sum));
+    assert!(!check(
+        &schema,
+        &Value::object()
+            .field("op", Value::string("Add"))
+            .field("left", num(1))
+            .field("right", Value::string("2"))
+            .build()
+    ));
+
+    // Mutual recursion through another type's `Box`.
+    let schema = Folder::schema();
+    let folder = Value::object()
+        .field("name", Value::string("src"))
+        .field(
+            "files",
+            Value::array(vec![Value::object()
+                .field("name", Value::string("lib.rs"))
+                .field(
+                    "parent",
+                    Value::object()
+                        .field("name", Value::string("root"))
+                        .field("files", Value::array(vec![]))
+                        .build(),
+                )
+                .build()]),
+        )
+        .build();
+    assert!(check(&schema, &folder));
+    assert!(matches!(
+        &inner(&File::schema()).kind,
+        SchemaKind::Recursive { .. }
+    ));
+}
@@ -0,0 +1,18 @@
+use typebox::TypeBox;
+
+#[derive(TypeBox)]
+struct Inner {
+    value: u32,
+}
+
+type Alias = Inner;
+
+#[derive(TypeBox)]
+struct Aliased {
+    #[typebox(min = 1)]
+    inner: Alias,
+    #[typebox(format = "email")]
+    boxed: Box<Alias>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use typebox::TypeBox;
+
+#[derive(TypeBox)]
+enum Mismatched {
+    Bytes(#[typebox(unique_items)] Vec<u8>),
+    Tags {
+        #[typebox(max = 10, pattern = "^a")]
+        tags: Vec<String>,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use typebox::TypeBox;
+
+#[derive(TypeBox)]
+struct Mismatched(#[typebox(format = "email", min_length = 1)] i32);
+
+fn main() {}
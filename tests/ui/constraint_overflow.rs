@@ -0,0 +1,9 @@
+use typebox::TypeBox;
+
+#[derive(TypeBox)]
+struct OutOfRange {
+    #[typebox(max = 300)]
+    byte: u8,
+}
+
+fn main() {}
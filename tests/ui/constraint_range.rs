@@ -0,0 +1,11 @@
+use typebox::TypeBox;
+
+#[derive(TypeBox)]
+struct OutOfRange {
+    #[typebox(min = -1)]
+    small: u8,
+    #[typebox(min = 1.5)]
+    count: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use typebox::TypeBox;
+
+#[derive(TypeBox)]
+struct Mismatched {
+    #[typebox(min = 1, min_items = 1, max_length = 10)]
+    name: Option<String>,
+}
+
+fn main() {}
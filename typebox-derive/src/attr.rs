@@ -0,0 +1,393 @@
+//! Parsing of `#[serde(...)]`, `#[typebox(...)]` and doc attributes.
+
+use crate::case::RenameRule;
+use crate::ty::{self, Shape};
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+use syn::{Attribute, Error, Expr, ExprLit, Lit, LitBool, LitStr, Meta, Type};
+
+/// Attributes on a struct or enum.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub rename: Option<String>,
+    pub rename_all: Option<RenameRule>,
+    pub rename_all_fields: Option<RenameRule>,
+    pub tag: Option<String>,
+    pub content: Option<String>,
+    pub untagged: bool,
+    pub default: bool,
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Attributes on a field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    pub skip: bool,
+    pub default: bool,
+    pub flatten: bool,
+    pub skip_serializing_if: bool,
+    pub deprecated: bool,
+    pub constraints: Constraints,
+}
+
+/// Attributes on an enum variant.
+#[derive(Default)]
+pub struct VariantAttrs {
+    pub rename: Option<String>,
+    pub rename_all: Option<RenameRule>,
+    pub skip: bool,
+    pub deprecated: bool,
+    pub description: Option<String>,
+}
+
+/// Schema constraints from `#[typebox(...)]`.
+#[derive(Default)]
+pub struct Constraints {
+    pub min: Option<Expr>,
+    pub max: Option<Expr>,
+    pub min_length: Option<Expr>,
+    pub max_length: Option<Expr>,
+    pub min_items: Option<Expr>,
+    pub max_items: Option<Expr>,
+    pub unique_items: Option<LitBool>,
+    pub format: Option<LitStr>,
+    pub pattern: Option<LitStr>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub read_only: Option<bool>,
+    pub write_only: Option<bool>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = ContainerAttrs {
+            description: doc_comment(attrs),
+            ..Default::default()
+        };
+
+        for attr in attrs {
+            if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        result.rename = Some(parse_rename(&meta)?);
+                    } else if meta.path.is_ident("rename_all") {
+                        result.rename_all = Some(parse_rename_rule(&meta)?);
+                    } else if meta.path.is_ident("rename_all_fields") {
+                        result.rename_all_fields = Some(parse_rename_rule(&meta)?);
+                    } else if meta.path.is_ident("tag") {
+                        result.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("content") {
+                        result.content = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("untagged") {
+                        result.untagged = true;
+                    } else if meta.path.is_ident("default") {
+                        result.default = true;
+                        skip_value(&meta)?;
+                    } else {
+                        skip_value(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("typebox") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        result.name = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("title") {
+                        result.title = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("description") {
+                        result.description = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else {
+                        return Err(meta.error("unknown typebox container attribute"));
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = FieldAttrs {
+            deprecated: has_deprecated(attrs),
+            constraints: Constraints {
+                description: doc_comment(attrs),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for attr in attrs {
+            if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        result.rename = Some(parse_rename(&meta)?);
+                    } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                        result.skip = true;
+                    } else if meta.path.is_ident("default") {
+                        result.default = true;
+                        skip_value(&meta)?;
+                    } else if meta.path.is_ident("flatten") {
+                        result.flatten = true;
+                    } else if meta.path.is_ident("skip_serializing_if") {
+                        result.skip_serializing_if = true;
+                        skip_value(&meta)?;
+                    } else {
+                        skip_value(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("typebox") {
+                attr.parse_nested_meta(|meta| {
+                    let c = &mut result.constraints;
+                    if meta.path.is_ident("skip") {
+                        result.skip = true;
+                    } else if meta.path.is_ident("rename") {
+                        result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("min") || meta.path.is_ident("minimum") {
+                        c.min = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("max") || meta.path.is_ident("maximum") {
+                        c.max = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("min_length") {
+                        c.min_length = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("max_length") {
+                        c.max_length = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("min_items") {
+                        c.min_items = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("max_items") {
+                        c.max_items = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("unique_items") {
+                        c.unique_items = Some(parse_flag(&meta)?);
+                    } else if meta.path.is_ident("format") {
+                        c.format = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("pattern") {
+                        c.pattern = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("title") {
+                        c.title = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("description") {
+                        c.description = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("read_only") {
+                        c.read_only = Some(parse_flag(&meta)?.value);
+                    } else if meta.path.is_ident("write_only") {
+                        c.write_only = Some(parse_flag(&meta)?.value);
+                    } else if meta.path.is_ident("deprecated") {
+                        result.deprecated = parse_flag(&meta)?.value;
+                    } else {
+                        return Err(meta.error("unknown typebox field attribute"));
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl Constraints {
+    /// Rejects constraints the schema of `ty` cannot hold. Types the macro
+    /// cannot see into are left to the trait bounds of the generated code.
+    pub fn check(&self, ty: &Type) -> syn::Result<()> {
+        let shape = ty::shape(ty);
+        if shape == Shape::Unknown {
+            return Ok(());
+        }
+
+        let numeric: &[Shape] = &[Shape::Integer, Shape::Float];
+        let lengths: &[Shape] = &[Shape::String, Shape::Bytes];
+        let items: &[Shape] = &[Shape::Array, Shape::Bytes];
+        let unique_items = self.unique_items.as_ref().filter(|flag| flag.value);
+        let checks = [
+            ("min", self.min.as_ref().map(Spanned::span), numeric),
+            ("max", self.max.as_ref().map(Spanned::span), numeric),
+            (
+                "min_length",
+                self.min_length.as_ref().map(Spanned::span),
+                lengths,
+            ),
+            (
+                "max_length",
+                self.max_length.as_ref().map(Spanned::span),
+                lengths,
+            ),
+            (
+                "min_items",
+                self.min_items.as_ref().map(Spanned::span),
+                items,
+            ),
+            (
+                "max_items",
+                self.max_items.as_ref().map(Spanned::span),
+                items,
+            ),
+            (
+                "unique_items",
+                unique_items.map(Spanned::span),
+                &[Shape::Array],
+            ),
+            (
+                "format",
+                self.format.as_ref().map(Spanned::span),
+                &[Shape::String],
+            ),
+            (
+                "pattern",
+                self.pattern.as_ref().map(Spanned::span),
+                &[Shape::String],
+            ),
+        ];
+
+        let mut errors: Option<Error> = None;
+        for (key, span, applies_to) in checks {
+            let Some(span) = span else { continue };
+            if applies_to.contains(&shape) {
+                continue;
+            }
+            let error = Error::new(
+                span,
+                format!("`{}` does not apply to {} fields", key, shape.describe()),
+            );
+            match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            }
+        }
+        errors.map_or(Ok(()), Err)
+    }
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = VariantAttrs {
+            deprecated: has_deprecated(attrs),
+            description: doc_comment(attrs),
+            ..Default::default()
+        };
+
+        for attr in attrs {
+            if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        result.rename = Some(parse_rename(&meta)?);
+                    } else if meta.path.is_ident("rename_all") {
+                        result.rename_all = Some(parse_rename_rule(&meta)?);
+                    } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                        result.skip = true;
+                    } else {
+                        skip_value(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("typebox") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("skip") {
+                        result.skip = true;
+                    } else if meta.path.is_ident("rename") {
+                        result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("description") {
+                        result.description = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else {
+                        return Err(meta.error("unknown typebox variant attribute"));
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Parses `rename = "x"` or `rename(serialize = "x", deserialize = "y")`,
+/// preferring the serialized name.
+fn parse_rename(meta: &ParseNestedMeta) -> syn::Result<String> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(meta.value()?.parse::<LitStr>()?.value());
+    }
+    let mut serialize = None;
+    let mut deserialize = None;
+    meta.parse_nested_meta(|inner| {
+        let value = inner.value()?.parse::<LitStr>()?.value();
+        if inner.path.is_ident("serialize") {
+            serialize = Some(value);
+        } else if inner.path.is_ident("deserialize") {
+            deserialize = Some(value);
+        }
+        Ok(())
+    })?;
+    serialize
+        .or(deserialize)
+        .ok_or_else(|| meta.error("expected `serialize` or `deserialize` name"))
+}
+
+fn parse_rename_rule(meta: &ParseNestedMeta) -> syn::Result<RenameRule> {
+    if meta.input.peek(syn::Token![=]) {
+        return RenameRule::from_lit(&meta.value()?.parse()?);
+    }
+    let mut rule = None;
+    meta.parse_nested_meta(|inner| {
+        let lit: LitStr = inner.value()?.parse()?;
+        if inner.path.is_ident("serialize") || rule.is_none() {
+            rule = Some(RenameRule::from_lit(&lit)?);
+        }
+        Ok(())
+    })?;
+    rule.ok_or_else(|| meta.error("expected a rename rule"))
+}
+
+/// Parses a bare flag (`unique_items`) or an explicit `unique_items = false`.
+fn parse_flag(meta: &ParseNestedMeta) -> syn::Result<LitBool> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse()
+    } else {
+        Ok(LitBool::new(true, meta.path.span()))
+    }
+}
+
+/// Consumes the value of a serde attribute this macro does not interpret.
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|inner| skip_value(&inner))?;
+    }
+    Ok(())
+}
+
+fn has_deprecated(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("deprecated"))
+}
+
+/// Joins `///` doc comment lines into a description.
+pub fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .unwrap_or(&line)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+
+    let joined = lines.join("\n").trim().to_string();
+    if joined.is_empty() {
+        None
+    } else {
+        Some(joined)
+    }
+}
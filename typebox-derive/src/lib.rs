@@ -0,0 +1,451 @@
+//! Derive macro for [`typebox`](https://docs.rs/typebox) schemas.
+//!
+//! Use through the `derive` feature of the `typebox` crate, which re-exports
+//! [`TypeBox`](macro@TypeBox).
+
+mod attr;
+mod case;
+mod ty;
+
+use attr::{Constraints, ContainerAttrs, FieldAttrs, VariantAttrs};
+use case::RenameRule;
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Expr, ExprLit, ExprUnary, Fields, GenericParam,
+    Ident, Lit, LitFloat, Type, UnOp,
+};
+use ty::{Mode, Shape, Target};
+
+/// Generates an inherent `fn schema() -> typebox::Schema` and a
+/// `typebox::TypeBoxSchema` impl for a struct or enum.
+///
+/// `schema()` builds the whole schema inline, while `TypeBoxSchema::register`
+/// registers the type and its dependencies as `Named` definitions and returns
+/// a `Ref`, which also supports recursive types.
+///
+/// The schema is wrapped in `Named` using the type name. Field types map to
+/// the schema kinds of matching width (`u16` becomes `UInt16`), `Option<T>`
+/// fields become optional, and `Vec<T>` becomes `Array`. Any other type must
+/// itself provide a `schema()` function and implement `TypeBoxSchema`,
+/// typically by deriving `TypeBox`.
+///
+/// # Serde attributes
+///
+/// The serialized shape is followed: `rename`, `rename_all`,
+/// `rename_all_fields`, `tag`, `content`, `untagged`, `skip`, `default`,
+/// `flatten` and `skip_serializing_if` are honored.
+///
+/// # Typebox attributes
+///
+/// - Container: `name`, `title`, `description`
+/// - Field: `min`, `max`, `min_length`, `max_length`, `min_items`,
+///   `max_items`, `unique_items`, `format`, `pattern`, `title`,
+///   `description`, `read_only`, `write_only`, `deprecated`, `rename`, `skip`
+/// - Variant: `rename`, `description`, `skip`
+///
+/// Doc comments become `description` and `#[deprecated]` sets `deprecated`.
+///
+/// Field constraints must suit the field's schema: `min`/`max` apply to
+/// numbers and are written in the field's own type, `min_length`/`max_length`
+/// to strings and bytes, `min_items`/`max_items` to arrays and bytes,
+/// `unique_items` to arrays, and `format`/`pattern` to strings. Anything else
+/// is a compile error, including for field types behind an alias.
+#[proc_macro_derive(TypeBox, attributes(typebox, serde))]
+pub fn derive_typebox(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if let Some(param) = input
+        .generics
+        .params
+        .iter()
+        .find(|p| !matches!(p, GenericParam::Lifetime(_)))
+    {
+        return Err(Error::new_spanned(
+            param,
+            "TypeBox cannot be derived for types with type or const parameters",
+        ));
+    }
+
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let ident = &input.ident;
+    let name = container
+        .name
+        .clone()
+        .or_else(|| container.rename.clone())
+        .unwrap_or_else(|| ident.to_string());
+
+    let target = Target {
+        ident: ident.clone(),
+        name: name.clone(),
+    };
+    let inline = body(input, &container, Mode::Inline(&target))?;
+    let registered = body(input, &container, Mode::Registry)?;
+
+    let mut metadata = TokenStream2::new();
+    if let Some(title) = &container.title {
+        metadata.extend(quote!(schema = schema.with_title(#title);));
+    }
+    if let Some(description) = &container.description {
+        metadata.extend(quote!(schema = schema.with_description(#description);));
+    }
+    if input.attrs.iter().any(|a| a.path().is_ident("deprecated")) {
+        metadata.extend(quote!(schema = schema.with_deprecated(true);));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns the `typebox` schema for this type.
+            pub fn schema() -> ::typebox::Schema {
+                ::typebox::__private::named(#name, || {
+                    #[allow(unused_mut)]
+                    let mut schema: ::typebox::Schema = #inline;
+                    #metadata
+                    schema
+                })
+            }
+        }
+
+        impl #impl_generics ::typebox::TypeBoxSchema for #ident #ty_generics #where_clause {
+            fn register(registry: &mut ::typebox::SchemaRegistry) -> ::typebox::Schema {
+                ::typebox::reflect::register_named::<Self, _>(registry, #name, |registry| {
+                    #[allow(unused_mut)]
+                    let mut schema: ::typebox::Schema = #registered;
+                    #metadata
+                    schema
+                })
+            }
+        }
+    })
+}
+
+/// Builds the unnamed schema of the type's serialized shape.
+fn body(input: &DeriveInput, container: &ContainerAttrs, mode: Mode) -> syn::Result<TokenStream2> {
+    Ok(match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                object(&data.fields, container.rename_all, container.default, mode)?
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let field = &fields.unnamed[0];
+                let attrs = FieldAttrs::parse(&field.attrs)?;
+                apply(
+                    ty::schema_for(&field.ty, mode),
+                    &field.ty,
+                    &attrs.constraints,
+                    false,
+                )?
+            }
+            Fields::Unnamed(_) => tuple(&data.fields, mode)?,
+            Fields::Unit => quote!(::typebox::SchemaBuilder::null()),
+        },
+        Data::Enum(data) => enumeration(data, container, mode)?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "TypeBox cannot be derived for unions",
+            ))
+        }
+    })
+}
+
+/// Builds an object schema from named fields.
+fn object(
+    fields: &Fields,
+    rename_all: Option<RenameRule>,
+    container_default: bool,
+    mode: Mode,
+) -> syn::Result<TokenStream2> {
+    let mut statements = TokenStream2::new();
+
+    for field in fields {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+
+        if attrs.flatten {
+            let inner = ty::option_inner(&field.ty).unwrap_or(&field.ty);
+            let schema = ty::schema_for(inner, mode);
+            statements.extend(flatten(schema, mode));
+            continue;
+        }
+
+        let ident = field.ident.as_ref().expect("named field");
+        let name = attrs.rename.clone().unwrap_or_else(|| {
+            let raw = unraw(ident);
+            match rename_all {
+                Some(rule) => rule.apply_to_field(&raw),
+                None => raw,
+            }
+        });
+
+        let (schema, required) = match ty::option_inner(&field.ty) {
+            Some(inner) if attrs.skip_serializing_if => (ty::schema_for(inner, mode), false),
+            Some(_) => (ty::schema_for(&field.ty, mode), false),
+            None => (
+                ty::schema_for(&field.ty, mode),
+                !(attrs.default || container_default || attrs.skip_serializing_if),
+            ),
+        };
+        let schema = apply(schema, &field.ty, &attrs.constraints, attrs.deprecated)?;
+        statements.extend(quote!(parts.field(#name, #schema, #required);));
+    }
+
+    Ok(quote!({
+        let mut parts = ::typebox::__private::ObjectParts::default();
+        #statements
+        parts.build()
+    }))
+}
+
+/// Builds a tuple schema from unnamed fields.
+fn tuple(fields: &Fields, mode: Mode) -> syn::Result<TokenStream2> {
+    let mut items = Vec::new();
+    for field in fields {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        items.push(apply(
+            ty::schema_for(&field.ty, mode),
+            &field.ty,
+            &attrs.constraints,
+            attrs.deprecated,
+        )?);
+    }
+    Ok(quote!(::typebox::SchemaBuilder::tuple(
+        ::std::vec![#(#items),*]
+    )))
+}
+
+/// Builds the payload schema of a variant, or `None` for unit variants.
+fn payload(
+    fields: &Fields,
+    rename_all: Option<RenameRule>,
+    mode: Mode,
+) -> syn::Result<Option<TokenStream2>> {
+    Ok(match fields {
+        Fields::Unit => None,
+        Fields::Named(_) => Some(object(fields, rename_all, false, mode)?),
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            let field = &unnamed.unnamed[0];
+            let attrs = FieldAttrs::parse(&field.attrs)?;
+            Some(apply(
+                ty::schema_for(&field.ty, mode),
+                &field.ty,
+                &attrs.constraints,
+                attrs.deprecated,
+            )?)
+        }
+        Fields::Unnamed(_) => Some(tuple(fields, mode)?),
+    })
+}
+
+fn enumeration(
+    data: &syn::DataEnum,
+    container: &ContainerAttrs,
+    mode: Mode,
+) -> syn::Result<TokenStream2> {
+    let tb = quote!(::typebox);
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        let attrs = VariantAttrs::parse(&variant.attrs)?;
+        if !attrs.skip {
+            variants.push((variant, attrs));
+        }
+    }
+
+    let is_external = container.tag.is_none() && !container.untagged;
+    if is_external
+        && variants
+            .iter()
+            .all(|(v, _)| matches!(v.fields, Fields::Unit))
+    {
+        let values: Vec<String> = variants
+            .iter()
+            .map(|(v, attrs)| variant_name(&v.ident, attrs, container))
+            .collect();
+        return Ok(quote!(#tb::SchemaBuilder::enum_values(::std::vec![#(#values),*])));
+    }
+
+    let mut schemas = Vec::new();
+    for (variant, attrs) in &variants {
+        let name = variant_name(&variant.ident, attrs, container);
+        let rename_all = attrs.rename_all.or(container.rename_all_fields);
+        let payload = payload(&variant.fields, rename_all, mode)?;
+
+        let schema = match (&container.tag, &container.content, container.untagged) {
+            (_, _, true) => payload.unwrap_or_else(|| quote!(#tb::SchemaBuilder::null())),
+            (Some(tag), None, false) => {
+                let merge = match (&variant.fields, payload) {
+                    (Fields::Unnamed(f), Some(_)) if f.unnamed.len() > 1 => {
+                        return Err(Error::new_spanned(
+                            variant,
+                            "internally tagged enums cannot contain tuple variants",
+                        ))
+                    }
+                    (_, Some(payload)) => flatten(payload, mode),
+                    (_, None) => TokenStream2::new(),
+                };
+                quote!({
+                    let mut parts = #tb::__private::ObjectParts::default();
+                    parts.field(#tag, #tb::SchemaBuilder::literal(#name), true);
+                    #merge
+                    parts.build()
+                })
+            }
+            (Some(tag), Some(content), false) => {
+                let content = payload
+                    .map(|payload| quote!(.field(#content, #payload)))
+                    .unwrap_or_default();
+                quote!(
+                    #tb::SchemaBuilder::object()
+                        .field(#tag, #tb::SchemaBuilder::literal(#name))
+                        #content
+                        .build()
+                )
+            }
+            (None, _, false) => match payload {
+                Some(payload) => quote!(
+                    #tb::SchemaBuilder::object().field(#name, #payload).build()
+                ),
+                None => quote!(#tb::SchemaBuilder::literal(#name)),
+            },
+        };
+
+        let mut metadata = TokenStream2::new();
+        if let Some(description) = &attrs.description {
+            metadata.extend(quote!(.with_description(#description)));
+        }
+        if attrs.deprecated {
+            metadata.extend(quote!(.with_deprecated(true)));
+        }
+        schemas.push(quote!((#schema) #metadata));
+    }
+
+    Ok(quote!(#tb::SchemaBuilder::union(::std::vec![#(#schemas),*])))
+}
+
+/// Merges a flattened schema into `parts`, resolving `Ref`s in registry mode.
+fn flatten(schema: TokenStream2, mode: Mode) -> TokenStream2 {
+    match mode {
+        Mode::Inline(_) => quote!(parts.flatten(#schema);),
+        Mode::Registry => quote!({
+            let flattened = #schema;
+            parts.flatten_from(flattened, registry);
+        }),
+    }
+}
+
+/// Wraps a schema expression for a field of type `ty` with constraint and
+/// metadata adjustments.
+fn apply(
+    schema: TokenStream2,
+    ty: &Type,
+    c: &Constraints,
+    deprecated: bool,
+) -> syn::Result<TokenStream2> {
+    c.check(ty)?;
+    let p = quote!(::typebox::__private);
+    let is_float = ty::shape(ty) == Shape::Float;
+    let mut steps = TokenStream2::new();
+    if let Some(min) = &c.min {
+        let min = bound(min, is_float);
+        steps.extend(quote!(schema = #p::minimum::<#ty>(schema, #min);));
+    }
+    if let Some(max) = &c.max {
+        let max = bound(max, is_float);
+        steps.extend(quote!(schema = #p::maximum::<#ty>(schema, #max);));
+    }
+    if let Some(min) = &c.min_length {
+        steps.extend(quote!(schema = #p::min_length::<#ty>(schema, #min);));
+    }
+    if let Some(max) = &c.max_length {
+        steps.extend(quote!(schema = #p::max_length::<#ty>(schema, #max);));
+    }
+    if let Some(min) = &c.min_items {
+        steps.extend(quote!(schema = #p::min_items::<#ty>(schema, #min);));
+    }
+    if let Some(max) = &c.max_items {
+        steps.extend(quote!(schema = #p::max_items::<#ty>(schema, #max);));
+    }
+    if let Some(unique) = &c.unique_items {
+        steps.extend(quote!(schema = #p::unique_items::<#ty>(schema, #unique);));
+    }
+    if let Some(format) = &c.format {
+        steps.extend(quote!(schema = #p::format::<#ty>(schema, #format);));
+    }
+    if let Some(pattern) = &c.pattern {
+        steps.extend(quote!(schema = #p::pattern::<#ty>(schema, #pattern);));
+    }
+    if let Some(title) = &c.title {
+        steps.extend(quote!(schema = schema.with_title(#title);));
+    }
+    if let Some(description) = &c.description {
+        steps.extend(quote!(schema = schema.with_description(#description);));
+    }
+    if let Some(read_only) = c.read_only {
+        steps.extend(quote!(schema = schema.with_read_only(#read_only);));
+    }
+    if let Some(write_only) = c.write_only {
+        steps.extend(quote!(schema = schema.with_write_only(#write_only);));
+    }
+    if deprecated {
+        steps.extend(quote!(schema = schema.with_deprecated(true);));
+    }
+
+    if steps.is_empty() {
+        return Ok(schema);
+    }
+    Ok(quote!({
+        let mut schema: ::typebox::Schema = #schema;
+        #steps
+        schema
+    }))
+}
+
+/// Writes an integer literal bound on a float field as a float literal, so
+/// `min = 0` type checks against `f64` without a cast.
+fn bound(value: &Expr, is_float: bool) -> TokenStream2 {
+    let (negative, literal) = match value {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => (true, &**expr),
+        _ => (false, value),
+    };
+    match literal {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) if is_float && int.suffix().is_empty() => {
+            let float = LitFloat::new(&format!("{}.0", int.base10_digits()), int.span());
+            if negative {
+                quote!(-#float)
+            } else {
+                quote!(#float)
+            }
+        }
+        _ => quote!(#value),
+    }
+}
+
+fn variant_name(ident: &Ident, attrs: &VariantAttrs, container: &ContainerAttrs) -> String {
+    attrs
+        .rename
+        .clone()
+        .unwrap_or_else(|| match container.rename_all {
+            Some(rule) => rule.apply_to_variant(&ident.to_string()),
+            None => ident.to_string(),
+        })
+}
+
+fn unraw(ident: &Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#").map(str::to_string).unwrap_or(name)
+}
@@ -0,0 +1,266 @@
+//! Mapping of Rust field types to schema expressions.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, GenericArgument, Ident, PathArguments, Type};
+
+/// Returns the `T` in `Option<T>`, if `ty` is an option.
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    match last_segment(ty) {
+        Some((name, args)) if name == "Option" && args.len() == 1 => Some(args[0]),
+        _ => None,
+    }
+}
+
+/// The schema a field type maps to, as far as the macro can see it, for
+/// checking `#[typebox(...)]` constraints against.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Shape {
+    Integer,
+    Float,
+    String,
+    Bytes,
+    Array,
+    /// A schema that takes no constraints, described for error messages.
+    Other(&'static str),
+    /// A type the macro cannot see into, such as an alias or another
+    /// derived type. Its constraints are checked by the compiler instead.
+    Unknown,
+}
+
+impl Shape {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Shape::Integer => "integer",
+            Shape::Float => "float",
+            Shape::String => "string",
+            Shape::Bytes => "byte sequence",
+            Shape::Array => "array",
+            Shape::Other(description) => description,
+            Shape::Unknown => "unknown",
+        }
+    }
+}
+
+/// Returns the [`Shape`] of `ty`, looking through `Option` and pointers.
+pub fn shape(ty: &Type) -> Shape {
+    match ty {
+        Type::Reference(r) => return shape(&r.elem),
+        Type::Paren(p) => return shape(&p.elem),
+        Type::Group(g) => return shape(&g.elem),
+        Type::Tuple(t) if t.elems.is_empty() => return Shape::Other("unit"),
+        Type::Tuple(_) => return Shape::Other("tuple"),
+        Type::Slice(s) if is_u8(&s.elem) => return Shape::Bytes,
+        Type::Array(a) if is_u8(&a.elem) => return Shape::Bytes,
+        Type::Slice(_) | Type::Array(_) => return Shape::Array,
+        _ => {}
+    }
+    if is_json_value(ty) {
+        return Shape::Other("JSON value");
+    }
+    let Some((name, args)) = last_segment(ty) else {
+        return Shape::Unknown;
+    };
+    match (name.as_str(), args.as_slice()) {
+        ("i8" | "i16" | "i32" | "i64" | "isize", []) => Shape::Integer,
+        ("u8" | "u16" | "u32" | "u64" | "usize", []) => Shape::Integer,
+        ("f32" | "f64", []) => Shape::Float,
+        ("String" | "str" | "char", []) => Shape::String,
+        ("bool", []) => Shape::Other("bool"),
+        ("Vec" | "VecDeque", [item]) if is_u8(item) => Shape::Bytes,
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet" | "IndexSet", [_]) => Shape::Array,
+        ("HashMap" | "BTreeMap" | "IndexMap", [_, _, ..]) => Shape::Other("map"),
+        ("Option", [inner]) | ("Box" | "Rc" | "Arc" | "Cow", [.., inner]) => shape(inner),
+        _ => Shape::Unknown,
+    }
+}
+
+/// The type being derived.
+pub struct Target {
+    pub ident: Ident,
+    /// The schema name.
+    pub name: String,
+}
+
+/// How generated code obtains the schemas of field types.
+#[derive(Clone, Copy)]
+pub enum Mode<'a> {
+    /// Build schemas inline, calling `schema()` on non-std types and
+    /// referring to the derived type by `Ref`.
+    Inline(&'a Target),
+    /// Go through `TypeBoxSchema::register` with a `registry` in scope.
+    Registry,
+}
+
+/// Generates an expression building the schema for `ty`.
+pub fn schema_for(ty: &Type, mode: Mode) -> TokenStream {
+    match mode {
+        Mode::Inline(target) => inline(ty, target),
+        Mode::Registry => quote!(<#ty as ::typebox::TypeBoxSchema>::register(registry)),
+    }
+}
+
+fn inline(ty: &Type, target: &Target) -> TokenStream {
+    let tb = quote!(::typebox);
+    match ty {
+        Type::Reference(r) => return inline(&r.elem, target),
+        Type::Paren(p) => return inline(&p.elem, target),
+        Type::Group(g) => return inline(&g.elem, target),
+        Type::Tuple(t) => {
+            if t.elems.is_empty() {
+                return quote!(#tb::SchemaBuilder::null());
+            }
+            let items = t.elems.iter().map(|elem| inline(elem, target));
+            return quote!(#tb::SchemaBuilder::tuple(::std::vec![#(#items),*]));
+        }
+        Type::Slice(s) => {
+            if is_u8(&s.elem) {
+                return quote!(#tb::SchemaBuilder::bytes());
+            }
+            let items = inline(&s.elem, target);
+            return quote!(#tb::SchemaBuilder::array(#items).build());
+        }
+        Type::Array(a) => {
+            let len = &a.len;
+            if is_u8(&a.elem) {
+                return fixed_bytes(len);
+            }
+            let items = inline(&a.elem, target);
+            return quote!(
+                #tb::SchemaBuilder::array(#items)
+                    .min_items(#len)
+                    .max_items(#len)
+                    .build()
+            );
+        }
+        _ => {}
+    }
+
+    if is_target(ty, target) {
+        let name = &target.name;
+        return quote!(#tb::__private::self_ref(#name));
+    }
+
+    if let Some((name, args)) = last_segment(ty) {
+        let primitive = match name.as_str() {
+            "bool" => Some(quote!(#tb::SchemaBuilder::bool())),
+            "i8" => Some(quote!(#tb::SchemaBuilder::int8())),
+            "i16" => Some(quote!(#tb::SchemaBuilder::int16())),
+            "i32" => Some(quote!(#tb::SchemaBuilder::int32())),
+            "i64" | "isize" => Some(quote!(#tb::SchemaBuilder::int64())),
+            "u8" => Some(quote!(#tb::SchemaBuilder::uint8())),
+            "u16" => Some(quote!(#tb::SchemaBuilder::uint16())),
+            "u32" => Some(quote!(#tb::SchemaBuilder::uint32())),
+            "u64" | "usize" => Some(quote!(#tb::SchemaBuilder::uint64())),
+            "f32" => Some(quote!(#tb::SchemaBuilder::float32())),
+            "f64" => Some(quote!(#tb::SchemaBuilder::float64())),
+            "String" | "str" => Some(quote!(#tb::SchemaBuilder::string().build())),
+            "char" => Some(quote!(
+                #tb::SchemaBuilder::string().min_length(1).max_length(1).build()
+            )),
+            _ => None,
+        };
+        if let Some(primitive) = primitive {
+            if args.is_empty() {
+                return primitive;
+            }
+        }
+
+        match (name.as_str(), args.as_slice()) {
+            ("Vec" | "VecDeque", [item]) => {
+                if is_u8(item) {
+                    return quote!(#tb::SchemaBuilder::bytes());
+                }
+                let items = inline(item, target);
+                return quote!(#tb::SchemaBuilder::array(#items).build());
+            }
+            ("HashSet" | "BTreeSet" | "IndexSet", [item]) => {
+                let items = inline(item, target);
+                return quote!(#tb::SchemaBuilder::array(#items).unique_items(true).build());
+            }
+            ("HashMap" | "BTreeMap" | "IndexMap", [_, value, ..]) => {
+                let values = inline(value, target);
+                return quote!(
+                    #tb::SchemaBuilder::object()
+                        .additional_properties(::std::option::Option::Some(#values))
+                        .build()
+                );
+            }
+            ("Option", [inner]) => {
+                let inner = inline(inner, target);
+                return quote!(#tb::SchemaBuilder::optional(#inner));
+            }
+            ("Box" | "Rc" | "Arc" | "Cow", [.., inner]) => return inline(inner, target),
+            ("Value", []) if is_json_value(ty) => return quote!(#tb::SchemaBuilder::any()),
+            _ => {}
+        }
+    }
+
+    quote!(<#ty>::schema())
+}
+
+/// Whether `ty` is `Self` or names the derived type. Paths to it, such as
+/// `crate::Tree`, are caught when `schema()` is re-entered instead.
+fn is_target(ty: &Type, target: &Target) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    if path.qself.is_some() {
+        return false;
+    }
+    match path.path.segments.iter().collect::<Vec<_>>().as_slice() {
+        [segment] => segment.ident == "Self" || segment.ident == target.ident,
+        _ => false,
+    }
+}
+
+fn fixed_bytes(len: &Expr) -> TokenStream {
+    quote!({
+        let mut schema = ::typebox::SchemaBuilder::bytes();
+        schema = ::typebox::__private::min_length::<[u8]>(schema, #len);
+        ::typebox::__private::max_length::<[u8]>(schema, #len)
+    })
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(last_segment(ty), Some((name, args)) if name == "u8" && args.is_empty())
+}
+
+fn is_json_value(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => {
+            let segments: Vec<String> = p
+                .path
+                .segments
+                .iter()
+                .map(|s| s.ident.to_string())
+                .collect();
+            segments.len() >= 2 && segments[segments.len() - 2] == "serde_json"
+        }
+        _ => false,
+    }
+}
+
+/// Returns the identifier of a path type's last segment along with its
+/// generic type arguments.
+fn last_segment(ty: &Type) -> Option<(String, Vec<&Type>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(angle) => angle
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Some((segment.ident.to_string(), args))
+}